@@ -0,0 +1,66 @@
+//! Benchmarks loading a long history from JSON lines and from the binary format, which should be
+//! at least five times faster.
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use std::time::{Duration, Instant, UNIX_EPOCH};
+use timetracker::storage::{BincodeStorage, JsonStorage, Storage, StoredTask};
+
+/// How many tasks the history holds.
+const TASKS: u64 = 10_000;
+
+/// How many times faster the binary format must load, at least.
+const TARGET_SPEEDUP: f64 = 5.0;
+
+/// Measures the fastest of a few loads, to keep one slow run from skewing the comparison.
+fn fastest_load(storage: &dyn Storage) -> Duration {
+    (0..5)
+        .map(|_| {
+            let start = Instant::now();
+            assert_eq!(storage.load().unwrap().len() as u64, TASKS);
+            start.elapsed()
+        })
+        .min()
+        .unwrap()
+}
+
+fn bench_load(c: &mut Criterion) {
+    let dir = std::env::temp_dir();
+    let json_path = dir.join(format!("history_bench_{}.json", std::process::id()));
+    let binary_path = dir.join(format!("history_bench_{}.bin", std::process::id()));
+    let _ = std::fs::remove_file(&json_path);
+    let _ = std::fs::remove_file(&binary_path);
+
+    let started_at = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+    let tasks: Vec<StoredTask> = (0..TASKS)
+        .map(|i| {
+            let started_at = started_at + Duration::from_secs(i * 3600);
+            StoredTask::new(
+                &format!("task {}", i % 50),
+                started_at,
+                started_at + Duration::from_secs(1800),
+            )
+        })
+        .collect();
+    let mut json = JsonStorage::new(&json_path);
+    json.append_all(&tasks).unwrap();
+    let mut binary = BincodeStorage::open(&binary_path).unwrap();
+    binary.append_all(&tasks).unwrap();
+
+    let speedup = fastest_load(&json).as_secs_f64() / fastest_load(&binary).as_secs_f64();
+    assert!(
+        speedup >= TARGET_SPEEDUP,
+        "binary loads {:.1}x faster than JSON, less than {}x",
+        speedup,
+        TARGET_SPEEDUP
+    );
+
+    c.bench_function("load 10,000 tasks from JSON", |b| b.iter(|| json.load()));
+    c.bench_function("load 10,000 tasks from binary", |b| {
+        b.iter(|| binary.load())
+    });
+    std::fs::remove_file(&json_path).unwrap();
+    std::fs::remove_file(&binary_path).unwrap();
+}
+
+criterion_group!(benches, bench_load);
+criterion_main!(benches);
@@ -0,0 +1,41 @@
+//! Benchmarks `time-tracker summary`, which has to be fast enough to run on every shell prompt.
+
+use criterion::{criterion_group, criterion_main, Criterion};
+use std::time::{Duration, Instant, SystemTime};
+use timetracker::{
+    running::{self, RunningTask, SummaryFormat},
+    storage::StoredTask,
+};
+
+/// How long the summary may take, at most.
+const TARGET: Duration = Duration::from_millis(100);
+
+/// Reads the running file and formats the summary line, like `time-tracker summary --oneline`.
+fn summarize(path: &std::path::Path) -> String {
+    let tasks = running::load(path).unwrap();
+    running::summary_line(&tasks, SystemTime::now(), SummaryFormat::Bash)
+}
+
+fn bench_summary(c: &mut Criterion) {
+    let path = std::env::temp_dir().join(format!("running_bench_{}.json", std::process::id()));
+    let started_at = SystemTime::now() - Duration::from_secs(4980);
+    let tasks: Vec<RunningTask> = (0..3)
+        .map(|i| RunningTask::from(StoredTask::running(&format!("task {}", i), started_at)))
+        .collect();
+    running::save(&path, &tasks).unwrap();
+
+    let start = Instant::now();
+    summarize(&path);
+    assert!(
+        start.elapsed() < TARGET,
+        "summary took {:?}, more than {:?}",
+        start.elapsed(),
+        TARGET
+    );
+
+    c.bench_function("summary --oneline", |b| b.iter(|| summarize(&path)));
+    running::save(&path, &[]).unwrap();
+}
+
+criterion_group!(benches, bench_summary);
+criterion_main!(benches);
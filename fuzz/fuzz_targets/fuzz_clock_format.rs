@@ -0,0 +1,14 @@
+//! Formats any number of seconds as a clock, which must never panic.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use timetracker::get_clock_format;
+
+fuzz_target!(|data: &[u8]| {
+    // the first eight bytes, little-endian, padded with zeros
+    let mut bytes = [0; 8];
+    let len = data.len().min(bytes.len());
+    bytes[..len].copy_from_slice(&data[..len]);
+    let _ = get_clock_format(u64::from_le_bytes(bytes));
+});
@@ -0,0 +1,10 @@
+//! Imports any bytes as a Redmine CSV export, which must fail with an `Err` rather than panic.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use timetracker::export::redmine::RedmineImporter;
+
+fuzz_target!(|data: &[u8]| {
+    let _ = RedmineImporter::parse(&String::from_utf8_lossy(data));
+});
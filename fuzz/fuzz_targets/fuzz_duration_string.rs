@@ -0,0 +1,14 @@
+//! Formats any number of seconds the way `time_tracked_string` does, which must never panic.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use timetracker::format_duration_human;
+
+fuzz_target!(|data: &[u8]| {
+    // the first eight bytes, little-endian, padded with zeros
+    let mut bytes = [0; 8];
+    let len = data.len().min(bytes.len());
+    bytes[..len].copy_from_slice(&data[..len]);
+    let _ = format_duration_human(u64::from_le_bytes(bytes));
+});
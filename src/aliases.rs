@@ -0,0 +1,157 @@
+//! Short names for frequently used task names.
+//!
+//! Aliases are kept in `~/.timetracker/aliases.json` as a JSON object mapping each alias to the
+//! name it stands for, e.g. `{ "standup": "daily standup" }`. An alias may point at another
+//! alias, as long as the chain doesn't loop back on itself.
+
+use crate::storage;
+use std::{
+    collections::{BTreeMap, HashSet},
+    error, fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+/// An alias that, followed far enough, expands back to itself.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CircularAliasError {
+    /// The names visited, ending with the one that was already seen.
+    pub chain: Vec<String>,
+}
+
+impl fmt::Display for CircularAliasError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "circular alias: {}", self.chain.join(" → "))
+    }
+}
+
+impl error::Error for CircularAliasError {}
+
+/// The user's aliases.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Aliases {
+    aliases: BTreeMap<String, String>,
+}
+
+impl Aliases {
+    /// Reads the aliases file, treating a missing file as having no aliases.
+    pub fn load(path: &Path) -> io::Result<Aliases> {
+        let contents = match fs::read_to_string(path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Aliases::default()),
+            Err(e) => return Err(e),
+        };
+        let aliases = serde_json::from_str(&contents).map_err(|e| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{}: {}", path.display(), e),
+            )
+        })?;
+        Ok(Aliases { aliases })
+    }
+
+    /// Writes the aliases file.
+    pub fn save(&self, path: &Path) -> io::Result<()> {
+        let json = serde_json::to_string_pretty(&self.aliases)?;
+        storage::write_atomically(path, format!("{}\n", json).as_bytes())
+    }
+
+    /// Adds or replaces an alias.
+    ///
+    /// Fails, leaving the aliases unchanged, if the new alias would make a loop.
+    pub fn add(&mut self, short: &str, full_name: &str) -> Result<(), CircularAliasError> {
+        let previous = self
+            .aliases
+            .insert(short.to_string(), full_name.to_string());
+        if let Err(e) = self.expand(short) {
+            match previous {
+                Some(previous) => self.aliases.insert(short.to_string(), previous),
+                None => self.aliases.remove(short),
+            };
+            return Err(e);
+        }
+        Ok(())
+    }
+
+    /// Removes an alias, returning what it stood for.
+    pub fn remove(&mut self, short: &str) -> Option<String> {
+        self.aliases.remove(short)
+    }
+
+    /// Iterates over `(alias, full name)` pairs in alphabetical order.
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &str)> {
+        self.aliases
+            .iter()
+            .map(|(short, full_name)| (short.as_str(), full_name.as_str()))
+    }
+
+    /// Expands `name` by following aliases until it reaches a name that isn't one.
+    ///
+    /// Names that aren't aliases are returned as they are.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// let mut aliases = timetracker::aliases::Aliases::default();
+    /// aliases.add("standup", "daily standup").unwrap();
+    /// assert_eq!(aliases.expand("standup").unwrap(), "daily standup");
+    /// assert_eq!(aliases.expand("lunch").unwrap(), "lunch");
+    /// ```
+    pub fn expand(&self, name: &str) -> Result<String, CircularAliasError> {
+        let mut seen = HashSet::new();
+        let mut chain = vec![name.to_string()];
+        let mut name = name;
+        while let Some(full_name) = self.aliases.get(name) {
+            seen.insert(name);
+            chain.push(full_name.clone());
+            if seen.contains(full_name.as_str()) {
+                return Err(CircularAliasError { chain });
+            }
+            name = full_name;
+        }
+        Ok(name.to_string())
+    }
+}
+
+/// Gets the path of the aliases file (`~/.timetracker/aliases.json`).
+pub fn aliases_file_path() -> PathBuf {
+    storage::data_dir().join("aliases.json")
+}
+
+#[cfg(test)]
+mod tests_aliases {
+    use super::*;
+
+    #[test]
+    fn follows_chains_and_rejects_loops() {
+        let mut aliases = Aliases::default();
+        aliases.add("su", "standup").unwrap();
+        aliases.add("standup", "daily standup").unwrap();
+        assert_eq!(aliases.expand("su").unwrap(), "daily standup");
+
+        let error = aliases.add("daily standup", "su").unwrap_err();
+        assert_eq!(
+            error.to_string(),
+            "circular alias: daily standup → su → standup → daily standup"
+        );
+        assert_eq!(aliases.expand("su").unwrap(), "daily standup");
+        assert!(aliases.add("self", "self").is_err());
+    }
+
+    #[test]
+    fn round_trips_through_file() {
+        let path = std::env::temp_dir().join(format!("aliases_{}.json", std::process::id()));
+        let _ = fs::remove_file(&path);
+        assert_eq!(Aliases::load(&path).unwrap(), Aliases::default());
+
+        let mut aliases = Aliases::default();
+        aliases.add("standup", "daily standup").unwrap();
+        aliases.save(&path).unwrap();
+        assert_eq!(
+            fs::read_to_string(&path).unwrap(),
+            "{\n  \"standup\": \"daily standup\"\n}\n"
+        );
+        assert_eq!(Aliases::load(&path).unwrap(), aliases);
+
+        fs::remove_file(&path).unwrap();
+    }
+}
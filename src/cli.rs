@@ -0,0 +1,2565 @@
+//! Command line arguments and subcommands.
+//!
+//! Running `time-tracker` without a subcommand starts the interactive timer.
+//! Subcommands work on the task history that the interactive timer writes.
+
+use chrono::{DateTime, NaiveDate, NaiveTime, Weekday};
+use chrono_tz::Tz;
+use clap::{ArgGroup, Args, Parser, Subcommand, ValueEnum};
+use dialoguer::Select;
+use notify::{RecursiveMode, Watcher};
+use std::{
+    collections::HashSet,
+    fs::File,
+    io::{self, BufWriter, IsTerminal, Write},
+    net::TcpListener,
+    path::{Path, PathBuf},
+    sync::mpsc,
+    time::{Duration, SystemTime},
+};
+use timetracker::{
+    aliases::{self, Aliases, CircularAliasError},
+    config::{self, CliOverrides, Config},
+    context::{ContextDetector, GitContextDetector},
+    countdown::{self, Countdown},
+    daemon,
+    doctor::{self, DiagnosticStatus},
+    environment,
+    export::{
+        ical::{self, IcsRecurringFormatter, RecurringEventDetector},
+        ods,
+        opf::OpfFormatter,
+        prometheus,
+        redmine::RedmineImporter,
+        split::{SplitBy, SplitExporter},
+        timewarrior,
+    },
+    format_duration_compact,
+    formatter::{
+        AnkiFormatter, AppleScriptFormatter, ClockifyJsonFormatter, ClockifyScriptFormatter,
+        CsvFormatter, Formatter, GoogleCalendarFormatter, HarvestCsvFormatter, JsonFeedFormatter,
+        JsonFormatter, JunitFormatter, KanbanFormatter, LedgerFormatter, NdjsonFormatter,
+        NotionFormatter, OrgClocktableFormatter, OrgScope, TempoFormatter, TogglApiFormatter,
+        TsvFormatter, DEFAULT_APPLESCRIPT_CALENDAR,
+    },
+    incomplete::{self, IncompleteTask, Resolution},
+    lint, parse_natural_duration,
+    reports::{
+        self, gantt, histogram, range, velocity, ActiveHoursReport, BillableFilter, BillableTotals,
+        BucketEdges, CoOccurrenceMatrix, ComparisonReport, ConsistencyReport, DailyLog, DateRange,
+        FocusAnalyzer, Forecaster, FrequencyReport, GanttChart, Heatmap, HierarchyReport,
+        Histogram, Invoice, MonthlyTrend, MultiWeekSparkline, PercentileAnalysis, Period,
+        RollingAverage, SankeyRenderer, Sparkline, SprintVelocity, StartEndAnalyzer,
+        StreakComputer, TagTimeSeries, TaskFilter, TaskGoalDelta, TaskStreakAnalyzer,
+        TimezoneAdapter, TopNReport, WeekHourHeatmap, WeekdayAverages, WeeklyReport,
+        WorkRateReport, WorkdayUtilization,
+    },
+    running::{self, PauseAllOperation, ResumeAllOperation, SummaryFormat},
+    selector::{self, FuzzySelector, TaskSelector},
+    show_timers,
+    storage::{self, JsonStorage, Storage, StorageFormat, StoredTask},
+    templates::{self, TaskTemplate},
+    timezone, Task,
+};
+
+/// A simple time tracker for the command line.
+#[derive(Parser)]
+#[command(name = "time-tracker", version, about)]
+pub struct Cli {
+    /// The subcommand to run. Starts the interactive timer when omitted.
+    #[command(subcommand)]
+    pub command: Option<Command>,
+    /// The file that completed tasks are saved to.
+    #[arg(long, global = true, value_name = "PATH")]
+    pub history_file: Option<PathBuf>,
+    /// Print detailed timing information to stderr.
+    #[arg(short, long, global = true)]
+    pub verbose: bool,
+}
+
+impl Cli {
+    /// Gets the settings given as command line flags.
+    pub fn overrides(&self) -> CliOverrides {
+        CliOverrides {
+            history_file: self.history_file.clone(),
+            verbose: self.verbose,
+        }
+    }
+}
+
+/// The subcommands of `time-tracker`.
+#[derive(Subcommand)]
+pub enum Command {
+    /// Export the task history in a machine-readable format.
+    Export(ExportArgs),
+    /// Inspect the configuration.
+    #[command(subcommand)]
+    Config(ConfigCommand),
+    /// Move every task from one storage backend to another.
+    Migrate(MigrateArgs),
+    /// Check the configured storage backend for corrupt or inconsistent entries.
+    Doctor(DoctorArgs),
+    /// Check every task name against the naming rules in a rules file.
+    Lint(LintArgs),
+    /// Summarize the time tracked for each task.
+    Report(Box<ReportArgs>),
+    /// Start tracking a single task, saving it once it is stopped.
+    Start(StartArgs),
+    /// Add a short name for a task name, or manage existing ones.
+    Alias(AliasArgs),
+    /// Start a task from a saved template, or manage the templates.
+    #[command(subcommand)]
+    Template(TemplateCommand),
+    /// Exit with code 1 if a task has been running for too long, e.g. in CI.
+    Check(CheckArgs),
+    /// Show the tasks that are running right now, e.g. in a shell prompt.
+    Summary(SummaryArgs),
+    /// List every saved task with its id.
+    List,
+    /// Serve Prometheus metrics over HTTP until interrupted.
+    Serve(ServeArgs),
+    /// Change saved tasks, e.g. to fix their time zone.
+    Edit(EditArgs),
+    /// Delete a saved task.
+    Delete(DeleteArgs),
+    /// Repair the history, e.g. tasks left running when the process was killed.
+    Fix(FixArgs),
+    /// Count down a break, then count the overtime until Enter is pressed.
+    Break(BreakArgs),
+    /// Add tasks exported from another time tracker to the history.
+    Import(ImportArgs),
+    /// Pause running tasks, e.g. for a meeting.
+    #[command(subcommand)]
+    Pause(PauseCommand),
+    /// Resume paused tasks.
+    #[command(subcommand)]
+    Resume(ResumeCommand),
+    /// Talk to the background daemon.
+    #[command(subcommand)]
+    Daemon(DaemonCommand),
+}
+
+impl Command {
+    /// Replaces aliases in the task names given on the command line with the names they stand for.
+    pub fn expand_aliases(&mut self, aliases: &Aliases) -> Result<(), CircularAliasError> {
+        match self {
+            Command::Start(args) => args.name = aliases.expand(&args.name)?,
+            Command::Report(args) => {
+                if let Some(name) = &mut args.name {
+                    *name = aliases.expand(name)?;
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Whether the command reads the setting with the config key `key`, so that it can't run
+    /// with an invalid value for it.
+    pub fn uses_setting(&self, key: &str) -> bool {
+        match key {
+            "timer_update_interval_ms" => matches!(
+                self,
+                Command::Start(_) | Command::Template(TemplateCommand::Start { .. })
+            ),
+            "max_task_duration_secs" => matches!(
+                self,
+                Command::Export(ExportArgs {
+                    format: ExportFormat::JenkinsJunit,
+                    ..
+                })
+            ),
+            _ => false,
+        }
+    }
+}
+
+/// Arguments for `time-tracker check`.
+#[derive(Args)]
+pub struct CheckArgs {
+    /// Fail if a task has been running for more than this many hours.
+    #[arg(long, value_name = "HOURS")]
+    pub max_running_hours: f64,
+    /// Print (without failing) tasks that have been running for more than this many hours.
+    #[arg(long, value_name = "HOURS")]
+    pub warn_running_hours: Option<f64>,
+}
+
+/// Arguments for `time-tracker summary`.
+#[derive(Args)]
+pub struct SummaryArgs {
+    /// Print every running task on one line, as `[task name: 1h23m]`.
+    #[arg(long)]
+    pub oneline: bool,
+    /// How to style the output (plain or bash).
+    #[arg(long, default_value_t = SummaryFormat::Plain)]
+    pub format: SummaryFormat,
+}
+
+/// Arguments for `time-tracker serve`.
+#[derive(Args)]
+pub struct ServeArgs {
+    /// The port to serve `/metrics` on, on localhost.
+    #[arg(long, value_name = "PORT", default_value_t = 9091)]
+    pub metrics_port: u16,
+}
+
+/// Arguments for `time-tracker edit`.
+#[derive(Args)]
+pub struct EditArgs {
+    /// The time zone the tasks were tracked in by mistake, e.g. America/New_York.
+    #[arg(long, value_name = "TZ", requires = "shift_to")]
+    pub time_zone: Tz,
+    /// The time zone the tasks were really tracked in, e.g. Europe/London.
+    #[arg(long, value_name = "TZ")]
+    pub shift_to: Tz,
+    /// Only change tasks started on this date or later, in --time-zone.
+    #[arg(long, value_name = "YYYY-MM-DD")]
+    pub after: Option<NaiveDate>,
+    /// Only change the task with this id, as shown by `time-tracker list`.
+    #[arg(long, value_name = "ID", conflicts_with = "after")]
+    pub id: Option<String>,
+    /// Only change one task, picked by searching the task names.
+    #[arg(long, conflicts_with_all = ["id", "after"])]
+    pub interactive: bool,
+}
+
+/// Arguments for `time-tracker delete`.
+#[derive(Args)]
+pub struct DeleteArgs {
+    /// The id of the task, as shown by `time-tracker list`. When omitted in a terminal, the task
+    /// is picked by searching the task names.
+    pub id: Option<String>,
+    /// Pick the task by searching the task names.
+    #[arg(long, conflicts_with = "id")]
+    pub interactive: bool,
+}
+
+/// Arguments for `time-tracker fix`.
+#[derive(Args)]
+#[command(group(
+    ArgGroup::new("resolution")
+        .required(true)
+        .args(["stop_at_started_at", "stop_now", "delete", "interactive"])
+))]
+pub struct FixArgs {
+    /// Fix the tasks that were saved without a stop time.
+    #[arg(long, required = true)]
+    pub incomplete: bool,
+    /// Stop each task when it started, so it took no time.
+    #[arg(long)]
+    pub stop_at_started_at: bool,
+    /// Stop each task now, counting all the time since it started.
+    #[arg(long)]
+    pub stop_now: bool,
+    /// Delete each task.
+    #[arg(long)]
+    pub delete: bool,
+    /// Ask what to do with each task.
+    #[arg(long)]
+    pub interactive: bool,
+}
+
+/// Arguments for `time-tracker break`.
+#[derive(Args)]
+pub struct BreakArgs {
+    /// How long the break is, e.g. 15m or 1h30m.
+    #[arg(long, value_name = "DURATION", value_parser = parse_natural_duration)]
+    pub duration: u64,
+    /// Show a desktop notification when the break is over (needs the `notifications` feature).
+    #[arg(long)]
+    pub remind: bool,
+}
+
+/// Arguments for `time-tracker import`.
+#[derive(Args)]
+pub struct ImportArgs {
+    /// A CSV export of Redmine time entries.
+    #[arg(long, value_name = "CSV")]
+    pub redmine: PathBuf,
+}
+
+/// Arguments for `time-tracker alias`.
+#[derive(Args)]
+#[command(args_conflicts_with_subcommands = true)]
+pub struct AliasArgs {
+    /// Lists or removes aliases instead of adding one.
+    #[command(subcommand)]
+    pub command: Option<AliasCommand>,
+    /// The alias to add.
+    #[arg(requires = "full_name")]
+    pub short: Option<String>,
+    /// The task name the alias stands for.
+    pub full_name: Option<String>,
+}
+
+/// The subcommands of `time-tracker alias`.
+#[derive(Subcommand)]
+pub enum AliasCommand {
+    /// Print every alias and the task name it stands for.
+    List,
+    /// Delete an alias.
+    Remove {
+        /// The alias to delete.
+        short: String,
+    },
+}
+
+/// The subcommands of `time-tracker template`.
+#[derive(Subcommand)]
+pub enum TemplateCommand {
+    /// Save a template to the config file, replacing any with the same name.
+    Add(TemplateAddArgs),
+    /// Start tracking a task with a template's settings, saving it once it is stopped.
+    Start {
+        /// The template to start.
+        template: String,
+    },
+    /// Print every template and its settings.
+    List,
+    /// Delete a template from the config file.
+    Remove {
+        /// The template to delete.
+        template: String,
+    },
+}
+
+/// Arguments for `time-tracker template add`.
+#[derive(Args)]
+pub struct TemplateAddArgs {
+    /// The name to start the template by.
+    pub template: String,
+    /// The name of the tasks started from the template. Defaults to the template's name.
+    #[arg(long)]
+    pub name: Option<String>,
+    /// The tags of the tasks, separated by commas.
+    #[arg(long, value_delimiter = ',')]
+    pub tags: Vec<String>,
+    /// How long the task is expected to take, e.g. 30m or 1h30m.
+    #[arg(long, value_name = "DURATION", value_parser = parse_natural_duration)]
+    pub estimated: Option<u64>,
+    /// The client the tasks are done for.
+    #[arg(long)]
+    pub client: Option<String>,
+    /// The project the tasks belong to.
+    #[arg(long)]
+    pub project: Option<String>,
+    /// Don't bill the time, even if an hourly rate is set.
+    #[arg(long)]
+    pub non_billable: bool,
+}
+
+/// The subcommands of `time-tracker config`.
+#[derive(Subcommand)]
+pub enum ConfigCommand {
+    /// Print every setting, its value, and where the value came from.
+    Show,
+    /// Check every setting against its allowed range.
+    Validate,
+}
+
+/// The subcommands of `time-tracker pause`.
+#[derive(Subcommand)]
+pub enum PauseCommand {
+    /// Pause every running task.
+    All,
+}
+
+/// The subcommands of `time-tracker resume`.
+#[derive(Subcommand)]
+pub enum ResumeCommand {
+    /// Resume every paused task.
+    All,
+}
+
+/// The subcommands of `time-tracker daemon`.
+#[derive(Subcommand)]
+pub enum DaemonCommand {
+    /// Print the daemon's process id, uptime, tasks, and connected clients.
+    Status,
+}
+
+/// Arguments for `time-tracker export`.
+#[derive(Args)]
+pub struct ExportArgs {
+    /// The output format.
+    #[arg(long, value_enum, default_value_t = ExportFormat::Json)]
+    pub format: ExportFormat,
+    /// Pretty-print JSON output with two-space indentation.
+    #[arg(long)]
+    pub pretty: bool,
+    /// The directory to write to, for formats that are split across several files.
+    #[arg(long, value_name = "DIR")]
+    pub output_dir: Option<PathBuf>,
+    /// The :scope of an org-clocktable (file or agenda).
+    #[arg(long, value_name = "SCOPE", default_value_t = OrgScope::File)]
+    pub org_scope: OrgScope,
+    /// The calendar an applescript export adds its events to.
+    #[arg(long, value_name = "NAME", default_value = DEFAULT_APPLESCRIPT_CALENDAR)]
+    pub calendar: String,
+    /// The workspace a toggl-api-json or clockify-api-json export creates its entries in.
+    #[arg(long, value_name = "ID")]
+    pub workspace_id: Option<String>,
+    /// The Clockify API key to write into a clockify-api-json script. Without it, the script
+    /// reads the CLOCKIFY_API_KEY environment variable when it runs, so no key is saved in it.
+    #[arg(long, value_name = "KEY")]
+    pub api_key: Option<String>,
+    /// Write the Clockify time entries as JSON instead of a script (clockify-api-json only).
+    #[arg(long)]
+    pub json_only: bool,
+    /// How many days a task has to be done on at about the same time (within 30 minutes) to
+    /// become a recurring event (ics-recurring only).
+    #[arg(
+        long,
+        value_name = "N",
+        default_value_t = ical::DEFAULT_MIN_OCCURRENCES,
+        value_parser = clap::builder::RangedU64ValueParser::<usize>::new().range(1..)
+    )]
+    pub min_occurrences: usize,
+    /// Keep running and write tasks as they are added to the history file (ndjson only).
+    #[arg(long)]
+    pub follow: bool,
+    /// The file to write to instead of stdout.
+    #[arg(long, value_name = "PATH")]
+    pub output: Option<PathBuf>,
+    /// Only export tasks started on or after this date (YYYY-MM-DD), time
+    /// (YYYY-MM-DDTHH:MM:SS), ISO week (YYYY-Www), or number of days ago (e.g. 30d).
+    #[arg(long, value_name = "DATE", value_parser = range::parse_since)]
+    pub since: Option<SystemTime>,
+    /// Write a file per month, week, or day into --output-dir, e.g. `2024-01.csv`.
+    #[arg(
+        long,
+        value_name = "PERIOD",
+        requires = "output_dir",
+        conflicts_with_all = ["output", "follow"]
+    )]
+    pub split_by: Option<SplitBy>,
+    /// Replace files that --split-by would write over.
+    #[arg(long, requires = "split_by")]
+    pub overwrite: bool,
+}
+
+/// Arguments for `time-tracker migrate`.
+#[derive(Args)]
+pub struct MigrateArgs {
+    /// The storage backend to read tasks from (json, sqlite, or binary).
+    #[arg(long)]
+    pub from: StorageFormat,
+    /// The storage backend to write tasks to (json, sqlite, or binary).
+    #[arg(long)]
+    pub to: StorageFormat,
+    /// Only print how many tasks would be migrated.
+    #[arg(long)]
+    pub dry_run: bool,
+}
+
+/// Arguments for `time-tracker doctor`.
+#[derive(Args)]
+pub struct DoctorArgs {
+    /// Repair what can be repaired: recalculate `seconds` and drop duplicate ids.
+    #[arg(long)]
+    pub fix: bool,
+}
+
+/// Arguments for `time-tracker lint`.
+#[derive(Args)]
+pub struct LintArgs {
+    /// The rules file. Defaults to `~/.timetracker/lint.toml`.
+    #[arg(long, value_name = "PATH")]
+    pub rules: Option<PathBuf>,
+    /// Only lint tasks started at or after this local date, date and time, ISO week, or number
+    /// of days ago (e.g. 30d).
+    #[arg(long, value_name = "YYYY-MM-DD[THH:MM:SS]", value_parser = range::parse_since)]
+    pub since: Option<SystemTime>,
+    /// Only lint tasks started on or before this local date, date and time, ISO week, or number
+    /// of days ago (e.g. 30d).
+    #[arg(long, value_name = "YYYY-MM-DD[THH:MM:SS]", value_parser = range::parse_until)]
+    pub until: Option<SystemTime>,
+}
+
+/// Arguments for `time-tracker start`.
+#[derive(Args)]
+pub struct StartArgs {
+    /// The name of the task.
+    pub name: String,
+    /// Don't bill the time, even if an hourly rate is set.
+    #[arg(long)]
+    pub non_billable: bool,
+}
+
+/// Arguments for `time-tracker report`.
+#[derive(Args)]
+pub struct ReportArgs {
+    /// Only include tasks started this week.
+    #[arg(long)]
+    pub week: bool,
+    /// Show this week's daily totals as a one-line sparkline, or with --rolling-average, a
+    /// block per day.
+    #[arg(long)]
+    pub sparkline: bool,
+    /// Show each day's total next to the average of it and the days before it, over this
+    /// many days.
+    #[arg(long, value_name = "DAYS", value_parser = clap::value_parser!(u16).range(1..))]
+    pub rolling_average: Option<u16>,
+    /// Show the past year's daily effort as a grid, like a GitHub contribution graph.
+    #[arg(long)]
+    pub heatmap: bool,
+    /// With --heatmap, show the time tracked in each hour of each day of the week instead.
+    #[arg(long, requires = "heatmap")]
+    pub by_hour_of_day: bool,
+    /// Show a sparkline for each of the last --weeks weeks.
+    #[arg(long)]
+    pub sparkline_week: bool,
+    /// How many weeks --sparkline-week shows, up to 52. `--since N` still works too.
+    #[arg(
+        long,
+        value_name = "WEEKS",
+        default_value_t = 4,
+        value_parser = clap::value_parser!(u8).range(1..=52)
+    )]
+    pub weeks: u8,
+    /// Only include tasks started at or after this local date, date and time, ISO week, or
+    /// number of days ago (e.g. 30d). With --sparkline-week, a plain number is the number of
+    /// weeks, like --weeks.
+    #[arg(long, value_name = "YYYY-MM-DD[THH:MM:SS]", value_parser = parse_report_since)]
+    pub since: Option<ReportSince>,
+    /// Only include tasks started on or before this local date, date and time, ISO week, or
+    /// number of days ago (e.g. 30d).
+    #[arg(long, value_name = "YYYY-MM-DD[THH:MM:SS]", value_parser = range::parse_until)]
+    pub until: Option<SystemTime>,
+    /// Show the hours tracked in each of the last 12 months, and whether they went up or down.
+    #[arg(long)]
+    pub monthly_trend: bool,
+    /// Show the current and longest runs of consecutive days with tracked time.
+    #[arg(long)]
+    pub streak: bool,
+    /// Show the task done on the most consecutive days, like a daily habit.
+    #[arg(long)]
+    pub longest_streak_task: bool,
+    /// Show how many tasks share each pair of tags.
+    #[arg(long)]
+    pub tags_co_occurrence: bool,
+    /// Show the time tracked on --tag in each week, to see how its use changes. With
+    /// --sparkline, each week is followed by a sparkline of its days.
+    #[arg(long, requires = "tag")]
+    pub by_tag_over_time: bool,
+    /// Show the average, earliest, and latest time the first task of the day started.
+    #[arg(long)]
+    pub first_task_time: bool,
+    /// Show the average, earliest, and latest time the last task of the day stopped.
+    #[arg(long)]
+    pub last_task_time: bool,
+    /// Only include tasks with this tag, which is also the tag --by-tag-over-time shows.
+    #[arg(long, value_name = "TAG")]
+    pub tag: Option<String>,
+    /// Show the time for each client, broken down by project and then by task.
+    #[arg(long)]
+    pub by_client_project: bool,
+    /// Show the average time tracked on each day of the week, over the days with time on them.
+    #[arg(long)]
+    pub by_weekday: bool,
+    /// Show how many times each task name was tracked, the most often first, with its average
+    /// duration and share of the time tracked.
+    #[arg(long)]
+    pub task_frequency: bool,
+    /// Leave task names tracked fewer than N times out of --task-frequency.
+    #[arg(long, value_name = "N", requires = "task_frequency")]
+    pub min_count: Option<u32>,
+    /// Show how much the duration of each task name varies, the most variable first, for names
+    /// tracked at least 3 times.
+    #[arg(long)]
+    pub task_consistency: bool,
+    /// List the tasks saved without a stop time, e.g. because the process tracking them was
+    /// killed, with how long ago they started. Fix them with `time-tracker fix --incomplete`.
+    #[arg(long)]
+    pub incomplete: bool,
+    /// Forecast the time each task will take over the next DAYS days, from its average over
+    /// the last 30.
+    #[arg(long, value_name = "DAYS")]
+    pub forecast: Option<u32>,
+    /// Show the tasks of a week (this week, or from --since) as a Markdown kanban board with a
+    /// column per day.
+    #[arg(long)]
+    pub kanban: bool,
+    /// The width of a --kanban column, in characters. Longer task names are cut short.
+    #[arg(long, value_name = "CHARS", default_value_t = 24, requires = "kanban")]
+    pub column_width: usize,
+    /// Show how the time tracked on each task compares to its daily goal in `task_goals`,
+    /// today or in --since/--until.
+    #[arg(long)]
+    pub delta: bool,
+    /// With --delta, compare to the goals (the only comparison so far, so the same as --delta).
+    #[arg(long, requires = "delta")]
+    pub compare_to_goal: bool,
+    /// Show how many tasks took each range of durations, as a histogram.
+    #[arg(long)]
+    pub distribution: bool,
+    /// The edges of the --distribution buckets, as durations ending with inf for no limit.
+    #[arg(long, value_name = "EDGES", default_value_t = BucketEdges::default())]
+    pub buckets: BucketEdges,
+    /// How wide the longest --distribution bar is, in characters.
+    #[arg(long, value_name = "COLUMNS", default_value_t = histogram::DEFAULT_BAR_WIDTH)]
+    pub bar_width: usize,
+    /// List tasks that were running at the same time, exiting with code 1 if there are any.
+    #[arg(long, conflicts_with = "normalize")]
+    pub overlap: bool,
+    /// List the periods of the work day when no task was running.
+    #[arg(long)]
+    pub gaps: bool,
+    /// Show the longest stretch of --date spent on tasks without a real break.
+    #[arg(long)]
+    pub focus: bool,
+    /// Compare the clock hours of --date, from the first task to the last, to the hours
+    /// tracked.
+    #[arg(long)]
+    pub active_hours: bool,
+    /// With --active-hours, leave out gaps between tasks shorter than this many minutes.
+    #[arg(
+        long,
+        value_name = "MINUTES",
+        default_value_t = 5,
+        requires = "active_hours"
+    )]
+    pub threshold: u64,
+    /// Show when each task of --date ran, as a Gantt chart.
+    #[arg(long)]
+    pub gantt: bool,
+    /// How wide --gantt is, in characters (defaults to the terminal width, or 80).
+    #[arg(long, value_name = "COLUMNS", requires = "gantt")]
+    pub width: Option<usize>,
+    /// Compare the time tracked on --date to the length of the work day.
+    #[arg(long)]
+    pub time_utilization: bool,
+    /// When the work day starts, for --gaps and --time-utilization.
+    #[arg(
+        long,
+        visible_alias = "start",
+        value_name = "HH:MM",
+        value_parser = parse_time,
+        default_value = "09:00"
+    )]
+    pub workday_start: NaiveTime,
+    /// When the work day ends, for --gaps and --time-utilization.
+    #[arg(
+        long,
+        visible_alias = "end",
+        value_name = "HH:MM",
+        value_parser = parse_time,
+        default_value = "18:00"
+    )]
+    pub workday_end: NaiveTime,
+    /// The day to look for gaps, utilization, or focus on, or to chart (defaults to today).
+    #[arg(long, value_name = "YYYY-MM-DD")]
+    pub date: Option<NaiveDate>,
+    /// Only include tasks with this name.
+    #[arg(long)]
+    pub name: Option<String>,
+    /// Leave out tasks shorter than this, like 1m, e.g. ones started by accident.
+    #[arg(long, value_name = "DURATION", value_parser = parse_natural_duration)]
+    pub exclude_short: Option<u64>,
+    /// Only include billable tasks.
+    #[arg(long, conflicts_with = "non_billable_only")]
+    pub billable_only: bool,
+    /// Only include non-billable tasks.
+    #[arg(long)]
+    pub non_billable_only: bool,
+    /// Print the JSON Schema of the history file format.
+    #[arg(long)]
+    pub json_schema: bool,
+    /// Pretty-print JSON output with two-space indentation.
+    #[arg(long, requires = "json_schema")]
+    pub pretty: bool,
+    /// Print a plain-text invoice of the billable time in --period.
+    #[arg(long, requires = "client")]
+    pub invoice: bool,
+    /// Only include tasks for this client, which is also the client --invoice and
+    /// --current-period bill.
+    #[arg(long)]
+    pub client: Option<String>,
+    /// Print an invoice for the client's current billing period, as set under
+    /// client_billing_periods in config.toml.
+    #[arg(long, requires = "client", conflicts_with = "period")]
+    pub current_period: bool,
+    /// Show how many tasks were completed and how quickly, in --period.
+    #[arg(long)]
+    pub rate_of_work: bool,
+    /// The period to report on: today, week, an ISO week as YYYY-Www, or a month as YYYY-MM.
+    /// --invoice needs a month and defaults to the current one; --rate-of-work defaults to today.
+    #[arg(long, value_name = "PERIOD")]
+    pub period: Option<Period>,
+    /// Compare the time spent on each task in two periods, e.g. 2024-W01 2024-W02.
+    #[arg(long, num_args = 2, value_names = ["PERIOD1", "PERIOD2"])]
+    pub compare: Option<Vec<Period>>,
+    /// Show the time spent on each git commit named in a task (e.g. `abc1234 fix login bug`),
+    /// looked up in the repository in the current directory. Combines with --period.
+    #[cfg(feature = "git-integration")]
+    #[arg(long)]
+    pub per_commit: bool,
+    /// List the N longest tasks, combined with the other filters and --period.
+    #[arg(long, value_name = "N")]
+    pub top: Option<usize>,
+    /// List when each task started and stopped, grouped by day, in UTC or --timezone. With a
+    /// report of times of day (e.g. --gantt or --first-task-time), show its times there.
+    #[arg(long)]
+    pub normalize: bool,
+    /// The time zone --normalize shows times and days in, e.g. America/Chicago.
+    #[arg(long, value_name = "TZ", requires = "normalize")]
+    pub timezone: Option<Tz>,
+    /// Show how time flows from tasks to their projects to their clients.
+    #[arg(long)]
+    pub sankey: bool,
+    /// List the longest tasks that together took at least this percent of the time.
+    #[arg(
+        long,
+        value_name = "PERCENT",
+        value_parser = clap::value_parser!(u8).range(1..=100)
+    )]
+    pub percentile: Option<u8>,
+    /// Show the story points completed each week, read from names like `PROJ-1 (5pts) login`.
+    #[arg(long)]
+    pub velocity: bool,
+    /// With --velocity, only show this sprint (ISO week), e.g. 2024-W03.
+    #[arg(long, value_name = "YYYY-Www", value_parser = range::parse_week, requires = "velocity")]
+    pub sprint: Option<NaiveDate>,
+    /// Show the totals in groups, e.g. by the repository each task was started in.
+    #[arg(long, value_name = "GROUP")]
+    pub group_by: Option<GroupBy>,
+    /// The file to write to instead of stdout.
+    #[arg(long, value_name = "PATH")]
+    pub output: Option<PathBuf>,
+}
+
+/// The ways `time-tracker report --group-by` can group tasks.
+#[derive(Clone, Copy, ValueEnum)]
+pub enum GroupBy {
+    /// The git repository (or directory) each task was started in.
+    Context,
+}
+
+impl ReportArgs {
+    /// Gets the filter that `--since`, `--until`, `--name`, `--tag`, `--client`, and
+    /// `--exclude-short` select.
+    fn task_filter(&self) -> TaskFilter {
+        TaskFilter {
+            range: DateRange {
+                start: match self.since {
+                    Some(ReportSince::Time(since)) => Some(since),
+                    Some(ReportSince::Weeks(_)) | None => None,
+                },
+                end: self.until,
+            },
+            name: self.name.clone(),
+            tag: self.tag.clone(),
+            client: self.client.clone(),
+            min_duration_secs: self.exclude_short,
+            max_duration_secs: None,
+        }
+    }
+
+    /// Gets the filter selected by `--billable-only` or `--non-billable-only`.
+    fn billable_filter(&self) -> BillableFilter {
+        if self.billable_only {
+            BillableFilter::BillableOnly
+        } else if self.non_billable_only {
+            BillableFilter::NonBillableOnly
+        } else {
+            BillableFilter::All
+        }
+    }
+
+    /// Gets how many weeks `--sparkline-week` shows, from `--weeks` or a number given to
+    /// `--since`.
+    fn sparkline_weeks(&self) -> u8 {
+        match self.since {
+            Some(ReportSince::Weeks(weeks)) => weeks,
+            _ => self.weeks,
+        }
+    }
+}
+
+/// What `report --since` was given.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ReportSince {
+    /// The start of the tasks to report on.
+    Time(SystemTime),
+    /// The number of weeks `--sparkline-week` shows, which `--since` took before `--weeks`.
+    Weeks(u8),
+}
+
+/// Parses `report --since`: a start like [`range::parse_since`] takes, or a number of weeks
+/// from 1 to 52.
+fn parse_report_since(value: &str) -> Result<ReportSince, String> {
+    match value.parse::<u8>() {
+        Ok(weeks @ 1..=52) => Ok(ReportSince::Weeks(weeks)),
+        Ok(_) => Err(String::from("a number of weeks must be from 1 to 52")),
+        Err(_) => range::parse_since(value).map(ReportSince::Time),
+    }
+}
+
+/// Parses a time of day written as `HH:MM`.
+fn parse_time(value: &str) -> Result<NaiveTime, String> {
+    NaiveTime::parse_from_str(value, "%H:%M").map_err(|e| format!("expected HH:MM: {}", e))
+}
+
+/// The formats supported by `time-tracker export`.
+#[derive(Clone, Copy, ValueEnum)]
+pub enum ExportFormat {
+    /// A JSON array of tasks.
+    Json,
+    /// Comma-separated values with a header row.
+    Csv,
+    /// Tab-separated values with a header row.
+    Tsv,
+    /// Timewarrior monthly data files (requires --output-dir).
+    Timewarrior,
+    /// One JSON object per line, each with a `timestamp`, for log aggregators.
+    Ndjson,
+    /// Prometheus metrics, including the running tasks.
+    Prometheus,
+    /// An Org-mode clocktable of the time spent on each task.
+    OrgClocktable,
+    /// An Anki deck with a card per task: its name on the front, the time on the back.
+    Anki,
+    /// JUnit XML with a test case per task, failing tasks over `max_task_duration_secs`.
+    JenkinsJunit,
+    /// A Google Calendar CSV import with an event per task, at least 15 minutes long.
+    Gcal,
+    /// An AppleScript that adds an event per task to a macOS Calendar calendar.
+    Applescript,
+    /// A Notion database CSV import with a row per task, its hours, tags, and client.
+    Notion,
+    /// A Harvest time entry CSV import, with fallbacks for tasks without a client or project.
+    HarvestCsv,
+    /// Tempo Timesheets worklogs, logged against the JIRA issue key in each task name.
+    TempoJson,
+    /// A Toggl Track bulk create request body (requires --workspace-id).
+    TogglApiJson,
+    /// A bash script that creates a Clockify time entry per task with curl (requires
+    /// --workspace-id), or just the entries with --json-only.
+    ClockifyApiJson,
+    /// An OpenDocument spreadsheet with a row per task, for LibreOffice Calc (requires --output
+    /// when stdout is a terminal).
+    Ods,
+    /// An OPF package listing each task as a chapter of a writing log, with --output-dir also
+    /// the chapters' content documents.
+    Opf,
+    /// A Ledger journal with a transaction per task, priced at `hourly_rate` when billable.
+    Ledger,
+    /// A JSON Feed with an item per completed task, for feed readers.
+    JsonFeed,
+    /// An iCalendar file with a weekly recurring event per task done at about the same time on
+    /// --min-occurrences days, and an event for each other task.
+    IcsRecurring,
+}
+
+impl ExportFormat {
+    /// Gets the extension of the files of a split export in the format.
+    fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Json => "json",
+            ExportFormat::Csv => "csv",
+            ExportFormat::Tsv => "tsv",
+            ExportFormat::Timewarrior => "data",
+            ExportFormat::Ndjson => "ndjson",
+            ExportFormat::Prometheus => "prom",
+            ExportFormat::OrgClocktable => "org",
+            ExportFormat::Anki => "txt",
+            ExportFormat::JenkinsJunit => "xml",
+            ExportFormat::Gcal => "csv",
+            ExportFormat::Applescript => "applescript",
+            ExportFormat::Notion => "csv",
+            ExportFormat::HarvestCsv => "csv",
+            ExportFormat::TempoJson => "json",
+            ExportFormat::TogglApiJson => "json",
+            ExportFormat::ClockifyApiJson => "sh",
+            ExportFormat::Ods => "ods",
+            ExportFormat::Opf => "opf",
+            ExportFormat::Ledger => "ledger",
+            ExportFormat::JsonFeed => "json",
+            ExportFormat::IcsRecurring => "ics",
+        }
+    }
+}
+
+/// Runs a subcommand.
+pub fn run(command: Command, config: &Config) {
+    match command {
+        Command::Export(args) => export(args, config),
+        Command::Config(ConfigCommand::Show) => config_show(config),
+        Command::Config(ConfigCommand::Validate) => config_validate(config),
+        Command::Migrate(args) => migrate(args, config),
+        Command::Doctor(args) => doctor(args, config),
+        Command::Lint(args) => lint(args, config),
+        Command::Report(args) => report(*args, config),
+        Command::Start(args) => start(args, config),
+        Command::Alias(args) => alias(args),
+        Command::Template(command) => template(command, config),
+        Command::Check(args) => check(args, config),
+        Command::Summary(args) => summary(args, config),
+        Command::List => list(config),
+        Command::Serve(args) => serve(args, config),
+        Command::Edit(args) => edit(args, config),
+        Command::Delete(args) => delete(args, config),
+        Command::Fix(args) => fix(args, config),
+        Command::Break(args) => take_break(args),
+        Command::Import(args) => import(args, config),
+        Command::Pause(PauseCommand::All) => pause_all(config),
+        Command::Resume(ResumeCommand::All) => resume_all(config),
+        Command::Daemon(DaemonCommand::Status) => daemon_status(config),
+    }
+}
+
+/// Writes the task history in the requested format to `--output` or stdout.
+fn export(args: ExportArgs, config: &Config) {
+    let range = DateRange {
+        start: args.since,
+        end: None,
+    };
+    let history = || -> Vec<StoredTask> {
+        load_history(config)
+            .into_iter()
+            .filter(|task| range.contains(task))
+            .collect()
+    };
+    if matches!(args.format, ExportFormat::Timewarrior) {
+        if args.output.is_some() {
+            eprintln!("Error: use --output-dir rather than --output for --format timewarrior.");
+            std::process::exit(1);
+        }
+        if args.split_by.is_some() {
+            eprintln!("Error: --format timewarrior is already split by month.");
+            std::process::exit(1);
+        }
+        return export_timewarrior(args, &history());
+    }
+    if matches!(args.format, ExportFormat::Ods) {
+        if args.split_by.is_some() || args.follow {
+            eprintln!("Error: --split-by and --follow don't work with --format ods.");
+            std::process::exit(1);
+        }
+        return export_ods(args, &history());
+    }
+    if let (ExportFormat::Opf, None, Some(output_dir)) =
+        (args.format, args.split_by, args.output_dir.as_deref())
+    {
+        return export_writing_log(output_dir, &history());
+    }
+    if (args.json_only || args.api_key.is_some())
+        && !matches!(args.format, ExportFormat::ClockifyApiJson)
+    {
+        eprintln!("Error: --json-only and --api-key only work with --format clockify-api-json.");
+        std::process::exit(1);
+    }
+    if matches!(args.format, ExportFormat::Prometheus) && args.split_by.is_some() {
+        eprintln!("Error: --split-by doesn't work with --format prometheus.");
+        std::process::exit(1);
+    }
+    if args.since.is_some() && (args.follow || matches!(args.format, ExportFormat::Prometheus)) {
+        eprintln!("Error: --since doesn't work with --follow or --format prometheus.");
+        std::process::exit(1);
+    }
+    let mut out = match output_writer(args.output.as_deref()) {
+        Ok(out) => out,
+        Err(e) => {
+            eprintln!("Error opening output: {}", e);
+            std::process::exit(1);
+        }
+    };
+    if args.follow {
+        return follow(args, config, out);
+    }
+    if matches!(args.format, ExportFormat::Prometheus) {
+        return export_prometheus(config, out);
+    }
+    let tasks = history();
+    let formatter: Box<dyn Formatter> = match args.format {
+        ExportFormat::Json => Box::new(JsonFormatter {
+            pretty: args.pretty,
+        }),
+        ExportFormat::Csv => Box::new(CsvFormatter),
+        ExportFormat::Tsv => Box::new(TsvFormatter),
+        ExportFormat::Ndjson => Box::new(NdjsonFormatter),
+        ExportFormat::Timewarrior | ExportFormat::Prometheus | ExportFormat::Ods => {
+            unreachable!("handled above")
+        }
+        ExportFormat::OrgClocktable => Box::new(OrgClocktableFormatter {
+            scope: args.org_scope,
+        }),
+        ExportFormat::Anki => Box::new(AnkiFormatter),
+        ExportFormat::JenkinsJunit => Box::new(JunitFormatter {
+            estimate_secs: config.max_task_duration_secs.value,
+        }),
+        ExportFormat::Gcal => Box::new(GoogleCalendarFormatter::default()),
+        ExportFormat::Applescript => Box::new(AppleScriptFormatter {
+            calendar: args.calendar,
+            zone: None,
+        }),
+        ExportFormat::Notion => Box::new(NotionFormatter::default()),
+        ExportFormat::HarvestCsv => {
+            for task in HarvestCsvFormatter::too_long(&tasks) {
+                eprintln!(
+                    "Warning: '{}' on {} is {:.2} hours, more than Harvest accepts for one entry.",
+                    task.name,
+                    reports::local_date(task.started_at),
+                    task.seconds as f64 / 3600.0
+                );
+            }
+            Box::new(HarvestCsvFormatter {
+                default_client: config.default_harvest_client.value.clone(),
+                default_project: config.default_harvest_project.value.clone(),
+                zone: None,
+            })
+        }
+        ExportFormat::TempoJson => Box::new(TempoFormatter {
+            default_issue: config.tempo_default_issue.value.clone(),
+            pretty: args.pretty,
+            zone: None,
+        }),
+        ExportFormat::TogglApiJson => {
+            let Some(workspace_id) = args.workspace_id.as_deref() else {
+                eprintln!("Error: --format toggl-api-json needs --workspace-id.");
+                std::process::exit(1);
+            };
+            let Ok(workspace_id) = workspace_id.parse() else {
+                eprintln!("Error: a Toggl Track --workspace-id is a number.");
+                std::process::exit(1);
+            };
+            Box::new(TogglApiFormatter {
+                workspace_id,
+                project_id: config.toggl_default_project_id.value,
+            })
+        }
+        ExportFormat::Opf => Box::new(OpfFormatter),
+        ExportFormat::Ledger => Box::new(LedgerFormatter {
+            hourly_rate_cents: config.hourly_rate_cents(),
+            currency_symbol: config.currency_symbol.value.clone(),
+            zone: None,
+        }),
+        ExportFormat::JsonFeed => Box::new(JsonFeedFormatter {
+            home_page_url: config.feed_home_page_url.value.clone(),
+        }),
+        ExportFormat::IcsRecurring => Box::new(IcsRecurringFormatter {
+            detector: RecurringEventDetector {
+                min_occurrences: args.min_occurrences,
+                ..RecurringEventDetector::default()
+            },
+        }),
+        ExportFormat::ClockifyApiJson if args.json_only => {
+            Box::new(ClockifyJsonFormatter::default())
+        }
+        ExportFormat::ClockifyApiJson => {
+            let Some(workspace_id) = args.workspace_id.clone() else {
+                eprintln!("Error: --format clockify-api-json needs --workspace-id.");
+                std::process::exit(1);
+            };
+            Box::new(ClockifyScriptFormatter {
+                workspace_id,
+                api_key: args.api_key.clone(),
+                project_id: None,
+            })
+        }
+    };
+    if let (Some(split_by), Some(output_dir)) = (args.split_by, args.output_dir) {
+        let exporter = SplitExporter {
+            formatter,
+            split_by,
+            output_dir,
+            extension: if args.json_only {
+                String::from("json")
+            } else {
+                args.format.extension().to_string()
+            },
+        };
+        return export_split(&exporter, &tasks, args.overwrite);
+    }
+    if let Err(e) = write!(out, "{}", formatter.format_output(&tasks)).and_then(|()| out.flush()) {
+        eprintln!("Error writing export: {}", e);
+        std::process::exit(1);
+    }
+}
+
+/// Writes the Prometheus metrics of the history and the running tasks to `out`.
+fn export_prometheus(config: &Config, mut out: Box<dyn Write>) {
+    let tasks = match metrics_tasks(config) {
+        Ok(tasks) => tasks,
+        Err(e) => {
+            eprintln!("Error reading history: {}", e);
+            std::process::exit(1);
+        }
+    };
+    if let Err(e) =
+        prometheus::write_prometheus_metrics(&mut out, &tasks).and_then(|()| out.flush())
+    {
+        eprintln!("Error writing metrics: {}", e);
+        std::process::exit(1);
+    }
+}
+
+/// Moves the tasks tracked with the wrong time zone so their local times are read in the right
+/// one, after copying the history file to `<file>.bak`.
+fn edit(args: EditArgs, config: &Config) {
+    if config.storage.value != StorageFormat::Json {
+        eprintln!("Error: edit only works with JSON storage.");
+        std::process::exit(1);
+    }
+    let history = JsonStorage::new(&config.history_file.value);
+    let mut tasks = match history.load() {
+        Ok(tasks) => tasks,
+        Err(e) => {
+            eprintln!("Error reading history: {}", e);
+            std::process::exit(1);
+        }
+    };
+    let only = (args.id.is_some() || args.interactive)
+        .then(|| pick_task(&tasks, args.id.as_deref(), &FuzzySelector).id);
+
+    let mut shifted = 0;
+    for task in &mut tasks {
+        if only.is_some_and(|id| task.id != id) {
+            continue;
+        }
+        let started_on = DateTime::<chrono::Utc>::from(task.started_at)
+            .with_timezone(&args.time_zone)
+            .date_naive();
+        if args.after.is_some_and(|after| started_on < after) {
+            continue;
+        }
+        timezone::reinterpret_task(task, &args.time_zone, &args.shift_to);
+        shifted += 1;
+    }
+    if shifted == 0 {
+        println!("No tasks to shift.");
+        return;
+    }
+
+    let backup_path = storage::backup_path(history.path());
+    if let Err(e) = std::fs::copy(history.path(), &backup_path) {
+        eprintln!("Error backing up {}: {}", history.path().display(), e);
+        std::process::exit(1);
+    }
+    if let Err(e) = history.replace_all(&tasks) {
+        eprintln!("Error writing history: {}", e);
+        std::process::exit(1);
+    }
+    println!(
+        "Shifted {} tasks from {} to {}. The old history was copied to {}.",
+        shifted,
+        args.time_zone,
+        args.shift_to,
+        backup_path.display()
+    );
+}
+
+/// Deletes the task with the given id or, in a terminal, the task the user picks.
+fn delete(args: DeleteArgs, config: &Config) {
+    if config.storage.value != StorageFormat::Json {
+        eprintln!("Error: delete only works with JSON storage.");
+        std::process::exit(1);
+    }
+    if args.id.is_none() && !args.interactive && !io::stdin().is_terminal() {
+        eprintln!("Error: give the id of the task to delete, as shown by `time-tracker list`.");
+        std::process::exit(1);
+    }
+    let history = JsonStorage::new(&config.history_file.value);
+    let mut tasks = match history.load() {
+        Ok(tasks) => tasks,
+        Err(e) => {
+            eprintln!("Error reading history: {}", e);
+            std::process::exit(1);
+        }
+    };
+    let task = pick_task(&tasks, args.id.as_deref(), &FuzzySelector).clone();
+    tasks.retain(|t| t.id != task.id);
+    if let Err(e) = history.replace_all(&tasks) {
+        eprintln!("Error writing history: {}", e);
+        std::process::exit(1);
+    }
+    println!("Deleted {} ({}).", task.name, task.short_id());
+}
+
+/// Stops or deletes the tasks saved without a stop time, all the same way or, with
+/// `--interactive`, as the user picks for each one.
+fn fix(args: FixArgs, config: &Config) {
+    if config.storage.value != StorageFormat::Json {
+        eprintln!("Error: fix only works with JSON storage.");
+        std::process::exit(1);
+    }
+    if args.interactive && !io::stdin().is_terminal() {
+        eprintln!("Error: --interactive needs a terminal.");
+        std::process::exit(1);
+    }
+    let history = JsonStorage::new(&config.history_file.value);
+    let mut tasks = match history.load() {
+        Ok(tasks) => tasks,
+        Err(e) => {
+            eprintln!("Error reading history: {}", e);
+            std::process::exit(1);
+        }
+    };
+    let resolution = if args.stop_at_started_at {
+        Some(Resolution::StopAtStart)
+    } else if args.stop_now {
+        Some(Resolution::StopNow)
+    } else if args.delete {
+        Some(Resolution::Delete)
+    } else {
+        None
+    };
+    let fixed = incomplete::fix(&mut tasks, SystemTime::now(), |task| {
+        resolution.or_else(|| prompt_resolution(task))
+    });
+    if fixed == 0 {
+        println!("No incomplete tasks to fix.");
+        return;
+    }
+    if let Err(e) = history.replace_all(&tasks) {
+        eprintln!("Error writing history: {}", e);
+        std::process::exit(1);
+    }
+    println!(
+        "Fixed {} incomplete task{}.",
+        fixed,
+        if fixed == 1 { "" } else { "s" }
+    );
+}
+
+/// Asks the user what to do with a task saved without a stop time. Esc leaves it as it is.
+fn prompt_resolution(task: &IncompleteTask) -> Option<Resolution> {
+    let choices = [
+        (
+            Resolution::StopAtStart,
+            String::from("Stop it when it started (0s)"),
+        ),
+        (
+            Resolution::StopNow,
+            format!(
+                "Stop it now ({})",
+                format_duration_compact(task.elapsed_secs)
+            ),
+        ),
+        (Resolution::Delete, String::from("Delete it")),
+    ];
+    let labels: Vec<&str> = choices.iter().map(|(_, label)| label.as_str()).collect();
+    let index = Select::new()
+        .with_prompt(task.to_string())
+        .items(&labels)
+        .default(0)
+        .interact_opt()
+        .ok()??;
+    Some(choices[index].0)
+}
+
+/// Finds the task with the id `id` or, without one, asks `selector` for a task. Exits the
+/// program if there is no such task or none was picked.
+fn pick_task<'a>(
+    tasks: &'a [StoredTask],
+    id: Option<&str>,
+    selector: &dyn TaskSelector,
+) -> &'a StoredTask {
+    match selector::pick_task(tasks, id, selector) {
+        Ok(task) => task,
+        Err(e) => {
+            eprintln!("Error: {}.", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Serves the Prometheus metrics at `http://localhost:<port>/metrics`.
+fn serve(args: ServeArgs, config: &Config) {
+    let listener = match TcpListener::bind(("127.0.0.1", args.metrics_port)) {
+        Ok(listener) => listener,
+        Err(e) => {
+            eprintln!("Error listening on port {}: {}", args.metrics_port, e);
+            std::process::exit(1);
+        }
+    };
+    println!(
+        "Serving metrics at http://localhost:{}/metrics",
+        args.metrics_port
+    );
+    if let Err(e) = prometheus::serve_metrics(listener, || metrics_tasks(config)) {
+        eprintln!("Error serving metrics: {}", e);
+        std::process::exit(1);
+    }
+}
+
+/// Reads the tasks the metrics are made from: the history followed by the running tasks.
+fn metrics_tasks(config: &Config) -> io::Result<Vec<StoredTask>> {
+    let mut tasks = config.open_storage()?.load()?;
+    tasks.extend(
+        running::load(&config.running_file.value)?
+            .into_iter()
+            .map(|running| running.task),
+    );
+    Ok(tasks)
+}
+
+/// Writes every task as NDJSON, then keeps watching the history file and writes tasks as they
+/// are appended to it, until the program is interrupted.
+fn follow(args: ExportArgs, config: &Config, out: Box<dyn Write>) {
+    if !matches!(args.format, ExportFormat::Ndjson) {
+        eprintln!("Error: --follow only works with --format ndjson.");
+        std::process::exit(1);
+    }
+    if config.storage.value != StorageFormat::Json {
+        eprintln!("Error: --follow only works with JSON storage.");
+        std::process::exit(1);
+    }
+    let history = JsonStorage::new(&config.history_file.value);
+    if let Err(e) = follow_history(&history, out) {
+        eprintln!("Error following {}: {}", history.path().display(), e);
+        std::process::exit(1);
+    }
+}
+
+/// Writes the tasks in `history` to `out` as NDJSON as they are written, waiting for changes
+/// with the operating system's file notifications rather than polling.
+fn follow_history(
+    history: &JsonStorage,
+    mut out: Box<dyn Write>,
+) -> Result<(), Box<dyn std::error::Error>> {
+    let path = history.path();
+    // the history file can be replaced rather than appended to, so watch its directory
+    let dir = match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => parent,
+        _ => Path::new("."),
+    };
+    let (sender, events) = mpsc::channel();
+    let mut watcher = notify::recommended_watcher(sender)?;
+    watcher.watch(dir, RecursiveMode::NonRecursive)?;
+
+    let mut offset = 0;
+    let mut print_new_tasks = || -> io::Result<()> {
+        let (tasks, next) = history.read_from(offset)?;
+        offset = next;
+        for task in &tasks {
+            writeln!(out, "{}", NdjsonFormatter.format_task(task))?;
+        }
+        out.flush()
+    };
+    print_new_tasks()?;
+    for event in events {
+        if event?
+            .paths
+            .iter()
+            .any(|changed| changed.file_name() == path.file_name())
+        {
+            print_new_tasks()?;
+        }
+    }
+    Ok(())
+}
+
+/// Writes the tasks into Timewarrior's monthly data files in `--output-dir`.
+fn export_timewarrior(args: ExportArgs, tasks: &[StoredTask]) {
+    let Some(output_dir) = args.output_dir else {
+        eprintln!("Error: --output-dir is required for --format timewarrior.");
+        std::process::exit(1);
+    };
+    match timewarrior::write_timewarrior_files(&output_dir, tasks) {
+        Ok(paths) => println!(
+            "Exported {} tasks to {} files in {}.",
+            tasks.len(),
+            paths.len(),
+            output_dir.display()
+        ),
+        Err(e) => {
+            eprintln!("Error writing Timewarrior data: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Writes an ODS spreadsheet to `--output`, or to stdout unless it is a terminal.
+fn export_ods(args: ExportArgs, tasks: &[StoredTask]) {
+    if args.output.is_none() && io::stdout().is_terminal() {
+        eprintln!("Error: --format ods writes a binary file, use --output report.ods.");
+        std::process::exit(1);
+    }
+    let written = ods::to_ods(tasks).and_then(|bytes| {
+        let mut out = output_writer(args.output.as_deref())?;
+        out.write_all(&bytes)?;
+        out.flush()
+    });
+    if let Err(e) = written {
+        eprintln!("Error writing spreadsheet: {}", e);
+        std::process::exit(1);
+    }
+}
+
+/// Writes an OPF package to `content.opf` in `output_dir`, next to the content document of each
+/// chapter.
+fn export_writing_log(output_dir: &Path, tasks: &[StoredTask]) {
+    let mut files = OpfFormatter.content_documents(tasks);
+    files.push((String::from("content.opf"), OpfFormatter.format_list(tasks)));
+    let written = std::fs::create_dir_all(output_dir).and_then(|()| {
+        files.iter().try_for_each(|(name, xml)| {
+            let path = output_dir.join(name);
+            std::fs::write(&path, format!("{}\n", xml))
+                .map_err(|e| io::Error::new(e.kind(), format!("{}: {}", path.display(), e)))
+        })
+    });
+    match written {
+        Ok(()) => println!(
+            "Exported {} chapters to {}.",
+            tasks.len(),
+            output_dir.join("content.opf").display()
+        ),
+        Err(e) => {
+            eprintln!("Error writing writing log: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Writes a file per period, unless that would write over a file and `overwrite` isn't set.
+fn export_split(exporter: &SplitExporter, tasks: &[StoredTask], overwrite: bool) {
+    if !overwrite {
+        let conflicts = exporter.conflicts(tasks);
+        for path in &conflicts {
+            eprintln!(
+                "Error: {} already exists, use --overwrite to replace it.",
+                path.display()
+            );
+        }
+        if !conflicts.is_empty() {
+            std::process::exit(1);
+        }
+    }
+    match exporter.write(tasks) {
+        Ok(paths) => println!(
+            "Exported {} tasks to {} files in {}.",
+            tasks.len(),
+            paths.len(),
+            exporter.output_dir.display()
+        ),
+        Err(e) => {
+            eprintln!("Error writing export: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Adds the tasks of a Redmine export to the history, billable when an hourly rate is set.
+fn import(args: ImportArgs, config: &Config) {
+    let tasks = match std::fs::read_to_string(&args.redmine) {
+        Ok(csv) => RedmineImporter::parse(&csv),
+        Err(e) => Err(e.to_string()),
+    };
+    let mut tasks = match tasks {
+        Ok(tasks) => tasks,
+        Err(e) => {
+            eprintln!("Error reading {}: {}", args.redmine.display(), e);
+            std::process::exit(1);
+        }
+    };
+    for task in &mut tasks {
+        task.billable = config.hourly_rate.value.is_some();
+    }
+    if let Err(e) = config
+        .open_storage()
+        .and_then(|mut storage| storage.append_all(&tasks))
+    {
+        eprintln!("Error saving tasks: {}", e);
+        std::process::exit(1);
+    }
+    println!(
+        "Imported {} tasks from {}.",
+        tasks.len(),
+        args.redmine.display()
+    );
+}
+
+/// Pauses every running task, all or nothing.
+fn pause_all(config: &Config) {
+    match PauseAllOperation::apply(&config.running_file.value, SystemTime::now()) {
+        Ok(count) => println!("Paused {} tasks.", count),
+        Err(e) => {
+            eprintln!("Error pausing tasks: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Prints the status of the daemon, or exits with code 1 if it isn't running.
+fn daemon_status(config: &Config) {
+    match daemon::query_status(&config.daemon_socket.value) {
+        Ok(status) => println!("{}", status),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => {
+            println!("Daemon not running.");
+            std::process::exit(1);
+        }
+        Err(e) => {
+            eprintln!("Error asking the daemon for its status: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Resumes every paused task, all or nothing.
+fn resume_all(config: &Config) {
+    match ResumeAllOperation::apply(&config.running_file.value, SystemTime::now()) {
+        Ok(count) => println!("Resumed {} tasks.", count),
+        Err(e) => {
+            eprintln!("Error resuming tasks: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
+
+/// Prints the active configuration, one `key = value [source]` line per setting.
+fn config_show(config: &Config) {
+    println!("# {}", config::config_file_path().display());
+    for (key, value, source) in config.entries() {
+        println!("{} = {} {}", key, value, source);
+    }
+}
+
+/// Prints an `ERROR:` line for every setting that is out of range, exiting with 1 if there
+/// are any.
+fn config_validate(config: &Config) {
+    let errors = config.validate();
+    for error in &errors {
+        eprintln!("ERROR: {}", error);
+    }
+    if !errors.is_empty() {
+        std::process::exit(1);
+    }
+    println!("{}: OK", config::config_file_path().display());
+}
+
+/// Copies every task from one storage backend to another, then renames the old file to
+/// `<file>.migrated` so it is kept as a backup.
+///
+/// If anything goes wrong the destination is left unchanged and the source isn't renamed.
+fn migrate(args: MigrateArgs, config: &Config) {
+    if args.from == args.to {
+        eprintln!("Error: --from and --to must be different storage formats.");
+        std::process::exit(1);
+    }
+
+    let source_path = config.storage_path(args.from);
+    if !source_path.exists() {
+        println!(
+            "Nothing to migrate: {} does not exist.",
+            source_path.display()
+        );
+        return;
+    }
+
+    let result = storage::open(args.from, source_path).and_then(|source| {
+        if args.dry_run {
+            return source.load().map(|tasks| tasks.len());
+        }
+        let mut destination = storage::open(args.to, config.storage_path(args.to))?;
+        storage::migrate(source.as_ref(), destination.as_mut())
+    });
+    let count = match result {
+        Ok(count) => count,
+        Err(e) => {
+            eprintln!("Error migrating tasks: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    if args.dry_run {
+        println!(
+            "Would migrate {} tasks from {} to {}.",
+            count, args.from, args.to
+        );
+        return;
+    }
+
+    let backup_path = storage::migrated_path(source_path);
+    if let Err(e) = std::fs::rename(source_path, &backup_path) {
+        eprintln!(
+            "Migrated {} tasks, but could not rename {}: {}",
+            count,
+            source_path.display(),
+            e
+        );
+        std::process::exit(1);
+    }
+    println!(
+        "Migrated {} tasks from {} to {}. The old file was renamed to {}.",
+        count,
+        args.from,
+        args.to,
+        backup_path.display()
+    );
+    if config.storage.value != args.to {
+        println!("Set `storage = \"{}\"` in config.toml to use it.", args.to);
+    }
+}
+
+/// Checks the configured storage backend, optionally repairing it, and exits with code 1 if any
+/// check fails.
+fn doctor(args: DoctorArgs, config: &Config) {
+    let format = config.storage.value;
+    let path = config.storage_path(format);
+    let (mut contents, mut storage) = if format == StorageFormat::Json {
+        match std::fs::read_to_string(path) {
+            Ok(contents) => (contents, None),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => (String::new(), None),
+            Err(e) => {
+                eprintln!("Error reading {}: {}", path.display(), e);
+                std::process::exit(1);
+            }
+        }
+    } else {
+        // the other backends can't hold lines that don't parse, so a history that won't load is
+        // the only problem the line checks can't find
+        match config.open_storage().and_then(|storage| {
+            let tasks = storage.load()?;
+            Ok((doctor::history_lines(&tasks), Some(storage)))
+        }) {
+            Ok(loaded) => loaded,
+            Err(e) => {
+                println!("[FAIL] the {} history loads: {}", format, e);
+                std::process::exit(1);
+            }
+        }
+    };
+
+    if args.fix {
+        let repaired = doctor::repair(&contents);
+        if repaired != contents {
+            let written = match storage.as_mut() {
+                None => storage::write_atomically(path, repaired.as_bytes()),
+                Some(storage) => doctor::parse_tasks(&repaired)
+                    .map_err(std::io::Error::from)
+                    .and_then(|tasks| storage.replace_all(&tasks)),
+            };
+            if let Err(e) = written {
+                eprintln!("Error writing {}: {}", path.display(), e);
+                std::process::exit(1);
+            }
+            println!("Repaired {}.", path.display());
+            contents = repaired;
+        }
+    }
+
+    let diagnostics = doctor::diagnose(&contents);
+    for diagnostic in &diagnostics {
+        println!("{}", diagnostic);
+    }
+    if diagnostics
+        .iter()
+        .any(|diagnostic| diagnostic.status == DiagnosticStatus::Fail)
+    {
+        std::process::exit(1);
+    }
+}
+
+/// Prints every task name that breaks a rule in the rules file, exiting with code 1 if there
+/// are any.
+fn lint(args: LintArgs, config: &Config) {
+    let path = args
+        .rules
+        .unwrap_or_else(|| storage::data_dir().join("lint.toml"));
+    let rules = match lint::load_rules(&path) {
+        Ok(rules) => rules,
+        Err(e) => {
+            eprintln!("Error reading lint rules: {}", e);
+            std::process::exit(1);
+        }
+    };
+    let range = DateRange {
+        start: args.since,
+        end: args.until,
+    };
+    if range.is_empty() {
+        eprintln!("Error: --until must not be before --since.");
+        std::process::exit(1);
+    }
+    let tasks: Vec<StoredTask> = load_history(config)
+        .into_iter()
+        .filter(|task| range.contains(task))
+        .collect();
+    let violations = lint::lint(&tasks, &rules);
+    for violation in &violations {
+        println!("{}", violation);
+    }
+    if violations.is_empty() {
+        println!("All {} task names follow the rules.", tasks.len());
+        return;
+    }
+    let failing: HashSet<_> = violations
+        .iter()
+        .map(|violation| violation.task.id)
+        .collect();
+    println!(
+        "{} violations in {} tasks.",
+        violations.len(),
+        failing.len()
+    );
+    std::process::exit(1);
+}
+
+/// Prints the total time tracked for each task, or one of the other reports if asked for, to
+/// `--output` or stdout.
+fn report(args: ReportArgs, config: &Config) {
+    let mut out = match output_writer(args.output.as_deref()) {
+        Ok(out) => out,
+        Err(e) => {
+            eprintln!("Error opening output: {}", e);
+            std::process::exit(1);
+        }
+    };
+    if let Err(e) = write_report(args, config, &mut out).and_then(|()| out.flush()) {
+        eprintln!("Error writing report: {}", e);
+        std::process::exit(1);
+    }
+}
+
+/// Writes the report selected by `args` to `out`.
+fn write_report(args: ReportArgs, config: &Config, out: &mut dyn Write) -> io::Result<()> {
+    if args.json_schema {
+        let schema = storage::history_schema();
+        let json = if args.pretty {
+            serde_json::to_string_pretty(&schema)
+        } else {
+            serde_json::to_string(&schema)
+        };
+        writeln!(out, "{}", json.expect("schemas always serialize to JSON"))?;
+        return Ok(());
+    }
+
+    if matches!(args.since, Some(ReportSince::Weeks(_))) && !args.sparkline_week {
+        eprintln!("Error: --since takes a date, or a number of weeks with --sparkline-week.");
+        std::process::exit(1);
+    }
+    let filter = args.task_filter();
+    if filter.range.is_empty() {
+        eprintln!("Error: --until must not be before --since.");
+        std::process::exit(1);
+    }
+    let week = WeeklyReport::for_week(
+        chrono::Local::now().date_naive(),
+        config.first_day_of_week.value,
+    );
+    let mut tasks: Vec<StoredTask> = load_history(config)
+        .into_iter()
+        .filter(|task| filter.matches(task))
+        .filter(|task| !args.week || week.contains(task))
+        .collect();
+    let billable_totals = BillableTotals::from_tasks(&tasks);
+    let billable_filter = args.billable_filter();
+    tasks.retain(|task| billable_filter.matches(task));
+    // the reports of times of day show them in the zone --normalize asks for
+    let adapter = args.normalize.then(|| {
+        args.timezone
+            .map_or_else(TimezoneAdapter::utc, |zone| TimezoneAdapter { zone })
+    });
+    let zoned = |tasks: &[StoredTask]| match adapter {
+        Some(adapter) => adapter.shift_tasks(tasks),
+        None => tasks.to_vec(),
+    };
+
+    if let (true, Some(tag)) = (args.by_tag_over_time, &args.tag) {
+        let series = TagTimeSeries::from_tasks(tag, &tasks);
+        if series.is_empty() {
+            writeln!(out, "No time tracked on tasks tagged {}.", tag)?;
+        } else {
+            writeln!(out, "{}", series.render(&tasks, args.sparkline))?;
+        }
+        return Ok(());
+    }
+
+    if args.first_task_time || args.last_task_time {
+        let analyzer = StartEndAnalyzer::from_tasks(&zoned(&tasks));
+        let mut summaries = Vec::new();
+        if args.first_task_time {
+            summaries.push(("First task started", analyzer.first_start_summary()));
+        }
+        if args.last_task_time {
+            summaries.push(("Last task stopped", analyzer.last_end_summary()));
+        }
+        for (label, summary) in summaries {
+            match summary {
+                Some(summary) => writeln!(
+                    out,
+                    "{}",
+                    label_zone(format!("{}: {}", label, summary), adapter)
+                )?,
+                None => writeln!(out, "{}: no days to average", label)?,
+            }
+        }
+        return Ok(());
+    }
+
+    if let Some(days) = args.rolling_average {
+        let rolling = RollingAverage::from_tasks(&tasks, usize::from(days));
+        writeln!(out, "{}", rolling.render(args.sparkline))?;
+        return Ok(());
+    }
+
+    if args.sparkline {
+        let sparkline: String = Sparkline::from_daily_totals(week.daily_totals(&tasks))
+            .chars()
+            .map(|block| format!(" {} ", block))
+            .collect();
+        writeln!(out, "{}", week.day_names().join(" "))?;
+        writeln!(out, "{}", sparkline)?;
+        return Ok(());
+    }
+
+    if args.heatmap && args.by_hour_of_day {
+        let heatmap = WeekHourHeatmap::from_tasks(&zoned(&tasks));
+        writeln!(
+            out,
+            "{}",
+            label_zone(heatmap.render(config.first_day_of_week.value), adapter)
+        )?;
+        return Ok(());
+    }
+
+    if args.heatmap {
+        let heatmap = Heatmap::ending(
+            &tasks,
+            reports::local_date(SystemTime::now()),
+            config.first_day_of_week.value,
+        );
+        match terminal_size::terminal_size() {
+            Some((terminal_size::Width(width), _)) => {
+                writeln!(out, "{}", heatmap.render(usize::from(width)))?
+            }
+            None => writeln!(out, "{}", heatmap)?,
+        }
+        return Ok(());
+    }
+
+    if args.sparkline_week {
+        let sparkline =
+            MultiWeekSparkline::for_weeks(&tasks, week, usize::from(args.sparkline_weeks()));
+        writeln!(out, "{}", sparkline)?;
+        return Ok(());
+    }
+
+    if args.monthly_trend {
+        let today = chrono::Local::now().date_naive();
+        writeln!(out, "{}", MonthlyTrend::for_months(&tasks, today, 12))?;
+        return Ok(());
+    }
+
+    if args.streak {
+        let streaks = StreakComputer::from_tasks(&tasks, config.streak_excludes_weekends.value);
+        let today = chrono::Local::now().date_naive();
+        writeln!(
+            out,
+            "Current streak: {}",
+            reports::format_days(streaks.current_streak(today))
+        )?;
+        writeln!(
+            out,
+            "Longest streak: {}",
+            reports::format_days(streaks.longest_streak())
+        )?;
+        return Ok(());
+    }
+
+    if args.delta {
+        let today = chrono::Local::now().date_naive();
+        let days = if filter.range.start.is_none() && filter.range.end.is_none() {
+            tasks.retain(|task| reports::local_date(task.started_at) == today);
+            1
+        } else {
+            let first = filter
+                .range
+                .start
+                .or_else(|| tasks.iter().map(|task| task.started_at).min())
+                .map_or(today, reports::local_date);
+            // the end of a range is the first second after it
+            let last = filter.range.end.map_or(today, |end| {
+                reports::local_date(end - Duration::from_secs(1))
+            });
+            u32::try_from((last - first).num_days() + 1).unwrap_or(0)
+        };
+        let goals = &config.task_goals.value;
+        if goals.is_empty() {
+            writeln!(
+                out,
+                "No task goals set. Add them to config.toml, e.g. [task_goals] Spanish = 1800"
+            )?;
+        }
+        let color = args.output.is_none()
+            && io::stdout().is_terminal()
+            && std::env::var_os("NO_COLOR").is_none();
+        for delta in TaskGoalDelta::compute(&tasks, goals, days) {
+            writeln!(out, "{}", delta.render(color))?;
+        }
+        return Ok(());
+    }
+
+    if let Some(forecast_days) = args.forecast {
+        let forecaster = Forecaster {
+            lookback_days: 30,
+            forecast_days,
+        };
+        let rows = forecaster.forecast(&tasks, chrono::Local::now().date_naive());
+        if rows.is_empty() {
+            writeln!(out, "No tasks in the last 30 days.")?;
+        }
+        for row in rows {
+            writeln!(out, "{}", row)?;
+        }
+        return Ok(());
+    }
+
+    if args.kanban {
+        let first = filter.range.start.map_or(week.start, reports::local_date);
+        // the end of a range is the first second after it
+        let last = filter
+            .range
+            .end
+            .map_or(first + chrono::Duration::days(6), |end| {
+                reports::local_date(end - Duration::from_secs(1))
+            });
+        let formatter = KanbanFormatter {
+            column_width: args.column_width,
+        };
+        writeln!(out, "{}", formatter.board(&tasks, first, last))?;
+        return Ok(());
+    }
+
+    if args.tags_co_occurrence {
+        let matrix = CoOccurrenceMatrix::from_tasks(&tasks);
+        if matrix.is_empty() {
+            writeln!(out, "No tags share a task yet.")?;
+        } else {
+            writeln!(out, "{}", matrix)?;
+        }
+        return Ok(());
+    }
+
+    if args.by_client_project {
+        let report = HierarchyReport::from_tasks(&tasks);
+        if report.is_empty() {
+            writeln!(out, "No tasks to report.")?;
+        } else {
+            writeln!(out, "{}", report)?;
+        }
+        return Ok(());
+    }
+
+    if args.by_weekday {
+        let averages = WeekdayAverages::from_tasks(&tasks);
+        if averages.is_empty() {
+            writeln!(out, "No tasks to report.")?;
+        } else {
+            writeln!(out, "{}", averages)?;
+        }
+        return Ok(());
+    }
+
+    if args.task_frequency {
+        let report = FrequencyReport::from_tasks(&tasks, args.min_count.unwrap_or(1));
+        if report.entries.is_empty() {
+            writeln!(out, "No tasks to report.")?;
+        } else {
+            writeln!(out, "{}", report)?;
+        }
+        return Ok(());
+    }
+
+    if args.incomplete {
+        let found = incomplete::find(&tasks, SystemTime::now());
+        if found.is_empty() {
+            writeln!(out, "No incomplete tasks.")?;
+        }
+        for task in found {
+            writeln!(out, "{}", task)?;
+        }
+        return Ok(());
+    }
+
+    if args.task_consistency {
+        let report = ConsistencyReport::from_tasks(&tasks);
+        if report.entries.is_empty() {
+            writeln!(out, "No task has been tracked 3 times yet.")?;
+        } else {
+            writeln!(out, "{}", report)?;
+        }
+        return Ok(());
+    }
+
+    if args.longest_streak_task {
+        let streaks = TaskStreakAnalyzer::longest(&tasks, config.streak_excludes_weekends.value);
+        if streaks.is_empty() {
+            writeln!(out, "No habit streaks yet.")?;
+        }
+        for streak in streaks {
+            writeln!(out, "Longest habit streak: {}", streak)?;
+        }
+        return Ok(());
+    }
+
+    if args.distribution {
+        let histogram = Histogram::from_tasks(&tasks, &args.buckets);
+        writeln!(out, "{}", histogram.render(args.bar_width))?;
+        return Ok(());
+    }
+
+    if let Some(periods) = &args.compare {
+        let in_period = |period: Period| {
+            let mut tasks = tasks.clone();
+            retain_period(&mut tasks, period, config);
+            tasks
+        };
+        let comparison = ComparisonReport::from_tasks(
+            &periods[0].to_string(),
+            &in_period(periods[0]),
+            &periods[1].to_string(),
+            &in_period(periods[1]),
+        );
+        let color = args.output.is_none()
+            && io::stdout().is_terminal()
+            && std::env::var_os("NO_COLOR").is_none();
+        writeln!(out, "{}", comparison.render(color))?;
+        return Ok(());
+    }
+
+    #[cfg(feature = "git-integration")]
+    if args.per_commit {
+        if let Some(period) = args.period {
+            retain_period(&mut tasks, period, config);
+        }
+        let resolver = reports::GitCommitResolver {
+            repo_path: std::env::current_dir()?,
+        };
+        let report = reports::CommitReport::from_tasks(&tasks, |hash| resolver.resolve(hash));
+        writeln!(out, "{}", report)?;
+        return Ok(());
+    }
+
+    if args.sankey {
+        let width = match terminal_size::terminal_size() {
+            Some((terminal_size::Width(width), _)) => usize::from(width),
+            None => gantt::DEFAULT_WIDTH,
+        };
+        writeln!(
+            out,
+            "{}",
+            SankeyRenderer::from_tasks(&tasks, width).render()
+        )?;
+        return Ok(());
+    }
+
+    if args.velocity {
+        let sprints = match args.sprint {
+            Some(monday) => {
+                let sprint = WeeklyReport::for_week(monday, Weekday::Mon);
+                tasks.retain(|task| sprint.contains(task));
+                vec![SprintVelocity::from_tasks(
+                    velocity::sprint_name(monday),
+                    &tasks,
+                )]
+            }
+            None => SprintVelocity::by_sprint(&tasks),
+        };
+        let unpointed = velocity::tasks_without_points(&tasks);
+        if unpointed > 0 {
+            eprintln!(
+                "Warning: {} tasks have no (<n>pts) in their name and count as 0 points.",
+                unpointed
+            );
+        }
+        for sprint in sprints {
+            writeln!(out, "{}", sprint)?;
+        }
+        return Ok(());
+    }
+
+    if let Some(percentile) = args.percentile {
+        let result = PercentileAnalysis::compute(&tasks, percentile);
+        writeln!(
+            out,
+            "Top {}% of time (by duration): {} tasks out of {} consumed {:.1}% of your tracked time",
+            100 - percentile,
+            result.task_count,
+            tasks.len(),
+            result.actual_percentage
+        )?;
+        if result.task_count > 0 {
+            writeln!(out, "{}", TopNReport::from_tasks(result.task_count, tasks))?;
+        }
+        return Ok(());
+    }
+
+    if let Some(n) = args.top {
+        if let Some(period) = args.period {
+            retain_period(&mut tasks, period, config);
+        }
+        writeln!(out, "{}", TopNReport::from_tasks(n, tasks))?;
+        return Ok(());
+    }
+
+    if args.current_period {
+        let client = args.client.as_deref().unwrap_or_default();
+        let Some(billing_period) = config.client_billing_periods.value.get(client) else {
+            eprintln!(
+                "Error: no billing period for '{}', add one under [client_billing_periods] in config.toml.",
+                client
+            );
+            std::process::exit(1);
+        };
+        let Some(rate) = config.hourly_rate_cents() else {
+            eprintln!("Error: set hourly_rate in config.toml to create an invoice.");
+            std::process::exit(1);
+        };
+        let dates = billing_period.current(chrono::Local::now().date_naive());
+        let label = format!(
+            "{} to {}",
+            dates.start,
+            dates.end.pred_opt().expect("the period has a last day")
+        );
+        let invoice = Invoice::for_dates(
+            client,
+            &label,
+            dates,
+            &tasks,
+            rate,
+            &config.currency_symbol.value,
+        );
+        writeln!(out, "{}", invoice)?;
+        return Ok(());
+    }
+
+    if args.invoice {
+        let Some(rate) = config.hourly_rate_cents() else {
+            eprintln!("Error: set hourly_rate in config.toml to create an invoice.");
+            std::process::exit(1);
+        };
+        let month = match args.period {
+            None => chrono::Local::now().date_naive(),
+            Some(Period::Month(first)) => first,
+            Some(period) => {
+                eprintln!(
+                    "Error: invoices cover a month, use --period YYYY-MM instead of '{}'.",
+                    period
+                );
+                std::process::exit(1);
+            }
+        };
+        let invoice = Invoice::new(
+            args.client.as_deref().unwrap_or_default(),
+            month,
+            &tasks,
+            rate,
+            &config.currency_symbol.value,
+        );
+        writeln!(out, "{}", invoice)?;
+        return Ok(());
+    }
+
+    if args.rate_of_work {
+        let period = args.period.unwrap_or(Period::Today);
+        retain_period(&mut tasks, period, config);
+        writeln!(
+            out,
+            "{}",
+            WorkRateReport::from_tasks(&period.to_string(), &tasks)
+        )?;
+        return Ok(());
+    }
+
+    let date = args.date.unwrap_or_else(|| match adapter {
+        Some(adapter) => adapter.date(SystemTime::now()),
+        None => chrono::Local::now().date_naive(),
+    });
+
+    if args.active_hours {
+        match ActiveHoursReport::for_date(&zoned(&tasks), date, args.threshold * 60) {
+            Some(report) => writeln!(out, "{}", report)?,
+            None => writeln!(out, "No tasks on {}.", date)?,
+        }
+        return Ok(());
+    }
+
+    if args.focus {
+        match FocusAnalyzer::longest_block(&zoned(&tasks), date, config.focus_gap_secs.value) {
+            Some(block) => writeln!(
+                out,
+                "{}",
+                label_zone(format!("Longest focus block: {}", block), adapter)
+            )?,
+            None => writeln!(out, "No tasks on {}.", date)?,
+        }
+        return Ok(());
+    }
+
+    if args.gantt {
+        let width = args
+            .width
+            .unwrap_or_else(|| match terminal_size::terminal_size() {
+                Some((terminal_size::Width(width), _)) => usize::from(width),
+                None => gantt::DEFAULT_WIDTH,
+            });
+        // leave room for the zone after the times
+        let width = width.saturating_sub(label_zone(String::new(), adapter).len());
+        match GanttChart::for_date(&zoned(&tasks), date) {
+            Some(chart) => writeln!(out, "{}", label_zone(chart.render(width), adapter))?,
+            None => writeln!(out, "No tasks on {}.", date)?,
+        }
+        return Ok(());
+    }
+
+    if args.time_utilization {
+        let utilization = WorkdayUtilization {
+            tracked_secs: zoned(&tasks)
+                .iter()
+                .filter(|task| reports::local_date(task.started_at) == date)
+                .map(|task| task.seconds)
+                .sum(),
+            work_day_secs: (args.workday_end - args.workday_start).num_seconds().max(0) as u64,
+        };
+        let color = args.output.is_none()
+            && io::stdout().is_terminal()
+            && std::env::var_os("NO_COLOR").is_none();
+        if color && utilization.is_overtime() {
+            writeln!(out, "\x1b[31m{}\x1b[0m", utilization)?;
+        } else {
+            writeln!(out, "{}", utilization)?;
+        }
+        writeln!(out, "{}", utilization.bar(10))?;
+        return Ok(());
+    }
+
+    if args.gaps {
+        let tasks = zoned(&tasks);
+        let gaps: Vec<_> = reports::detect_gaps(&tasks, args.workday_start, args.workday_end, date)
+            .into_iter()
+            .filter(|gap| gap.duration_secs >= config.min_gap_secs.value)
+            .collect();
+        if gaps.is_empty() {
+            writeln!(out, "No gaps.")?;
+        }
+        for gap in &gaps {
+            writeln!(out, "{}", label_zone(gap.to_string(), adapter))?;
+        }
+        return Ok(());
+    }
+
+    if let Some(adapter) = adapter {
+        writeln!(out, "{}", DailyLog::from_tasks(&tasks, adapter))?;
+        return Ok(());
+    }
+
+    if args.overlap {
+        let overlaps = reports::detect_overlaps(&tasks);
+        if overlaps.is_empty() {
+            writeln!(out, "No overlapping tasks.")?;
+            return Ok(());
+        }
+        for overlap in &overlaps {
+            writeln!(out, "{}", overlap)?;
+        }
+        out.flush()?;
+        std::process::exit(1);
+    }
+    let totals = reports::totals_by_name(&tasks);
+    match args.group_by {
+        Some(GroupBy::Context) => {
+            for (context, totals) in reports::totals_by_context(&tasks) {
+                writeln!(out, "{}", context)?;
+                for (name, seconds) in &totals {
+                    writeln!(out, "  {}: {}", name, format_duration_compact(*seconds))?;
+                }
+            }
+        }
+        None => {
+            for (name, seconds) in &totals {
+                writeln!(out, "{}: {}", name, format_duration_compact(*seconds))?;
+            }
+        }
+    }
+    let total = totals.iter().map(|(_, seconds)| seconds).sum();
+    writeln!(out, "Total: {}", format_duration_compact(total))?;
+
+    let billable = format_duration_compact(billable_totals.billable_secs);
+    match config.hourly_rate_cents() {
+        Some(rate) => writeln!(
+            out,
+            "Billable: {} ({})",
+            billable,
+            reports::format_amount(
+                billable_totals.billable_cents(rate),
+                &config.currency_symbol.value
+            )
+        )?,
+        None => writeln!(out, "Billable: {}", billable)?,
+    }
+    writeln!(
+        out,
+        "Non-billable: {}",
+        format_duration_compact(billable_totals.non_billable_secs)
+    )?;
+    Ok(())
+}
+
+/// Adds the time zone `--normalize` shows times in, like `(UTC)`, to the end of the first line
+/// of `report`. Without `--normalize` the report is left as it is.
+fn label_zone(report: String, adapter: Option<TimezoneAdapter>) -> String {
+    let Some(adapter) = adapter else {
+        return report;
+    };
+    match report.split_once('\n') {
+        Some((first, rest)) => format!("{} ({})\n{}", first, adapter, rest),
+        None if report.is_empty() => format!(" ({})", adapter),
+        None => format!("{} ({})", report, adapter),
+    }
+}
+
+/// Keeps only the tasks that started during `period`.
+fn retain_period(tasks: &mut Vec<StoredTask>, period: Period, config: &Config) {
+    let days = period.dates(
+        chrono::Local::now().date_naive(),
+        config.first_day_of_week.value,
+    );
+    tasks.retain(|task| days.contains(&reports::local_date(task.started_at)));
+}
+
+/// Tracks a single task until the user types 'stop', then saves it.
+fn start(args: StartArgs, config: &Config) {
+    let billable = !args.non_billable && config.hourly_rate.value.is_some();
+    track(&args.name, billable, None, config);
+}
+
+/// Runs the timer for one task until it is stopped, then saves it with the settings of the
+/// template it was started from, if any.
+fn track(name: &str, billable: bool, template: Option<&TaskTemplate>, config: &Config) {
+    let mut task = Task::new(&name.to_string());
+    let context = GitContextDetector.detect();
+    crate::add_running(&task, config);
+    match template.and_then(|template| template.estimated_secs) {
+        Some(estimated) => println!(
+            "Started task '{}' (estimated {}), stop the task with 'stop'",
+            task.name,
+            format_duration_compact(estimated)
+        ),
+        None => println!("Started task '{}', stop the task with 'stop'", task.name),
+    }
+    let interval = Duration::from_millis(config.timer_update_interval_ms.value);
+    let env = environment::detect();
+    // the timer leaves out time paused with 'pause all', like the saved task
+    while show_timers(
+        std::slice::from_ref(&task),
+        env.as_ref(),
+        interval,
+        &config.running_file.value,
+    )
+    .trim()
+        != "stop"
+    {
+        println!(
+            "{}: Invalid input. Please type 'stop' to stop the timer.",
+            task.name
+        );
+    }
+    task.stop();
+    crate::remove_running(&mut task, config);
+    crate::save_task(&task, billable, template, &context, config);
+    println!(
+        "Task '{}' completed in {}.",
+        task.name,
+        task.time_tracked_string()
+    );
+}
+
+/// Counts down a break until the user comes back. Breaks aren't saved to the history.
+fn take_break(args: BreakArgs) {
+    if args.remind && !cfg!(feature = "notifications") {
+        eprintln!(
+            "Warning: --remind needs the `notifications` feature, only the terminal bell will ring."
+        );
+    }
+    let mut countdown = Countdown::new(args.duration);
+    println!("Taking a break, press Enter to get back to work.");
+    countdown::show_countdown(&mut countdown, environment::detect().as_ref(), args.remind);
+    let taken = args.duration - countdown.remaining_secs + countdown.overtime_secs;
+    println!("Break ended after {}.", format_duration_compact(taken));
+}
+
+/// Prints the running tasks, reading only the running file so it is fast enough for a prompt.
+fn summary(args: SummaryArgs, config: &Config) {
+    let tasks = match running::load(&config.running_file.value) {
+        Ok(tasks) => tasks,
+        Err(e) => {
+            eprintln!("Error reading running tasks: {}", e);
+            std::process::exit(1);
+        }
+    };
+    let now = SystemTime::now();
+    if args.oneline {
+        println!("{}", running::summary_line(&tasks, now, args.format));
+        return;
+    }
+    for task in &tasks {
+        println!(
+            "{}",
+            running::summary_line(std::slice::from_ref(task), now, args.format)
+        );
+    }
+}
+
+/// Reports tasks that have been running for too long, exiting with code 1 if any are over
+/// `--max-running-hours`. Nothing is printed when every task is within the limits.
+fn check(args: CheckArgs, config: &Config) {
+    let tasks = load_history(config);
+    let now = SystemTime::now();
+    let hours_to_secs = |hours: f64| (hours * 3600.0).max(0.0) as u64;
+
+    let failing = reports::running_longer_than(&tasks, now, hours_to_secs(args.max_running_hours));
+    for (task, elapsed) in &failing {
+        eprintln!(
+            "{}: running for {}",
+            task.name,
+            format_duration_compact(*elapsed)
+        );
+    }
+
+    if let Some(warn_hours) = args.warn_running_hours {
+        for (task, elapsed) in reports::running_longer_than(&tasks, now, hours_to_secs(warn_hours))
+        {
+            if !failing
+                .iter()
+                .any(|(failed, _)| std::ptr::eq(*failed, task))
+            {
+                println!(
+                    "Warning: {}: running for {}",
+                    task.name,
+                    format_duration_compact(elapsed)
+                );
+            }
+        }
+    }
+
+    if !failing.is_empty() {
+        std::process::exit(1);
+    }
+}
+
+/// Adds, lists, or removes aliases.
+fn alias(args: AliasArgs) {
+    let path = aliases::aliases_file_path();
+    let mut aliases = match Aliases::load(&path) {
+        Ok(aliases) => aliases,
+        Err(e) => {
+            eprintln!("Error reading aliases: {}", e);
+            std::process::exit(1);
+        }
+    };
+
+    match (args.command, args.short, args.full_name) {
+        (Some(AliasCommand::List), _, _) => {
+            for (short, full_name) in aliases.iter() {
+                println!("{} = {}", short, full_name);
+            }
+            return;
+        }
+        (Some(AliasCommand::Remove { short }), _, _) => {
+            if aliases.remove(&short).is_none() {
+                eprintln!("Error: there is no alias named '{}'.", short);
+                std::process::exit(1);
+            }
+            println!("Removed alias '{}'.", short);
+        }
+        (None, Some(short), Some(full_name)) => {
+            if let Err(e) = aliases.add(&short, &full_name) {
+                eprintln!("Error: {}", e);
+                std::process::exit(1);
+            }
+            println!("'{}' now stands for '{}'.", short, full_name);
+        }
+        _ => {
+            eprintln!("Error: give an alias and the task name it stands for.");
+            std::process::exit(1);
+        }
+    }
+
+    if let Err(e) = aliases.save(&path) {
+        eprintln!("Error writing {}: {}", path.display(), e);
+        std::process::exit(1);
+    }
+}
+
+/// Adds, starts, lists, or removes task templates.
+fn template(command: TemplateCommand, config: &Config) {
+    let path = config::config_file_path();
+    match command {
+        TemplateCommand::Add(args) => {
+            let template = TaskTemplate {
+                name: args.name.unwrap_or_else(|| args.template.clone()),
+                tags: args.tags,
+                client: args.client,
+                project: args.project,
+                estimated_secs: args.estimated,
+                billable: args.non_billable.then_some(false),
+            };
+            if let Err(e) = templates::save(&path, &args.template, &template) {
+                eprintln!("Error writing {}: {}", path.display(), e);
+                std::process::exit(1);
+            }
+            println!("Saved template '{}': {}", args.template, template);
+        }
+        TemplateCommand::Start { template } => {
+            let Some(template) = config.templates.value.get(&template) else {
+                eprintln!("Error: there is no template named '{}'.", template);
+                std::process::exit(1);
+            };
+            let billable = template
+                .billable
+                .unwrap_or(config.hourly_rate.value.is_some());
+            track(&template.name, billable, Some(template), config);
+        }
+        TemplateCommand::List => {
+            let mut names: Vec<&String> = config.templates.value.keys().collect();
+            names.sort();
+            for name in names {
+                println!("{} = {}", name, config.templates.value[name]);
+            }
+        }
+        TemplateCommand::Remove { template } => match templates::remove(&path, &template) {
+            Ok(true) => println!("Removed template '{}'.", template),
+            Ok(false) => {
+                eprintln!("Error: there is no template named '{}'.", template);
+                std::process::exit(1);
+            }
+            Err(e) => {
+                eprintln!("Error writing {}: {}", path.display(), e);
+                std::process::exit(1);
+            }
+        },
+    }
+}
+
+/// Prints every task as `a1b2c3d4  2024-01-15 09:00  1h30m  name`, oldest first.
+fn list(config: &Config) {
+    for task in load_history(config) {
+        println!("{}", selector::label(&task));
+    }
+}
+
+/// Opens the file at `path` for writing, or stdout if there is no path, buffering either.
+fn output_writer(path: Option<&Path>) -> io::Result<Box<dyn Write>> {
+    match path {
+        Some(path) => {
+            let file = File::create(path)
+                .map_err(|e| io::Error::new(e.kind(), format!("{}: {}", path.display(), e)))?;
+            Ok(Box::new(BufWriter::new(file)))
+        }
+        None => Ok(Box::new(BufWriter::new(io::stdout()))),
+    }
+}
+
+/// Reads the task history, exiting the program if it can't be read.
+fn load_history(config: &Config) -> Vec<StoredTask> {
+    match config.open_storage().and_then(|storage| storage.load()) {
+        Ok(tasks) => tasks,
+        Err(e) => {
+            eprintln!("Error reading history: {}", e);
+            std::process::exit(1);
+        }
+    }
+}
@@ -0,0 +1,901 @@
+//! User configuration.
+//!
+//! Every setting can come from four places, in increasing order of priority:
+//! the built-in default, `~/.timetracker/config.toml`, a `TT_*` environment variable,
+//! and a command line flag. The [`Config`] remembers which of these each value came from
+//! so `time-tracker config show` can explain it.
+
+use crate::{
+    format_duration_compact,
+    reports::BillingPeriod,
+    storage::{self, Storage, StorageFormat},
+    templates::TaskTemplate,
+};
+use chrono::Weekday;
+use serde::Deserialize;
+use std::{
+    collections::HashMap,
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+/// The environment variable that overrides the location of the history file.
+///
+/// Every other setting has one too, named by [`env_var_name`].
+pub const HISTORY_FILE_ENV: &str = "TT_HISTORY_FILE";
+
+/// Where a configuration value came from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConfigSource {
+    /// The built-in default.
+    Default,
+    /// The `config.toml` file.
+    ConfigFile,
+    /// A `TT_*` environment variable.
+    EnvVar,
+    /// A command line flag.
+    CliFlag,
+}
+
+/// Formats the source as it is shown by `time-tracker config show`, e.g. `[env var]`.
+impl fmt::Display for ConfigSource {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConfigSource::Default => write!(f, "[default]"),
+            ConfigSource::ConfigFile => write!(f, "[config file]"),
+            ConfigSource::EnvVar => write!(f, "[env var]"),
+            ConfigSource::CliFlag => write!(f, "[cli flag]"),
+        }
+    }
+}
+
+/// A configuration value along with the place it came from.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConfigValue<T> {
+    /// The value itself.
+    pub value: T,
+    /// Where the value came from.
+    pub source: ConfigSource,
+}
+
+impl<T> ConfigValue<T> {
+    /// Creates a value that came from the built-in default.
+    pub fn default_value(value: T) -> ConfigValue<T> {
+        ConfigValue {
+            value,
+            source: ConfigSource::Default,
+        }
+    }
+
+    /// Replaces the value if `value` is `Some`, recording the new source.
+    fn override_with(&mut self, value: Option<T>, source: ConfigSource) {
+        if let Some(value) = value {
+            self.value = value;
+            self.source = source;
+        }
+    }
+}
+
+/// The settings that can be given on the command line.
+#[derive(Clone, Debug, Default)]
+pub struct CliOverrides {
+    /// The `--history-file` flag.
+    pub history_file: Option<PathBuf>,
+    /// The `--verbose` flag.
+    pub verbose: bool,
+}
+
+/// The contents of `config.toml`. Every key is optional.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct FileConfig {
+    history_file: Option<PathBuf>,
+    storage: Option<StorageFormat>,
+    database_file: Option<PathBuf>,
+    binary_file: Option<PathBuf>,
+    min_gap_secs: Option<u64>,
+    focus_gap_secs: Option<u64>,
+    first_day_of_week: Option<Weekday>,
+    hourly_rate: Option<f64>,
+    currency_symbol: Option<String>,
+    default_harvest_client: Option<String>,
+    default_harvest_project: Option<String>,
+    tempo_default_issue: Option<String>,
+    toggl_default_project_id: Option<u64>,
+    feed_home_page_url: Option<String>,
+    running_file: Option<PathBuf>,
+    daemon_socket: Option<PathBuf>,
+    timer_update_interval_ms: Option<u64>,
+    max_task_duration_secs: Option<u64>,
+    streak_excludes_weekends: Option<bool>,
+    templates: Option<HashMap<String, TaskTemplate>>,
+    client_billing_periods: Option<HashMap<String, BillingPeriod>>,
+    task_goals: Option<HashMap<String, u64>>,
+}
+
+/// The active configuration.
+#[derive(Clone, Debug)]
+pub struct Config {
+    /// The file that completed tasks are saved to when using JSON storage.
+    pub history_file: ConfigValue<PathBuf>,
+    /// The storage backend tasks are saved to.
+    pub storage: ConfigValue<StorageFormat>,
+    /// The database that completed tasks are saved to when using SQLite storage.
+    ///
+    /// Defaults to the history file with a `.sqlite` extension.
+    pub database_file: ConfigValue<PathBuf>,
+    /// The file that completed tasks are saved to when using binary storage.
+    ///
+    /// Defaults to the history file with a `.bin` extension.
+    pub binary_file: ConfigValue<PathBuf>,
+    /// Whether to print detailed timing information to stderr.
+    pub verbose: ConfigValue<bool>,
+    /// Gaps between tasks shorter than this many seconds are left out of `report --gaps`.
+    pub min_gap_secs: ConfigValue<u64>,
+    /// Gaps between tasks up to this many seconds long don't end a block in `report --focus`.
+    pub focus_gap_secs: ConfigValue<u64>,
+    /// The day weekly reports start on.
+    pub first_day_of_week: ConfigValue<Weekday>,
+    /// The hourly rate billable time is charged at, if any.
+    ///
+    /// New tasks are billable by default only when this is set.
+    pub hourly_rate: ConfigValue<Option<f64>>,
+    /// The symbol amounts of money are shown with.
+    pub currency_symbol: ConfigValue<String>,
+    /// The client `export --format harvest-csv` puts tasks without one under.
+    pub default_harvest_client: ConfigValue<String>,
+    /// The project `export --format harvest-csv` puts tasks without one under.
+    pub default_harvest_project: ConfigValue<String>,
+    /// The JIRA issue `export --format tempo-json` logs time on tasks without an issue key to.
+    pub tempo_default_issue: ConfigValue<String>,
+    /// The Toggl Track project `export --format toggl-api-json` puts entries in, if any.
+    pub toggl_default_project_id: ConfigValue<Option<u64>>,
+    /// The `home_page_url` of the feed from `export --format json-feed`, if any.
+    pub feed_home_page_url: ConfigValue<Option<String>>,
+    /// The file that running tasks are kept in.
+    ///
+    /// Defaults to `running.json` next to the history file.
+    pub running_file: ConfigValue<PathBuf>,
+    /// The Unix socket `time-tracker daemon` commands talk to the daemon over.
+    ///
+    /// Defaults to `daemon.sock` next to the history file.
+    pub daemon_socket: ConfigValue<PathBuf>,
+    /// How often the interactive timer is redrawn, in milliseconds.
+    pub timer_update_interval_ms: ConfigValue<u64>,
+    /// The longest a single task is expected to run, in seconds, if there is a limit.
+    pub max_task_duration_secs: ConfigValue<Option<u64>>,
+    /// Whether `report --streak` skips Saturdays and Sundays.
+    pub streak_excludes_weekends: ConfigValue<bool>,
+    /// The tasks `time-tracker template start` can start, by template name.
+    pub templates: ConfigValue<HashMap<String, TaskTemplate>>,
+    /// When each client is billed, by client name, for `report --current-period`.
+    pub client_billing_periods: ConfigValue<HashMap<String, BillingPeriod>>,
+    /// The seconds a day each task is meant to get, by task name, for `report --delta`.
+    pub task_goals: ConfigValue<HashMap<String, u64>>,
+}
+
+/// Resolves the configuration from its sources, in increasing order of priority: the
+/// defaults, `config.toml`, `TT_<KEY>` environment variables (e.g. `TT_MIN_GAP_SECS` for
+/// `min_gap_secs`), and command line flags.
+///
+/// An environment variable that can't be parsed doesn't stop the program: it is skipped,
+/// leaving the value from the config file or the default, and a warning is kept for
+/// [`ConfigLoader::warnings`]. Settings that are tables in `config.toml` take an inline TOML
+/// table, e.g. `TT_TASK_GOALS='{ Spanish = 1800 }'`.
+///
+/// # Examples
+///
+/// ```
+/// use timetracker::config::{CliOverrides, ConfigLoader, ConfigSource};
+///
+/// let env = |key: &str| match key {
+///     "TT_TIMER_UPDATE_INTERVAL_MS" => Some(String::from("500")),
+///     "TT_MAX_TASK_DURATION_SECS" => Some(String::from("abc")),
+///     _ => None,
+/// };
+/// let mut loader = ConfigLoader::new(env);
+/// let config = loader.resolve_defaults(&CliOverrides::default());
+/// assert_eq!(config.timer_update_interval_ms.value, 500);
+/// assert_eq!(config.timer_update_interval_ms.source, ConfigSource::EnvVar);
+/// assert_eq!(config.max_task_duration_secs.value, None);
+/// assert_eq!(loader.warnings().len(), 1);
+/// ```
+pub struct ConfigLoader<E> {
+    env: E,
+    warnings: Vec<String>,
+}
+
+impl<E: Fn(&str) -> Option<String>> ConfigLoader<E> {
+    /// Creates a loader that looks environment variables up with `env`.
+    pub fn new(env: E) -> ConfigLoader<E> {
+        ConfigLoader {
+            env,
+            warnings: Vec::new(),
+        }
+    }
+
+    /// Gets the environment variables that were skipped because they couldn't be parsed, as
+    /// messages like `ignoring TT_MIN_GAP_SECS=abc: invalid digit found in string`.
+    pub fn warnings(&self) -> &[String] {
+        &self.warnings
+    }
+
+    /// Resolves the configuration without a config file.
+    pub fn resolve_defaults(&mut self, overrides: &CliOverrides) -> Config {
+        self.resolve(FileConfig::default(), overrides)
+    }
+
+    /// Layers `file`, the environment variables, and `overrides` over the defaults.
+    fn resolve(&mut self, file: FileConfig, overrides: &CliOverrides) -> Config {
+        let mut history_file = self.setting(
+            "history_file",
+            storage::data_dir().join("history.json"),
+            file.history_file,
+            parse_path,
+        );
+        history_file.override_with(overrides.history_file.clone(), ConfigSource::CliFlag);
+
+        let storage = self.setting("storage", StorageFormat::Json, file.storage, parse);
+        let database_file = self.setting(
+            "database_file",
+            history_file.value.with_extension("sqlite"),
+            file.database_file,
+            parse_path,
+        );
+        let binary_file = self.setting(
+            "binary_file",
+            history_file.value.with_extension("bin"),
+            file.binary_file,
+            parse_path,
+        );
+        let running_file = self.setting(
+            "running_file",
+            history_file.value.with_file_name("running.json"),
+            file.running_file,
+            parse_path,
+        );
+        let daemon_socket = self.setting(
+            "daemon_socket",
+            history_file.value.with_file_name("daemon.sock"),
+            file.daemon_socket,
+            parse_path,
+        );
+
+        let mut verbose = self.setting("verbose", false, None, parse);
+        verbose.override_with(overrides.verbose.then_some(true), ConfigSource::CliFlag);
+
+        Config {
+            history_file,
+            storage,
+            database_file,
+            binary_file,
+            verbose,
+            min_gap_secs: self.setting("min_gap_secs", 60, file.min_gap_secs, parse),
+            focus_gap_secs: self.setting("focus_gap_secs", 300, file.focus_gap_secs, parse),
+            first_day_of_week: self.setting(
+                "first_day_of_week",
+                Weekday::Mon,
+                file.first_day_of_week,
+                parse,
+            ),
+            hourly_rate: self.setting("hourly_rate", None, file.hourly_rate.map(Some), parse_some),
+            currency_symbol: self.setting(
+                "currency_symbol",
+                String::from("$"),
+                file.currency_symbol,
+                parse,
+            ),
+            default_harvest_client: self.setting(
+                "default_harvest_client",
+                String::from("Internal"),
+                file.default_harvest_client,
+                parse,
+            ),
+            default_harvest_project: self.setting(
+                "default_harvest_project",
+                String::from("Internal"),
+                file.default_harvest_project,
+                parse,
+            ),
+            tempo_default_issue: self.setting(
+                "tempo_default_issue",
+                String::from("INTERNAL-1"),
+                file.tempo_default_issue,
+                parse,
+            ),
+            toggl_default_project_id: self.setting(
+                "toggl_default_project_id",
+                None,
+                file.toggl_default_project_id.map(Some),
+                parse_some,
+            ),
+            feed_home_page_url: self.setting(
+                "feed_home_page_url",
+                None,
+                file.feed_home_page_url.map(Some),
+                parse_some,
+            ),
+            running_file,
+            daemon_socket,
+            timer_update_interval_ms: self.setting(
+                "timer_update_interval_ms",
+                1000,
+                file.timer_update_interval_ms,
+                parse,
+            ),
+            max_task_duration_secs: self.setting(
+                "max_task_duration_secs",
+                None,
+                file.max_task_duration_secs.map(Some),
+                parse_some,
+            ),
+            streak_excludes_weekends: self.setting(
+                "streak_excludes_weekends",
+                false,
+                file.streak_excludes_weekends,
+                parse,
+            ),
+            templates: self.setting("templates", HashMap::new(), file.templates, parse_table),
+            client_billing_periods: self.setting(
+                "client_billing_periods",
+                HashMap::new(),
+                file.client_billing_periods,
+                parse_table,
+            ),
+            task_goals: self.setting("task_goals", HashMap::new(), file.task_goals, parse_table),
+        }
+    }
+
+    /// Resolves a setting from its default, its value in the config file, and its
+    /// environment variable, parsed with `parse`.
+    fn setting<T>(
+        &mut self,
+        key: &str,
+        default: T,
+        file: Option<T>,
+        parse: fn(&str) -> Result<T, String>,
+    ) -> ConfigValue<T> {
+        let mut value = ConfigValue::default_value(default);
+        value.override_with(file, ConfigSource::ConfigFile);
+        let name = env_var_name(key);
+        // an empty variable is the same as an unset one
+        if let Some(raw) = (self.env)(&name).filter(|raw| !raw.is_empty()) {
+            match parse(&raw) {
+                Ok(parsed) => value.override_with(Some(parsed), ConfigSource::EnvVar),
+                Err(e) => self
+                    .warnings
+                    .push(format!("ignoring {}={}: {}", name, raw, e)),
+            }
+        }
+        value
+    }
+}
+
+/// Gets the environment variable that overrides a config key, e.g. `TT_MIN_GAP_SECS`.
+pub fn env_var_name(key: &str) -> String {
+    format!("TT_{}", key.to_uppercase())
+}
+
+/// Parses an environment variable with [`FromStr`](std::str::FromStr).
+fn parse<T: std::str::FromStr>(value: &str) -> Result<T, String>
+where
+    T::Err: fmt::Display,
+{
+    value.trim().parse().map_err(|e: T::Err| e.to_string())
+}
+
+/// Parses an environment variable for a setting that is unset by default.
+fn parse_some<T: std::str::FromStr>(value: &str) -> Result<Option<T>, String>
+where
+    T::Err: fmt::Display,
+{
+    parse(value).map(Some)
+}
+
+/// Parses an environment variable holding a path. Any text is a path.
+fn parse_path(value: &str) -> Result<PathBuf, String> {
+    Ok(PathBuf::from(value))
+}
+
+/// Parses an environment variable holding an inline TOML table, e.g. `{ Spanish = 1800 }`.
+fn parse_table<T: serde::de::DeserializeOwned>(value: &str) -> Result<T, String> {
+    #[derive(Deserialize)]
+    struct Wrapper<T> {
+        value: T,
+    }
+    toml::from_str::<Wrapper<T>>(&format!("value = {}", value))
+        .map(|wrapper| wrapper.value)
+        .map_err(|e| e.message().to_string())
+}
+
+/// The smallest allowed `timer_update_interval_ms`. Anything shorter redraws the timer in a
+/// near busy loop.
+pub const MIN_TIMER_UPDATE_INTERVAL_MS: u64 = 100;
+
+/// A setting whose value is out of its allowed range, as found by [`Config::validate`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ValidationError {
+    /// `timer_update_interval_ms` is below [`MIN_TIMER_UPDATE_INTERVAL_MS`].
+    TimerUpdateIntervalTooShort(u64),
+    /// `max_task_duration_secs` is set to 0.
+    MaxTaskDurationNotPositive(u64),
+}
+
+impl ValidationError {
+    /// Gets the config key of the setting that failed the check.
+    pub fn key(&self) -> &'static str {
+        match self {
+            ValidationError::TimerUpdateIntervalTooShort(_) => "timer_update_interval_ms",
+            ValidationError::MaxTaskDurationNotPositive(_) => "max_task_duration_secs",
+        }
+    }
+}
+
+/// Formats the error as `<key> must be <constraint>, got <value>`.
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ValidationError::TimerUpdateIntervalTooShort(got) => write!(
+                f,
+                "{} must be ≥ {}, got {}",
+                self.key(),
+                MIN_TIMER_UPDATE_INTERVAL_MS,
+                got
+            ),
+            ValidationError::MaxTaskDurationNotPositive(got) => {
+                write!(f, "{} must be > 0, got {}", self.key(), got)
+            }
+        }
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+impl Config {
+    /// Loads the configuration from `config.toml`, the environment, and the command line.
+    ///
+    /// A missing `config.toml` is not an error; one that can't be parsed is. Environment
+    /// variables that can't be parsed are only warned about on stderr.
+    pub fn load(overrides: &CliOverrides) -> io::Result<Config> {
+        let file = read_config_file(&config_file_path())?;
+        let mut loader = ConfigLoader::new(|key: &str| std::env::var(key).ok());
+        let config = loader.resolve(file, overrides);
+        for warning in loader.warnings() {
+            eprintln!("Warning: {}", warning);
+        }
+        Ok(config)
+    }
+
+    /// Layers the config file, environment variables (looked up with `env`), and
+    /// command line flags over the defaults, ignoring environment variables that can't be
+    /// parsed. See [`ConfigLoader`].
+    #[cfg(test)]
+    fn resolve(
+        file: FileConfig,
+        env: impl Fn(&str) -> Option<String>,
+        overrides: &CliOverrides,
+    ) -> Config {
+        ConfigLoader::new(env).resolve(file, overrides)
+    }
+
+    /// Checks every setting against its allowed range, returning the ones that fail.
+    pub fn validate(&self) -> Vec<ValidationError> {
+        let mut errors = Vec::new();
+        let interval = self.timer_update_interval_ms.value;
+        if interval < MIN_TIMER_UPDATE_INTERVAL_MS {
+            errors.push(ValidationError::TimerUpdateIntervalTooShort(interval));
+        }
+        if let Some(0) = self.max_task_duration_secs.value {
+            errors.push(ValidationError::MaxTaskDurationNotPositive(0));
+        }
+        errors
+    }
+
+    /// Lists every setting as `(key, value, source)`, in the order they are shown to the user.
+    pub fn entries(&self) -> Vec<(&'static str, String, ConfigSource)> {
+        vec![
+            (
+                "history_file",
+                self.history_file.value.display().to_string(),
+                self.history_file.source,
+            ),
+            (
+                "storage",
+                self.storage.value.to_string(),
+                self.storage.source,
+            ),
+            (
+                "database_file",
+                self.database_file.value.display().to_string(),
+                self.database_file.source,
+            ),
+            (
+                "binary_file",
+                self.binary_file.value.display().to_string(),
+                self.binary_file.source,
+            ),
+            (
+                "verbose",
+                self.verbose.value.to_string(),
+                self.verbose.source,
+            ),
+            (
+                "min_gap_secs",
+                self.min_gap_secs.value.to_string(),
+                self.min_gap_secs.source,
+            ),
+            (
+                "focus_gap_secs",
+                self.focus_gap_secs.value.to_string(),
+                self.focus_gap_secs.source,
+            ),
+            (
+                "first_day_of_week",
+                self.first_day_of_week.value.to_string(),
+                self.first_day_of_week.source,
+            ),
+            (
+                "hourly_rate",
+                self.hourly_rate
+                    .value
+                    .map_or_else(|| String::from("none"), |rate| format!("{:.2}", rate)),
+                self.hourly_rate.source,
+            ),
+            (
+                "currency_symbol",
+                self.currency_symbol.value.clone(),
+                self.currency_symbol.source,
+            ),
+            (
+                "default_harvest_client",
+                self.default_harvest_client.value.clone(),
+                self.default_harvest_client.source,
+            ),
+            (
+                "default_harvest_project",
+                self.default_harvest_project.value.clone(),
+                self.default_harvest_project.source,
+            ),
+            (
+                "tempo_default_issue",
+                self.tempo_default_issue.value.clone(),
+                self.tempo_default_issue.source,
+            ),
+            (
+                "toggl_default_project_id",
+                optional_entry(self.toggl_default_project_id.value),
+                self.toggl_default_project_id.source,
+            ),
+            (
+                "feed_home_page_url",
+                optional_entry(self.feed_home_page_url.value.as_deref()),
+                self.feed_home_page_url.source,
+            ),
+            (
+                "running_file",
+                self.running_file.value.display().to_string(),
+                self.running_file.source,
+            ),
+            (
+                "daemon_socket",
+                self.daemon_socket.value.display().to_string(),
+                self.daemon_socket.source,
+            ),
+            (
+                "timer_update_interval_ms",
+                self.timer_update_interval_ms.value.to_string(),
+                self.timer_update_interval_ms.source,
+            ),
+            (
+                "max_task_duration_secs",
+                optional_entry(self.max_task_duration_secs.value),
+                self.max_task_duration_secs.source,
+            ),
+            (
+                "streak_excludes_weekends",
+                self.streak_excludes_weekends.value.to_string(),
+                self.streak_excludes_weekends.source,
+            ),
+            (
+                "templates",
+                templates_entry(&self.templates.value),
+                self.templates.source,
+            ),
+            (
+                "client_billing_periods",
+                billing_periods_entry(&self.client_billing_periods.value),
+                self.client_billing_periods.source,
+            ),
+            (
+                "task_goals",
+                task_goals_entry(&self.task_goals.value),
+                self.task_goals.source,
+            ),
+        ]
+    }
+
+    /// Gets the hourly rate in cents, if one is set.
+    pub fn hourly_rate_cents(&self) -> Option<u64> {
+        self.hourly_rate
+            .value
+            .map(|rate| (rate * 100.0).round().max(0.0) as u64)
+    }
+
+    /// Gets the file that the storage backend `format` keeps its data in.
+    pub fn storage_path(&self, format: StorageFormat) -> &Path {
+        match format {
+            StorageFormat::Json => &self.history_file.value,
+            StorageFormat::Sqlite => &self.database_file.value,
+            StorageFormat::Binary => &self.binary_file.value,
+        }
+    }
+
+    /// Opens the configured storage backend.
+    pub fn open_storage(&self) -> io::Result<Box<dyn Storage>> {
+        storage::open(self.storage.value, self.storage_path(self.storage.value))
+    }
+}
+
+/// Shows an optional setting in `config show`, as `none` if it isn't set.
+fn optional_entry<T: fmt::Display>(value: Option<T>) -> String {
+    value.map_or_else(|| String::from("none"), |value| value.to_string())
+}
+
+/// Shows the billing periods in `config show` as `client: period` pairs in alphabetical order,
+/// or `none` if there are none.
+fn billing_periods_entry(periods: &HashMap<String, BillingPeriod>) -> String {
+    if periods.is_empty() {
+        return String::from("none");
+    }
+    let mut entries: Vec<String> = periods
+        .iter()
+        .map(|(client, period)| format!("{}: {}", client, period))
+        .collect();
+    entries.sort();
+    entries.join(", ")
+}
+
+/// Shows the template names in `config show` in alphabetical order, or `none` if there are none.
+fn templates_entry(templates: &HashMap<String, TaskTemplate>) -> String {
+    if templates.is_empty() {
+        return String::from("none");
+    }
+    let mut names: Vec<&str> = templates.keys().map(String::as_str).collect();
+    names.sort();
+    names.join(", ")
+}
+
+/// Shows the task goals in `config show`, e.g. `Spanish: 30m`, or `none` if there are none.
+fn task_goals_entry(goals: &HashMap<String, u64>) -> String {
+    if goals.is_empty() {
+        return String::from("none");
+    }
+    let mut entries: Vec<String> = goals
+        .iter()
+        .map(|(name, secs)| format!("{}: {}", name, format_duration_compact(*secs)))
+        .collect();
+    entries.sort();
+    entries.join(", ")
+}
+
+/// Gets the path of the config file (`~/.timetracker/config.toml`).
+pub fn config_file_path() -> PathBuf {
+    storage::data_dir().join("config.toml")
+}
+
+/// Reads and parses the config file, treating a missing file as an empty one.
+fn read_config_file(path: &Path) -> io::Result<FileConfig> {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(FileConfig::default()),
+        Err(e) => return Err(e),
+    };
+    toml::from_str(&contents).map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{}: {}", path.display(), e),
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests_config {
+    use super::*;
+
+    fn no_env(_: &str) -> Option<String> {
+        None
+    }
+
+    #[test]
+    fn history_file_defaults_to_data_dir() {
+        let config = Config::resolve(FileConfig::default(), no_env, &CliOverrides::default());
+        assert_eq!(config.history_file.source, ConfigSource::Default);
+        assert!(config
+            .history_file
+            .value
+            .ends_with(".timetracker/history.json"));
+    }
+
+    #[test]
+    fn env_var_shows_env_var_source() {
+        let file = toml::from_str("history_file = \"/from/file.json\"").unwrap();
+        let env = |key: &str| (key == HISTORY_FILE_ENV).then(|| String::from("/from/env.json"));
+        let config = Config::resolve(file, env, &CliOverrides::default());
+
+        let (key, value, source) = &config.entries()[0];
+        assert_eq!(*key, "history_file");
+        assert_eq!(value, "/from/env.json");
+        assert_eq!(source.to_string(), "[env var]");
+    }
+
+    #[test]
+    fn cli_flag_wins_over_everything() {
+        let file = toml::from_str("history_file = \"/from/file.json\"").unwrap();
+        let env = |key: &str| (key == HISTORY_FILE_ENV).then(|| String::from("/from/env.json"));
+        let overrides = CliOverrides {
+            history_file: Some(PathBuf::from("/from/flag.json")),
+            verbose: true,
+        };
+        let config = Config::resolve(file, env, &overrides);
+        assert_eq!(config.history_file.value, PathBuf::from("/from/flag.json"));
+        assert_eq!(config.history_file.source, ConfigSource::CliFlag);
+        assert!(config.verbose.value);
+    }
+
+    #[test]
+    fn first_day_of_week_is_read_from_file() {
+        let file = toml::from_str("first_day_of_week = \"sunday\"").unwrap();
+        let config = Config::resolve(file, no_env, &CliOverrides::default());
+        assert_eq!(config.first_day_of_week.value, Weekday::Sun);
+        assert_eq!(config.first_day_of_week.source, ConfigSource::ConfigFile);
+    }
+
+    #[test]
+    fn database_file_follows_history_file() {
+        let env = |key: &str| (key == HISTORY_FILE_ENV).then(|| String::from("/from/env.json"));
+        let config = Config::resolve(FileConfig::default(), env, &CliOverrides::default());
+        assert_eq!(config.storage.value, StorageFormat::Json);
+        assert_eq!(
+            config.storage_path(StorageFormat::Sqlite),
+            Path::new("/from/env.sqlite")
+        );
+    }
+
+    #[test]
+    fn defaults_are_valid() {
+        let config = Config::resolve(FileConfig::default(), no_env, &CliOverrides::default());
+        assert_eq!(config.validate(), vec![]);
+    }
+
+    #[test]
+    fn validate_reports_every_failed_check() {
+        let file = toml::from_str(
+            "timer_update_interval_ms = 0\n\
+             max_task_duration_secs = 0",
+        )
+        .unwrap();
+        let config = Config::resolve(file, no_env, &CliOverrides::default());
+        let errors = config.validate();
+        assert_eq!(
+            errors,
+            vec![
+                ValidationError::TimerUpdateIntervalTooShort(0),
+                ValidationError::MaxTaskDurationNotPositive(0),
+            ]
+        );
+        assert_eq!(
+            errors[0].to_string(),
+            "timer_update_interval_ms must be ≥ 100, got 0"
+        );
+    }
+
+    #[test]
+    fn client_billing_periods_are_read_from_file() {
+        let file = toml::from_str(
+            "[client_billing_periods.ACME]\n\
+             cycle = \"biweekly\"\n\
+             anchor_date = \"2024-01-01\"\n\
+             [client_billing_periods.Globex]\n\
+             cycle = \"monthly\"\n\
+             anchor_date = \"2024-01-15\"",
+        )
+        .unwrap();
+        let config = Config::resolve(file, no_env, &CliOverrides::default());
+        let acme = config.client_billing_periods.value["ACME"];
+        assert_eq!(acme.cycle, crate::reports::BillingCycle::BiWeekly);
+        let entries = config.entries();
+        let (key, value, _) = entries[entries.len() - 2].clone();
+        assert_eq!(key, "client_billing_periods");
+        assert_eq!(
+            value,
+            "ACME: biweekly from 2024-01-01, Globex: monthly from 2024-01-15"
+        );
+    }
+
+    #[test]
+    fn task_goals_are_read_from_file() {
+        let file = toml::from_str("[task_goals]\nSpanish = 1800\n\"deep work\" = 7200").unwrap();
+        let config = Config::resolve(file, no_env, &CliOverrides::default());
+        assert_eq!(config.task_goals.value["deep work"], 7200);
+        let (key, value, source) = config.entries().pop().unwrap();
+        assert_eq!(key, "task_goals");
+        assert_eq!(value, "Spanish: 30m, deep work: 2h");
+        assert_eq!(source, ConfigSource::ConfigFile);
+    }
+
+    #[test]
+    fn templates_are_read_from_file() {
+        let file = toml::from_str(
+            "[templates.standup]\n\
+             name = \"Morning Standup\"\n\
+             tags = [\"team\", \"meetings\"]\n\
+             estimated_secs = 1800\n\
+             [templates.review]\n\
+             name = \"Code review\"",
+        )
+        .unwrap();
+        let config = Config::resolve(file, no_env, &CliOverrides::default());
+        let standup = &config.templates.value["standup"];
+        assert_eq!(standup.name, "Morning Standup");
+        assert_eq!(standup.tags, ["team", "meetings"]);
+        assert_eq!(standup.estimated_secs, Some(1800));
+        assert_eq!(config.templates.value["review"].client, None);
+        let entries = config.entries();
+        let (key, value, _) = &entries[entries.len() - 3];
+        assert_eq!(*key, "templates");
+        assert_eq!(value, "review, standup");
+    }
+
+    #[test]
+    fn every_key_has_a_prefixed_env_var() {
+        assert_eq!(env_var_name("history_file"), HISTORY_FILE_ENV);
+        assert_eq!(
+            env_var_name("max_task_duration_secs"),
+            "TT_MAX_TASK_DURATION_SECS"
+        );
+    }
+
+    #[test]
+    fn env_var_wins_over_file() {
+        let file = toml::from_str("timer_update_interval_ms = 2000").unwrap();
+        let env = |key: &str| (key == "TT_TIMER_UPDATE_INTERVAL_MS").then(|| String::from("500"));
+        let config = Config::resolve(file, env, &CliOverrides::default());
+        assert_eq!(config.timer_update_interval_ms.value, 500);
+        assert_eq!(config.timer_update_interval_ms.source, ConfigSource::EnvVar);
+    }
+
+    #[test]
+    fn invalid_env_var_warns_and_keeps_file_value() {
+        let file = toml::from_str("max_task_duration_secs = 3600").unwrap();
+        let env = |key: &str| (key == "TT_MAX_TASK_DURATION_SECS").then(|| String::from("abc"));
+        let mut loader = ConfigLoader::new(env);
+        let config = loader.resolve(file, &CliOverrides::default());
+        assert_eq!(config.max_task_duration_secs.value, Some(3600));
+        assert_eq!(
+            config.max_task_duration_secs.source,
+            ConfigSource::ConfigFile
+        );
+        assert_eq!(loader.warnings().len(), 1);
+        assert!(loader.warnings()[0].starts_with("ignoring TT_MAX_TASK_DURATION_SECS=abc: "));
+    }
+
+    #[test]
+    fn empty_env_var_is_ignored() {
+        let env = |key: &str| (key == "TT_VERBOSE").then(String::new);
+        let mut loader = ConfigLoader::new(env);
+        let config = loader.resolve(FileConfig::default(), &CliOverrides::default());
+        assert!(!config.verbose.value);
+        assert!(loader.warnings().is_empty());
+    }
+
+    #[test]
+    fn table_settings_are_read_from_env_var() {
+        let env = |key: &str| {
+            (key == "TT_TASK_GOALS").then(|| String::from("{ Spanish = 1800, reading = 900 }"))
+        };
+        let config = Config::resolve(FileConfig::default(), env, &CliOverrides::default());
+        assert_eq!(config.task_goals.value["Spanish"], 1800);
+        assert_eq!(config.task_goals.value["reading"], 900);
+        assert_eq!(config.task_goals.source, ConfigSource::EnvVar);
+    }
+}
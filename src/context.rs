@@ -0,0 +1,38 @@
+//! The project a task is worked on in.
+//!
+//! When a task is started, the time tracker records the git repository it was started in, or the
+//! current directory outside of a repository, so `time-tracker report --group-by context` can
+//! split the time by project. Finding it is left to a [`ContextDetector`] so the rest of the
+//! program doesn't depend on git.
+
+use std::process::{Command, Stdio};
+
+/// Finds the context new tasks are started in.
+pub trait ContextDetector {
+    /// Gets the context, e.g. `/home/me/projects/time-tracker`.
+    fn detect(&self) -> String;
+}
+
+/// Uses the top level of the git repository containing the current directory, or the current
+/// directory itself if it isn't in a repository (or git isn't installed).
+#[derive(Clone, Copy, Debug, Default)]
+pub struct GitContextDetector;
+
+impl ContextDetector for GitContextDetector {
+    fn detect(&self) -> String {
+        let toplevel = Command::new("git")
+            .args(["rev-parse", "--show-toplevel"])
+            .stderr(Stdio::null())
+            .output()
+            .ok()
+            .filter(|output| output.status.success())
+            .and_then(|output| String::from_utf8(output.stdout).ok())
+            .map(|stdout| stdout.trim().to_string())
+            .filter(|toplevel| !toplevel.is_empty());
+        toplevel.unwrap_or_else(|| {
+            std::env::current_dir()
+                .map(|dir| dir.display().to_string())
+                .unwrap_or_default()
+        })
+    }
+}
@@ -0,0 +1,182 @@
+//! Break timers.
+//!
+//! A break is the opposite of a task: time that is deliberately not tracked. `time-tracker
+//! break --duration 15m` counts down to the end of the break, rings the terminal bell (and, with
+//! `--remind` and the `notifications` feature, shows a desktop notification) when it is over,
+//! then counts the overtime until the user comes back.
+
+use crate::{environment::Environment, get_clock_format, terminal::LineUpdater};
+use std::{
+    fmt,
+    io::{self, Write},
+    thread,
+    time::Duration,
+};
+
+/// What a [`Countdown`] is doing after a second has passed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CountdownState {
+    /// The break isn't over yet; this many seconds are left.
+    Running(u64),
+    /// The break has just ended.
+    Finished,
+    /// The break ended this many seconds ago.
+    Overtime(u64),
+}
+
+/// Counts down the seconds left of a break, then the seconds past its end.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Countdown {
+    /// The seconds left until the break is over.
+    pub remaining_secs: u64,
+    /// The seconds since the break was over.
+    pub overtime_secs: u64,
+}
+
+impl Countdown {
+    /// Creates a countdown for a break of `secs` seconds.
+    pub fn new(secs: u64) -> Countdown {
+        Countdown {
+            remaining_secs: secs,
+            overtime_secs: 0,
+        }
+    }
+
+    /// Checks whether the break is over.
+    pub fn is_over(&self) -> bool {
+        self.remaining_secs == 0
+    }
+
+    /// Gets the state of a countdown that hasn't ticked yet: a break with no time left ends as
+    /// soon as it starts.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use timetracker::countdown::{Countdown, CountdownState};
+    ///
+    /// assert_eq!(Countdown::new(900).start(), CountdownState::Running(900));
+    /// assert_eq!(Countdown::new(0).start(), CountdownState::Finished);
+    /// ```
+    pub fn start(&self) -> CountdownState {
+        if self.is_over() {
+            CountdownState::Finished
+        } else {
+            CountdownState::Running(self.remaining_secs)
+        }
+    }
+
+    /// Moves the countdown on by a second.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use timetracker::countdown::{Countdown, CountdownState};
+    ///
+    /// let mut countdown = Countdown::new(2);
+    /// assert_eq!(countdown.tick(), CountdownState::Running(1));
+    /// assert_eq!(countdown.tick(), CountdownState::Finished);
+    /// assert_eq!(countdown.tick(), CountdownState::Overtime(1));
+    /// ```
+    pub fn tick(&mut self) -> CountdownState {
+        if self.remaining_secs == 0 {
+            self.overtime_secs += 1;
+            return CountdownState::Overtime(self.overtime_secs);
+        }
+        self.remaining_secs -= 1;
+        if self.remaining_secs == 0 {
+            CountdownState::Finished
+        } else {
+            CountdownState::Running(self.remaining_secs)
+        }
+    }
+}
+
+/// Formats the countdown as `Break ends in 14:32`, or `Break over! (00:00:30 overtime)` once
+/// it has run out.
+impl fmt::Display for Countdown {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_over() {
+            return write!(
+                f,
+                "Break over! ({} overtime)",
+                get_clock_format(self.overtime_secs)
+            );
+        }
+        let minutes = self.remaining_secs / 60;
+        let seconds = self.remaining_secs % 60;
+        write!(f, "Break ends in {:02}:{:02}", minutes, seconds)
+    }
+}
+
+/// Shows the countdown, updating it every second, until the user presses Enter.
+///
+/// When the break is over the terminal bell rings, even for a break that takes no time, and
+/// with `remind` a desktop notification is shown too if the program was built with the
+/// `notifications` feature.
+pub fn show_countdown(countdown: &mut Countdown, env: &dyn Environment, remind: bool) {
+    let input = crate::read_input(env);
+    let mut line = LineUpdater::new();
+    let mut state = countdown.start();
+    loop {
+        if state == CountdownState::Finished {
+            print!("\x07");
+            if remind {
+                notify_break_over();
+            }
+        }
+        print!("{}", line.update(&countdown.to_string()));
+        io::stdout().flush().unwrap();
+        if input.try_recv().is_ok() {
+            break;
+        }
+        thread::sleep(Duration::from_secs(1));
+        state = countdown.tick();
+    }
+    println!();
+}
+
+/// Shows a desktop notification that the break is over. Failing to show it isn't worth
+/// interrupting the countdown for, since the bell has already rung.
+#[cfg(feature = "notifications")]
+fn notify_break_over() {
+    if let Err(e) = notify_rust::Notification::new()
+        .summary("Break over")
+        .body("Time to get back to work.")
+        .show()
+    {
+        crate::verbose!("could not show notification: {}", e);
+    }
+}
+
+/// Without the `notifications` feature, the bell is the only reminder.
+#[cfg(not(feature = "notifications"))]
+fn notify_break_over() {}
+
+#[cfg(test)]
+mod tests_countdown {
+    use super::*;
+
+    #[test]
+    fn counts_down_then_counts_overtime() {
+        let mut countdown = Countdown::new(872);
+        assert_eq!(countdown.to_string(), "Break ends in 14:32");
+        for _ in 0..871 {
+            assert!(matches!(countdown.tick(), CountdownState::Running(_)));
+        }
+        assert_eq!(countdown.to_string(), "Break ends in 00:01");
+        assert_eq!(countdown.tick(), CountdownState::Finished);
+        assert_eq!(countdown.to_string(), "Break over! (00:00:00 overtime)");
+        for _ in 0..30 {
+            countdown.tick();
+        }
+        assert_eq!(countdown.to_string(), "Break over! (00:00:30 overtime)");
+    }
+
+    #[test]
+    fn zero_length_break_is_already_over() {
+        let mut countdown = Countdown::new(0);
+        assert_eq!(countdown.start(), CountdownState::Finished);
+        assert_eq!(countdown.tick(), CountdownState::Overtime(1));
+    }
+}
@@ -0,0 +1,165 @@
+//! Talking to the time tracker daemon over its Unix socket.
+//!
+//! Requests and responses are one line of JSON each. `time-tracker daemon status` sends
+//! `{"cmd":"status"}` and the daemon answers with a [`StatusResponse`]:
+//!
+//! ```text
+//! {"pid":4242,"uptime_secs":3600,"running_task_count":1,"completed_task_count":5,"connected_clients":2}
+//! ```
+//!
+//! This module only has the client side. Unix sockets don't exist on other platforms, so there
+//! the requests fail with [`io::ErrorKind::Unsupported`].
+
+use crate::format_duration_compact;
+use serde::{Deserialize, Serialize};
+use std::{fmt, io, path::Path, time::Duration};
+
+/// The request that asks the daemon for its status.
+pub const STATUS_REQUEST: &str = r#"{"cmd":"status"}"#;
+
+/// How long to wait for the daemon to accept a request or answer it before giving up.
+pub const TIMEOUT: Duration = Duration::from_secs(5);
+
+/// What the daemon reports about itself.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StatusResponse {
+    /// The process id of the daemon.
+    pub pid: u32,
+    /// How long the daemon has been running, in seconds.
+    pub uptime_secs: u64,
+    /// How many tasks are running.
+    pub running_task_count: usize,
+    /// How many tasks were completed since the daemon started.
+    pub completed_task_count: usize,
+    /// How many clients are connected to the daemon, including the one asking.
+    pub connected_clients: usize,
+}
+
+/// Formats the status a line per field, e.g. `Uptime: 1h2m`.
+impl fmt::Display for StatusResponse {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "PID: {}", self.pid)?;
+        writeln!(f, "Uptime: {}", format_duration_compact(self.uptime_secs))?;
+        writeln!(f, "Running tasks: {}", self.running_task_count)?;
+        writeln!(f, "Completed this session: {}", self.completed_task_count)?;
+        write!(f, "Connected clients: {}", self.connected_clients)
+    }
+}
+
+/// Asks the daemon listening on `socket` for its status, waiting at most [`TIMEOUT`] for each
+/// read and write.
+///
+/// Fails with [`io::ErrorKind::NotFound`] if the daemon isn't running: if there is no socket, or
+/// if nothing is listening on it any more.
+#[cfg(unix)]
+pub fn query_status(socket: &Path) -> io::Result<StatusResponse> {
+    query_status_within(socket, TIMEOUT)
+}
+
+/// Asks the daemon listening on `socket` for its status, waiting at most `timeout` for each
+/// read and write.
+#[cfg(unix)]
+fn query_status_within(socket: &Path, timeout: Duration) -> io::Result<StatusResponse> {
+    use std::io::{BufRead, BufReader, Write};
+
+    let mut stream = std::os::unix::net::UnixStream::connect(socket).map_err(|e| {
+        match e.kind() {
+            // a socket left behind by a daemon that exited has nothing listening on it
+            io::ErrorKind::NotFound | io::ErrorKind::ConnectionRefused => io::Error::new(
+                io::ErrorKind::NotFound,
+                format!("{}: {}", socket.display(), e),
+            ),
+            _ => e,
+        }
+    })?;
+    stream.set_read_timeout(Some(timeout))?;
+    stream.set_write_timeout(Some(timeout))?;
+    writeln!(stream, "{}", STATUS_REQUEST)?;
+    stream.flush()?;
+    let mut line = String::new();
+    BufReader::new(stream).read_line(&mut line)?;
+    serde_json::from_str(&line).map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{}: {}", socket.display(), e),
+        )
+    })
+}
+
+/// Unix sockets don't exist on this platform, so there is no daemon to ask.
+#[cfg(not(unix))]
+pub fn query_status(socket: &Path) -> io::Result<StatusResponse> {
+    Err(io::Error::new(
+        io::ErrorKind::Unsupported,
+        format!("{}: the daemon needs Unix sockets", socket.display()),
+    ))
+}
+
+#[cfg(all(test, unix))]
+mod tests_daemon {
+    use super::*;
+    use std::{
+        io::{BufRead, BufReader, Write},
+        os::unix::net::UnixListener,
+    };
+
+    #[test]
+    fn sends_status_request_and_reads_response() {
+        let socket = std::env::temp_dir().join(format!("daemon_test_{}.sock", std::process::id()));
+        let _ = std::fs::remove_file(&socket);
+        let listener = UnixListener::bind(&socket).unwrap();
+        let server = std::thread::spawn(move || {
+            let (stream, _) = listener.accept().unwrap();
+            let mut request = String::new();
+            BufReader::new(&stream).read_line(&mut request).unwrap();
+            writeln!(
+                &stream,
+                r#"{{"pid":42,"uptime_secs":3720,"running_task_count":1,"completed_task_count":5,"connected_clients":2}}"#
+            )
+            .unwrap();
+            request
+        });
+
+        let status = query_status(&socket).unwrap();
+        let request = server.join().unwrap();
+        std::fs::remove_file(&socket).unwrap();
+
+        assert_eq!(request.trim_end(), STATUS_REQUEST);
+        assert_eq!(
+            status.to_string(),
+            "PID: 42\nUptime: 1h2m\nRunning tasks: 1\nCompleted this session: 5\nConnected clients: 2"
+        );
+    }
+
+    #[test]
+    fn missing_socket_means_not_running() {
+        let socket = std::env::temp_dir().join("no_daemon_here.sock");
+        let error = query_status(&socket).unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn stale_socket_means_not_running() {
+        let socket = std::env::temp_dir().join(format!("stale_daemon_{}.sock", std::process::id()));
+        let _ = std::fs::remove_file(&socket);
+        drop(UnixListener::bind(&socket).unwrap());
+        let error = query_status(&socket).unwrap_err();
+        std::fs::remove_file(&socket).unwrap();
+        assert_eq!(error.kind(), io::ErrorKind::NotFound);
+    }
+
+    #[test]
+    fn gives_up_on_a_daemon_that_never_answers() {
+        let socket =
+            std::env::temp_dir().join(format!("silent_daemon_{}.sock", std::process::id()));
+        let _ = std::fs::remove_file(&socket);
+        let listener = UnixListener::bind(&socket).unwrap();
+        let error = query_status_within(&socket, Duration::from_millis(100)).unwrap_err();
+        drop(listener);
+        std::fs::remove_file(&socket).unwrap();
+        assert!(matches!(
+            error.kind(),
+            io::ErrorKind::WouldBlock | io::ErrorKind::TimedOut
+        ));
+    }
+}
@@ -0,0 +1,318 @@
+//! Integrity checks for the history file, used by `time-tracker doctor`.
+//!
+//! The checks work on the raw JSON of each line rather than on [`StoredTask`]s, so they can
+//! point out problems (like a negative `seconds`) that would stop the history from loading at all.
+//! The SQLite and binary backends are checked by writing their tasks out as history file lines
+//! with [`history_lines`].
+
+use crate::storage::StoredTask;
+use chrono::DateTime;
+use serde_json::Value;
+use std::{collections::HashSet, fmt};
+
+/// How far `seconds` may drift from `stopped_at - started_at` before it is reported.
+const SECONDS_TOLERANCE: i64 = 2;
+
+/// Whether a check passed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DiagnosticStatus {
+    /// Nothing wrong was found.
+    Pass,
+    /// At least one line has the problem.
+    Fail,
+}
+
+/// The result of a single check.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Diagnostic {
+    /// A short description of what was checked.
+    pub check: &'static str,
+    /// Whether the check passed.
+    pub status: DiagnosticStatus,
+    /// Which lines failed the check, if any.
+    pub message: Option<String>,
+}
+
+/// Formats the diagnostic as `[PASS] check` or `[FAIL] check: message`.
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let status = match self.status {
+            DiagnosticStatus::Pass => "PASS",
+            DiagnosticStatus::Fail => "FAIL",
+        };
+        write!(f, "[{}] {}", status, self.check)?;
+        if let Some(message) = &self.message {
+            write!(f, ": {}", message)?;
+        }
+        Ok(())
+    }
+}
+
+/// A line of the history file, parsed as far as it would go.
+struct Line<'a> {
+    /// The line number (starting at 1).
+    number: usize,
+    /// The line as it appears in the file.
+    text: &'a str,
+    /// The line as a JSON object, if it is one.
+    object: Option<serde_json::Map<String, Value>>,
+}
+
+impl Line<'_> {
+    /// Gets a timestamp field as seconds since the Unix epoch.
+    fn timestamp(&self, key: &str) -> Option<i64> {
+        let value = self.object.as_ref()?.get(key)?.as_str()?;
+        DateTime::parse_from_rfc3339(value)
+            .ok()
+            .map(|datetime| datetime.timestamp())
+    }
+
+    /// Gets `stopped_at - started_at` in seconds, if both timestamps are present.
+    fn duration(&self) -> Option<i64> {
+        Some(self.timestamp("stopped_at")? - self.timestamp("started_at")?)
+    }
+
+    /// Gets the `seconds` field.
+    fn seconds(&self) -> Option<i64> {
+        self.object.as_ref()?.get("seconds")?.as_i64()
+    }
+
+    /// Gets the `id` field, if the task has one.
+    fn id(&self) -> Option<&Value> {
+        self.object.as_ref()?.get("id")
+    }
+}
+
+/// Splits the history file into non-blank lines.
+fn parse_lines(contents: &str) -> Vec<Line<'_>> {
+    contents
+        .lines()
+        .enumerate()
+        .filter(|(_, text)| !text.trim().is_empty())
+        .map(|(index, text)| Line {
+            number: index + 1,
+            text,
+            object: match serde_json::from_str(text) {
+                Ok(Value::Object(object)) => Some(object),
+                _ => None,
+            },
+        })
+        .collect()
+}
+
+/// Builds a diagnostic from the line numbers that failed a check.
+fn diagnostic(check: &'static str, failed: Vec<usize>) -> Diagnostic {
+    if failed.is_empty() {
+        return Diagnostic {
+            check,
+            status: DiagnosticStatus::Pass,
+            message: None,
+        };
+    }
+    let lines = failed
+        .iter()
+        .map(|number| number.to_string())
+        .collect::<Vec<String>>()
+        .join(", ");
+    Diagnostic {
+        check,
+        status: DiagnosticStatus::Fail,
+        message: Some(format!(
+            "line{} {}",
+            if failed.len() > 1 { "s" } else { "" },
+            lines
+        )),
+    }
+}
+
+/// Runs every check against the contents of a history file.
+pub fn diagnose(contents: &str) -> Vec<Diagnostic> {
+    let lines = parse_lines(contents);
+    let failing = |check: &dyn Fn(&Line) -> bool| -> Vec<usize> {
+        lines
+            .iter()
+            .filter(|line| check(line))
+            .map(|line| line.number)
+            .collect()
+    };
+
+    let mut seen_ids = HashSet::new();
+    let mut duplicate_ids = Vec::new();
+    for line in &lines {
+        if let Some(id) = line.id() {
+            if !seen_ids.insert(id.to_string()) {
+                duplicate_ids.push(line.number);
+            }
+        }
+    }
+
+    vec![
+        diagnostic(
+            "all lines are parseable JSON",
+            failing(&|line| line.object.is_none()),
+        ),
+        diagnostic(
+            "stopped_at is not before started_at",
+            failing(&|line| line.duration().is_some_and(|duration| duration < 0)),
+        ),
+        diagnostic(
+            "seconds is not negative",
+            failing(&|line| line.seconds().is_some_and(|seconds| seconds < 0)),
+        ),
+        diagnostic("ids are unique", duplicate_ids),
+        diagnostic(
+            "seconds matches the timestamps",
+            failing(&|line| match (line.seconds(), line.duration()) {
+                (Some(seconds), Some(duration)) => (seconds - duration).abs() > SECONDS_TOLERANCE,
+                _ => false,
+            }),
+        ),
+    ]
+}
+
+/// Repairs what can be repaired automatically and returns the new contents of the history file.
+///
+/// * `seconds` is recalculated from the timestamps when it doesn't match them.
+/// * Tasks with a duplicate `id` are dropped, keeping the first occurrence.
+///
+/// Lines that aren't valid JSON, or whose timestamps are out of order, are kept as they are.
+pub fn repair(contents: &str) -> String {
+    let mut seen_ids = HashSet::new();
+    let mut output = String::new();
+
+    for mut line in parse_lines(contents) {
+        if let Some(id) = line.id() {
+            if !seen_ids.insert(id.to_string()) {
+                continue;
+            }
+        }
+
+        let needs_new_seconds = match (line.seconds(), line.duration()) {
+            (Some(seconds), Some(duration)) => {
+                duration >= 0 && (seconds - duration).abs() > SECONDS_TOLERANCE
+            }
+            (None, Some(duration)) => duration >= 0,
+            _ => false,
+        };
+        if needs_new_seconds {
+            let duration = line.duration().unwrap_or_default();
+            let mut object = line.object.take().unwrap_or_default();
+            object.insert(String::from("seconds"), Value::from(duration));
+            output.push_str(&to_line(Value::Object(object)));
+        } else {
+            output.push_str(line.text);
+        }
+        output.push('\n');
+    }
+
+    output
+}
+
+/// Writes tasks loaded from another storage backend as the contents of a history file, so they
+/// can be checked and repaired like one. Line `n` is the `n`th stored task.
+pub fn history_lines(tasks: &[StoredTask]) -> String {
+    tasks
+        .iter()
+        .map(|task| {
+            serde_json::to_string(task).expect("stored tasks always serialize to JSON") + "\n"
+        })
+        .collect()
+}
+
+/// Reads the tasks back from the contents of a history file, skipping blank lines.
+pub fn parse_tasks(contents: &str) -> serde_json::Result<Vec<StoredTask>> {
+    contents
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(serde_json::from_str)
+        .collect()
+}
+
+/// Writes a repaired task back out as a single line.
+///
+/// Tasks keep their usual field order as long as nothing would be lost by going through
+/// `StoredTask`; otherwise the JSON object is written as it is.
+fn to_line(value: Value) -> String {
+    if let Ok(task) = serde_json::from_value::<StoredTask>(value.clone()) {
+        if serde_json::to_value(&task).ok().as_ref() == Some(&value) {
+            return serde_json::to_string(&task).expect("stored tasks always serialize to JSON");
+        }
+    }
+    value.to_string()
+}
+
+#[cfg(test)]
+mod tests_doctor {
+    use super::*;
+
+    const GOOD: &str = r#"{"name":"a","started_at":"2024-01-15T09:00:00Z","stopped_at":"2024-01-15T09:30:00Z","seconds":1800}"#;
+
+    fn statuses(contents: &str) -> Vec<DiagnosticStatus> {
+        diagnose(contents)
+            .into_iter()
+            .map(|diagnostic| diagnostic.status)
+            .collect()
+    }
+
+    #[test]
+    fn healthy_history_passes_every_check() {
+        let contents = format!("{}\n\n{}\n", GOOD, GOOD);
+        assert!(statuses(&contents)
+            .iter()
+            .all(|status| *status == DiagnosticStatus::Pass));
+    }
+
+    #[test]
+    fn reports_each_problem_with_line_numbers() {
+        let contents = [
+            GOOD,
+            "not json",
+            r#"{"name":"b","started_at":"2024-01-15T10:00:00Z","stopped_at":"2024-01-15T09:00:00Z","seconds":0}"#,
+            r#"{"name":"c","started_at":"2024-01-15T09:00:00Z","stopped_at":"2024-01-15T09:00:10Z","seconds":-10}"#,
+            r#"{"id":"x","name":"d","started_at":"2024-01-15T09:00:00Z","stopped_at":"2024-01-15T09:01:00Z","seconds":60}"#,
+            r#"{"id":"x","name":"e","started_at":"2024-01-15T09:00:00Z","stopped_at":"2024-01-15T09:01:00Z","seconds":60}"#,
+        ]
+        .join("\n");
+
+        let diagnostics = diagnose(&contents);
+        let messages: Vec<Option<&str>> = diagnostics
+            .iter()
+            .map(|diagnostic| diagnostic.message.as_deref())
+            .collect();
+        assert_eq!(
+            messages,
+            vec![
+                Some("line 2"),
+                Some("line 3"),
+                Some("line 4"),
+                Some("line 6"),
+                Some("lines 3, 4"),
+            ]
+        );
+        assert_eq!(
+            diagnostics[0].to_string(),
+            "[FAIL] all lines are parseable JSON: line 2"
+        );
+    }
+
+    #[test]
+    fn repair_recalculates_seconds_and_drops_duplicate_ids() {
+        let contents = [
+            r#"{"id":"x","name":"a","started_at":"2024-01-15T09:00:00Z","stopped_at":"2024-01-15T09:01:00Z","seconds":5}"#,
+            r#"{"id":"x","name":"b","started_at":"2024-01-15T09:00:00Z","stopped_at":"2024-01-15T09:01:00Z","seconds":60}"#,
+            GOOD,
+        ]
+        .join("\n");
+
+        let repaired = repair(&contents);
+        assert_eq!(repaired.lines().count(), 2);
+        assert!(repaired.contains(r#""seconds":60"#));
+        assert!(repaired.ends_with(&format!("{}\n", GOOD)));
+
+        let reordered = repair(&GOOD.replace("1800", "1"));
+        assert_eq!(reordered, format!("{}\n", GOOD));
+        assert!(statuses(&repaired)
+            .iter()
+            .all(|status| *status == DiagnosticStatus::Pass));
+    }
+}
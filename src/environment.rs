@@ -0,0 +1,48 @@
+//! The environment the timer runs in.
+//!
+//! The interactive timer normally reads input silently from the terminal so that typing doesn't
+//! garble the timer line. That doesn't work when there is no terminal, e.g. when the tests drive
+//! the program through a pipe, so the timer asks an [`Environment`] which way to read input.
+
+/// Decides how the interactive timer reads input.
+pub trait Environment {
+    /// Whether the timer is running under test, reading plain lines from stdin instead of
+    /// reading silently from the terminal.
+    fn is_test(&self) -> bool;
+}
+
+/// The environment of a user running the timer in a terminal.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ProductionEnv;
+
+impl Environment for ProductionEnv {
+    fn is_test(&self) -> bool {
+        false
+    }
+}
+
+/// The environment of a test driving the timer through stdin.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TestEnv;
+
+impl Environment for TestEnv {
+    fn is_test(&self) -> bool {
+        true
+    }
+}
+
+/// The variable that selects [`TestEnv`] when set to `test`.
+pub const ENV_VAR: &str = "TT_ENV";
+
+/// Picks the environment for the current process: [`TestEnv`] when `TT_ENV=test` is set, and
+/// [`ProductionEnv`] otherwise.
+///
+/// The test environment is only ever chosen explicitly, so piping input into the program doesn't
+/// change how it reads it.
+pub fn detect() -> Box<dyn Environment> {
+    if std::env::var(ENV_VAR).is_ok_and(|value| value == "test") {
+        Box::new(TestEnv)
+    } else {
+        Box::new(ProductionEnv)
+    }
+}
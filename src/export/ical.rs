@@ -0,0 +1,505 @@
+//! Export to [iCalendar](https://datatracker.ietf.org/doc/html/rfc5545) with habits as recurring
+//! events.
+//!
+//! A task done on several days at about the same time, like a daily standup, becomes a single
+//! `VEVENT` with an `RRULE` repeating it weekly on the days of the week it was done, and an
+//! `EXDATE` for each of those days it was skipped. The other tasks get an event each.
+
+use crate::{
+    formatter::{end_of, Formatter},
+    storage::StoredTask,
+};
+use chrono::{
+    DateTime, Datelike, Duration, Local, NaiveDate, NaiveDateTime, NaiveTime, Timelike, Utc,
+    Weekday,
+};
+use std::{
+    collections::{BTreeMap, HashSet},
+    time::SystemTime,
+};
+use uuid::Uuid;
+
+/// How many days a task has to be done on to become a recurring event, by default.
+pub const DEFAULT_MIN_OCCURRENCES: usize = 3;
+
+/// How far from the usual time a task can start and still be an occurrence, by default.
+pub const DEFAULT_TOLERANCE_SECS: u32 = 30 * 60;
+
+/// The longest an iCalendar content line may be, in bytes, before it is folded.
+const MAX_LINE_BYTES: usize = 75;
+
+/// The days of the week, in the order `BYDAY` lists them.
+const WEEKDAYS: [Weekday; 7] = [
+    Weekday::Mon,
+    Weekday::Tue,
+    Weekday::Wed,
+    Weekday::Thu,
+    Weekday::Fri,
+    Weekday::Sat,
+    Weekday::Sun,
+];
+
+/// A task done repeatedly at about the same time of day.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RecurringEvent {
+    /// The name of the task.
+    pub name: String,
+    /// The local date and time of the first occurrence, at the usual start time.
+    pub start: NaiveDateTime,
+    /// How long an occurrence usually takes (the median), in seconds.
+    pub duration_secs: u64,
+    /// The days of the week the task was done on, Monday first.
+    pub weekdays: Vec<Weekday>,
+    /// The date of the last occurrence.
+    pub until: NaiveDate,
+    /// The days on one of `weekdays` between the first and last occurrence the task wasn't done.
+    pub exdates: Vec<NaiveDate>,
+    /// How many days the task was done on.
+    pub occurrences: usize,
+}
+
+/// Finds the tasks done at about the same time of day on several days.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RecurringEventDetector {
+    /// The fewest days a task has to be done on to recur.
+    pub min_occurrences: usize,
+    /// How far either side of the usual start time an occurrence can start, in seconds.
+    pub tolerance_secs: u32,
+}
+
+impl Default for RecurringEventDetector {
+    fn default() -> RecurringEventDetector {
+        RecurringEventDetector {
+            min_occurrences: DEFAULT_MIN_OCCURRENCES,
+            tolerance_secs: DEFAULT_TOLERANCE_SECS,
+        }
+    }
+}
+
+/// The local start of a task as seconds since midnight.
+fn start_secs(task: &StoredTask) -> u32 {
+    DateTime::<Local>::from(task.started_at)
+        .time()
+        .num_seconds_from_midnight()
+}
+
+/// The middle value of some numbers, the lower of the two middle ones for an even count.
+fn median(mut values: Vec<u64>) -> u64 {
+    values.sort_unstable();
+    values[(values.len() - 1) / 2]
+}
+
+impl RecurringEventDetector {
+    /// Groups the completed tasks into recurring events, giving back the tasks that aren't part
+    /// of one too, in their original order.
+    ///
+    /// The tasks with each name are sorted by their local start time of day and split into
+    /// runs that start within twice the tolerance of each other. The event of a run starts at
+    /// the median start time of its days, and only the tasks within the tolerance of that time
+    /// are occurrences of it; the rest of the run is grouped again the same way. An event that
+    /// covers at least `min_occurrences` days is a recurring event; a second task on the same
+    /// day is left out of it. Times aren't compared across midnight.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chrono::{NaiveDate, NaiveTime};
+    /// use std::time::Duration;
+    /// use timetracker::{
+    ///     export::ical::RecurringEventDetector, reports::local_time, storage::StoredTask,
+    /// };
+    ///
+    /// let standup = |day: u32, minute: u32| {
+    ///     let date = NaiveDate::from_ymd_opt(2024, 1, day).unwrap();
+    ///     let start = local_time(date, NaiveTime::from_hms_opt(9, minute, 0).unwrap());
+    ///     StoredTask::new("standup", start, start + Duration::from_secs(900))
+    /// };
+    /// let tasks = [standup(15, 0), standup(16, 5), standup(17, 0)];
+    /// let (events, others) = RecurringEventDetector::default().detect(&tasks);
+    /// assert_eq!(events.len(), 1);
+    /// assert_eq!(events[0].occurrences, 3);
+    /// assert!(others.is_empty());
+    /// ```
+    pub fn detect<'a>(
+        &self,
+        tasks: &'a [StoredTask],
+    ) -> (Vec<RecurringEvent>, Vec<&'a StoredTask>) {
+        let mut by_name: BTreeMap<&str, Vec<&StoredTask>> = BTreeMap::new();
+        for task in tasks.iter().filter(|task| task.stopped_at.is_some()) {
+            by_name.entry(&task.name).or_default().push(task);
+        }
+        let mut events = Vec::new();
+        let mut recurring = HashSet::new();
+        for (name, mut named) in by_name {
+            named.sort_by_key(|task| (start_secs(task), task.started_at));
+            let mut runs: Vec<Vec<&StoredTask>> = Vec::new();
+            for task in named {
+                match runs.last_mut() {
+                    Some(run)
+                        if start_secs(task) - start_secs(run[0]) <= 2 * self.tolerance_secs =>
+                    {
+                        run.push(task)
+                    }
+                    _ => runs.push(vec![task]),
+                }
+            }
+            for mut run in runs {
+                while !run.is_empty() {
+                    let start_time = usual_start(&first_each_day(&run));
+                    let (near, far): (Vec<&StoredTask>, Vec<&StoredTask>) =
+                        run.into_iter().partition(|task| {
+                            start_secs(task).abs_diff(start_time) <= self.tolerance_secs
+                        });
+                    if near.is_empty() {
+                        break;
+                    }
+                    run = far;
+                    let days = first_each_day(&near);
+                    if days.len() < self.min_occurrences.max(1) {
+                        continue;
+                    }
+                    recurring.extend(days.values().map(|task| task.id));
+                    events.push(recurring_event(name, start_time, &days));
+                }
+            }
+        }
+        let others = tasks
+            .iter()
+            .filter(|task| !recurring.contains(&task.id))
+            .collect();
+        (events, others)
+    }
+}
+
+/// Picks the first of the tasks started on each local date.
+fn first_each_day<'a>(tasks: &[&'a StoredTask]) -> BTreeMap<NaiveDate, &'a StoredTask> {
+    let mut days: BTreeMap<NaiveDate, &StoredTask> = BTreeMap::new();
+    for &task in tasks {
+        let date = DateTime::<Local>::from(task.started_at).date_naive();
+        let first = days.entry(date).or_insert(task);
+        if task.started_at < first.started_at {
+            *first = task;
+        }
+    }
+    days
+}
+
+/// The start time of the event of the tasks done on each of `days`: the median of their start
+/// times, in seconds since midnight, rounded down to the minute.
+fn usual_start(days: &BTreeMap<NaiveDate, &StoredTask>) -> u32 {
+    let start_time = median(
+        days.values()
+            .map(|task| u64::from(start_secs(task)))
+            .collect(),
+    );
+    start_time as u32 / 60 * 60
+}
+
+/// Builds the recurring event starting at `start_time` (in seconds since midnight) of the tasks
+/// done on each of `days`.
+fn recurring_event(
+    name: &str,
+    start_time: u32,
+    days: &BTreeMap<NaiveDate, &StoredTask>,
+) -> RecurringEvent {
+    let start_time =
+        NaiveTime::from_num_seconds_from_midnight_opt(start_time, 0).expect("within a day");
+    let first = *days.keys().next().expect("at least one day");
+    let until = *days.keys().next_back().expect("at least one day");
+    let weekdays: Vec<Weekday> = WEEKDAYS
+        .into_iter()
+        .filter(|weekday| days.keys().any(|date| date.weekday() == *weekday))
+        .collect();
+    let exdates = first
+        .iter_days()
+        .take_while(|date| *date <= until)
+        .filter(|date| weekdays.contains(&date.weekday()) && !days.contains_key(date))
+        .collect();
+    RecurringEvent {
+        name: name.to_string(),
+        start: first.and_time(start_time),
+        duration_secs: median(days.values().map(|task| task.seconds).collect()),
+        weekdays,
+        until,
+        exdates,
+        occurrences: days.len(),
+    }
+}
+
+/// Escapes the characters that have a meaning in an iCalendar text value.
+fn escape_text(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+/// Folds a content line longer than 75 bytes onto continuation lines starting with a space,
+/// without splitting a character.
+fn fold(line: &str) -> String {
+    let mut folded = String::new();
+    let mut width = 0;
+    for c in line.chars() {
+        if width + c.len_utf8() > MAX_LINE_BYTES {
+            folded.push_str("\r\n ");
+            width = 1;
+        }
+        folded.push(c);
+        width += c.len_utf8();
+    }
+    folded
+}
+
+/// A date and time in UTC, like `20240115T090000Z`.
+fn utc_stamp(time: SystemTime) -> String {
+    DateTime::<Utc>::from(time)
+        .format("%Y%m%dT%H%M%SZ")
+        .to_string()
+}
+
+/// A floating local date and time, like `20240115T090000`, which calendars read in the time
+/// zone they are shown in. Recurrences are repeated in local time, across daylight saving.
+fn local_stamp(time: NaiveDateTime) -> String {
+    time.format("%Y%m%dT%H%M%S").to_string()
+}
+
+/// The two-letter `BYDAY` code of a day of the week.
+fn weekday_code(weekday: Weekday) -> &'static str {
+    match weekday {
+        Weekday::Mon => "MO",
+        Weekday::Tue => "TU",
+        Weekday::Wed => "WE",
+        Weekday::Thu => "TH",
+        Weekday::Fri => "FR",
+        Weekday::Sat => "SA",
+        Weekday::Sun => "SU",
+    }
+}
+
+impl RecurringEvent {
+    /// The content lines of the event's `VEVENT`, not yet folded.
+    fn lines(&self, stamp: &str) -> Vec<String> {
+        let uid = Uuid::new_v5(
+            &Uuid::NAMESPACE_OID,
+            format!("{}@{}", self.name, local_stamp(self.start)).as_bytes(),
+        );
+        let end = self.start + Duration::seconds(self.duration_secs as i64);
+        let days: Vec<&str> = self.weekdays.iter().copied().map(weekday_code).collect();
+        let mut lines = vec![
+            String::from("BEGIN:VEVENT"),
+            format!("UID:{}", uid),
+            format!("DTSTAMP:{}", stamp),
+            format!("SUMMARY:{}", escape_text(&self.name)),
+            format!("DTSTART:{}", local_stamp(self.start)),
+            format!("DTEND:{}", local_stamp(end)),
+            format!(
+                "RRULE:FREQ=WEEKLY;BYDAY={};UNTIL={}",
+                days.join(","),
+                local_stamp(self.until.and_time(self.start.time()))
+            ),
+        ];
+        if !self.exdates.is_empty() {
+            let exdates: Vec<String> = self
+                .exdates
+                .iter()
+                .map(|date| local_stamp(date.and_time(self.start.time())))
+                .collect();
+            lines.push(format!("EXDATE:{}", exdates.join(",")));
+        }
+        lines.push(String::from("END:VEVENT"));
+        lines
+    }
+}
+
+/// The content lines of a single task's `VEVENT`, not yet folded.
+fn task_lines(task: &StoredTask, stamp: &str) -> Vec<String> {
+    vec![
+        String::from("BEGIN:VEVENT"),
+        format!("UID:{}", task.id),
+        format!("DTSTAMP:{}", stamp),
+        format!("SUMMARY:{}", escape_text(&task.name)),
+        format!("DTSTART:{}", utc_stamp(task.started_at)),
+        format!("DTEND:{}", utc_stamp(end_of(task))),
+        String::from("END:VEVENT"),
+    ]
+}
+
+/// Writes an iCalendar with a recurring event per habit found by `detector`, and an event for
+/// each of the other completed tasks.
+///
+/// `DTSTAMP` is the end of the latest task, so exporting the same history twice gives the same
+/// calendar. Every line ends with CRLF, as the format requires, including the last.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct IcsRecurringFormatter {
+    /// How the recurring events are found.
+    pub detector: RecurringEventDetector,
+}
+
+impl Formatter for IcsRecurringFormatter {
+    fn format_list(&self, tasks: &[StoredTask]) -> String {
+        let stamp = utc_stamp(
+            tasks
+                .iter()
+                .map(end_of)
+                .max()
+                .unwrap_or(SystemTime::UNIX_EPOCH),
+        );
+        let (events, others) = self.detector.detect(tasks);
+        let mut lines = vec![
+            String::from("BEGIN:VCALENDAR"),
+            String::from("VERSION:2.0"),
+            String::from("PRODID:-//time-tracker//Recurring Tasks//EN"),
+            String::from("CALSCALE:GREGORIAN"),
+        ];
+        for event in &events {
+            lines.extend(event.lines(&stamp));
+        }
+        for task in others.into_iter().filter(|task| task.stopped_at.is_some()) {
+            lines.extend(task_lines(task, &stamp));
+        }
+        lines.push(String::from("END:VCALENDAR"));
+        lines.iter().map(|line| fold(line) + "\r\n").collect()
+    }
+}
+
+#[cfg(test)]
+mod tests_ical {
+    use super::*;
+    use crate::reports::local_time;
+
+    /// A task started at a local time on a day of January 2024, which began on a Monday.
+    fn task(name: &str, day: u32, hour: u32, minute: u32, minutes: u64) -> StoredTask {
+        let start = local_time(
+            NaiveDate::from_ymd_opt(2024, 1, day).unwrap(),
+            NaiveTime::from_hms_opt(hour, minute, 0).unwrap(),
+        );
+        StoredTask::new(
+            name,
+            start,
+            start + std::time::Duration::from_secs(minutes * 60),
+        )
+    }
+
+    #[test]
+    fn a_week_of_standups_becomes_one_recurring_event() {
+        let tasks = vec![
+            task("standup", 15, 9, 0, 15),
+            task("standup", 16, 9, 10, 20),
+            task("standup", 17, 8, 50, 15),
+            task("standup", 18, 9, 25, 10),
+            task("standup", 19, 9, 5, 15),
+            task("write docs", 16, 14, 0, 90),
+        ];
+        let (events, others) = RecurringEventDetector::default().detect(&tasks);
+        assert_eq!(
+            events,
+            [RecurringEvent {
+                name: String::from("standup"),
+                start: NaiveDate::from_ymd_opt(2024, 1, 15)
+                    .unwrap()
+                    .and_hms_opt(9, 5, 0)
+                    .unwrap(),
+                duration_secs: 15 * 60,
+                weekdays: vec![
+                    Weekday::Mon,
+                    Weekday::Tue,
+                    Weekday::Wed,
+                    Weekday::Thu,
+                    Weekday::Fri
+                ],
+                until: NaiveDate::from_ymd_opt(2024, 1, 19).unwrap(),
+                exdates: Vec::new(),
+                occurrences: 5,
+            }]
+        );
+        assert_eq!(others.len(), 1);
+        assert_eq!(others[0].name, "write docs");
+
+        let ics = IcsRecurringFormatter::default().format_list(&tasks);
+        assert_eq!(ics.matches("BEGIN:VEVENT").count(), 2);
+        assert!(ics.contains(
+            "\r\nDTSTART:20240115T090500\r\nDTEND:20240115T092000\r\n\
+             RRULE:FREQ=WEEKLY;BYDAY=MO,TU,WE,TH,FR;UNTIL=20240119T090500\r\n"
+        ));
+        assert!(ics.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+        assert!(ics.ends_with("END:VEVENT\r\nEND:VCALENDAR\r\n"));
+        assert_eq!(IcsRecurringFormatter::default().format_output(&tasks), ics);
+    }
+
+    #[test]
+    fn skipped_days_become_exdates() {
+        let tasks = vec![
+            task("gym", 1, 7, 0, 60),
+            task("gym", 3, 7, 0, 60),
+            // skipped Friday the 5th and Monday the 8th
+            task("gym", 10, 7, 15, 45),
+            task("gym", 12, 6, 45, 60),
+        ];
+        let (events, _) = RecurringEventDetector::default().detect(&tasks);
+        assert_eq!(
+            events[0].weekdays,
+            [Weekday::Mon, Weekday::Wed, Weekday::Fri]
+        );
+        let date = |day| NaiveDate::from_ymd_opt(2024, 1, day).unwrap();
+        assert_eq!(events[0].exdates, [date(5), date(8)]);
+        let ics = IcsRecurringFormatter::default().format_list(&tasks);
+        assert!(ics.contains("\r\nEXDATE:20240105T070000,20240108T070000\r\n"));
+    }
+
+    #[test]
+    fn tasks_at_different_times_or_on_too_few_days_stay_single() {
+        let tasks = vec![
+            task("review", 15, 9, 0, 30),
+            task("review", 16, 10, 30, 30),
+            task("review", 17, 16, 0, 30),
+            // twice on one day counts once
+            task("lunch", 15, 12, 0, 30),
+            task("lunch", 15, 12, 20, 30),
+            task("lunch", 16, 12, 0, 30),
+        ];
+        let (events, others) = RecurringEventDetector::default().detect(&tasks);
+        assert!(events.is_empty());
+        assert_eq!(others.len(), tasks.len());
+        let (events, others) = RecurringEventDetector {
+            min_occurrences: 2,
+            ..RecurringEventDetector::default()
+        }
+        .detect(&tasks);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].name, "lunch");
+        assert_eq!(others.len(), 4);
+    }
+
+    #[test]
+    fn occurrences_start_within_the_tolerance_of_the_event() {
+        let tasks = vec![
+            task("standup", 15, 9, 0, 15),
+            task("standup", 16, 9, 0, 15),
+            task("standup", 17, 9, 0, 15),
+            task("standup", 18, 10, 0, 15),
+        ];
+        let (events, others) = RecurringEventDetector::default().detect(&tasks);
+        assert_eq!(events.len(), 1);
+        assert_eq!(
+            events[0].start.time(),
+            NaiveTime::from_hms_opt(9, 0, 0).unwrap()
+        );
+        assert_eq!(events[0].occurrences, 3);
+        assert_eq!(
+            events[0].until,
+            NaiveDate::from_ymd_opt(2024, 1, 17).unwrap()
+        );
+        assert_eq!(others.len(), 1);
+        assert_eq!(others[0].started_at, tasks[3].started_at);
+    }
+
+    #[test]
+    fn long_lines_are_folded_and_text_escaped() {
+        let name = "a, very; long task name that goes on and on past the seventy-five bytes";
+        let ics = IcsRecurringFormatter::default().format_list(&[task(name, 15, 9, 0, 30)]);
+        assert!(ics.contains("SUMMARY:a\\, very\\; long task name"));
+        assert!(ics.split("\r\n").all(|line| line.len() <= MAX_LINE_BYTES));
+        // the continuation line starts with a space
+        assert!(ics.contains("\r\n "));
+    }
+}
@@ -0,0 +1,13 @@
+//! Exports that write task history in the native format of other tools.
+//!
+//! Unlike the [`formatter`](crate::formatter)s, which produce a single document, these can
+//! spread tasks across several files the way the other tool expects. Imports go the other way,
+//! reading the native format of another tool into tasks.
+
+pub mod ical;
+pub mod ods;
+pub mod opf;
+pub mod prometheus;
+pub mod redmine;
+pub mod split;
+pub mod timewarrior;
@@ -0,0 +1,162 @@
+//! Export to an [OpenDocument Spreadsheet](https://docs.oasis-open.org/office/OpenDocument/)
+//! (`.ods`), as opened by LibreOffice Calc.
+//!
+//! An ODS file is a ZIP archive whose `content.xml` holds the sheets. The archive is built from
+//! the templates in `src/export/sheets`, with a row per task on a sheet named "Time Tracking".
+
+use crate::{
+    formatter::{escape_xml, wall_clock},
+    storage::StoredTask,
+};
+use std::io::{self, Cursor, Write};
+use zip::{write::SimpleFileOptions, CompressionMethod, ZipWriter};
+
+/// The media type an ODS file declares in its `mimetype` entry.
+const MIMETYPE: &str = "application/vnd.oasis.opendocument.spreadsheet";
+
+/// The sheet's `content.xml`, with `{rows}` where the table rows go.
+const CONTENT_TEMPLATE: &str = include_str!("sheets/content.xml");
+
+/// The manifest listing the files in the archive.
+const MANIFEST: &str = include_str!("sheets/manifest.xml");
+
+/// The header row of the sheet.
+const HEADERS: [&str; 7] = ["Name", "Start", "End", "Hours", "Tags", "Client", "Project"];
+
+/// A cell with text in it, or an empty cell if there is no text.
+fn text_cell(text: &str) -> String {
+    if text.is_empty() {
+        return String::from("<table:table-cell/>");
+    }
+    format!(
+        "<table:table-cell office:value-type=\"string\"><text:p>{}</text:p></table:table-cell>",
+        escape_xml(text)
+    )
+}
+
+/// A cell with a date and time, shown like `2024-01-15 09:00` (in the local time zone).
+fn date_cell(time: std::time::SystemTime) -> String {
+    let time = wall_clock(time, None).format("%Y-%m-%dT%H:%M:%S");
+    format!(
+        "<table:table-cell table:style-name=\"ce1\" office:value-type=\"date\" \
+         office:date-value=\"{}\"><text:p>{}</text:p></table:table-cell>",
+        time,
+        time.to_string().replace('T', " ")
+    )
+}
+
+/// A cell with a number in it.
+fn number_cell(value: f64) -> String {
+    format!(
+        "<table:table-cell office:value-type=\"float\" office:value=\"{}\"><text:p>{}</text:p>\
+         </table:table-cell>",
+        value, value
+    )
+}
+
+/// A table row of cells.
+fn row(cells: &[String]) -> String {
+    format!(
+        "        <table:table-row>{}</table:table-row>\n",
+        cells.concat()
+    )
+}
+
+/// Builds the `content.xml` of the sheet: a header row, then a row per task with its name,
+/// start, end (empty while it is running), hours rounded to the hundredth, tags, client, and
+/// project.
+pub fn content_xml(tasks: &[StoredTask]) -> String {
+    let mut rows = row(&HEADERS.map(text_cell));
+    for task in tasks {
+        rows.push_str(&row(&[
+            text_cell(&task.name),
+            date_cell(task.started_at),
+            task.stopped_at.map_or_else(|| text_cell(""), date_cell),
+            number_cell((task.seconds as f64 / 36.0).round() / 100.0),
+            text_cell(&task.tags.join(", ")),
+            text_cell(task.client.as_deref().unwrap_or_default()),
+            text_cell(task.project.as_deref().unwrap_or_default()),
+        ]));
+    }
+    CONTENT_TEMPLATE.replace("{rows}", &rows)
+}
+
+/// Builds an ODS file with the tasks on a sheet named "Time Tracking".
+///
+/// The `mimetype` entry comes first and isn't compressed, as the format requires, so tools can
+/// tell what the file is from its first bytes.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::{Duration, UNIX_EPOCH};
+/// use timetracker::{export::ods, storage::StoredTask};
+///
+/// let task = StoredTask::new("write docs", UNIX_EPOCH, UNIX_EPOCH + Duration::from_secs(5400));
+/// let bytes = ods::to_ods(&[task]).unwrap();
+/// assert!(bytes.starts_with(b"PK\x03\x04"));
+/// ```
+pub fn to_ods(tasks: &[StoredTask]) -> io::Result<Vec<u8>> {
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    let stored = SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+    let deflated = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    zip.start_file("mimetype", stored)?;
+    zip.write_all(MIMETYPE.as_bytes())?;
+    zip.start_file("META-INF/manifest.xml", deflated)?;
+    zip.write_all(MANIFEST.as_bytes())?;
+    zip.start_file("content.xml", deflated)?;
+    zip.write_all(content_xml(tasks).as_bytes())?;
+    Ok(zip.finish()?.into_inner())
+}
+
+#[cfg(test)]
+mod tests_ods {
+    use super::*;
+    use std::io::Read;
+    use std::time::{Duration, UNIX_EPOCH};
+    use zip::ZipArchive;
+
+    fn tasks() -> Vec<StoredTask> {
+        let start = UNIX_EPOCH + Duration::from_secs(1_705_309_200);
+        let mut review =
+            StoredTask::new("review <PR #4>", start, start + Duration::from_secs(5400));
+        review.tags = vec![String::from("code"), String::from("team")];
+        review.client = Some(String::from("ACME & Co"));
+        let mut running = StoredTask::running("deploy", start);
+        running.seconds = 60;
+        vec![review, running]
+    }
+
+    #[test]
+    fn writes_a_row_per_task() {
+        let content = content_xml(&tasks());
+        assert!(content.contains("<table:table table:name=\"Time Tracking\">"));
+        assert!(content.contains("<text:p>review &lt;PR #4&gt;</text:p>"));
+        assert!(content.contains("<text:p>ACME &amp; Co</text:p>"));
+        assert!(content.contains("<text:p>code, team</text:p>"));
+        assert!(content.contains("office:value=\"1.5\""));
+        assert!(content.contains("office:value=\"0.02\""));
+        assert_eq!(content.matches("<table:table-row>").count(), 3);
+        assert!(!content.contains("{rows}"));
+    }
+
+    #[test]
+    fn archive_starts_with_an_uncompressed_mimetype() {
+        let bytes = to_ods(&tasks()).unwrap();
+        let mut archive = ZipArchive::new(Cursor::new(bytes)).unwrap();
+        let mut mimetype = archive.by_index(0).unwrap();
+        assert_eq!(mimetype.name(), "mimetype");
+        assert_eq!(mimetype.compression(), CompressionMethod::Stored);
+        let mut contents = String::new();
+        mimetype.read_to_string(&mut contents).unwrap();
+        assert_eq!(contents, MIMETYPE);
+        drop(mimetype);
+        let mut content = String::new();
+        archive
+            .by_name("content.xml")
+            .unwrap()
+            .read_to_string(&mut content)
+            .unwrap();
+        assert_eq!(content, content_xml(&tasks()));
+    }
+}
@@ -0,0 +1,300 @@
+//! Export to an [OPF](https://www.w3.org/TR/epub-33/#sec-package-doc) package document, the
+//! manifest of an EPUB, for writers keeping a log of their writing sessions.
+//!
+//! Every task is a session, listed as a chapter: an item in the manifest and spine, and a
+//! `collection` with the session's `dc:title` (the task name), `dc:date`, and a link to its
+//! content document, which says how long the session took.
+
+use crate::{
+    format_duration_human,
+    formatter::{end_of, wall_clock, Formatter},
+    storage::StoredTask,
+};
+use chrono::{DateTime, Utc};
+use quick_xml::{
+    events::{BytesDecl, BytesText, Event},
+    Writer,
+};
+use std::{
+    io::{self, Cursor},
+    time::SystemTime,
+};
+use uuid::Uuid;
+
+/// The namespace of OPF package documents.
+pub const OPF_NAMESPACE: &str = "http://www.idpf.org/2007/opf";
+
+/// The namespace of the Dublin Core elements (`dc:title`, `dc:date`, ...).
+pub const DC_NAMESPACE: &str = "http://purl.org/dc/elements/1.1/";
+
+/// The namespace of XHTML content documents.
+const XHTML_NAMESPACE: &str = "http://www.w3.org/1999/xhtml";
+
+/// The title of the package.
+const TITLE: &str = "Writing Log";
+
+/// Writes the package document of a writing log.
+///
+/// The package's identifier is derived from the ids of the tasks, so exporting the same sessions
+/// again gives the same package.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::{Duration, UNIX_EPOCH};
+/// use timetracker::{export::opf::OpfFormatter, formatter::Formatter, storage::StoredTask};
+///
+/// let start = UNIX_EPOCH + Duration::from_secs(1_705_320_000);
+/// let task = StoredTask::new("Chapter 3 draft", start, start + Duration::from_secs(5400));
+/// let opf = OpfFormatter.format_list(&[task]);
+/// assert!(opf.contains("<dc:title>Chapter 3 draft</dc:title>"));
+/// assert!(opf.contains("<item id=\"session-1\" href=\"session-1.xhtml\""));
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct OpfFormatter;
+
+/// The file name of the content document of the `number`th session, counting from 1.
+fn session_href(number: usize) -> String {
+    format!("session-{}.xhtml", number)
+}
+
+/// The date of a session, in the local time zone.
+fn session_date(task: &StoredTask) -> String {
+    wall_clock(task.started_at, None)
+        .format("%Y-%m-%d")
+        .to_string()
+}
+
+/// Runs `write` on an XML writer indenting by two spaces, giving back what it wrote.
+fn write_xml(write: impl FnOnce(&mut Writer<Cursor<Vec<u8>>>) -> io::Result<()>) -> String {
+    let mut writer = Writer::new_with_indent(Cursor::new(Vec::new()), b' ', 2);
+    write(&mut writer).expect("writing to memory doesn't fail");
+    String::from_utf8(writer.into_inner().into_inner()).expect("XML is UTF-8")
+}
+
+impl OpfFormatter {
+    /// Writes the content document of each session, as its file name and XHTML, in the order
+    /// the package lists them.
+    pub fn content_documents(&self, tasks: &[StoredTask]) -> Vec<(String, String)> {
+        tasks
+            .iter()
+            .enumerate()
+            .map(|(index, task)| {
+                let xhtml = write_xml(|writer| {
+                    writer.write_event(Event::Decl(BytesDecl::new("1.0", Some("UTF-8"), None)))?;
+                    writer
+                        .create_element("html")
+                        .with_attribute(("xmlns", XHTML_NAMESPACE))
+                        .write_inner_content(|writer| {
+                            writer
+                                .create_element("head")
+                                .write_inner_content(|writer| {
+                                    writer
+                                        .create_element("title")
+                                        .write_text_content(BytesText::new(&task.name))?;
+                                    Ok(())
+                                })?;
+                            writer
+                                .create_element("body")
+                                .write_inner_content(|writer| {
+                                    writer
+                                        .create_element("h1")
+                                        .write_text_content(BytesText::new(&task.name))?;
+                                    writer.create_element("p").write_text_content(
+                                        BytesText::new(&format!(
+                                            "{}: {}",
+                                            session_date(task),
+                                            format_duration_human(task.seconds)
+                                        )),
+                                    )?;
+                                    Ok(())
+                                })?;
+                            Ok(())
+                        })?;
+                    Ok(())
+                });
+                (session_href(index + 1), xhtml)
+            })
+            .collect()
+    }
+}
+
+impl Formatter for OpfFormatter {
+    fn format_list(&self, tasks: &[StoredTask]) -> String {
+        let ids: String = tasks.iter().map(|task| task.id.to_string()).collect();
+        let identifier = Uuid::new_v5(&Uuid::NAMESPACE_OID, ids.as_bytes());
+        let modified = tasks
+            .iter()
+            .map(end_of)
+            .max()
+            .unwrap_or(SystemTime::UNIX_EPOCH);
+        write_xml(|writer| {
+            writer.write_event(Event::Decl(BytesDecl::new("1.0", Some("UTF-8"), None)))?;
+            writer
+                .create_element("package")
+                .with_attribute(("xmlns", OPF_NAMESPACE))
+                .with_attribute(("xmlns:dc", DC_NAMESPACE))
+                .with_attribute(("version", "3.0"))
+                .with_attribute(("unique-identifier", "log-id"))
+                .write_inner_content(|writer| {
+                    writer
+                        .create_element("metadata")
+                        .write_inner_content(|writer| {
+                            writer
+                                .create_element("dc:identifier")
+                                .with_attribute(("id", "log-id"))
+                                .write_text_content(BytesText::new(
+                                    &identifier.urn().to_string(),
+                                ))?;
+                            writer
+                                .create_element("dc:title")
+                                .write_text_content(BytesText::new(TITLE))?;
+                            writer
+                                .create_element("dc:language")
+                                .write_text_content(BytesText::new("en"))?;
+                            writer
+                                .create_element("meta")
+                                .with_attribute(("property", "dcterms:modified"))
+                                .write_text_content(BytesText::new(
+                                    &DateTime::<Utc>::from(modified)
+                                        .format("%Y-%m-%dT%H:%M:%SZ")
+                                        .to_string(),
+                                ))?;
+                            Ok(())
+                        })?;
+                    writer
+                        .create_element("manifest")
+                        .write_inner_content(|writer| {
+                            for number in 1..=tasks.len() {
+                                writer
+                                    .create_element("item")
+                                    .with_attribute(("id", format!("session-{}", number).as_str()))
+                                    .with_attribute(("href", session_href(number).as_str()))
+                                    .with_attribute(("media-type", "application/xhtml+xml"))
+                                    .write_empty()?;
+                            }
+                            Ok(())
+                        })?;
+                    writer
+                        .create_element("spine")
+                        .write_inner_content(|writer| {
+                            for number in 1..=tasks.len() {
+                                writer
+                                    .create_element("itemref")
+                                    .with_attribute((
+                                        "idref",
+                                        format!("session-{}", number).as_str(),
+                                    ))
+                                    .write_empty()?;
+                            }
+                            Ok(())
+                        })?;
+                    for (index, task) in tasks.iter().enumerate() {
+                        writer
+                            .create_element("collection")
+                            .with_attribute(("role", "chapter"))
+                            .write_inner_content(|writer| {
+                                writer.create_element("metadata").write_inner_content(
+                                    |writer| {
+                                        writer
+                                            .create_element("dc:title")
+                                            .write_text_content(BytesText::new(&task.name))?;
+                                        writer.create_element("dc:date").write_text_content(
+                                            BytesText::new(&session_date(task)),
+                                        )?;
+                                        Ok(())
+                                    },
+                                )?;
+                                writer
+                                    .create_element("link")
+                                    .with_attribute(("href", session_href(index + 1).as_str()))
+                                    .write_empty()?;
+                                Ok(())
+                            })?;
+                    }
+                    Ok(())
+                })?;
+            Ok(())
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests_opf {
+    use super::*;
+    use quick_xml::{events::attributes::Attribute, Reader};
+    use std::time::{Duration, UNIX_EPOCH};
+
+    fn tasks() -> Vec<StoredTask> {
+        // noon UTC on January 15th and 16th, 2024
+        let task = |name: &str, day: u64, minutes: u64| {
+            let start = UNIX_EPOCH + Duration::from_secs(1_705_320_000 + day * 86_400);
+            StoredTask::new(name, start, start + Duration::from_secs(minutes * 60))
+        };
+        vec![
+            task("Chapter 3 <draft>", 0, 90),
+            task("Edits & rewrites", 1, 45),
+        ]
+    }
+
+    /// Reads the document through, failing on anything that isn't well-formed, and gets the
+    /// names of the elements in order along with the root's attributes.
+    fn parse(xml: &str) -> (Vec<String>, Vec<(String, String)>) {
+        let mut reader = Reader::from_str(xml);
+        let mut names = Vec::new();
+        let mut root_attributes = Vec::new();
+        loop {
+            match reader.read_event().unwrap() {
+                Event::Start(element) | Event::Empty(element) => {
+                    if names.is_empty() {
+                        root_attributes = element
+                            .attributes()
+                            .map(|attribute| {
+                                let Attribute { key, value } = attribute.unwrap();
+                                (
+                                    String::from_utf8(key.as_ref().to_vec()).unwrap(),
+                                    String::from_utf8(value.to_vec()).unwrap(),
+                                )
+                            })
+                            .collect();
+                    }
+                    names.push(String::from_utf8(element.name().as_ref().to_vec()).unwrap());
+                }
+                Event::Eof => break,
+                _ => {}
+            }
+        }
+        (names, root_attributes)
+    }
+
+    #[test]
+    fn package_is_well_formed_with_the_opf_and_dc_namespaces() {
+        let tasks = tasks();
+        let opf = OpfFormatter.format_list(&tasks);
+        let (names, root_attributes) = parse(&opf);
+        assert_eq!(names[0], "package");
+        assert!(root_attributes.contains(&(String::from("xmlns"), String::from(OPF_NAMESPACE))));
+        assert!(root_attributes.contains(&(String::from("xmlns:dc"), String::from(DC_NAMESPACE))));
+        assert_eq!(names.iter().filter(|name| *name == "collection").count(), 2);
+        assert_eq!(names.iter().filter(|name| *name == "itemref").count(), 2);
+        assert!(opf.contains("<dc:title>Chapter 3 &lt;draft&gt;</dc:title>"));
+        assert!(opf.contains("<dc:date>2024-01-16</dc:date>"));
+        assert!(opf.contains("<meta property=\"dcterms:modified\">2024-01-16T12:45:00Z</meta>"));
+        assert_eq!(opf, OpfFormatter.format_list(&tasks));
+    }
+
+    #[test]
+    fn content_documents_describe_each_session() {
+        let documents = OpfFormatter.content_documents(&tasks());
+        assert_eq!(documents[0].0, "session-1.xhtml");
+        assert_eq!(documents[1].0, "session-2.xhtml");
+        let (names, root_attributes) = parse(&documents[1].1);
+        assert_eq!(names, ["html", "head", "title", "body", "h1", "p"]);
+        assert_eq!(
+            root_attributes,
+            [(String::from("xmlns"), String::from(XHTML_NAMESPACE))]
+        );
+        assert!(documents[1].1.contains("<p>2024-01-16: 45 Minutes</p>"));
+        assert!(documents[1].1.contains("<h1>Edits &amp; rewrites</h1>"));
+    }
+}
@@ -0,0 +1,195 @@
+//! Export to the [Prometheus text format](https://prometheus.io/docs/instrumenting/exposition_formats/).
+//!
+//! The metrics can be printed once with `time-tracker export --format prometheus`, or scraped
+//! from `/metrics` while `time-tracker serve` is running:
+//!
+//! ```text
+//! timetracker_task_duration_seconds{name="daily standup",tags="team,meetings",id="a1b2c3d4"} 900
+//! timetracker_total_seconds 900
+//! timetracker_running_tasks 0
+//! ```
+//!
+//! Tasks are labelled with their tags, joined with commas, and with their short id as well as
+//! their name, so that tasks with the same name are still separate series.
+
+use crate::{storage::StoredTask, verbose};
+use std::{
+    io::{self, BufRead, BufReader, Write},
+    net::{TcpListener, TcpStream},
+};
+
+/// The content type of the Prometheus text format.
+pub const CONTENT_TYPE: &str = "text/plain; version=0.0.4; charset=utf-8";
+
+/// Escapes a label value: backslashes, double quotes, and line breaks are written as `\\`,
+/// `\"`, and `\n`.
+fn escape_label_value(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Writes the `# HELP` and `# TYPE` lines that come before a metric's samples.
+fn write_header(mut writer: impl Write, name: &str, help: &str) -> io::Result<()> {
+    writeln!(writer, "# HELP {} {}", name, help)?;
+    writeln!(writer, "# TYPE {} gauge", name)
+}
+
+/// Writes the metrics for `tasks`: the duration of each completed task, the total of those,
+/// and the number of tasks that are still running.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::{Duration, UNIX_EPOCH};
+/// use timetracker::{export::prometheus::write_prometheus_metrics, storage::StoredTask};
+///
+/// let started_at = UNIX_EPOCH + Duration::from_secs(1_705_309_200);
+/// let task = StoredTask::new("standup", started_at, started_at + Duration::from_secs(900));
+///
+/// let mut metrics = Vec::new();
+/// write_prometheus_metrics(&mut metrics, &[task]).unwrap();
+/// assert!(String::from_utf8(metrics)
+///     .unwrap()
+///     .contains("\ntimetracker_total_seconds 900\n"));
+/// ```
+pub fn write_prometheus_metrics(mut writer: impl Write, tasks: &[StoredTask]) -> io::Result<()> {
+    let (completed, running): (Vec<&StoredTask>, Vec<&StoredTask>) =
+        tasks.iter().partition(|task| task.stopped_at.is_some());
+
+    write_header(
+        &mut writer,
+        "timetracker_task_duration_seconds",
+        "Time tracked on each completed task.",
+    )?;
+    for task in &completed {
+        writeln!(
+            writer,
+            "timetracker_task_duration_seconds{{name=\"{}\",tags=\"{}\",id=\"{}\"}} {}",
+            escape_label_value(&task.name),
+            escape_label_value(&task.tags.join(",")),
+            task.short_id(),
+            task.seconds
+        )?;
+    }
+
+    write_header(
+        &mut writer,
+        "timetracker_total_seconds",
+        "Time tracked on all completed tasks.",
+    )?;
+    let total: u64 = completed.iter().map(|task| task.seconds).sum();
+    writeln!(writer, "timetracker_total_seconds {}", total)?;
+
+    write_header(
+        &mut writer,
+        "timetracker_running_tasks",
+        "Tasks that are running right now.",
+    )?;
+    writeln!(writer, "timetracker_running_tasks {}", running.len())
+}
+
+/// Answers HTTP requests on `listener`, one at a time, serving the metrics of the tasks
+/// returned by `load_tasks` at `/metrics`. Every other path is a 404.
+///
+/// The tasks are loaded again for every request, so the metrics are always current. A
+/// connection that fails is dropped without stopping the server.
+pub fn serve_metrics(
+    listener: TcpListener,
+    load_tasks: impl Fn() -> io::Result<Vec<StoredTask>>,
+) -> io::Result<()> {
+    for stream in listener.incoming() {
+        if let Err(e) = stream.and_then(|stream| handle_request(stream, &load_tasks)) {
+            verbose!("metrics request failed: {}", e);
+        }
+    }
+    Ok(())
+}
+
+/// Reads a single HTTP request and writes the response.
+fn handle_request(
+    stream: TcpStream,
+    load_tasks: impl Fn() -> io::Result<Vec<StoredTask>>,
+) -> io::Result<()> {
+    let mut reader = BufReader::new(&stream);
+    let mut request_line = String::new();
+    reader.read_line(&mut request_line)?;
+    // skip the headers, which don't change the response
+    let mut header = String::new();
+    while reader.read_line(&mut header)? > 0 && header.trim_end() != "" {
+        header.clear();
+    }
+
+    let mut parts = request_line.split_whitespace();
+    let (status, content_type, body) = match (parts.next(), parts.next()) {
+        (Some("GET"), Some("/metrics")) => match load_tasks() {
+            Ok(tasks) => {
+                let mut body = Vec::new();
+                write_prometheus_metrics(&mut body, &tasks)?;
+                ("200 OK", CONTENT_TYPE, body)
+            }
+            Err(e) => (
+                "500 Internal Server Error",
+                "text/plain",
+                format!("{}\n", e).into_bytes(),
+            ),
+        },
+        _ => ("404 Not Found", "text/plain", b"not found\n".to_vec()),
+    };
+
+    let mut stream = &stream;
+    write!(
+        stream,
+        "HTTP/1.1 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        status,
+        content_type,
+        body.len()
+    )?;
+    stream.write_all(&body)?;
+    stream.flush()
+}
+
+#[cfg(test)]
+mod tests_prometheus {
+    use super::*;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    #[test]
+    fn escapes_label_values() {
+        assert_eq!(
+            escape_label_value("say \"hi\"\\\n"),
+            "say \\\"hi\\\"\\\\\\n"
+        );
+    }
+
+    #[test]
+    fn writes_every_metric_without_trailing_spaces() {
+        let started_at = UNIX_EPOCH + Duration::from_secs(1_705_309_200);
+        let mut done = StoredTask::new("a \"b\"", started_at, started_at + Duration::from_secs(60));
+        done.tags = vec![String::from("deep work"), String::from("say \"hi\"")];
+        let running = StoredTask::running("c", started_at);
+
+        let mut metrics = Vec::new();
+        write_prometheus_metrics(&mut metrics, &[done.clone(), running]).unwrap();
+        let metrics = String::from_utf8(metrics).unwrap();
+
+        assert!(metrics.contains(&format!(
+            concat!(
+                "\ntimetracker_task_duration_seconds{{name=\"a \\\"b\\\"\",",
+                "tags=\"deep work,say \\\"hi\\\"\",id=\"{}\"}} 60\n"
+            ),
+            done.short_id()
+        )));
+        assert!(metrics.contains("\ntimetracker_total_seconds 60\n"));
+        assert!(metrics.ends_with("\ntimetracker_running_tasks 1\n"));
+        assert!(metrics.lines().all(|line| !line.ends_with(' ')));
+        assert_eq!(
+            metrics
+                .lines()
+                .filter(|line| line.starts_with("# TYPE"))
+                .count(),
+            3
+        );
+    }
+}
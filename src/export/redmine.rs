@@ -0,0 +1,277 @@
+//! Import from [Redmine](https://www.redmine.org) time entry exports.
+//!
+//! Redmine exports spent time as CSV, one entry per row:
+//!
+//! ```text
+//! Date,User,Activity,Project,Issue,Comment,Hours
+//! 2024-01-15,Jane Doe,Development,Website,1234,Fix login form,1.5
+//! ```
+//!
+//! Entries only have a date, so they are imported as starting at midnight UTC.
+
+use crate::storage::StoredTask;
+use chrono::NaiveDate;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// The columns every Redmine export has to have. They can be in any order, and other columns
+/// (like `User`) are ignored.
+const COLUMNS: [&str; 6] = ["Date", "Activity", "Project", "Issue", "Comment", "Hours"];
+
+/// Reads the time entries of a Redmine CSV export as tasks.
+///
+/// - `Comment` becomes the task name, or `Issue #<Issue>` if there is no comment.
+/// - `Hours` becomes the tracked time, exactly (`1.5` is 5400 seconds).
+/// - `Date` becomes the start, at midnight UTC.
+/// - `Project` becomes the project and `Activity` a tag.
+pub struct RedmineImporter;
+
+impl RedmineImporter {
+    /// Parses the CSV text of a Redmine export.
+    ///
+    /// Fails on the first row that can't be read, naming its line.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use timetracker::export::redmine::RedmineImporter;
+    ///
+    /// let csv = "Date,User,Activity,Project,Issue,Comment,Hours\n\
+    ///            2024-01-15,Jane Doe,Development,Website,1234,,1.5\n";
+    /// let tasks = RedmineImporter::parse(csv).unwrap();
+    /// assert_eq!(tasks[0].name, "Issue #1234");
+    /// assert_eq!(tasks[0].seconds, 5400);
+    /// ```
+    pub fn parse(csv: &str) -> Result<Vec<StoredTask>, String> {
+        let mut rows = parse_csv(csv).into_iter();
+        let Some((_, header)) = rows.next() else {
+            return Err(String::from("the file is empty"));
+        };
+        let mut indices = [0; COLUMNS.len()];
+        for (index, column) in indices.iter_mut().zip(COLUMNS) {
+            *index = header
+                .iter()
+                .position(|name| name.trim() == column)
+                .ok_or_else(|| format!("missing column {}", column))?;
+        }
+        let [date, activity, project, issue, comment, hours] = indices;
+
+        let mut tasks = Vec::new();
+        for (line, row) in rows {
+            if row.iter().all(|field| field.trim().is_empty()) {
+                continue;
+            }
+            let field = |index: usize| row.get(index).map_or("", |field| field.trim());
+            let started_at =
+                parse_date(field(date)).map_err(|e| format!("line {}: {}", line, e))?;
+            let seconds = parse_hours(field(hours)).map_err(|e| format!("line {}: {}", line, e))?;
+            let name = match field(comment) {
+                "" => format!("Issue #{}", field(issue)),
+                comment => comment.to_string(),
+            };
+            let stopped_at = started_at
+                .checked_add(Duration::from_secs(seconds))
+                .ok_or_else(|| format!("line {}: {} hours is too long", line, field(hours)))?;
+            let mut task = StoredTask::new(&name, started_at, stopped_at);
+            task.project = Some(field(project))
+                .filter(|project| !project.is_empty())
+                .map(String::from);
+            task.tags = Some(field(activity))
+                .filter(|activity| !activity.is_empty())
+                .map(String::from)
+                .into_iter()
+                .collect();
+            tasks.push(task);
+        }
+        Ok(tasks)
+    }
+}
+
+/// Parses a `YYYY-MM-DD` date as midnight UTC.
+fn parse_date(date: &str) -> Result<SystemTime, String> {
+    let date = NaiveDate::parse_from_str(date, "%Y-%m-%d")
+        .map_err(|_| format!("invalid date '{}', expected YYYY-MM-DD", date))?;
+    let secs = date
+        .and_hms_opt(0, 0, 0)
+        .expect("midnight is a valid time")
+        .and_utc()
+        .timestamp();
+    u64::try_from(secs)
+        .map(|secs| UNIX_EPOCH + Duration::from_secs(secs))
+        .map_err(|_| format!("date {} is before 1970", date))
+}
+
+/// Parses a decimal number of hours (`1.5`, or `1,5` as some locales write it) into seconds,
+/// rounded to the nearest second.
+///
+/// The digits are added up exactly rather than going through a float, so `1.5` is 5400 seconds
+/// and `0.1` is 360.
+fn parse_hours(hours: &str) -> Result<u64, String> {
+    let invalid = || format!("invalid hours '{}'", hours);
+    let (whole, fraction) = hours.split_once(['.', ',']).unwrap_or((hours, ""));
+    let is_digits = |s: &str| s.chars().all(|c| c.is_ascii_digit());
+    if (whole.is_empty() && fraction.is_empty()) || !is_digits(whole) || !is_digits(fraction) {
+        return Err(invalid());
+    }
+    let whole: u64 = if whole.is_empty() {
+        0
+    } else {
+        whole.parse().map_err(|_| invalid())?
+    };
+    // a second is 1/3600 of an hour, so digits past the ninth can't change the result
+    let fraction = &fraction[..fraction.len().min(9)];
+    let scale = 10u64.pow(fraction.len() as u32);
+    let numerator: u64 = if fraction.is_empty() {
+        0
+    } else {
+        fraction.parse().map_err(|_| invalid())?
+    };
+    let fraction_secs = (numerator * 3600 + scale / 2) / scale;
+    whole
+        .checked_mul(3600)
+        .and_then(|secs| secs.checked_add(fraction_secs))
+        .ok_or_else(invalid)
+}
+
+/// Splits CSV text into rows of fields, each with the line it starts on.
+///
+/// Fields can be quoted to contain commas, line breaks, and (doubled) quotes. A byte order mark,
+/// which Redmine puts at the start of its exports, is skipped.
+fn parse_csv(csv: &str) -> Vec<(usize, Vec<String>)> {
+    let csv = csv.strip_prefix('\u{feff}').unwrap_or(csv);
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut in_quotes = false;
+    let mut line = 1;
+    let mut row_line = 1;
+    let mut chars = csv.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if in_quotes && chars.peek() == Some(&'"') => {
+                chars.next();
+                field.push('"');
+            }
+            '"' => in_quotes = !in_quotes,
+            ',' if !in_quotes => row.push(std::mem::take(&mut field)),
+            '\r' if !in_quotes && chars.peek() == Some(&'\n') => {}
+            '\n' if !in_quotes => {
+                row.push(std::mem::take(&mut field));
+                rows.push((row_line, std::mem::take(&mut row)));
+                line += 1;
+                row_line = line;
+            }
+            '\n' => {
+                field.push(c);
+                line += 1;
+            }
+            _ => field.push(c),
+        }
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push((row_line, row));
+    }
+    rows
+}
+
+#[cfg(test)]
+mod tests_redmine {
+    use super::*;
+
+    const SAMPLE: &str = "\u{feff}Date,User,Activity,Project,Issue,Comment,Hours\r\n\
+        2024-01-15,Jane Doe,Development,Website,1234,Fix login form,1.5\r\n\
+        2024-01-15,Jane Doe,Design,Website,1235,,0.25\r\n\
+        2024-01-16,John Roe,Support,Intranet,987,\"Call with \"\"ACME\"\", Inc.\",2\r\n\
+        2024-01-17,John Roe,,,42,\"Notes\nover two lines\",\"0,1\"\r\n";
+
+    fn at_midnight(date: &str) -> SystemTime {
+        parse_date(date).unwrap()
+    }
+
+    fn task(name: &str, date: &str, seconds: u64, project: Option<&str>, tag: &str) -> StoredTask {
+        let started_at = at_midnight(date);
+        let mut task = StoredTask::new(name, started_at, started_at + Duration::from_secs(seconds));
+        task.project = project.map(String::from);
+        task.tags = [tag]
+            .into_iter()
+            .filter(|tag| !tag.is_empty())
+            .map(String::from)
+            .collect();
+        task
+    }
+
+    #[test]
+    fn imports_sample_export() {
+        let mut tasks = RedmineImporter::parse(SAMPLE).unwrap();
+        for task in &mut tasks {
+            task.id = uuid::Uuid::nil();
+        }
+        let mut expected = vec![
+            task(
+                "Fix login form",
+                "2024-01-15",
+                5400,
+                Some("Website"),
+                "Development",
+            ),
+            task("Issue #1235", "2024-01-15", 900, Some("Website"), "Design"),
+            task(
+                "Call with \"ACME\", Inc.",
+                "2024-01-16",
+                7200,
+                Some("Intranet"),
+                "Support",
+            ),
+            task("Notes\nover two lines", "2024-01-17", 360, None, ""),
+        ];
+        for task in &mut expected {
+            task.id = uuid::Uuid::nil();
+        }
+        assert_eq!(tasks, expected);
+        assert_eq!(
+            crate::storage::format_timestamp(tasks[0].started_at),
+            "2024-01-15T00:00:00Z"
+        );
+    }
+
+    #[test]
+    fn parses_decimal_hours_exactly() {
+        assert_eq!(parse_hours("1.5"), Ok(5400));
+        assert_eq!(parse_hours("1"), Ok(3600));
+        assert_eq!(parse_hours(".75"), Ok(2700));
+        assert_eq!(parse_hours("0.1"), Ok(360));
+        assert_eq!(parse_hours("0.3333"), Ok(1200));
+        assert_eq!(parse_hours("2,25"), Ok(8100));
+        assert!(parse_hours("").is_err());
+        assert!(parse_hours("1.5h").is_err());
+        assert!(parse_hours("-1").is_err());
+    }
+
+    #[test]
+    fn reports_the_line_of_bad_rows() {
+        let csv = "Date,User,Activity,Project,Issue,Comment,Hours\n\
+                   2024-01-15,Jane Doe,Development,Website,1,Fine,1\n\
+                   15/01/2024,Jane Doe,Development,Website,2,Bad date,1\n";
+        assert_eq!(
+            RedmineImporter::parse(csv),
+            Err(String::from(
+                "line 3: invalid date '15/01/2024', expected YYYY-MM-DD"
+            ))
+        );
+        assert_eq!(
+            RedmineImporter::parse("Date,Hours\n"),
+            Err(String::from("missing column Activity"))
+        );
+    }
+
+    #[test]
+    fn rejects_hours_past_the_end_of_time() {
+        let csv = "Date,Activity,Project,Issue,Comment,Hours
+                   2024-01-15,Development,Website,1,Forever,5000000000000000
+";
+        assert_eq!(
+            RedmineImporter::parse(csv),
+            Err(String::from("line 2: 5000000000000000 hours is too long"))
+        );
+    }
+}
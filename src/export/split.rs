@@ -0,0 +1,216 @@
+//! Exports split into a file per month, week, or day, for archiving the history.
+
+use crate::{formatter::Formatter, reports::local_date, storage::StoredTask};
+use std::{
+    collections::BTreeMap,
+    fmt, fs, io,
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::SystemTime,
+};
+
+/// The stretch of time each file of a split export covers.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SplitBy {
+    /// A file per calendar month, e.g. `2024-01`.
+    Month,
+    /// A file per ISO week, e.g. `2024-W03`.
+    Week,
+    /// A file per day, e.g. `2024-01-15`.
+    Day,
+}
+
+impl SplitBy {
+    /// Gets the name of the period `time` falls in (in local time), which is also the name of
+    /// its file without the extension.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chrono::{Local, TimeZone};
+    /// use timetracker::export::split::SplitBy;
+    ///
+    /// let time = Local.with_ymd_and_hms(2024, 1, 1, 12, 0, 0).unwrap().into();
+    /// assert_eq!(SplitBy::Month.period(time), "2024-01");
+    /// assert_eq!(SplitBy::Week.period(time), "2024-W01");
+    /// assert_eq!(SplitBy::Day.period(time), "2024-01-01");
+    /// ```
+    pub fn period(self, time: SystemTime) -> String {
+        let format = match self {
+            SplitBy::Month => "%Y-%m",
+            SplitBy::Week => "%G-W%V",
+            SplitBy::Day => "%Y-%m-%d",
+        };
+        local_date(time).format(format).to_string()
+    }
+}
+
+impl fmt::Display for SplitBy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SplitBy::Month => write!(f, "month"),
+            SplitBy::Week => write!(f, "week"),
+            SplitBy::Day => write!(f, "day"),
+        }
+    }
+}
+
+impl FromStr for SplitBy {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<SplitBy, String> {
+        match value {
+            "month" => Ok(SplitBy::Month),
+            "week" => Ok(SplitBy::Week),
+            "day" => Ok(SplitBy::Day),
+            _ => Err(format!("expected month, week, or day, got '{}'", value)),
+        }
+    }
+}
+
+/// Writes tasks with a [`Formatter`], a file per period, into a directory.
+///
+/// Each task goes in the file of the period it started in, so a task running past midnight
+/// is only in the file of the day it started.
+pub struct SplitExporter {
+    /// Formats the tasks of each file.
+    pub formatter: Box<dyn Formatter>,
+    /// How much time each file covers.
+    pub split_by: SplitBy,
+    /// The directory the files are written to.
+    pub output_dir: PathBuf,
+    /// The extension of the files, without the dot, e.g. `csv`.
+    pub extension: String,
+}
+
+impl SplitExporter {
+    /// Groups `tasks` by the file they go in, in the order they started.
+    pub fn split<'a>(&self, tasks: &'a [StoredTask]) -> BTreeMap<PathBuf, Vec<&'a StoredTask>> {
+        let mut files: BTreeMap<PathBuf, Vec<&StoredTask>> = BTreeMap::new();
+        for task in tasks {
+            files
+                .entry(self.path(&self.split_by.period(task.started_at)))
+                .or_default()
+                .push(task);
+        }
+        for tasks in files.values_mut() {
+            tasks.sort_by_key(|task| task.started_at);
+        }
+        files
+    }
+
+    /// Gets the files that exporting `tasks` would write over.
+    pub fn conflicts(&self, tasks: &[StoredTask]) -> Vec<PathBuf> {
+        self.split(tasks)
+            .into_keys()
+            .filter(|path| path.exists())
+            .collect()
+    }
+
+    /// Writes the files, replacing any that already exist. Returns the files that were written.
+    pub fn write(&self, tasks: &[StoredTask]) -> io::Result<Vec<PathBuf>> {
+        fs::create_dir_all(&self.output_dir).map_err(|e| with_path(&self.output_dir, e))?;
+        let mut paths = Vec::new();
+        for (path, tasks) in self.split(tasks) {
+            let tasks: Vec<StoredTask> = tasks.into_iter().cloned().collect();
+            fs::write(&path, self.formatter.format_output(&tasks))
+                .map_err(|e| with_path(&path, e))?;
+            paths.push(path);
+        }
+        Ok(paths)
+    }
+
+    /// Gets the file of a period.
+    fn path(&self, period: &str) -> PathBuf {
+        self.output_dir
+            .join(format!("{}.{}", period, self.extension))
+    }
+}
+
+/// Adds the path to an error, like the rest of the storage errors.
+fn with_path(path: &Path, e: io::Error) -> io::Error {
+    io::Error::new(e.kind(), format!("{}: {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests_split {
+    use super::*;
+    use crate::{formatter::CsvFormatter, reports::local_time};
+    use chrono::{NaiveDate, NaiveTime};
+    use std::time::Duration;
+
+    fn task(name: &str, month: u32, day: u32) -> StoredTask {
+        let start = local_time(
+            NaiveDate::from_ymd_opt(2024, month, day).unwrap(),
+            NaiveTime::from_hms_opt(9, 0, 0).unwrap(),
+        );
+        StoredTask::new(name, start, start + Duration::from_secs(600))
+    }
+
+    fn exporter(split_by: SplitBy, output_dir: PathBuf) -> SplitExporter {
+        SplitExporter {
+            formatter: Box::new(CsvFormatter),
+            split_by,
+            output_dir,
+            extension: String::from("csv"),
+        }
+    }
+
+    #[test]
+    fn groups_tasks_by_period() {
+        let tasks = [
+            task("feb", 2, 1),
+            task("jan 31", 1, 31),
+            task("jan 1", 1, 1),
+        ];
+        let dir = PathBuf::from("archive");
+        let stems = |split_by| -> Vec<(String, usize)> {
+            exporter(split_by, dir.clone())
+                .split(&tasks)
+                .into_iter()
+                .map(|(path, tasks)| (path.display().to_string(), tasks.len()))
+                .collect()
+        };
+        let path = |name: &str| dir.join(name).display().to_string();
+        assert_eq!(
+            stems(SplitBy::Month),
+            [(path("2024-01.csv"), 2), (path("2024-02.csv"), 1)]
+        );
+        // January 31 and February 1 2024 are both in week 5
+        assert_eq!(
+            stems(SplitBy::Week),
+            [(path("2024-W01.csv"), 1), (path("2024-W05.csv"), 2)]
+        );
+        assert_eq!(stems(SplitBy::Day).len(), 3);
+    }
+
+    #[test]
+    fn writes_a_file_per_period() {
+        let dir = std::env::temp_dir().join(format!("split_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let exporter = exporter(SplitBy::Month, dir.clone());
+        let tasks = [task("b", 1, 2), task("a", 1, 1), task("c", 3, 1)];
+
+        assert_eq!(exporter.conflicts(&tasks), Vec::<PathBuf>::new());
+        let paths = exporter.write(&tasks).unwrap();
+        assert_eq!(paths, [dir.join("2024-01.csv"), dir.join("2024-03.csv")]);
+        assert_eq!(exporter.conflicts(&tasks[..1]), [dir.join("2024-01.csv")]);
+
+        let january = fs::read_to_string(dir.join("2024-01.csv")).unwrap();
+        let names: Vec<&str> = january
+            .lines()
+            .skip(1)
+            .map(|line| line.split(',').next().unwrap())
+            .collect();
+        assert_eq!(names, ["a", "b"]);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn parses_split_by() {
+        assert_eq!("week".parse(), Ok(SplitBy::Week));
+        assert!("year".parse::<SplitBy>().is_err());
+        assert_eq!(SplitBy::Day.to_string(), "day");
+    }
+}
@@ -0,0 +1,191 @@
+//! Export to the [Timewarrior](https://timewarrior.net) database format.
+//!
+//! Timewarrior keeps one file per month (`YYYY-MM.data`) with one interval per line:
+//!
+//! ```text
+//! inc 20240115T090000Z - 20240115T093000Z # "daily standup" team meetings
+//! ```
+//!
+//! The task name is written as the interval's first tag, followed by the task's own tags.
+
+use crate::storage::StoredTask;
+use chrono::{DateTime, Utc};
+use std::{
+    collections::BTreeMap,
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+/// Formats a timestamp the way Timewarrior does (`YYYYMMDDTHHMMSSZ`).
+fn timewarrior_timestamp(time: SystemTime) -> String {
+    DateTime::<Utc>::from(time)
+        .format("%Y%m%dT%H%M%SZ")
+        .to_string()
+}
+
+/// Quotes a tag if Timewarrior would otherwise split it or misread it.
+fn quote_tag(tag: &str) -> String {
+    let is_plain = !tag.is_empty()
+        && tag
+            .chars()
+            .all(|c| c.is_alphanumeric() || c == '-' || c == '_' || c == '.');
+    if is_plain {
+        tag.to_string()
+    } else {
+        format!("\"{}\"", tag.replace('\\', "\\\\").replace('"', "\\\""))
+    }
+}
+
+/// Writes a single task as a Timewarrior `inc` line, tagged with its name and then its tags.
+///
+/// A task that is still running is written as an open interval, without an end time.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::{Duration, UNIX_EPOCH};
+/// use timetracker::{export::timewarrior::write_timewarrior_entry, storage::StoredTask};
+///
+/// let started_at = UNIX_EPOCH + Duration::from_secs(1_705_309_200);
+/// let task = StoredTask::new("standup", started_at, started_at + Duration::from_secs(900));
+///
+/// let mut line = Vec::new();
+/// write_timewarrior_entry(&mut line, &task).unwrap();
+/// assert_eq!(
+///     String::from_utf8(line).unwrap(),
+///     "inc 20240115T090000Z - 20240115T091500Z # standup\n"
+/// );
+/// ```
+pub fn write_timewarrior_entry(mut writer: impl Write, task: &StoredTask) -> io::Result<()> {
+    write!(writer, "inc {}", timewarrior_timestamp(task.started_at))?;
+    if let Some(stopped_at) = task.stopped_at {
+        write!(writer, " - {}", timewarrior_timestamp(stopped_at))?;
+    }
+    write!(writer, " # {}", quote_tag(&task.name))?;
+    for tag in &task.tags {
+        write!(writer, " {}", quote_tag(tag))?;
+    }
+    writeln!(writer)
+}
+
+/// Writes every task into the monthly data file for the month it started in, appending to files
+/// that already exist. Returns the files that were written to.
+pub fn write_timewarrior_files(
+    output_dir: &Path,
+    tasks: &[StoredTask],
+) -> io::Result<Vec<PathBuf>> {
+    let mut months: BTreeMap<String, Vec<&StoredTask>> = BTreeMap::new();
+    for task in tasks {
+        let month = DateTime::<Utc>::from(task.started_at)
+            .format("%Y-%m")
+            .to_string();
+        months.entry(month).or_default().push(task);
+    }
+
+    fs::create_dir_all(output_dir)?;
+    let mut paths = Vec::new();
+    for (month, mut tasks) in months {
+        tasks.sort_by_key(|task| task.started_at);
+
+        let path = output_dir.join(format!("{}.data", month));
+        let file = fs::OpenOptions::new()
+            .append(true)
+            .create(true)
+            .open(&path)?;
+        let mut writer = io::BufWriter::new(file);
+        for task in tasks {
+            write_timewarrior_entry(&mut writer, task)?;
+        }
+        writer.flush()?;
+        paths.push(path);
+    }
+
+    Ok(paths)
+}
+
+#[cfg(test)]
+mod tests_timewarrior {
+    use super::*;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    /// 2024-01-31T23:00:00Z
+    const JAN_31: u64 = 1_706_742_000;
+
+    #[test]
+    fn quotes_tags_with_spaces_and_quotes() {
+        assert_eq!(quote_tag("standup"), "standup");
+        assert_eq!(quote_tag("daily standup"), "\"daily standup\"");
+        assert_eq!(quote_tag("say \"hi\""), "\"say \\\"hi\\\"\"");
+    }
+
+    #[test]
+    fn writes_the_tasks_tags_after_its_name() {
+        let started_at = UNIX_EPOCH + Duration::from_secs(JAN_31);
+        let mut task = StoredTask::new(
+            "daily standup",
+            started_at,
+            started_at + Duration::from_secs(900),
+        );
+        task.tags = vec![String::from("team"), String::from("deep work")];
+
+        let mut line = Vec::new();
+        write_timewarrior_entry(&mut line, &task).unwrap();
+        assert_eq!(
+            String::from_utf8(line).unwrap(),
+            "inc 20240131T230000Z - 20240131T231500Z # \"daily standup\" team \"deep work\"\n"
+        );
+    }
+
+    #[test]
+    fn writes_open_interval_for_running_task() {
+        let mut task = StoredTask::new(
+            "a",
+            UNIX_EPOCH + Duration::from_secs(JAN_31),
+            UNIX_EPOCH + Duration::from_secs(JAN_31),
+        );
+        task.stopped_at = None;
+
+        let mut line = Vec::new();
+        write_timewarrior_entry(&mut line, &task).unwrap();
+        assert_eq!(
+            String::from_utf8(line).unwrap(),
+            "inc 20240131T230000Z # a\n"
+        );
+    }
+
+    #[test]
+    fn groups_tasks_by_month_and_appends() {
+        let dir = std::env::temp_dir().join(format!("timewarrior_{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+
+        let january = UNIX_EPOCH + Duration::from_secs(JAN_31);
+        let february = january + Duration::from_secs(2 * 3600);
+        let tasks = vec![
+            StoredTask::new("feb", february, february + Duration::from_secs(60)),
+            StoredTask::new("jan", january, january + Duration::from_secs(60)),
+        ];
+
+        let paths = write_timewarrior_files(&dir, &tasks).unwrap();
+        assert_eq!(
+            paths,
+            vec![dir.join("2024-01.data"), dir.join("2024-02.data")]
+        );
+        write_timewarrior_files(&dir, &tasks[..1]).unwrap();
+
+        assert_eq!(
+            fs::read_to_string(dir.join("2024-01.data")).unwrap(),
+            "inc 20240131T230000Z - 20240131T230100Z # jan\n"
+        );
+        assert_eq!(
+            fs::read_to_string(dir.join("2024-02.data"))
+                .unwrap()
+                .lines()
+                .count(),
+            2
+        );
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
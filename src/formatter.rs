@@ -0,0 +1,1876 @@
+//! Output formats for lists of stored tasks.
+//!
+//! Each format implements the [`Formatter`] trait, which turns a slice of
+//! [`StoredTask`]s into the text written by `time-tracker export`.
+
+use crate::{
+    format_duration_human, reports,
+    storage::{self, StoredTask},
+};
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, Utc};
+use chrono_tz::Tz;
+use serde::Serialize;
+use std::{
+    collections::BTreeMap,
+    fmt,
+    str::FromStr,
+    time::{Duration, SystemTime},
+};
+
+/// Turns a list of tasks into a string in some output format.
+pub trait Formatter {
+    /// Formats every task in `tasks`.
+    fn format_list(&self, tasks: &[StoredTask]) -> String;
+
+    /// Formats every task in `tasks` as the contents of a file or of standard output, which end
+    /// with a newline unless the format already ends its last line itself.
+    fn format_output(&self, tasks: &[StoredTask]) -> String {
+        let mut output = self.format_list(tasks);
+        if !output.ends_with('\n') {
+            output.push('\n');
+        }
+        output
+    }
+}
+
+/// Formats tasks as a JSON array.
+///
+/// The array is written on a single line unless `pretty` is set, in which case it is
+/// indented with two spaces.
+///
+/// # Examples
+///
+/// ```
+/// use timetracker::formatter::{Formatter, JsonFormatter};
+///
+/// let formatter = JsonFormatter { pretty: false };
+/// assert_eq!(formatter.format_list(&[]), "[]");
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+pub struct JsonFormatter {
+    /// Whether to pretty-print the output.
+    pub pretty: bool,
+}
+
+impl Formatter for JsonFormatter {
+    fn format_list(&self, tasks: &[StoredTask]) -> String {
+        let json = if self.pretty {
+            serde_json::to_string_pretty(tasks)
+        } else {
+            serde_json::to_string(tasks)
+        };
+        json.expect("stored tasks always serialize to JSON")
+    }
+}
+
+/// Formats tasks as newline-delimited JSON, one object per line, for log aggregators.
+///
+/// Every object starts with a `timestamp` field holding the time the task started, which log
+/// aggregators can use as the time of the entry.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::{Duration, UNIX_EPOCH};
+/// use timetracker::{formatter::NdjsonFormatter, storage::StoredTask};
+///
+/// let started_at = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+/// let task = StoredTask::new("Task 1", started_at, started_at + Duration::from_secs(90));
+/// assert!(NdjsonFormatter
+///     .format_task(&task)
+///     .starts_with(r#"{"timestamp":"2023-11-14T22:13:20Z","name":"Task 1","#));
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NdjsonFormatter;
+
+impl NdjsonFormatter {
+    /// Formats a single task as one line of JSON, without a line break.
+    pub fn format_task(&self, task: &StoredTask) -> String {
+        let mut object = serde_json::Map::new();
+        object.insert(
+            String::from("timestamp"),
+            storage::format_timestamp(task.started_at).into(),
+        );
+        match serde_json::to_value(task).expect("stored tasks always serialize to JSON") {
+            serde_json::Value::Object(fields) => object.extend(fields),
+            _ => unreachable!("stored tasks serialize to JSON objects"),
+        }
+        serde_json::Value::Object(object).to_string()
+    }
+}
+
+impl Formatter for NdjsonFormatter {
+    fn format_list(&self, tasks: &[StoredTask]) -> String {
+        let lines: Vec<String> = tasks.iter().map(|task| self.format_task(task)).collect();
+        lines.join("\n")
+    }
+}
+
+/// Which files an Org-mode clocktable collects clocked time from.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum OrgScope {
+    /// The file the table is in.
+    #[default]
+    File,
+    /// Every agenda file.
+    Agenda,
+}
+
+impl fmt::Display for OrgScope {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            OrgScope::File => write!(f, "file"),
+            OrgScope::Agenda => write!(f, "agenda"),
+        }
+    }
+}
+
+impl FromStr for OrgScope {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<OrgScope, String> {
+        match value {
+            "file" => Ok(OrgScope::File),
+            "agenda" => Ok(OrgScope::Agenda),
+            _ => Err(format!("expected file or agenda, got '{}'", value)),
+        }
+    }
+}
+
+/// Formats the total time of each task as an Org-mode clocktable, the way Org writes one.
+///
+/// Each task name is a headline, and times are written as `H:MM`, rounded to the minute.
+///
+/// # Examples
+///
+/// ```
+/// use timetracker::formatter::{Formatter, OrgClocktableFormatter};
+///
+/// let table = OrgClocktableFormatter::default().format_list(&[]);
+/// assert!(table.starts_with("#+BEGIN: clocktable :maxlevel 2 :scope file\n"));
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+pub struct OrgClocktableFormatter {
+    /// The `:scope` of the table.
+    pub scope: OrgScope,
+}
+
+/// Formats seconds as Org does, `H:MM`, rounded to the nearest minute.
+fn org_duration(seconds: u64) -> String {
+    let minutes = (seconds + 30) / 60;
+    format!("{}:{:02}", minutes / 60, minutes % 60)
+}
+
+impl Formatter for OrgClocktableFormatter {
+    fn format_list(&self, tasks: &[StoredTask]) -> String {
+        let total: u64 = tasks.iter().map(|task| task.seconds).sum();
+        let mut rows = vec![(
+            String::from("*Total time*"),
+            format!("*{}*", org_duration(total)),
+        )];
+        rows.extend(
+            reports::totals_by_name(tasks)
+                .into_iter()
+                // a `|` would end the cell early
+                .map(|(name, seconds)| (name.replace('|', "\\vert{}"), org_duration(seconds))),
+        );
+        let name_width = rows
+            .iter()
+            .map(|(name, _)| name.chars().count())
+            .chain(["Headline".len()])
+            .max()
+            .unwrap_or_default();
+        let time_width = rows
+            .iter()
+            .map(|(_, time)| time.len())
+            .chain(["Time".len()])
+            .max()
+            .unwrap_or_default();
+        let row =
+            |name: &str, time: &str| format!("| {:<name_width$} | {:<time_width$} |", name, time);
+        let rule = format!(
+            "|{}+{}|",
+            "-".repeat(name_width + 2),
+            "-".repeat(time_width + 2)
+        );
+
+        let mut lines = vec![
+            format!("#+BEGIN: clocktable :maxlevel 2 :scope {}", self.scope),
+            row("Headline", "Time"),
+            rule.clone(),
+        ];
+        let (total_name, total_time) = &rows[0];
+        lines.push(row(total_name, total_time));
+        lines.push(rule);
+        lines.extend(rows[1..].iter().map(|(name, time)| row(name, time)));
+        lines.push(String::from("#+END:"));
+        lines.join("\n")
+    }
+}
+
+/// The longest front an Anki card gets, in characters. Longer task names are cut short and
+/// end in `...`.
+pub const ANKI_MAX_FRONT_CHARS: usize = 100;
+
+/// Formats tasks as an Anki deck to import, one card per task.
+///
+/// The front of a card is the task name and the back the time tracked, e.g. `1 Hour and 30
+/// Minutes`. Fields are separated by tabs, which the `#separator:tab` header tells Anki, and
+/// are plain text rather than HTML.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::{Duration, UNIX_EPOCH};
+/// use timetracker::{formatter::{AnkiFormatter, Formatter}, storage::StoredTask};
+///
+/// let started_at = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+/// let task = StoredTask::new("Spanish verbs", started_at, started_at + Duration::from_secs(5400));
+/// assert_eq!(
+///     AnkiFormatter.format_list(&[task]),
+///     "#separator:tab\n#html:false\nSpanish verbs\t1 Hour and 30 Minutes"
+/// );
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+pub struct AnkiFormatter;
+
+impl AnkiFormatter {
+    /// Gets the front of the card for a task name: the name on one line, cut short if it is
+    /// longer than [`ANKI_MAX_FRONT_CHARS`].
+    fn front(name: &str) -> String {
+        let name = name.replace(['\t', '\n', '\r'], " ");
+        if name.chars().count() <= ANKI_MAX_FRONT_CHARS {
+            return name;
+        }
+        let kept: String = name.chars().take(ANKI_MAX_FRONT_CHARS - 3).collect();
+        format!("{}...", kept)
+    }
+}
+
+impl Formatter for AnkiFormatter {
+    fn format_list(&self, tasks: &[StoredTask]) -> String {
+        let mut lines = vec![String::from("#separator:tab"), String::from("#html:false")];
+        lines.extend(tasks.iter().map(|task| {
+            format!(
+                "{}\t{}",
+                Self::front(&task.name),
+                format_duration_human(task.seconds)
+            )
+        }));
+        lines.join("\n")
+    }
+}
+
+/// Formats tasks as a JUnit XML test suite, for CI systems like Jenkins to chart.
+///
+/// Each task is a `<testcase>` whose `time` is the seconds tracked. A task that took longer
+/// than `estimate_secs` fails, and a task that is still running is skipped.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::{Duration, UNIX_EPOCH};
+/// use timetracker::{formatter::{Formatter, JunitFormatter}, storage::StoredTask};
+///
+/// let task = StoredTask::new("deploy", UNIX_EPOCH, UNIX_EPOCH + Duration::from_secs(90));
+/// let xml = JunitFormatter { estimate_secs: Some(60) }.format_list(&[task]);
+/// assert!(xml.contains(r#"<testcase classname="time-tracker" name="deploy" time="90.000">"#));
+/// assert!(xml.contains("<failure"));
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+pub struct JunitFormatter {
+    /// The longest a task is expected to take, in seconds. Tasks that take longer fail.
+    pub estimate_secs: Option<u64>,
+}
+
+/// Escapes text for an XML attribute. Control characters XML doesn't allow become spaces.
+pub(crate) fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            '\t' => escaped.push_str("&#9;"),
+            '\n' => escaped.push_str("&#10;"),
+            '\r' => escaped.push_str("&#13;"),
+            c if c.is_control() => escaped.push(' '),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+impl Formatter for JunitFormatter {
+    fn format_list(&self, tasks: &[StoredTask]) -> String {
+        let over_estimate = |task: &StoredTask| {
+            task.stopped_at.is_some() && self.estimate_secs.is_some_and(|max| task.seconds > max)
+        };
+        let failures = tasks.iter().filter(|task| over_estimate(task)).count();
+        let skipped = tasks
+            .iter()
+            .filter(|task| task.stopped_at.is_none())
+            .count();
+        let total: u64 = tasks.iter().map(|task| task.seconds).sum();
+
+        let mut lines = vec![
+            String::from(r#"<?xml version="1.0" encoding="UTF-8"?>"#),
+            format!(
+                concat!(
+                    r#"<testsuite name="time-tracker" tests="{}" failures="{}" errors="0""#,
+                    r#" skipped="{}" time="{:.3}">"#
+                ),
+                tasks.len(),
+                failures,
+                skipped,
+                total as f64
+            ),
+        ];
+        for task in tasks {
+            let testcase = format!(
+                r#"  <testcase classname="time-tracker" name="{}" time="{:.3}""#,
+                escape_xml(&task.name),
+                task.seconds as f64
+            );
+            if task.stopped_at.is_none() {
+                lines.push(format!("{}>", testcase));
+                lines.push(String::from(r#"    <skipped message="still running"/>"#));
+                lines.push(String::from("  </testcase>"));
+            } else if over_estimate(task) {
+                let estimate = self.estimate_secs.unwrap_or_default();
+                lines.push(format!("{}>", testcase));
+                lines.push(format!(
+                    r#"    <failure message="took {}, over the estimate of {}" type="{}"/>"#,
+                    crate::format_duration_compact(task.seconds),
+                    crate::format_duration_compact(estimate),
+                    "OverEstimate"
+                ));
+                lines.push(String::from("  </testcase>"));
+            } else {
+                lines.push(format!("{}/>", testcase));
+            }
+        }
+        lines.push(String::from("</testsuite>"));
+        lines.join("\n")
+    }
+}
+
+/// The shortest event a Google Calendar export writes, in seconds. Shorter tasks are padded.
+pub const GCAL_MIN_EVENT_SECS: u64 = 15 * 60;
+
+/// Formats tasks as a CSV file for Google Calendar to import, one event per task.
+///
+/// Dates are written as `MM/DD/YYYY` and times as `HH:MM AM`, in `zone` (or the local time
+/// zone when it is `None`). Google Calendar barely shows events under 15 minutes, so shorter
+/// tasks last [`GCAL_MIN_EVENT_SECS`] instead, and their description says how long they
+/// really took. A task that is still running ends at the time tracked so far.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::{Duration, UNIX_EPOCH};
+/// use timetracker::{formatter::{Formatter, GoogleCalendarFormatter}, storage::StoredTask};
+///
+/// let started_at = UNIX_EPOCH + Duration::from_secs(1_705_330_800);
+/// let task = StoredTask::new("standup", started_at, started_at + Duration::from_secs(480));
+/// let formatter = GoogleCalendarFormatter { zone: Some(chrono_tz::UTC) };
+/// assert_eq!(
+///     formatter.format_list(&[task]).lines().nth(1),
+///     Some("standup,01/15/2024,03:00 PM,01/15/2024,03:15 PM,False,Actual duration: 8 minutes")
+/// );
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+pub struct GoogleCalendarFormatter {
+    /// The time zone of the dates and times, or `None` for the local time zone.
+    pub zone: Option<Tz>,
+}
+
+/// Gets the wall-clock time of `time` in `zone`, or in the local time zone when it is `None`.
+pub(crate) fn wall_clock(time: SystemTime, zone: Option<Tz>) -> NaiveDateTime {
+    let time = DateTime::<Utc>::from(time);
+    match zone {
+        Some(zone) => time.with_timezone(&zone).naive_local(),
+        None => time.with_timezone(&Local).naive_local(),
+    }
+}
+
+/// Gets when a task ends, or when it would end if it stopped now for one that is running.
+pub(crate) fn end_of(task: &StoredTask) -> SystemTime {
+    task.stopped_at
+        .unwrap_or(task.started_at + Duration::from_secs(task.seconds))
+}
+
+impl GoogleCalendarFormatter {
+    /// Gets the date and time of `time` as Google Calendar writes them.
+    fn date_and_time(&self, time: SystemTime) -> (String, String) {
+        let local = wall_clock(time, self.zone);
+        (
+            local.format("%m/%d/%Y").to_string(),
+            local.format("%I:%M %p").to_string(),
+        )
+    }
+}
+
+/// Describes how long a padded task really took, e.g. `8 minutes` or `45 seconds`.
+fn actual_duration(seconds: u64) -> String {
+    let (amount, unit) = if seconds < 60 {
+        (seconds, "second")
+    } else {
+        (seconds / 60, "minute")
+    };
+    let plural = if amount == 1 { "" } else { "s" };
+    format!("{} {}{}", amount, unit, plural)
+}
+
+impl Formatter for GoogleCalendarFormatter {
+    fn format_list(&self, tasks: &[StoredTask]) -> String {
+        let mut lines = vec![String::from(
+            "Subject,Start Date,Start Time,End Date,End Time,All Day Event,Description",
+        )];
+        for task in tasks {
+            let (description, seconds) = if task.seconds < GCAL_MIN_EVENT_SECS {
+                (
+                    format!("Actual duration: {}", actual_duration(task.seconds)),
+                    GCAL_MIN_EVENT_SECS,
+                )
+            } else {
+                (String::new(), task.seconds)
+            };
+            let (start_date, start_time) = self.date_and_time(task.started_at);
+            let (end_date, end_time) =
+                self.date_and_time(task.started_at + Duration::from_secs(seconds));
+            lines.push(format!(
+                "{},{},{},{},{},False,{}",
+                CsvFormatter.escape_field(&task.name),
+                start_date,
+                start_time,
+                end_date,
+                end_time,
+                description
+            ));
+        }
+        lines.join("\n")
+    }
+}
+
+/// The calendar an AppleScript export adds events to when none is given.
+pub const DEFAULT_APPLESCRIPT_CALENDAR: &str = "Time Tracking";
+
+/// Formats tasks as an AppleScript that adds an event per task to a macOS Calendar calendar
+/// when run with `osascript`.
+///
+/// Times are written as AppleScript date literals, e.g. `date "January 15, 2024 09:15:00"`, in
+/// `zone` (or the local time zone when it is `None`).
+///
+/// # Examples
+///
+/// ```
+/// use std::time::{Duration, UNIX_EPOCH};
+/// use timetracker::{formatter::{AppleScriptFormatter, Formatter}, storage::StoredTask};
+///
+/// let started_at = UNIX_EPOCH + Duration::from_secs(1_705_310_100);
+/// let task = StoredTask::new("standup", started_at, started_at + Duration::from_secs(900));
+/// let formatter = AppleScriptFormatter {
+///     calendar: String::from("Work"),
+///     zone: Some(chrono_tz::UTC),
+/// };
+/// let script = formatter.format_list(&[task]);
+/// assert!(script.starts_with("tell application \"Calendar\"\n\ttell calendar \"Work\"\n"));
+/// assert!(script.contains(r#"start date:date "January 15, 2024 09:15:00""#));
+/// ```
+#[derive(Clone, Debug)]
+pub struct AppleScriptFormatter {
+    /// The name of the calendar to add the events to.
+    pub calendar: String,
+    /// The time zone of the dates, or `None` for the local time zone.
+    pub zone: Option<Tz>,
+}
+
+impl Default for AppleScriptFormatter {
+    fn default() -> AppleScriptFormatter {
+        AppleScriptFormatter {
+            calendar: String::from(DEFAULT_APPLESCRIPT_CALENDAR),
+            zone: None,
+        }
+    }
+}
+
+/// Writes text as an AppleScript string literal, escaping backslashes and double quotes.
+fn applescript_string(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+impl AppleScriptFormatter {
+    /// Writes `time` as an AppleScript date literal.
+    fn date(&self, time: SystemTime) -> String {
+        let local = wall_clock(time, self.zone);
+        format!("date \"{}\"", local.format("%B %-d, %Y %H:%M:%S"))
+    }
+}
+
+impl Formatter for AppleScriptFormatter {
+    fn format_list(&self, tasks: &[StoredTask]) -> String {
+        let mut lines = vec![
+            String::from("tell application \"Calendar\""),
+            format!("\ttell calendar {}", applescript_string(&self.calendar)),
+        ];
+        for task in tasks {
+            lines.push(format!(
+                "\t\tmake new event with properties {{summary:{}, start date:{}, end date:{}}}",
+                applescript_string(&task.name),
+                self.date(task.started_at),
+                self.date(end_of(task)),
+            ));
+        }
+        lines.push(String::from("\tend tell"));
+        lines.push(String::from("end tell"));
+        lines.join("\n")
+    }
+}
+
+/// Formats tasks as a CSV file for Notion to import into a database, one row per task.
+///
+/// The columns are `Name` (the title), `Date` (`YYYY-MM-DD`, in `zone` or the local time zone
+/// when it is `None`), `Duration (Hours)` as a decimal, `Tags` as a comma-separated list in a
+/// single cell, `Client`, and `Notes`, which is left empty. Tags that contain a comma are quoted
+/// within the list, so that they aren't split into several tags.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::{Duration, UNIX_EPOCH};
+/// use timetracker::{formatter::{Formatter, NotionFormatter}, storage::StoredTask};
+///
+/// let started_at = UNIX_EPOCH + Duration::from_secs(1_705_310_100);
+/// let task = StoredTask::new("standup", started_at, started_at + Duration::from_secs(5400));
+/// let csv = NotionFormatter { zone: Some(chrono_tz::UTC) }.format_list(&[task]);
+/// assert_eq!(csv.lines().nth(1), Some("standup,2024-01-15,1.5,,,"));
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+pub struct NotionFormatter {
+    /// The time zone of the dates, or `None` for the local time zone.
+    pub zone: Option<Tz>,
+}
+
+impl Formatter for NotionFormatter {
+    fn format_list(&self, tasks: &[StoredTask]) -> String {
+        let mut lines = vec![String::from("Name,Date,Duration (Hours),Tags,Client,Notes")];
+        for task in tasks {
+            // rounded to the hundredth, and written with `{:?}` so whole hours keep a `.0`
+            let hours = (task.seconds as f64 / 36.0).round() / 100.0;
+            let fields = [
+                task.name.clone(),
+                wall_clock(task.started_at, self.zone)
+                    .format("%Y-%m-%d")
+                    .to_string(),
+                format!("{:?}", hours),
+                task.tags
+                    .iter()
+                    .map(|tag| CsvFormatter.escape_field(tag))
+                    .collect::<Vec<String>>()
+                    .join(","),
+                task.client.clone().unwrap_or_default(),
+                String::new(),
+            ];
+            let fields: Vec<String> = fields
+                .iter()
+                .map(|field| CsvFormatter.escape_field(field))
+                .collect();
+            lines.push(fields.join(","));
+        }
+        lines.join("\n")
+    }
+}
+
+/// The most hours Harvest accepts for a single time entry.
+pub const HARVEST_MAX_ENTRY_HOURS: f64 = 24.0;
+
+/// Formats tasks as a CSV file of time entries for Harvest to import.
+///
+/// Each task is a row of `Date` (`YYYY-MM-DD`, in `zone` or the local time zone when it is
+/// `None`), `Client`, `Project`, `Task` (the task name), `Notes` (its tags), and `Hours` with
+/// two decimal places. Tasks without a client or project get `default_client` or
+/// `default_project`, since Harvest needs both.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::{Duration, UNIX_EPOCH};
+/// use timetracker::{formatter::{Formatter, HarvestCsvFormatter}, storage::StoredTask};
+///
+/// let started_at = UNIX_EPOCH + Duration::from_secs(1_705_310_100);
+/// let task = StoredTask::new("standup", started_at, started_at + Duration::from_secs(5400));
+/// let formatter = HarvestCsvFormatter {
+///     default_client: String::from("Internal"),
+///     default_project: String::from("Meetings"),
+///     zone: Some(chrono_tz::UTC),
+/// };
+/// assert_eq!(
+///     formatter.format_list(&[task]).lines().nth(1),
+///     Some("2024-01-15,Internal,Meetings,standup,,1.50")
+/// );
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct HarvestCsvFormatter {
+    /// The client of tasks that don't have one.
+    pub default_client: String,
+    /// The project of tasks that don't have one.
+    pub default_project: String,
+    /// The time zone of the dates, or `None` for the local time zone.
+    pub zone: Option<Tz>,
+}
+
+impl HarvestCsvFormatter {
+    /// Gets the tasks Harvest would reject for having more than [`HARVEST_MAX_ENTRY_HOURS`].
+    pub fn too_long(tasks: &[StoredTask]) -> impl Iterator<Item = &StoredTask> {
+        tasks
+            .iter()
+            .filter(|task| task.seconds as f64 / 3600.0 > HARVEST_MAX_ENTRY_HOURS)
+    }
+}
+
+impl Formatter for HarvestCsvFormatter {
+    fn format_list(&self, tasks: &[StoredTask]) -> String {
+        let mut lines = vec![String::from("Date,Client,Project,Task,Notes,Hours")];
+        for task in tasks {
+            let fields = [
+                wall_clock(task.started_at, self.zone)
+                    .format("%Y-%m-%d")
+                    .to_string(),
+                task.client
+                    .clone()
+                    .unwrap_or_else(|| self.default_client.clone()),
+                task.project
+                    .clone()
+                    .unwrap_or_else(|| self.default_project.clone()),
+                task.name.clone(),
+                task.tags.join(", "),
+                format!("{:.2}", task.seconds as f64 / 3600.0),
+            ];
+            let fields: Vec<String> = fields
+                .iter()
+                .map(|field| CsvFormatter.escape_field(field))
+                .collect();
+            lines.push(fields.join(","));
+        }
+        lines.join("\n")
+    }
+}
+
+/// Finds the first JIRA issue key in a task name, e.g. `PROJ-123` in `PROJ-123 fix login`.
+///
+/// A key is a project key (an uppercase letter, then uppercase letters or digits), a dash, and
+/// an issue number, standing on its own rather than inside a longer word.
+///
+/// # Examples
+///
+/// ```
+/// use timetracker::formatter::jira_issue_key;
+///
+/// assert_eq!(jira_issue_key("fix login (PROJ-123)"), Some("PROJ-123"));
+/// assert_eq!(jira_issue_key("follow-up 2"), None);
+/// assert_eq!(jira_issue_key("xPROJ-1"), None);
+/// ```
+pub fn jira_issue_key(name: &str) -> Option<&str> {
+    let bytes = name.as_bytes();
+    let is_word = |i: usize| bytes.get(i).is_some_and(|b| b.is_ascii_alphanumeric());
+    let mut start = 0;
+    while start < bytes.len() {
+        if !bytes[start].is_ascii_uppercase() || (start > 0 && is_word(start - 1)) {
+            start += 1;
+            continue;
+        }
+        let mut dash = start + 1;
+        while dash < bytes.len()
+            && (bytes[dash].is_ascii_uppercase() || bytes[dash].is_ascii_digit())
+        {
+            dash += 1;
+        }
+        let mut end = dash + 1;
+        while end < bytes.len() && bytes[end].is_ascii_digit() {
+            end += 1;
+        }
+        if dash > start + 1 && bytes.get(dash) == Some(&b'-') && end > dash + 1 && !is_word(end) {
+            return Some(&name[start..end]);
+        }
+        start = dash;
+    }
+    None
+}
+
+/// A worklog as Tempo's REST API takes it.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct TempoWorklog {
+    issue_key: String,
+    time_spent_seconds: u64,
+    start_date: String,
+    description: String,
+}
+
+/// Formats tasks as a JSON array of Tempo Timesheets worklogs.
+///
+/// A task whose name has a JIRA issue key (see [`jira_issue_key`]) is logged against that
+/// issue, with the task name as the description. Tasks without one are added up into a
+/// single worklog per day against `default_issue`, described by their names. Start dates are
+/// in `zone`, or the local time zone when it is `None`.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::{Duration, UNIX_EPOCH};
+/// use timetracker::{formatter::{Formatter, TempoFormatter}, storage::StoredTask};
+///
+/// let started_at = UNIX_EPOCH + Duration::from_secs(1_705_310_100);
+/// let stopped_at = started_at + Duration::from_secs(3600);
+/// let task = StoredTask::new("PROJ-123 login", started_at, stopped_at);
+/// let formatter = TempoFormatter {
+///     default_issue: String::from("OPS-1"),
+///     pretty: false,
+///     zone: Some(chrono_tz::UTC),
+/// };
+/// assert_eq!(
+///     formatter.format_list(&[task]),
+///     concat!(
+///         r#"[{"issueKey":"PROJ-123","timeSpentSeconds":3600,"#,
+///         r#""startDate":"2024-01-15","description":"PROJ-123 login"}]"#
+///     )
+/// );
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct TempoFormatter {
+    /// The issue that time on tasks without an issue key is logged against.
+    pub default_issue: String,
+    /// Whether to pretty-print the output.
+    pub pretty: bool,
+    /// The time zone of the start dates, or `None` for the local time zone.
+    pub zone: Option<Tz>,
+}
+
+impl Formatter for TempoFormatter {
+    fn format_list(&self, tasks: &[StoredTask]) -> String {
+        let mut worklogs = Vec::new();
+        let mut unlinked: BTreeMap<NaiveDate, (u64, Vec<&str>)> = BTreeMap::new();
+        for task in tasks {
+            let date = wall_clock(task.started_at, self.zone).date();
+            match jira_issue_key(&task.name) {
+                Some(key) => worklogs.push(TempoWorklog {
+                    issue_key: key.to_string(),
+                    time_spent_seconds: task.seconds,
+                    start_date: date.to_string(),
+                    description: task.name.clone(),
+                }),
+                None => {
+                    let (seconds, names) = unlinked.entry(date).or_default();
+                    *seconds += task.seconds;
+                    if !names.contains(&task.name.as_str()) {
+                        names.push(&task.name);
+                    }
+                }
+            }
+        }
+        worklogs.extend(
+            unlinked
+                .into_iter()
+                .map(|(date, (seconds, names))| TempoWorklog {
+                    issue_key: self.default_issue.clone(),
+                    time_spent_seconds: seconds,
+                    start_date: date.to_string(),
+                    description: names.join("; "),
+                }),
+        );
+        let json = if self.pretty {
+            serde_json::to_string_pretty(&worklogs)
+        } else {
+            serde_json::to_string(&worklogs)
+        };
+        json.expect("worklogs always serialize to JSON")
+    }
+}
+
+/// A time entry as Toggl Track's bulk create API takes it.
+#[derive(Serialize)]
+struct TogglTimeEntry<'a> {
+    created_with: &'static str,
+    description: &'a str,
+    workspace_id: u64,
+    project_id: Option<u64>,
+    start: String,
+    stop: Option<String>,
+    tags: &'a [String],
+}
+
+/// Formats tasks as the body of a Toggl Track bulk create request, a JSON array of time
+/// entries ready to `POST` to `/api/v9/workspaces/{workspace_id}/time_entries`.
+///
+/// Start and stop times are RFC 3339 timestamps in UTC, and a running task has a `null` stop.
+/// The output is always pretty-printed, so it's easy to check before sending it.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::{Duration, UNIX_EPOCH};
+/// use timetracker::{formatter::{Formatter, TogglApiFormatter}, storage::StoredTask};
+///
+/// let started_at = UNIX_EPOCH + Duration::from_secs(1_705_310_100);
+/// let task = StoredTask::new("standup", started_at, started_at + Duration::from_secs(900));
+/// let json = TogglApiFormatter { workspace_id: 12345, project_id: None }.format_list(&[task]);
+/// assert!(json.contains(r#""start": "2024-01-15T09:15:00Z","#));
+/// assert!(json.contains(r#""project_id": null,"#));
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TogglApiFormatter {
+    /// The workspace the entries are created in.
+    pub workspace_id: u64,
+    /// The project the entries belong to, if any.
+    pub project_id: Option<u64>,
+}
+
+impl Formatter for TogglApiFormatter {
+    fn format_list(&self, tasks: &[StoredTask]) -> String {
+        let entries: Vec<TogglTimeEntry> = tasks
+            .iter()
+            .map(|task| TogglTimeEntry {
+                created_with: "time-tracker",
+                description: &task.name,
+                workspace_id: self.workspace_id,
+                project_id: self.project_id,
+                start: storage::format_timestamp(task.started_at),
+                stop: task.stopped_at.map(storage::format_timestamp),
+                tags: &task.tags,
+            })
+            .collect();
+        serde_json::to_string_pretty(&entries).expect("time entries always serialize to JSON")
+    }
+}
+
+/// A time entry as Clockify's API takes it.
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ClockifyTimeEntry<'a> {
+    description: &'a str,
+    start: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    end: Option<String>,
+    billable: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    project_id: Option<&'a str>,
+}
+
+/// Gets the Clockify time entry for a task. A running task has no `end`, which Clockify
+/// takes as a timer that is still going.
+fn clockify_entry<'a>(task: &'a StoredTask, project_id: Option<&'a str>) -> ClockifyTimeEntry<'a> {
+    ClockifyTimeEntry {
+        description: &task.name,
+        start: storage::format_timestamp(task.started_at),
+        end: task.stopped_at.map(storage::format_timestamp),
+        billable: task.billable,
+        project_id,
+    }
+}
+
+/// Formats tasks as a pretty-printed JSON array of Clockify time entries.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::{Duration, UNIX_EPOCH};
+/// use timetracker::{formatter::{ClockifyJsonFormatter, Formatter}, storage::StoredTask};
+///
+/// let started_at = UNIX_EPOCH + Duration::from_secs(1_705_310_100);
+/// let task = StoredTask::new("standup", started_at, started_at + Duration::from_secs(900));
+/// let json = ClockifyJsonFormatter::default().format_list(&[task]);
+/// assert!(json.contains(r#""end": "2024-01-15T09:30:00Z","#));
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct ClockifyJsonFormatter {
+    /// The project the entries belong to, if any.
+    pub project_id: Option<String>,
+}
+
+impl Formatter for ClockifyJsonFormatter {
+    fn format_list(&self, tasks: &[StoredTask]) -> String {
+        let entries: Vec<ClockifyTimeEntry> = tasks
+            .iter()
+            .map(|task| clockify_entry(task, self.project_id.as_deref()))
+            .collect();
+        serde_json::to_string_pretty(&entries).expect("time entries always serialize to JSON")
+    }
+}
+
+/// Quotes text for a POSIX shell, in single quotes.
+fn shell_quote(text: &str) -> String {
+    format!("'{}'", text.replace('\'', r"'\''"))
+}
+
+/// Formats tasks as a bash script that creates a Clockify time entry for each one with `curl`.
+///
+/// The script uses `api_key` if one is given, and otherwise reads the `CLOCKIFY_API_KEY`
+/// environment variable when it runs, which keeps the key out of scripts that get shared.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::{Duration, UNIX_EPOCH};
+/// use timetracker::{formatter::{ClockifyScriptFormatter, Formatter}, storage::StoredTask};
+///
+/// let started_at = UNIX_EPOCH + Duration::from_secs(1_705_310_100);
+/// let task = StoredTask::new("standup", started_at, started_at + Duration::from_secs(900));
+/// let formatter = ClockifyScriptFormatter {
+///     workspace_id: String::from("abc123"),
+///     ..Default::default()
+/// };
+/// let script = formatter.format_list(&[task]);
+/// assert!(script.starts_with("#!/usr/bin/env bash\n"));
+/// assert!(script.contains("/workspaces/abc123/time-entries"));
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct ClockifyScriptFormatter {
+    /// The workspace the entries are created in.
+    pub workspace_id: String,
+    /// The API key the script authenticates with, or `None` to read `CLOCKIFY_API_KEY`.
+    pub api_key: Option<String>,
+    /// The project the entries belong to, if any.
+    pub project_id: Option<String>,
+}
+
+impl Formatter for ClockifyScriptFormatter {
+    fn format_list(&self, tasks: &[StoredTask]) -> String {
+        let api_key = match &self.api_key {
+            Some(key) => shell_quote(key),
+            None => String::from(r#""${CLOCKIFY_API_KEY:?set CLOCKIFY_API_KEY}""#),
+        };
+        let url = format!(
+            "https://api.clockify.me/api/v1/workspaces/{}/time-entries",
+            self.workspace_id
+        );
+        let mut lines = vec![
+            String::from("#!/usr/bin/env bash"),
+            String::from("set -euo pipefail"),
+            format!("api_key={}", api_key),
+            format!("url={}", shell_quote(&url)),
+        ];
+        for task in tasks {
+            let entry = clockify_entry(task, self.project_id.as_deref());
+            let body =
+                serde_json::to_string(&entry).expect("time entries always serialize to JSON");
+            lines.push(format!(
+                concat!(
+                    r#"curl -sS -X POST "$url" -H "X-Api-Key: $api_key""#,
+                    r#" -H 'Content-Type: application/json' -d {}"#
+                ),
+                shell_quote(&body)
+            ));
+        }
+        lines.join("\n")
+    }
+}
+
+/// A [JSON Feed](https://jsonfeed.org/version/1.1) document.
+#[derive(Serialize)]
+struct JsonFeed<'a> {
+    version: &'static str,
+    title: &'static str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    home_page_url: Option<&'a str>,
+    items: Vec<JsonFeedItem<'a>>,
+}
+
+/// A completed task as a JSON Feed item.
+#[derive(Serialize)]
+struct JsonFeedItem<'a> {
+    id: String,
+    title: &'a str,
+    date_published: String,
+    content_text: String,
+}
+
+/// Formats the completed tasks as a [JSON Feed](https://jsonfeed.org), so a feed reader can
+/// subscribe to them.
+///
+/// Each task is an item with the task's id, its name as the `title`, when it stopped as
+/// `date_published`, and how long it took (like [`Task::time_tracked_string`]) as
+/// `content_text`. Items are newest first, and running tasks are left out.
+///
+/// [`Task::time_tracked_string`]: crate::Task::time_tracked_string
+///
+/// # Examples
+///
+/// ```
+/// use std::time::{Duration, UNIX_EPOCH};
+/// use timetracker::{formatter::{Formatter, JsonFeedFormatter}, storage::StoredTask};
+///
+/// let started_at = UNIX_EPOCH + Duration::from_secs(1_705_310_100);
+/// let task = StoredTask::new("standup", started_at, started_at + Duration::from_secs(900));
+/// let feed = JsonFeedFormatter::default().format_list(&[task]);
+/// assert!(feed.contains(r#""title": "Time Tracking Feed","#));
+/// assert!(feed.contains(r#""content_text": "15 Minutes""#));
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct JsonFeedFormatter {
+    /// The URL of the website the feed belongs to, if any.
+    pub home_page_url: Option<String>,
+}
+
+impl Formatter for JsonFeedFormatter {
+    fn format_list(&self, tasks: &[StoredTask]) -> String {
+        let mut completed: Vec<(&StoredTask, SystemTime)> = tasks
+            .iter()
+            .filter_map(|task| Some((task, task.stopped_at?)))
+            .collect();
+        completed.sort_by_key(|&(_, stopped_at)| std::cmp::Reverse(stopped_at));
+        let feed = JsonFeed {
+            version: "https://jsonfeed.org/version/1.1",
+            title: "Time Tracking Feed",
+            home_page_url: self.home_page_url.as_deref(),
+            items: completed
+                .into_iter()
+                .map(|(task, stopped_at)| JsonFeedItem {
+                    id: task.id.to_string(),
+                    title: &task.name,
+                    date_published: storage::format_timestamp(stopped_at),
+                    content_text: format_duration_human(task.seconds),
+                })
+                .collect(),
+        };
+        serde_json::to_string_pretty(&feed).expect("a feed always serializes to JSON")
+    }
+}
+
+/// Lays tasks out as a Markdown kanban board with a column per day, each task a card with its
+/// name and duration.
+///
+/// Cards are sorted longest first within a column, and names are cut short (ending in `…`) so
+/// that every card fits in `column_width` characters. [`Formatter::format_list`] shows every
+/// day from the first task to the last; use [`KanbanFormatter::board`] for a fixed range.
+///
+/// # Examples
+///
+/// ```
+/// use chrono::NaiveDate;
+/// use std::time::{Duration, UNIX_EPOCH};
+/// use timetracker::{formatter::KanbanFormatter, storage::StoredTask};
+///
+/// let started_at = UNIX_EPOCH + Duration::from_secs(1_705_320_000);
+/// let task = StoredTask::new("standup", started_at, started_at + Duration::from_secs(900));
+/// let monday = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+/// let board = KanbanFormatter { column_width: 14 }.board(&[task], monday, monday);
+/// assert_eq!(board, "| Mon 2024-01-15 |\n| -------------- |\n| standup (15m)  |");
+/// ```
+#[derive(Clone, Copy, Debug)]
+pub struct KanbanFormatter {
+    /// The width of a column, in characters.
+    pub column_width: usize,
+}
+
+impl KanbanFormatter {
+    /// Writes a task as a card, cutting its name short if the card would be wider than a
+    /// column.
+    fn card(&self, task: &StoredTask) -> String {
+        let duration = format!(" ({})", crate::format_duration_compact(task.seconds));
+        let name = task.name.replace('|', "/");
+        let card = format!("{}{}", name, duration);
+        if card.chars().count() <= self.column_width {
+            return card;
+        }
+        let kept = self
+            .column_width
+            .saturating_sub(duration.chars().count() + 1);
+        let name: String = name.chars().take(kept).collect();
+        format!("{}…{}", name, duration)
+    }
+
+    /// Lays out the tasks started from `first` to `last` (inclusive), a column per day.
+    pub fn board(&self, tasks: &[StoredTask], first: NaiveDate, last: NaiveDate) -> String {
+        let days: Vec<NaiveDate> = first.iter_days().take_while(|day| *day <= last).collect();
+        let mut columns: Vec<Vec<&StoredTask>> = vec![Vec::new(); days.len()];
+        for task in tasks {
+            let date = reports::local_date(task.started_at);
+            if let Some(index) = days.iter().position(|day| *day == date) {
+                columns[index].push(task);
+            }
+        }
+        for column in &mut columns {
+            column.sort_by(|a, b| b.seconds.cmp(&a.seconds).then_with(|| a.name.cmp(&b.name)));
+        }
+
+        let width = self.column_width;
+        let row = |cells: Vec<String>| {
+            let cells: Vec<String> = cells
+                .iter()
+                .map(|cell| format!("{:width$}", cell))
+                .collect();
+            format!("| {} |", cells.join(" | "))
+        };
+        let mut lines = vec![
+            row(days
+                .iter()
+                .map(|day| day.format("%a %Y-%m-%d").to_string())
+                .collect()),
+            row(days.iter().map(|_| "-".repeat(width.max(3))).collect()),
+        ];
+        let height = columns.iter().map(Vec::len).max().unwrap_or_default();
+        for index in 0..height {
+            lines.push(row(columns
+                .iter()
+                .map(|column| {
+                    column
+                        .get(index)
+                        .map(|task| self.card(task))
+                        .unwrap_or_default()
+                })
+                .collect()));
+        }
+        lines.join("\n")
+    }
+}
+
+impl Formatter for KanbanFormatter {
+    fn format_list(&self, tasks: &[StoredTask]) -> String {
+        let dates = tasks
+            .iter()
+            .map(|task| reports::local_date(task.started_at));
+        match (dates.clone().min(), dates.max()) {
+            (Some(first), Some(last)) => self.board(tasks, first, last),
+            _ => String::new(),
+        }
+    }
+}
+
+/// Formats tasks as a [Ledger](https://ledger-cli.org) journal, with a transaction per task.
+///
+/// Each transaction is dated with the day the task started (in `zone`, or the local time zone
+/// when it is `None`) and has the task name as its payee. The hours, rounded to the hundredth,
+/// are posted out of `Income:Consulting:<client>` at `hourly_rate_cents` an hour, balanced by
+/// what they are worth in `Assets:Receivable:<client>`. Tasks that aren't billable, or every
+/// task when there is no rate, only post their hours, as a virtual posting that doesn't need
+/// balancing. Tasks without a client post to `Income:Consulting` and `Assets:Receivable`.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::{Duration, UNIX_EPOCH};
+/// use timetracker::{formatter::{Formatter, LedgerFormatter}, storage::StoredTask};
+///
+/// let started_at = UNIX_EPOCH + Duration::from_secs(1_705_320_000);
+/// let mut task =
+///     StoredTask::new("Morning Standup", started_at, started_at + Duration::from_secs(5400));
+/// task.client = Some(String::from("ACME"));
+/// task.billable = true;
+/// let formatter = LedgerFormatter {
+///     hourly_rate_cents: Some(15_000),
+///     currency_symbol: String::from("$"),
+///     zone: Some(chrono_tz::UTC),
+/// };
+/// assert_eq!(
+///     formatter.format_list(&[task]),
+///     "2024-01-15 Morning Standup\n    \
+///      Income:Consulting:ACME    -1.5h @ $150.00\n    \
+///      Assets:Receivable:ACME    $225.00"
+/// );
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct LedgerFormatter {
+    /// What an hour of billable time is worth, in cents, if anything.
+    pub hourly_rate_cents: Option<u64>,
+    /// The commodity of the amounts, like `$`.
+    pub currency_symbol: String,
+    /// The time zone of the dates, or `None` for the local time zone.
+    pub zone: Option<Tz>,
+}
+
+impl LedgerFormatter {
+    /// Gets the name of an account under `parent` for a client, e.g. `Income:Consulting:ACME`.
+    ///
+    /// Colons in the client's name would start a sub-account and runs of spaces would end the
+    /// account name, so they become dashes and single spaces.
+    pub fn account(parent: &str, client: Option<&str>) -> String {
+        match client {
+            Some(client) if !client.trim().is_empty() => {
+                let client: Vec<&str> = client.split_whitespace().collect();
+                format!("{}:{}", parent, client.join(" ").replace(':', "-"))
+            }
+            _ => parent.to_string(),
+        }
+    }
+
+    /// Writes a number of hundredths as a decimal without trailing zeros, e.g. `1.5` for 150.
+    fn decimal(hundredths: u64) -> String {
+        let decimal = format!("{}.{:02}", hundredths / 100, hundredths % 100);
+        decimal
+            .trim_end_matches('0')
+            .trim_end_matches('.')
+            .to_string()
+    }
+
+    /// Writes a transaction for a task.
+    fn transaction(&self, task: &StoredTask) -> String {
+        let date = wall_clock(task.started_at, self.zone).format("%Y-%m-%d");
+        let payee = task.name.split_whitespace().collect::<Vec<_>>().join(" ");
+        let income = Self::account("Income:Consulting", task.client.as_deref());
+        // rounded to the hundredth, so the amount matches the hours Ledger sees
+        let hundredths = (task.seconds * 100 + 1800) / 3600;
+        let hours = format!("-{}h", Self::decimal(hundredths));
+        match self.hourly_rate_cents.filter(|_| task.billable) {
+            Some(rate) => {
+                let receivable = Self::account("Assets:Receivable", task.client.as_deref());
+                let width = income.chars().count().max(receivable.chars().count());
+                let amount = (hundredths * rate + 50) / 100;
+                format!(
+                    "{} {}\n    {:width$}    {} @ {}\n    {:width$}    {}",
+                    date,
+                    payee,
+                    income,
+                    hours,
+                    reports::format_amount(rate, &self.currency_symbol),
+                    receivable,
+                    reports::format_amount(amount, &self.currency_symbol)
+                )
+            }
+            None => format!("{} {}\n    ({})    {}", date, payee, income, hours),
+        }
+    }
+}
+
+impl Formatter for LedgerFormatter {
+    fn format_list(&self, tasks: &[StoredTask]) -> String {
+        tasks
+            .iter()
+            .map(|task| self.transaction(task))
+            .collect::<Vec<String>>()
+            .join("\n\n")
+    }
+}
+
+/// A format that writes one task per line as fields separated by `DELIMITER`, under a header.
+///
+/// Every delimited format has the same columns; implementors only decide how a field that
+/// contains special characters is written. Anything implementing this trait is a
+/// [`Formatter`].
+pub trait DelimitedFormatter {
+    /// The character between fields.
+    const DELIMITER: char;
+
+    /// Writes a single field so that it can't be confused with a delimiter or line break.
+    fn escape_field(&self, field: &str) -> String;
+}
+
+/// The header of every delimited format.
+const DELIMITED_COLUMNS: [&str; 5] = ["name", "started_at", "stopped_at", "seconds", "billable"];
+
+/// Gets the fields of a task, in the order of [`DELIMITED_COLUMNS`].
+fn delimited_fields(task: &StoredTask) -> [String; 5] {
+    [
+        task.name.clone(),
+        storage::format_timestamp(task.started_at),
+        task.stopped_at
+            .map(storage::format_timestamp)
+            .unwrap_or_default(),
+        task.seconds.to_string(),
+        task.billable.to_string(),
+    ]
+}
+
+impl<T: DelimitedFormatter> Formatter for T {
+    fn format_list(&self, tasks: &[StoredTask]) -> String {
+        let delimiter = T::DELIMITER.to_string();
+        let mut output = DELIMITED_COLUMNS.join(&delimiter);
+        for task in tasks {
+            let fields: Vec<String> = delimited_fields(task)
+                .iter()
+                .map(|field| self.escape_field(field))
+                .collect();
+            output.push('\n');
+            output.push_str(&fields.join(&delimiter));
+        }
+        output
+    }
+}
+
+/// Formats tasks as comma-separated values.
+///
+/// Fields containing a comma, a quote, or a line break are quoted, with quotes doubled, as
+/// described in RFC 4180.
+///
+/// # Examples
+///
+/// ```
+/// use timetracker::formatter::{CsvFormatter, Formatter};
+///
+/// assert_eq!(CsvFormatter.format_list(&[]), "name,started_at,stopped_at,seconds,billable");
+/// ```
+#[derive(Clone, Copy, Debug, Default)]
+pub struct CsvFormatter;
+
+impl DelimitedFormatter for CsvFormatter {
+    const DELIMITER: char = ',';
+
+    fn escape_field(&self, field: &str) -> String {
+        if field.contains([Self::DELIMITER, '"', '\n', '\r']) {
+            format!("\"{}\"", field.replace('"', "\"\""))
+        } else {
+            field.to_string()
+        }
+    }
+}
+
+/// Formats tasks as tab-separated values.
+///
+/// Fields are never quoted. Tabs, line breaks, and backslashes are escaped as `\t`, `\n`,
+/// `\r`, and `\\` instead.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct TsvFormatter;
+
+impl DelimitedFormatter for TsvFormatter {
+    const DELIMITER: char = '\t';
+
+    fn escape_field(&self, field: &str) -> String {
+        field
+            .replace('\\', "\\\\")
+            .replace('\t', "\\t")
+            .replace('\n', "\\n")
+            .replace('\r', "\\r")
+    }
+}
+
+#[cfg(test)]
+mod tests_formatter {
+    use super::*;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    fn sample_tasks() -> Vec<StoredTask> {
+        let started_at = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        vec![
+            StoredTask::new("Task 1", started_at, started_at + Duration::from_secs(90)),
+            StoredTask::new(
+                "Task \"2\"",
+                started_at + Duration::from_secs(100),
+                started_at + Duration::from_secs(3700),
+            ),
+        ]
+    }
+
+    #[test]
+    fn pretty_json_round_trips_like_compact_json() {
+        let tasks = sample_tasks();
+        let compact = JsonFormatter { pretty: false }.format_list(&tasks);
+        let pretty = JsonFormatter { pretty: true }.format_list(&tasks);
+
+        assert_eq!(compact.lines().count(), 1);
+        assert!(pretty.contains("\n  {\n    \"name\": \"Task 1\","));
+
+        let from_compact: Vec<StoredTask> = serde_json::from_str(&compact).unwrap();
+        let from_pretty: Vec<StoredTask> = serde_json::from_str(&pretty).unwrap();
+        assert_eq!(from_pretty, from_compact);
+        assert_eq!(from_pretty, tasks);
+    }
+
+    #[test]
+    fn ndjson_writes_a_line_per_task_with_a_timestamp() {
+        let tasks = sample_tasks();
+        let ndjson = NdjsonFormatter.format_list(&tasks);
+        let lines: Vec<&str> = ndjson.lines().collect();
+        assert_eq!(lines.len(), 2);
+        for (line, task) in lines.iter().zip(&tasks) {
+            let value: serde_json::Value = serde_json::from_str(line).unwrap();
+            assert_eq!(
+                value["timestamp"],
+                storage::format_timestamp(task.started_at)
+            );
+            assert_eq!(&serde_json::from_value::<StoredTask>(value).unwrap(), task);
+        }
+    }
+
+    #[test]
+    fn org_clocktable_matches_org_mode() {
+        let tasks = sample_tasks();
+        let table = OrgClocktableFormatter::default().format_list(&tasks);
+        assert_eq!(
+            table,
+            "#+BEGIN: clocktable :maxlevel 2 :scope file
+| Headline     | Time   |
+|--------------+--------|
+| *Total time* | *1:02* |
+|--------------+--------|
+| Task \"2\"     | 1:00   |
+| Task 1       | 0:02   |
+#+END:"
+        );
+    }
+
+    #[test]
+    fn org_clocktable_scope_can_be_agenda() {
+        let formatter = OrgClocktableFormatter {
+            scope: "agenda".parse().unwrap(),
+        };
+        assert!(formatter
+            .format_list(&[])
+            .starts_with("#+BEGIN: clocktable :maxlevel 2 :scope agenda\n"));
+    }
+
+    #[test]
+    fn csv_quotes_fields_with_commas() {
+        let mut tasks = sample_tasks();
+        tasks[0].name = String::from("email, calls");
+        let csv = CsvFormatter.format_list(&tasks);
+        let lines: Vec<&str> = csv.lines().collect();
+        assert_eq!(
+            lines[1],
+            "\"email, calls\",2023-11-14T22:13:20Z,2023-11-14T22:14:50Z,90,true"
+        );
+        assert_eq!(
+            lines[2],
+            "\"Task \"\"2\"\"\",2023-11-14T22:15:00Z,2023-11-14T23:15:00Z,3600,true"
+        );
+    }
+
+    #[test]
+    fn tsv_has_the_same_columns_without_quoting() {
+        let mut tasks = sample_tasks();
+        tasks[0].name = String::from("email, calls\tand more");
+        let tsv = TsvFormatter.format_list(&tasks);
+        let lines: Vec<&str> = tsv.lines().collect();
+
+        assert_eq!(lines.len(), 3);
+        for line in &lines {
+            let fields: Vec<&str> = line.split('\t').collect();
+            assert_eq!(fields.len(), DELIMITED_COLUMNS.len());
+            assert!(fields[1..].iter().all(|field| !field.contains(',')));
+        }
+        assert_eq!(lines[1].split('\t').next(), Some("email, calls\\tand more"));
+        assert!(lines[2].starts_with("Task \"2\"\t"));
+    }
+
+    #[test]
+    fn anki_writes_a_tab_separated_card_per_task() {
+        let mut tasks = sample_tasks();
+        tasks[0].name = "x".repeat(150);
+        let deck = AnkiFormatter.format_list(&tasks);
+        let lines: Vec<&str> = deck.lines().collect();
+
+        assert_eq!(lines[..2], ["#separator:tab", "#html:false"]);
+        assert_eq!(lines.len(), 4);
+        let cards: Vec<Vec<&str>> = lines[2..]
+            .iter()
+            .map(|line| line.split('\t').collect())
+            .collect();
+        assert_eq!(cards[0][0], format!("{}...", "x".repeat(97)));
+        assert_eq!(cards[0][0].chars().count(), ANKI_MAX_FRONT_CHARS);
+        assert_eq!(cards[0][1], "1 Minute and 30 Seconds");
+        assert_eq!(cards[1], ["Task \"2\"", "1 Hour"]);
+    }
+
+    #[test]
+    fn anki_keeps_names_of_exactly_the_limit() {
+        let name = "é".repeat(ANKI_MAX_FRONT_CHARS);
+        assert_eq!(AnkiFormatter::front(&name), name);
+        assert_eq!(AnkiFormatter::front("a\tb\nc"), "a b c");
+    }
+
+    #[test]
+    fn junit_fails_tasks_over_the_estimate() {
+        let mut tasks = sample_tasks();
+        tasks.push(StoredTask::running("<deploy> & \"ship\"", UNIX_EPOCH));
+        let xml = JunitFormatter {
+            estimate_secs: Some(1800),
+        }
+        .format_list(&tasks);
+        assert_eq!(
+            xml,
+            r#"<?xml version="1.0" encoding="UTF-8"?>
+<testsuite name="time-tracker" tests="3" failures="1" errors="0" skipped="1" time="3690.000">
+  <testcase classname="time-tracker" name="Task 1" time="90.000"/>
+  <testcase classname="time-tracker" name="Task &quot;2&quot;" time="3600.000">
+    <failure message="took 1h, over the estimate of 30m" type="OverEstimate"/>
+  </testcase>
+  <testcase classname="time-tracker" name="&lt;deploy&gt; &amp; &quot;ship&quot;" time="0.000">
+    <skipped message="still running"/>
+  </testcase>
+</testsuite>"#
+        );
+    }
+
+    #[test]
+    fn junit_without_an_estimate_never_fails() {
+        let xml = JunitFormatter::default().format_list(&sample_tasks());
+        assert!(xml.contains(r#"tests="2" failures="0""#));
+        assert!(!xml.contains("<failure"));
+        assert_eq!(escape_xml("a\u{1}b\tc"), "a b&#9;c");
+    }
+
+    #[test]
+    fn gcal_writes_us_dates_and_twelve_hour_times() {
+        let utc = GoogleCalendarFormatter {
+            zone: Some(chrono_tz::UTC),
+        };
+        let lines: Vec<String> = utc
+            .format_list(&sample_tasks())
+            .lines()
+            .map(String::from)
+            .collect();
+        assert_eq!(
+            lines,
+            [
+                "Subject,Start Date,Start Time,End Date,End Time,All Day Event,Description",
+                "Task 1,11/14/2023,10:13 PM,11/14/2023,10:28 PM,False,Actual duration: 1 minute",
+                "\"Task \"\"2\"\"\",11/14/2023,10:15 PM,11/14/2023,11:15 PM,False,",
+            ]
+        );
+
+        let new_york = GoogleCalendarFormatter {
+            zone: Some(chrono_tz::America::New_York),
+        };
+        let noon = UNIX_EPOCH + Duration::from_secs(1_699_981_200);
+        let task = StoredTask::new("lunch", noon, noon + Duration::from_secs(3600));
+        assert!(new_york
+            .format_list(&[task])
+            .ends_with("lunch,11/14/2023,12:00 PM,11/14/2023,01:00 PM,False,"));
+    }
+
+    #[test]
+    fn gcal_pads_short_tasks_to_fifteen_minutes() {
+        let formatter = GoogleCalendarFormatter {
+            zone: Some(chrono_tz::UTC),
+        };
+        let late = UNIX_EPOCH + Duration::from_secs(1_700_006_100);
+        let task = StoredTask::new("quick fix", late, late + Duration::from_secs(30));
+        assert!(formatter.format_list(&[task]).ends_with(
+            "quick fix,11/14/2023,11:55 PM,11/15/2023,12:10 AM,False,Actual duration: 30 seconds"
+        ));
+
+        let exact = StoredTask::new(
+            "review",
+            late,
+            late + Duration::from_secs(GCAL_MIN_EVENT_SECS),
+        );
+        assert!(formatter
+            .format_list(&[exact])
+            .ends_with(",12:10 AM,False,"));
+        assert_eq!(actual_duration(8 * 60 + 59), "8 minutes");
+    }
+
+    #[test]
+    fn applescript_writes_date_literals_and_escapes_quotes() {
+        let mut tasks = sample_tasks();
+        tasks[0].name = String::from(r#"review "quoted" \ PR"#);
+        let script = AppleScriptFormatter {
+            calendar: String::from(DEFAULT_APPLESCRIPT_CALENDAR),
+            zone: Some(chrono_tz::UTC),
+        }
+        .format_list(&tasks);
+        assert_eq!(
+            script,
+            r#"tell application "Calendar"
+	tell calendar "Time Tracking"
+		make new event with properties {summary:"review \"quoted\" \\ PR", start date:date "November 14, 2023 22:13:20", end date:date "November 14, 2023 22:14:50"}
+		make new event with properties {summary:"Task \"2\"", start date:date "November 14, 2023 22:15:00", end date:date "November 14, 2023 23:15:00"}
+	end tell
+end tell"#
+        );
+    }
+
+    #[test]
+    fn notion_quotes_tags_with_commas() {
+        let mut tasks = sample_tasks();
+        tasks[0].tags = vec![String::from("work"), String::from("client,billing")];
+        tasks[0].client = Some(String::from("ACME"));
+        let csv = NotionFormatter {
+            zone: Some(chrono_tz::UTC),
+        }
+        .format_list(&tasks);
+        assert_eq!(
+            csv,
+            concat!(
+                "Name,Date,Duration (Hours),Tags,Client,Notes\n",
+                "Task 1,2023-11-14,0.03,\"work,\"\"client,billing\"\"\",ACME,\n",
+                "\"Task \"\"2\"\"\",2023-11-14,1.0,,,"
+            )
+        );
+    }
+
+    #[test]
+    fn harvest_matches_a_reference_import() {
+        let mut tasks = sample_tasks();
+        tasks[0].client = Some(String::from("ACME, Inc."));
+        tasks[0].project = Some(String::from("Website"));
+        tasks[0].tags = vec![String::from("design"), String::from("review")];
+        tasks.push(StoredTask::new(
+            "migration",
+            UNIX_EPOCH + Duration::from_secs(1_700_006_400),
+            UNIX_EPOCH + Duration::from_secs(1_700_006_400 + 25 * 3600 + 36),
+        ));
+        let formatter = HarvestCsvFormatter {
+            default_client: String::from("Internal"),
+            default_project: String::from("Admin"),
+            zone: Some(chrono_tz::UTC),
+        };
+        assert_eq!(
+            formatter.format_list(&tasks),
+            concat!(
+                "Date,Client,Project,Task,Notes,Hours\n",
+                "2023-11-14,\"ACME, Inc.\",Website,Task 1,\"design, review\",0.03\n",
+                "2023-11-14,Internal,Admin,\"Task \"\"2\"\"\",,1.00\n",
+                "2023-11-15,Internal,Admin,migration,,25.01"
+            )
+        );
+        let too_long: Vec<&str> = HarvestCsvFormatter::too_long(&tasks)
+            .map(|task| task.name.as_str())
+            .collect();
+        assert_eq!(too_long, ["migration"]);
+    }
+
+    #[test]
+    fn tempo_logs_tasks_without_a_key_against_the_default_issue() {
+        let mut tasks = sample_tasks();
+        tasks[0].name = String::from("PROJ-42 review");
+        tasks.push(StoredTask::new(
+            "Task \"2\"",
+            UNIX_EPOCH + Duration::from_secs(1_700_003_000),
+            UNIX_EPOCH + Duration::from_secs(1_700_003_600),
+        ));
+        tasks.push(StoredTask::new(
+            "email",
+            UNIX_EPOCH + Duration::from_secs(1_700_004_000),
+            UNIX_EPOCH + Duration::from_secs(1_700_004_300),
+        ));
+        let json = TempoFormatter {
+            default_issue: String::from("OPS-1"),
+            pretty: false,
+            zone: Some(chrono_tz::UTC),
+        }
+        .format_list(&tasks);
+        let worklogs: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            worklogs,
+            serde_json::json!([
+                {
+                    "issueKey": "PROJ-42",
+                    "timeSpentSeconds": 90,
+                    "startDate": "2023-11-14",
+                    "description": "PROJ-42 review",
+                },
+                {
+                    "issueKey": "OPS-1",
+                    "timeSpentSeconds": 4500,
+                    "startDate": "2023-11-14",
+                    "description": "Task \"2\"; email",
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn toggl_writes_rfc_3339_times() {
+        let mut tasks = sample_tasks();
+        tasks[0].tags = vec![String::from("meetings")];
+        tasks.push(StoredTask::running("deploy", UNIX_EPOCH));
+        let json = TogglApiFormatter {
+            workspace_id: 12345,
+            project_id: Some(678),
+        }
+        .format_list(&tasks);
+        assert!(json.starts_with("[\n  {\n    \"created_with\": \"time-tracker\",\n"));
+
+        let entries: Vec<serde_json::Value> = serde_json::from_str(&json).unwrap();
+        assert_eq!(entries.len(), 3);
+        for (entry, task) in entries.iter().zip(&tasks) {
+            let start = entry["start"].as_str().unwrap();
+            let parsed = DateTime::parse_from_rfc3339(start).unwrap();
+            assert_eq!(SystemTime::from(parsed), task.started_at);
+            assert_eq!(entry["workspace_id"], 12345);
+            assert_eq!(entry["project_id"], 678);
+        }
+        assert_eq!(entries[0]["start"], "2023-11-14T22:13:20Z");
+        assert_eq!(entries[0]["stop"], "2023-11-14T22:14:50Z");
+        assert_eq!(entries[0]["tags"], serde_json::json!(["meetings"]));
+        assert_eq!(entries[1]["description"], "Task \"2\"");
+        assert!(entries[2]["stop"].is_null());
+    }
+
+    #[test]
+    fn clockify_json_has_an_entry_per_task() {
+        let mut tasks = sample_tasks();
+        tasks[1].billable = false;
+        tasks.push(StoredTask::running("deploy", UNIX_EPOCH));
+        let json = ClockifyJsonFormatter {
+            project_id: Some(String::from("p1")),
+        }
+        .format_list(&tasks);
+        let entries: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            entries,
+            serde_json::json!([
+                {
+                    "description": "Task 1",
+                    "start": "2023-11-14T22:13:20Z",
+                    "end": "2023-11-14T22:14:50Z",
+                    "billable": true,
+                    "projectId": "p1",
+                },
+                {
+                    "description": "Task \"2\"",
+                    "start": "2023-11-14T22:15:00Z",
+                    "end": "2023-11-14T23:15:00Z",
+                    "billable": false,
+                    "projectId": "p1",
+                },
+                {
+                    "description": "deploy",
+                    "start": "1970-01-01T00:00:00Z",
+                    "billable": true,
+                    "projectId": "p1",
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn clockify_script_curls_each_entry() {
+        let mut tasks = sample_tasks();
+        tasks[0].name = String::from("Bob's task");
+        let script = ClockifyScriptFormatter {
+            workspace_id: String::from("abc123"),
+            api_key: Some(String::from("xyz")),
+            project_id: None,
+        }
+        .format_list(&tasks);
+        let lines: Vec<&str> = script.lines().collect();
+        assert_eq!(
+            lines[..4],
+            [
+                "#!/usr/bin/env bash",
+                "set -euo pipefail",
+                "api_key='xyz'",
+                "url='https://api.clockify.me/api/v1/workspaces/abc123/time-entries'",
+            ]
+        );
+        assert_eq!(lines.len(), 6);
+        assert!(lines[4].starts_with(r#"curl -sS -X POST "$url" -H "X-Api-Key: $api_key""#));
+        assert!(lines[4].ends_with(
+            r#"-d '{"description":"Bob'\''s task","start":"2023-11-14T22:13:20Z","end":"2023-11-14T22:14:50Z","billable":true}'"#
+        ));
+
+        let from_env = ClockifyScriptFormatter::default().format_list(&[]);
+        assert!(from_env.contains(r#"api_key="${CLOCKIFY_API_KEY:?set CLOCKIFY_API_KEY}""#));
+    }
+
+    #[test]
+    fn json_feed_has_an_item_per_completed_task() {
+        let mut tasks = sample_tasks();
+        tasks.push(StoredTask::running("still going", UNIX_EPOCH));
+        let formatter = JsonFeedFormatter {
+            home_page_url: Some(String::from("https://example.com/time")),
+        };
+        let feed: serde_json::Value = serde_json::from_str(&formatter.format_list(&tasks)).unwrap();
+        assert_eq!(feed["version"], "https://jsonfeed.org/version/1.1");
+        assert_eq!(feed["home_page_url"], "https://example.com/time");
+        let items = feed["items"].as_array().unwrap();
+        assert_eq!(items.len(), tasks.len() - 1);
+        let newest = tasks
+            .iter()
+            .filter(|task| task.stopped_at.is_some())
+            .max_by_key(|task| task.stopped_at)
+            .unwrap();
+        assert_eq!(items[0]["id"], newest.id.to_string());
+        assert_eq!(items[0]["title"], newest.name.as_str());
+        assert_eq!(
+            items[0]["content_text"],
+            format_duration_human(newest.seconds)
+        );
+        assert!(JsonFeedFormatter::default()
+            .format_list(&[])
+            .contains(r#""items": []"#));
+    }
+
+    #[test]
+    fn ledger_builds_accounts_from_the_client() {
+        assert_eq!(
+            LedgerFormatter::account("Income:Consulting", Some("ACME")),
+            "Income:Consulting:ACME"
+        );
+        assert_eq!(
+            LedgerFormatter::account("Assets:Receivable", Some(" Globex:  EU ")),
+            "Assets:Receivable:Globex- EU"
+        );
+        assert_eq!(
+            LedgerFormatter::account("Income:Consulting", None),
+            "Income:Consulting"
+        );
+        assert_eq!(
+            LedgerFormatter::account("Income:Consulting", Some("")),
+            "Income:Consulting"
+        );
+    }
+
+    #[test]
+    fn ledger_prices_billable_hours_at_the_rate() {
+        let started_at = UNIX_EPOCH + Duration::from_secs(1_705_320_000);
+        let task = |name: &str, seconds: u64, billable: bool| {
+            let mut task =
+                StoredTask::new(name, started_at, started_at + Duration::from_secs(seconds));
+            task.billable = billable;
+            task
+        };
+        let mut review = task("code review", 4800, true);
+        review.client = Some(String::from("Globex"));
+        let tasks = [review, task("admin", 900, false)];
+        let formatter = LedgerFormatter {
+            hourly_rate_cents: Some(12_050),
+            currency_symbol: String::from("€"),
+            zone: Some(chrono_tz::UTC),
+        };
+        // 1h20m is 1.33 hours, and 1.33 × €120.50 is €160.265
+        assert_eq!(
+            formatter.format_list(&tasks),
+            "2024-01-15 code review\n    \
+             Income:Consulting:Globex    -1.33h @ €120.50\n    \
+             Assets:Receivable:Globex    €160.27\n\n\
+             2024-01-15 admin\n    \
+             (Income:Consulting)    -0.25h"
+        );
+        let without_rate = LedgerFormatter {
+            hourly_rate_cents: None,
+            ..formatter
+        };
+        assert!(without_rate
+            .format_list(&tasks)
+            .starts_with("2024-01-15 code review\n    (Income:Consulting:Globex)    -1.33h\n\n"));
+    }
+
+    #[test]
+    fn kanban_has_a_column_per_day_of_the_week() {
+        // noon UTC on Monday, January 15th 2024, which is the same day in most time zones
+        let monday = UNIX_EPOCH + Duration::from_secs(1_705_320_000);
+        let day = Duration::from_secs(24 * 60 * 60);
+        let tasks = [
+            StoredTask::new("standup", monday, monday + Duration::from_secs(900)),
+            StoredTask::new(
+                "write the quarterly planning document",
+                monday + Duration::from_secs(1000),
+                monday + Duration::from_secs(1000 + 5400),
+            ),
+            StoredTask::new(
+                "review | merge",
+                monday + 2 * day,
+                monday + 2 * day + day / 24,
+            ),
+        ];
+        let first = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+        let last = NaiveDate::from_ymd_opt(2024, 1, 21).unwrap();
+        let board = KanbanFormatter { column_width: 18 }.board(&tasks, first, last);
+        let lines: Vec<&str> = board.lines().collect();
+
+        assert_eq!(lines.len(), 4);
+        for line in &lines {
+            assert_eq!(line.matches(" | ").count() + 1, 7);
+        }
+        assert!(lines[0].starts_with("| Mon 2024-01-15     | Tue 2024-01-16     |"));
+        assert!(lines[0].ends_with("| Sun 2024-01-21     |"));
+        assert!(lines[2]
+            .starts_with("| write the… (1h30m) |                    | review / mer… (1h) |"));
+        assert!(lines[3]
+            .starts_with("| standup (15m)      |                    |                    |"));
+    }
+}
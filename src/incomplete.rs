@@ -0,0 +1,185 @@
+//! Tasks in the history that never stopped, e.g. because the process tracking them was killed.
+//!
+//! Such a task is saved without a stop time, and `seconds` stays at 0. `time-tracker report
+//! --incomplete` lists them with how long ago they started, and `time-tracker fix --incomplete`
+//! stops or deletes them.
+
+use crate::{format_duration_compact, storage::StoredTask};
+use chrono::{DateTime, Local};
+use std::{fmt, time::SystemTime};
+
+/// A task in the history without a stop time.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct IncompleteTask<'a> {
+    /// The task.
+    pub task: &'a StoredTask,
+    /// The seconds from its start until now, which is how long it took at most.
+    pub elapsed_secs: u64,
+}
+
+/// Formats the task like `time-tracker list` does, with how long ago it started, e.g.
+/// `1a2b3c4d  2024-01-15 09:00  write docs  (started 3h20m ago)`.
+impl fmt::Display for IncompleteTask<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}  {}  {}  (started {} ago)",
+            self.task.short_id(),
+            DateTime::<Local>::from(self.task.started_at).format("%Y-%m-%d %H:%M"),
+            self.task.name,
+            format_duration_compact(self.elapsed_secs)
+        )
+    }
+}
+
+/// The seconds from the start of a task until `now`.
+fn elapsed_secs(task: &StoredTask, now: SystemTime) -> u64 {
+    now.duration_since(task.started_at)
+        .unwrap_or_default()
+        .as_secs()
+}
+
+/// Finds the tasks without a stop time, in the order they are saved in.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::{Duration, SystemTime};
+/// use timetracker::{incomplete, storage::StoredTask};
+///
+/// let now = SystemTime::now();
+/// let tasks = [
+///     StoredTask::new("done", now - Duration::from_secs(600), now),
+///     StoredTask::running("crashed", now - Duration::from_secs(3600)),
+/// ];
+/// let found = incomplete::find(&tasks, now);
+/// assert_eq!(found.len(), 1);
+/// assert_eq!(found[0].task.name, "crashed");
+/// ```
+pub fn find(tasks: &[StoredTask], now: SystemTime) -> Vec<IncompleteTask<'_>> {
+    tasks
+        .iter()
+        .filter(|task| task.stopped_at.is_none())
+        .map(|task| IncompleteTask {
+            task,
+            elapsed_secs: elapsed_secs(task, now),
+        })
+        .collect()
+}
+
+/// What to do with a task that never stopped.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Resolution {
+    /// Stop it when it started, so it took no time.
+    StopAtStart,
+    /// Stop it now, so it took all the time since it started.
+    StopNow,
+    /// Remove it from the history.
+    Delete,
+}
+
+/// Stops or deletes each task without a stop time as `resolve` says, leaving the ones it gives
+/// `None` for as they are. Returns how many tasks were changed.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::{Duration, SystemTime};
+/// use timetracker::{incomplete::{self, Resolution}, storage::StoredTask};
+///
+/// let now = SystemTime::now();
+/// let mut tasks = vec![StoredTask::running("crashed", now - Duration::from_secs(3600))];
+/// assert_eq!(incomplete::fix(&mut tasks, now, |_| Some(Resolution::StopNow)), 1);
+/// assert_eq!(tasks[0].seconds, 3600);
+/// ```
+pub fn fix(
+    tasks: &mut Vec<StoredTask>,
+    now: SystemTime,
+    mut resolve: impl FnMut(&IncompleteTask) -> Option<Resolution>,
+) -> usize {
+    let mut fixed = 0;
+    let mut fixed_tasks = Vec::with_capacity(tasks.len());
+    for mut task in tasks.drain(..) {
+        if task.stopped_at.is_none() {
+            let incomplete = IncompleteTask {
+                task: &task,
+                elapsed_secs: elapsed_secs(&task, now),
+            };
+            match resolve(&incomplete) {
+                Some(Resolution::StopAtStart) => {
+                    task.stopped_at = Some(task.started_at);
+                    task.seconds = 0;
+                }
+                Some(Resolution::StopNow) => {
+                    task.seconds = elapsed_secs(&task, now);
+                    task.stopped_at = Some(now.max(task.started_at));
+                }
+                Some(Resolution::Delete) => {
+                    fixed += 1;
+                    continue;
+                }
+                None => {
+                    fixed_tasks.push(task);
+                    continue;
+                }
+            }
+            fixed += 1;
+        }
+        fixed_tasks.push(task);
+    }
+    *tasks = fixed_tasks;
+    fixed
+}
+
+#[cfg(test)]
+mod tests_incomplete {
+    use super::*;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    fn history(now: SystemTime) -> Vec<StoredTask> {
+        vec![
+            StoredTask::new("done", now - Duration::from_secs(7200), now),
+            StoredTask::running("crashed", now - Duration::from_secs(5400)),
+            StoredTask::running("killed", now - Duration::from_secs(60)),
+        ]
+    }
+
+    #[test]
+    fn finds_the_tasks_without_a_stop_time() {
+        let now = UNIX_EPOCH + Duration::from_secs(1_705_320_000);
+        let tasks = history(now);
+        let found = find(&tasks, now);
+        let elapsed: Vec<(&str, u64)> = found
+            .iter()
+            .map(|incomplete| (incomplete.task.name.as_str(), incomplete.elapsed_secs))
+            .collect();
+        assert_eq!(elapsed, [("crashed", 5400), ("killed", 60)]);
+        assert!(found[0]
+            .to_string()
+            .ends_with("  crashed  (started 1h30m ago)"));
+    }
+
+    #[test]
+    fn stops_or_deletes_each_task_as_resolved() {
+        let now = UNIX_EPOCH + Duration::from_secs(1_705_320_000);
+        let mut tasks = history(now);
+        let mut resolutions = vec![Resolution::StopNow, Resolution::Delete];
+        assert_eq!(fix(&mut tasks, now, |_| Some(resolutions.remove(0))), 2);
+        assert_eq!(tasks.len(), 2);
+        assert_eq!(tasks[1].name, "crashed");
+        assert_eq!(tasks[1].stopped_at, Some(now));
+        assert_eq!(tasks[1].seconds, 5400);
+
+        let mut tasks = history(now);
+        let fixed = fix(&mut tasks, now, |incomplete| {
+            (incomplete.task.name == "crashed").then_some(Resolution::StopAtStart)
+        });
+        assert_eq!(fixed, 1);
+        assert_eq!(tasks[1].stopped_at, Some(tasks[1].started_at));
+        assert_eq!(tasks[1].seconds, 0);
+        assert_eq!(tasks[2].stopped_at, None);
+        // completed tasks are left alone
+        assert_eq!(tasks[0].stopped_at, Some(now));
+        assert_eq!(tasks[0].seconds, 7200);
+    }
+}
@@ -8,8 +8,38 @@
 //! * The `Task` struct can also be converted to a string to show the total time spent on a task in a clock format.
 
 use debug::log;
+use environment::Environment;
 use lazy_static::lazy_static;
-use std::{io, io::Write, thread, time::Instant};
+use std::{
+    io,
+    io::Write,
+    path::Path,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc,
+    },
+    thread,
+    time::{Duration, Instant, SystemTime},
+};
+
+pub mod aliases;
+pub mod config;
+pub mod context;
+pub mod countdown;
+pub mod daemon;
+pub mod doctor;
+pub mod environment;
+pub mod export;
+pub mod formatter;
+pub mod incomplete;
+pub mod lint;
+pub mod reports;
+pub mod running;
+pub mod selector;
+pub mod storage;
+pub mod templates;
+pub mod terminal;
+pub mod timezone;
 
 lazy_static! {
     static ref DAYS_DIVISOR: u64 = {
@@ -27,31 +57,275 @@ lazy_static! {
         minutes_divisor
     };
 
-    static ref DAY_IN_SECONDS: u64 = {
-        let day_in_seconds = 86400;
-        day_in_seconds
-    };
+    static ref DAY_IN_SECONDS: u64 = 86400;
 
-    static ref HOUR_IN_SECONDS: u64 = {
-        let hour_in_seconds = 3600;
-        hour_in_seconds
-    };
+    static ref HOUR_IN_SECONDS: u64 = 3600;
+
+    static ref MINUTE_IN_SECONDS: u64 = 60;
+}
+
+/// Whether detailed timing information should be printed to stderr.
+static VERBOSE: AtomicBool = AtomicBool::new(false);
 
-    static ref MINUTE_IN_SECONDS: u64 = {
-        let minute_in_seconds = 60;
-        minute_in_seconds
+/// Turns verbose output (the `--verbose` flag) on or off.
+pub fn set_verbose(verbose: bool) {
+    VERBOSE.store(verbose, Ordering::Relaxed);
+}
+
+/// Whether verbose output is turned on.
+pub fn is_verbose() -> bool {
+    VERBOSE.load(Ordering::Relaxed)
+}
+
+/// Prints a line of detailed timing information to stderr, if verbose output is turned on.
+///
+/// Verbose output goes to stderr so it doesn't end up in piped output.
+#[macro_export]
+macro_rules! verbose {
+    ($($arg:tt)*) => {
+        if $crate::is_verbose() {
+            eprintln!("[verbose] {}", format!($($arg)*));
+        }
     };
 }
 
-fn get_clock_format(elapsed: u64) -> String {
+/// Converts an `Instant` to the wall-clock time it happened at.
+fn instant_to_system_time(instant: Instant) -> SystemTime {
+    let now = SystemTime::now();
+    let since = Instant::now().saturating_duration_since(instant);
+    now.checked_sub(since).unwrap_or(now)
+}
+
+/// Formats an `Instant` as a local RFC 3339 timestamp, for verbose output.
+fn fmt_instant(instant: Instant) -> String {
+    chrono::DateTime::<chrono::Local>::from(instant_to_system_time(instant)).to_rfc3339()
+}
+
+/// Formats a number of seconds as a clock, `HH:MM:SS`. Hours go past 24 instead of wrapping.
+pub fn get_clock_format(elapsed: u64) -> String {
     let hours = elapsed / *HOURS_DIVISOR;
     let minutes = (elapsed % *HOURS_DIVISOR) / *MINUTES_DIVISOR;
     let seconds = elapsed % *MINUTES_DIVISOR;
-    return vec![hours, minutes, seconds]
+    [hours, minutes, seconds]
         .iter()
         .map(|time_unit| format!("{:02}", time_unit))
         .collect::<Vec<String>>()
-        .join(":");
+        .join(":")
+}
+
+/// Formats a number of seconds as a terse duration like `1h23m45s`.
+///
+/// Zero components are left out (`23m45s`, `1h5s`), except that no time at all is `0s`.
+/// Durations of a day or more are still counted in hours (`26h`).
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(timetracker::format_duration_compact(5025), "1h23m45s");
+/// assert_eq!(timetracker::format_duration_compact(45), "45s");
+/// ```
+pub fn format_duration_compact(secs: u64) -> String {
+    if secs == 0 {
+        return String::from("0s");
+    }
+
+    let hours = secs / *HOURS_DIVISOR;
+    let minutes = (secs % *HOURS_DIVISOR) / *MINUTES_DIVISOR;
+    let seconds = secs % *MINUTES_DIVISOR;
+    [(hours, "h"), (minutes, "m"), (seconds, "s")]
+        .iter()
+        .filter(|(value, _)| *value > 0)
+        .map(|(value, unit)| format!("{}{}", value, unit))
+        .collect()
+}
+
+/// Parses a duration written like [`format_duration_compact`] writes it, e.g. `1h30m`, into
+/// seconds.
+///
+/// Days (`d`), hours (`h`), minutes (`m`), and seconds (`s`) can be combined in any order, and a
+/// number without a unit is a number of seconds.
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(timetracker::parse_natural_duration("1h30m"), Ok(5400));
+/// assert_eq!(timetracker::parse_natural_duration("15m"), Ok(900));
+/// assert_eq!(timetracker::parse_natural_duration("0"), Ok(0));
+/// assert!(timetracker::parse_natural_duration("soon").is_err());
+/// ```
+pub fn parse_natural_duration(s: &str) -> Result<u64, String> {
+    let invalid = || format!("expected a duration like 1h30m, got '{}'", s);
+    let s = s.trim();
+    if s.is_empty() {
+        return Err(invalid());
+    }
+    if let Ok(seconds) = s.parse::<u64>() {
+        return Ok(seconds);
+    }
+
+    let mut total: u64 = 0;
+    let mut number = String::new();
+    for c in s.chars() {
+        if c.is_ascii_digit() {
+            number.push(c);
+            continue;
+        }
+        let unit = match c {
+            'd' => *DAYS_DIVISOR,
+            'h' => *HOURS_DIVISOR,
+            'm' => *MINUTES_DIVISOR,
+            's' => 1,
+            _ => return Err(invalid()),
+        };
+        let value: u64 = number.parse().map_err(|_| invalid())?;
+        total = value
+            .checked_mul(unit)
+            .and_then(|seconds| total.checked_add(seconds))
+            .ok_or_else(invalid)?;
+        number.clear();
+    }
+    if !number.is_empty() {
+        return Err(invalid());
+    }
+    Ok(total)
+}
+
+/// Which units [`format_duration_human_with_flags`] includes even when they are 0.
+///
+/// The default includes none of them, so only the units with a value are shown.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DurationFormatFlags {
+    /// Include the days even when there are none.
+    pub always_include_days: bool,
+    /// Include the hours even when there are none.
+    pub always_include_hours: bool,
+    /// Include the minutes even when there are none.
+    pub always_include_minutes: bool,
+    /// Include the seconds even when there are none.
+    pub always_include_seconds: bool,
+}
+
+impl DurationFormatFlags {
+    /// Gets the flags that include all four units, for fixed-width or machine-read output.
+    pub fn always_all() -> DurationFormatFlags {
+        DurationFormatFlags {
+            always_include_days: true,
+            always_include_hours: true,
+            always_include_minutes: true,
+            always_include_seconds: true,
+        }
+    }
+}
+
+/// Formats a number of seconds as X Days, X Hours, Y Minutes, and Z Seconds.
+///
+/// If a time unit is 0, it will not be included in the string.
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(timetracker::format_duration_human(3661), "1 Hour, 1 Minute, and 1 Second");
+/// assert_eq!(timetracker::format_duration_human(120), "2 Minutes");
+/// ```
+pub fn format_duration_human(total_seconds: u64) -> String {
+    format_duration_human_with_flags(total_seconds, DurationFormatFlags::default())
+}
+
+/// Formats a number of seconds like [`format_duration_human`], but also includes the units
+/// `flags` asks for when they are 0.
+///
+/// # Examples
+///
+/// ```
+/// use timetracker::{format_duration_human_with_flags, DurationFormatFlags};
+///
+/// assert_eq!(
+///     format_duration_human_with_flags(61, DurationFormatFlags::always_all()),
+///     "0 Days, 0 Hours, 1 Minute, and 1 Second"
+/// );
+/// ```
+pub fn format_duration_human_with_flags(total_seconds: u64, flags: DurationFormatFlags) -> String {
+    // get the number of days
+    let days = total_seconds / *DAYS_DIVISOR;
+
+    // get the number of hours left over.
+    let hours = (total_seconds % *DAYS_DIVISOR) / *HOURS_DIVISOR;
+
+    // get the number of minutes left over.
+    let minutes = (total_seconds % *HOURS_DIVISOR) / *MINUTES_DIVISOR;
+
+    // get the number of seconds left over.
+    let seconds = total_seconds % *MINUTES_DIVISOR;
+
+    verbose!(
+        "{} seconds = {} days, {} hours, {} minutes, {} seconds",
+        total_seconds,
+        days,
+        hours,
+        minutes,
+        seconds
+    );
+
+    // create a vector to hold the time units.
+    let mut time_units: Vec<String> = Vec::new();
+
+    // add the days to the vector.
+    if days > 0 || flags.always_include_days {
+        time_units.push(format!("{} Day{}", days, if days != 1 { "s" } else { "" }));
+    }
+
+    // add the hours to the vector.
+    if hours > 0 || flags.always_include_hours {
+        time_units.push(format!(
+            "{} Hour{}",
+            hours,
+            if hours != 1 { "s" } else { "" }
+        ));
+    }
+
+    // add the minutes to the vector.
+    if minutes > 0 || flags.always_include_minutes {
+        time_units.push(format!(
+            "{} Minute{}",
+            minutes,
+            if minutes != 1 { "s" } else { "" }
+        ));
+    }
+
+    // add the seconds to the vector.
+    if seconds > 0 || flags.always_include_seconds {
+        time_units.push(format!(
+            "{} Second{}",
+            seconds,
+            if seconds != 1 { "s" } else { "" }
+        ));
+    }
+
+    // create a string to hold the output.
+    let mut output = String::new();
+
+    // loop through the time units.
+    for (index, time_unit) in time_units.iter().enumerate() {
+        // if this is the last time unit, we add "and" before it, unless there is only one time unit.
+        if index == time_units.len() - 1 && time_units.len() > 1 {
+            output.push_str("and ");
+        }
+
+        // add the time unit to the output.
+        output.push_str(time_unit);
+
+        // if this is not the last time unit, add a comma.
+        if index != time_units.len() - 1 {
+            output.push_str(", ");
+        }
+    }
+
+    // if there are only two time units, remove the comma.
+    if time_units.len() == 2 {
+        output = output.replace(", ", " ");
+    }
+
+    output
 }
 
 /// A timer that can be used to track the time elapsed since it was started.
@@ -90,7 +364,117 @@ impl Timer {
 
     /// Gets the time elapsed since the timer was started (in seconds).
     pub fn elapsed(&self) -> u64 {
-        return self.end.duration_since(self.start).as_secs();
+        self.end.duration_since(self.start).as_secs()
+    }
+
+    /// Gets the time elapsed as X Days, X Hours, Y Minutes, and Z Seconds.
+    ///
+    /// See [`format_duration_human`] for the exact format.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// let mut timer = timetracker::Timer::new();
+    /// std::thread::sleep(std::time::Duration::from_secs(61));
+    /// timer.update();
+    /// println!("{}", timer.elapsed_humanized()); // -> 1 Minute and 1 Second
+    /// ```
+    pub fn elapsed_humanized(&self) -> String {
+        format_duration_human(self.elapsed())
+    }
+
+    /// Gets the time elapsed as a terse duration like `1h23m45s`.
+    ///
+    /// See [`format_duration_compact`] for the exact format.
+    pub fn elapsed_compact(&self) -> String {
+        format_duration_compact(self.elapsed())
+    }
+}
+
+/// Something that measures how long a task has been running, for [`Task::show_timer`].
+pub trait TimerTrait {
+    /// Brings the elapsed time up to date.
+    fn update(&mut self);
+
+    /// Gets the elapsed time (in seconds) as of the last update.
+    fn elapsed(&self) -> u64;
+
+    /// Gets the elapsed time as a clock, `HH:MM:SS`.
+    fn fmt_clock(&self) -> String {
+        get_clock_format(self.elapsed())
+    }
+}
+
+impl TimerTrait for Timer {
+    fn update(&mut self) {
+        Timer::update(self);
+    }
+
+    fn elapsed(&self) -> u64 {
+        Timer::elapsed(self)
+    }
+}
+
+/// A [`Timer`] that waits a second before every update, so that a timer shown on screen ticks
+/// once per second.
+#[derive(Clone, Copy, Default)]
+pub struct ProductionTimer {
+    /// The timer that measures the time.
+    pub timer: Timer,
+    /// How long each update waits for.
+    pub interval: std::time::Duration,
+}
+
+impl ProductionTimer {
+    /// Creates a new `ProductionTimer` that updates every second and starts it.
+    pub fn new() -> ProductionTimer {
+        ProductionTimer::with_interval(std::time::Duration::from_secs(1))
+    }
+
+    /// Creates a new `ProductionTimer` that updates every `interval` and starts it.
+    pub fn with_interval(interval: std::time::Duration) -> ProductionTimer {
+        ProductionTimer {
+            timer: Timer::new(),
+            interval,
+        }
+    }
+}
+
+impl TimerTrait for ProductionTimer {
+    fn update(&mut self) {
+        thread::sleep(self.interval);
+        self.timer.update();
+    }
+
+    fn elapsed(&self) -> u64 {
+        self.timer.elapsed()
+    }
+}
+
+/// A timer that moves forward by a fixed amount on every update instead of following the
+/// clock, so that code showing a timer can be tested without waiting.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FakeTimer {
+    /// The seconds elapsed so far.
+    pub ticks: u64,
+    /// How many seconds every update adds.
+    pub advance_per_update: u64,
+}
+
+impl TimerTrait for FakeTimer {
+    fn update(&mut self) {
+        self.ticks += self.advance_per_update;
+    }
+
+    fn elapsed(&self) -> u64 {
+        self.ticks
+    }
+}
+
+/// Creates a new `Timer` and starts it, the same as [`Timer::new`].
+impl Default for Timer {
+    fn default() -> Timer {
+        Timer::new()
     }
 }
 
@@ -115,11 +499,27 @@ impl std::fmt::Display for Timer {
 /// let seconds = task.time_tracked_seconds();
 /// let time_tracked = task.time_tracked_string();
 /// ```
+///
+/// A task can be cloned, which makes it easy to keep a template around and start a fresh copy
+/// of it whenever it's needed:
+///
+/// ```no_run
+/// let template = timetracker::Task::new(&String::from("daily standup"));
+/// let today = template.duplicate();
+/// assert_eq!(today.name, template.name);
+/// ```
+#[derive(Clone)]
 pub struct Task {
     /// The name of the task.
     pub name: String,
     start: Instant,
     end: Instant,
+    /// The wall-clock time the task was started at.
+    started_at: SystemTime,
+    /// How long the task was paused for, which doesn't count as time tracked.
+    paused: Duration,
+    /// Whether [`Task::stop`] was called, or the task was loaded already stopped.
+    stopped: bool,
 }
 
 impl Task {
@@ -135,9 +535,31 @@ impl Task {
             name: name.to_string(),
             start: Instant::now(),
             end: Instant::now(),
+            started_at: SystemTime::now(),
+            paused: Duration::ZERO,
+            stopped: false,
         }
     }
 
+    /// Gets the wall-clock time the task was started at.
+    pub fn started_at(&self) -> SystemTime {
+        self.started_at
+    }
+
+    /// Creates a fresh task with the same name as this one, started now.
+    ///
+    /// Unlike `clone`, which copies the tracked time as well, the new task starts from zero.
+    ///
+    /// # Examples
+    /// ```no_run
+    /// let name = String::from("Task 1");
+    /// let task = timetracker::Task::new(&name);
+    /// let copy = task.duplicate();
+    /// ```
+    pub fn duplicate(&self) -> Task {
+        Task::new(&self.name)
+    }
+
     /// Stops the task by setting the end time to the current time.
     ///
     /// # Examples
@@ -150,6 +572,21 @@ impl Task {
     /// ```
     pub fn stop(&mut self) {
         self.end = Instant::now();
+        self.stopped = true;
+
+        verbose!(
+            "task '{}' started at {}, stopped at {}, duration {:?}",
+            self.name,
+            fmt_instant(self.start),
+            fmt_instant(self.end),
+            self.end.duration_since(self.start)
+        );
+    }
+
+    /// Leaves time the task was paused for out of the time tracked, e.g. after
+    /// `time-tracker pause all` paused it from another process.
+    pub fn exclude_paused(&mut self, paused: Duration) {
+        self.paused += paused;
     }
 
     /// Gets the total time tracked since the task was started (in seconds).
@@ -168,7 +605,42 @@ impl Task {
     /// println!("Time tracked: {} seconds", time_tracked); // -> Time elapsed: 1 seconds
     /// ```
     pub fn time_tracked_seconds(&self) -> u64 {
-        return self.end.duration_since(self.start).as_secs();
+        self.end
+            .duration_since(self.start)
+            .saturating_sub(self.paused)
+            .as_secs()
+    }
+
+    /// Checks whether the task is still running, i.e. hasn't been stopped.
+    pub fn is_running(&self) -> bool {
+        !self.stopped
+    }
+
+    /// Gets the time tracked (in seconds) as of right now, without stopping the task.
+    ///
+    /// Unlike [`Task::time_tracked_seconds`], which only counts up to the last time the end
+    /// was set, this counts up to the current time while the task is running, so a display can
+    /// call it every frame. Once the task is stopped, it is the same as
+    /// [`Task::time_tracked_seconds`].
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// let mut task = timetracker::Task::new(&String::from("Task 1"));
+    /// std::thread::sleep(std::time::Duration::from_secs(1));
+    /// assert_eq!(task.elapsed_live(), 1);
+    /// task.stop();
+    /// std::thread::sleep(std::time::Duration::from_secs(1));
+    /// assert_eq!(task.elapsed_live(), 1);
+    /// ```
+    pub fn elapsed_live(&self) -> u64 {
+        if self.stopped {
+            return self.time_tracked_seconds();
+        }
+        Instant::now()
+            .duration_since(self.start)
+            .saturating_sub(self.paused)
+            .as_secs()
     }
 
     /// Gets  the amount of time tracked as X Days, X Hours, Y Minutes, and Z Seconds.
@@ -186,163 +658,242 @@ impl Task {
     /// println!("{}", duration); // -> 0 Days, 0 Hours, 0 Minutes, 1 Second
     /// ```
     pub fn time_tracked_string(&self) -> String {
-        // get the total number of seconds.
-        let total_seconds = self.time_tracked_seconds();
-
-        // get the number of days
-        let days = total_seconds / *DAYS_DIVISOR;
-
-        // get the number of hours left over.
-        let hours = (total_seconds % *DAYS_DIVISOR) / *HOURS_DIVISOR;
-
-        // get the number of minutes left over.
-        let minutes = (total_seconds % *HOURS_DIVISOR) / *MINUTES_DIVISOR;
-
-        // get the number of seconds left over.
-        let seconds = total_seconds % *MINUTES_DIVISOR;
-
-        // create a vector to hold the time units.
-        let mut time_units: Vec<String> = Vec::new();
-
-        // add the days to the vector.
-        if days > 0 {
-            time_units.push(format!("{} Day{}", days, if days > 1 { "s" } else { "" }));
-        }
-
-        // add the hours to the vector.
-        if hours > 0 {
-            time_units.push(format!(
-                "{} Hour{}",
-                hours,
-                if hours > 1 { "s" } else { "" }
-            ));
-        }
-
-        // add the minutes to the vector.
-        if minutes > 0 {
-            time_units.push(format!(
-                "{} Minute{}",
-                minutes,
-                if minutes > 1 { "s" } else { "" }
-            ));
-        }
-
-        // add the seconds to the vector.
-        if seconds > 0 {
-            time_units.push(format!(
-                "{} Second{}",
-                seconds,
-                if seconds > 1 { "s" } else { "" }
-            ));
-        }
-
-        // create a string to hold the output.
-        let mut output = String::new();
-
-        // loop through the time units.
-        for (index, time_unit) in time_units.iter().enumerate() {
-            // if this is the last time unit, we add "and" before it, unless there is only one time unit.
-            if index == time_units.len() - 1 && time_units.len() > 1 {
-                output.push_str("and ");
-            }
-
-            // add the time unit to the output.
-            output.push_str(time_unit);
-
-            // if this is not the last time unit, add a comma.
-            if index != time_units.len() - 1 {
-                output.push_str(", ");
-            }
-        }
+        self.time_tracked_string_with_flags(DurationFormatFlags::default())
+    }
 
-        // if there are only two time units, remove the comma.
-        if time_units.len() == 2 {
-            output = output.replace(", ", " ");
-        }
+    /// Gets the amount of time tracked as a string, including the units `flags` asks for even
+    /// when they are 0.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// use timetracker::DurationFormatFlags;
+    ///
+    /// let name = String::from("Task 1");
+    /// let mut task = timetracker::Task::new(&name);
+    /// std::thread::sleep(std::time::Duration::from_secs(1));
+    /// task.stop();
+    /// let duration = task.time_tracked_string_with_flags(DurationFormatFlags::always_all());
+    /// println!("{}", duration); // -> 0 Days, 0 Hours, 0 Minutes, and 1 Second
+    /// ```
+    pub fn time_tracked_string_with_flags(&self, flags: DurationFormatFlags) -> String {
+        format_duration_human_with_flags(self.time_tracked_seconds(), flags)
+    }
 
-        return output;
+    /// Gets the amount of time tracked as a terse duration like `1h23m45s`.
+    ///
+    /// See [`format_duration_compact`] for the exact format.
+    ///
+    /// # Examples
+    ///
+    /// ```no_run
+    /// let name = String::from("Task 1");
+    /// let mut task = timetracker::Task::new(&name);
+    /// std::thread::sleep(std::time::Duration::from_secs(1));
+    /// task.stop();
+    /// println!("{}", task.fmt_duration_compact()); // -> 1s
+    /// ```
+    pub fn fmt_duration_compact(&self) -> String {
+        format_duration_compact(self.time_tracked_seconds())
     }
 
     /// Shows a timer for the given task name.
     ///
     /// Displays a timer for the given task name as 'Task Name: 00:00:00'.
-    /// The timer will update every second until the user types 'stop'.
+    /// The timer is updated (which takes a second for a [`ProductionTimer`]) until the user
+    /// types 'stop'.
     ///
-    /// ! When testing, this function will immediately return to prevent the program from hanging.
+    /// In a test environment input is read line by line from stdin, so the timer can be
+    /// driven through a pipe.
     ///
     /// # Examples
     ///
     /// ```no_run
+    /// use timetracker::{environment::ProductionEnv, ProductionTimer};
+    ///
     /// let name = String::from("Task 1");
     /// let mut task = timetracker::Task::new(&name);
-    /// let mut timer = timetracker::Timer::new();
-    /// task.show_timer(&mut timer);
+    /// task.show_timer(&mut ProductionTimer::new(), &ProductionEnv);
     /// ```
-    pub fn show_timer(&self, timer: &mut Timer) {
-        let (tx, rx) = std::sync::mpsc::channel();
-        let mut invalid = false;
-        // holds the input while the timer is running
-        thread::spawn(move || {
-            let mut input = String::new();
-
-            // detect test environment
-            let tt_env = std::env::var("TT_ENV").unwrap_or_else(|_| String::from(""));
-
-            if tt_env == "test" {
-                std::io::stdin().read_line(&mut input).unwrap();
-                debug::log::info!("Test environment detected.");
-            } else {
-                // read input from stdin silently so that the user doesn't see what they type
-                // (prevents ugly output when the user types while the timer is running)
-                input = match rpassword::read_password() {
-                    Ok(input) => input,
-                    Err(e) => {
-                        log::error!(&format!("Error reading input: {}", e));
-                        print!("Error reading input: {}", e);
-                        std::process::exit(1);
-                    }
-                };
+    pub fn show_timer<T: TimerTrait>(&self, timer: &mut T, env: &dyn Environment) {
+        loop {
+            let input = self.show_timer_until_input(timer, &read_input(env));
+            if input.trim() == "stop" {
+                break;
             }
+            println!(
+                "{}: Invalid input. Please type 'stop' to stop the timer.",
+                self.name
+            );
+        }
+    }
 
-            // send the input to the main thread
-            tx.send(input).unwrap();
-        });
-        // loop until the user has typed 'stop'
+    /// Shows the timer until a line arrives on `input`, and returns it.
+    ///
+    /// Nothing here waits on its own, so with a [`FakeTimer`] and the input already sent this
+    /// returns right away.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use timetracker::{FakeTimer, Task, TimerTrait};
+    ///
+    /// let task = Task::new(&String::from("Task 1"));
+    /// let mut timer = FakeTimer { ticks: 0, advance_per_update: 1 };
+    /// let (sender, input) = std::sync::mpsc::channel();
+    /// sender.send(String::from("stop")).unwrap();
+    /// assert_eq!(task.show_timer_until_input(&mut timer, &input), "stop");
+    /// assert_eq!(timer.elapsed(), 1);
+    /// ```
+    pub fn show_timer_until_input<T: TimerTrait>(
+        &self,
+        timer: &mut T,
+        input: &mpsc::Receiver<String>,
+    ) -> String {
+        let mut line = terminal::LineUpdater::new();
         loop {
-            timer.update();
-
-            // replace the timer and the user input with the new timer and user input
-            // print the task name and the timer
-            print!("\r{}: {}", self.name, timer);
-            io::stdout().flush().unwrap();
-
-            // check if notification is empty, if not, print it
-            print!("\n\r{}", "> ");
+            // redraw the part of the timer that changed
+            print!(
+                "{}",
+                line.update(&format!("{}: {}", self.name, timer.fmt_clock()))
+            );
+            print!("\n\r> ");
             io::stdout().flush().unwrap();
 
-            // wait for 1 second
-            thread::sleep(std::time::Duration::from_secs(1));
+            timer.update();
 
-            // remove the last line
+            // go back up to the timer line
             print!("\x1B[1A");
 
-            if let Ok(input) = rx.try_recv() {
-                if input.trim() == "stop" {
-                    break;
-                } else {
-                    invalid = true;
-                    break;
-                }
+            if let Ok(input) = input.try_recv() {
+                return input;
             }
         }
+    }
+}
 
-        if invalid {
-            println!(
-                "{}: Invalid input. Please type 'stop' to stop the timer.",
-                self.name
-            );
-            self.show_timer(timer);
+/// Reads one line of input on a separate thread, so a timer can keep running while it waits.
+///
+/// Outside of tests the input is read silently, so that the user doesn't see what they type
+/// (which prevents ugly output when the user types while the timer is running).
+fn read_input(env: &dyn Environment) -> mpsc::Receiver<String> {
+    let (tx, rx) = mpsc::channel();
+    let is_test = env.is_test();
+    thread::spawn(move || {
+        let mut input = String::new();
+
+        if is_test {
+            std::io::stdin().read_line(&mut input).unwrap();
+            if is_verbose() {
+                debug::log::info!("Test environment detected.");
+            }
+        } else {
+            input = match rpassword::read_password() {
+                Ok(input) => input,
+                Err(e) => {
+                    if is_verbose() {
+                        log::error!(&format!("Error reading input: {}", e));
+                    }
+                    print!("Error reading input: {}", e);
+                    std::process::exit(1);
+                }
+            };
         }
+
+        // send the input to the main thread
+        let _ = tx.send(input);
+    });
+    rx
+}
+
+/// Shows a timer for every running task until the user types something, and returns what
+/// they typed.
+///
+/// The timers are shown on a single line, as 'Task A: 00:01:23 | Task B: 00:00:45', and
+/// redrawn every `interval`. Time a task was paused for in the running file at `running_file`
+/// (with `time-tracker pause all`) isn't counted, as it isn't when the task is saved.
+///
+/// # Examples
+///
+/// ```no_run
+/// use std::{path::Path, time::Duration};
+/// use timetracker::{environment::ProductionEnv, show_timers, Task};
+///
+/// let tasks = [Task::new(&String::from("Task A")), Task::new(&String::from("Task B"))];
+/// let running_file = Path::new("running.json");
+/// let input = show_timers(&tasks, &ProductionEnv, Duration::from_secs(1), running_file);
+/// ```
+pub fn show_timers(
+    tasks: &[Task],
+    env: &dyn Environment,
+    interval: Duration,
+    running_file: &Path,
+) -> String {
+    let rx = read_input(env);
+    let mut line = terminal::LineUpdater::new();
+    loop {
+        // a missing or unreadable running file only means no pauses are known
+        let running = running::load(running_file).unwrap_or_default();
+        let now = SystemTime::now();
+        let timers: Vec<String> = tasks
+            .iter()
+            .map(|task| {
+                let elapsed = unpaused_seconds(task, &running, now);
+                format!("{}: {}", task.name, get_clock_format(elapsed))
+            })
+            .collect();
+        print!("{}", line.update(&timers.join(" | ")));
+        print!("\n\r> ");
+        io::stdout().flush().unwrap();
+
+        thread::sleep(interval);
+
+        // remove the last line
+        print!("\x1B[1A");
+
+        if let Ok(input) = rx.try_recv() {
+            return input;
+        }
+    }
+}
+
+/// Gets the time tracked for a task as of `now` (in seconds), leaving out the time it has been
+/// paused for according to its entry among the `running` tasks.
+fn unpaused_seconds(task: &Task, running: &[running::RunningTask], now: SystemTime) -> u64 {
+    let paused = running::find(running, &task.name, task.started_at)
+        .map_or(Duration::ZERO, |running| running.paused_duration(now));
+    task.elapsed_live().saturating_sub(paused.as_secs())
+}
+
+/// Tasks are equal when they have the same name and the same time tracked (in seconds).
+impl PartialEq for Task {
+    fn eq(&self, other: &Task) -> bool {
+        (&self.name, self.time_tracked_seconds()) == (&other.name, other.time_tracked_seconds())
+    }
+}
+
+impl Eq for Task {}
+
+/// Orders tasks by the time tracked, shortest first. Tasks with the same time tracked are
+/// ordered by name.
+impl Ord for Task {
+    fn cmp(&self, other: &Task) -> std::cmp::Ordering {
+        self.time_tracked_seconds()
+            .cmp(&other.time_tracked_seconds())
+            .then_with(|| self.name.cmp(&other.name))
+    }
+}
+
+impl PartialOrd for Task {
+    fn partial_cmp(&self, other: &Task) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Creates a new task named "Untitled", since a default task can't know its name.
+impl Default for Task {
+    fn default() -> Task {
+        Task::new(&String::from("Untitled"))
     }
 }
 
@@ -356,10 +907,130 @@ impl std::fmt::Display for Task {
     }
 }
 
+/// Snapshots the task, stopped or not, as it would be saved to the history file.
+///
+/// Both timestamps are truncated to whole seconds, so `seconds` can be a second off from
+/// [`Task::time_tracked_seconds`]. Time the task was paused for is left out of `seconds`.
+impl From<Task> for storage::StoredTask {
+    fn from(task: Task) -> storage::StoredTask {
+        let mut stored = storage::StoredTask::new(
+            &task.name,
+            instant_to_system_time(task.start),
+            instant_to_system_time(task.end),
+        );
+        stored.seconds = stored.seconds.saturating_sub(task.paused.as_secs());
+        stored
+    }
+}
+
+/// Why a stored task can't be turned back into a [`Task`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ConversionError {
+    /// The task starts or stops after the current time.
+    FutureTimestamp,
+    /// The task started too long ago for this system's clock to represent, e.g. before it
+    /// last booted.
+    OutOfRange,
+}
+
+impl std::fmt::Display for ConversionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            ConversionError::FutureTimestamp => write!(f, "the task has a timestamp in the future"),
+            ConversionError::OutOfRange => write!(f, "the task started too long ago"),
+        }
+    }
+}
+
+impl std::error::Error for ConversionError {}
+
+/// Rebuilds a task from the history file, with `Instant`s as far in the past as the timestamps.
+///
+/// A task that is still running gets the current time as its end.
+impl TryFrom<storage::StoredTask> for Task {
+    type Error = ConversionError;
+
+    fn try_from(stored: storage::StoredTask) -> Result<Task, ConversionError> {
+        let now = SystemTime::now();
+        let now_instant = Instant::now();
+        let to_instant = |time: SystemTime| {
+            let age = now
+                .duration_since(time)
+                .map_err(|_| ConversionError::FutureTimestamp)?;
+            now_instant
+                .checked_sub(age)
+                .ok_or(ConversionError::OutOfRange)
+        };
+        let end = to_instant(stored.stopped_at.unwrap_or(now))?;
+        let start = to_instant(stored.started_at)?;
+        Ok(Task {
+            name: stored.name,
+            start,
+            end: end.max(start),
+            started_at: stored.started_at,
+            paused: Duration::ZERO,
+            stopped: stored.stopped_at.is_some(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests_timer {
+    use super::*;
+
+    #[test]
+    fn default_timer_has_not_elapsed() {
+        let timer = Timer::default();
+        assert_eq!(timer.elapsed(), 0);
+        assert_eq!(timer.to_string(), "00:00:00");
+    }
+
+    #[test]
+    fn fake_timer_advances_without_waiting() {
+        let mut timer = FakeTimer {
+            ticks: 0,
+            advance_per_update: 61,
+        };
+        timer.update();
+        timer.update();
+        assert_eq!(timer.elapsed(), 122);
+        assert_eq!(timer.fmt_clock(), "00:02:02");
+    }
+
+    #[test]
+    fn shows_timer_until_input() {
+        let task = Task::new(&"Test".to_string());
+        let mut timer = FakeTimer {
+            ticks: 0,
+            advance_per_update: 1,
+        };
+        let (sender, input) = mpsc::channel();
+        let started = Instant::now();
+        sender.send(String::from("stop")).unwrap();
+        assert_eq!(task.show_timer_until_input(&mut timer, &input), "stop");
+        assert!(started.elapsed() < std::time::Duration::from_secs(1));
+    }
+
+    #[test]
+    fn formats_elapsed_time() {
+        let mut timer = Timer::new();
+        timer.start = timer.end - std::time::Duration::from_secs(4980);
+        assert_eq!(timer.elapsed_humanized(), "1 Hour and 23 Minutes");
+        assert_eq!(timer.elapsed_compact(), "1h23m");
+    }
+}
+
 #[cfg(test)]
 mod tests_task {
     use super::*;
 
+    #[test]
+    fn default_task_is_untitled() {
+        let task = Task::default();
+        assert_eq!(task.name, "Untitled");
+        assert_eq!(task.time_tracked_seconds(), 0);
+    }
+
     #[test]
     fn creates_new_task() {
         let mut task = Task::new(&"Test".to_string());
@@ -369,6 +1040,154 @@ mod tests_task {
         assert_eq!(task.time_tracked_seconds(), 1);
     }
 
+    #[test]
+    fn elapsed_live_counts_up_until_stopped() {
+        let mut task = Task::new(&"Test".to_string());
+        // started 90 seconds ago, with the end never updated since
+        task.start = Instant::now() - std::time::Duration::from_secs(90);
+        task.end = task.start;
+        assert!(task.is_running());
+        assert_eq!(task.time_tracked_seconds(), 0);
+        assert_eq!(task.elapsed_live(), 90);
+
+        task.exclude_paused(Duration::from_secs(30));
+        assert_eq!(task.elapsed_live(), 60);
+
+        task.stop();
+        // moving the start back again must not change a stopped task's time
+        let frozen = task.time_tracked_seconds();
+        task.start -= std::time::Duration::from_secs(60);
+        task.end -= std::time::Duration::from_secs(60);
+        assert!(!task.is_running());
+        assert_eq!(task.elapsed_live(), frozen);
+        assert_eq!(task.elapsed_live(), task.time_tracked_seconds());
+    }
+
+    #[test]
+    fn timers_leave_out_paused_time() {
+        let mut task = Task::new(&"Test".to_string());
+        task.start = Instant::now() - std::time::Duration::from_secs(600);
+        let now = SystemTime::now();
+        let mut running =
+            running::RunningTask::from(storage::StoredTask::running("Test", task.started_at()));
+        running.paused_secs = 120;
+        running.pause(now - std::time::Duration::from_secs(60));
+
+        assert_eq!(unpaused_seconds(&task, &[], now), 600);
+        assert_eq!(unpaused_seconds(&task, &[running.clone()], now), 420);
+        running.task.name = String::from("Other");
+        assert_eq!(unpaused_seconds(&task, &[running], now), 600);
+    }
+
+    #[test]
+    fn stored_tasks_keep_whether_they_are_running() {
+        let started_at = SystemTime::now() - std::time::Duration::from_secs(60);
+        let running = storage::StoredTask::running("Test", started_at);
+        assert!(Task::try_from(running.clone()).unwrap().is_running());
+        let stopped = storage::StoredTask::new("Test", started_at, started_at);
+        assert!(!Task::try_from(stopped).unwrap().is_running());
+    }
+
+    #[test]
+    fn converts_to_stored_task_and_back() {
+        let mut task = Task::new(&"Test".to_string());
+        task.start = Instant::now() - std::time::Duration::from_secs(90);
+        task.stop();
+
+        let stored = storage::StoredTask::from(task.clone());
+        assert_eq!(stored.name, "Test");
+        assert!(stored.seconds.abs_diff(task.time_tracked_seconds()) <= 1);
+
+        let restored = Task::try_from(stored.clone()).unwrap();
+        assert_eq!(restored.name, "Test");
+        assert!(restored.time_tracked_seconds().abs_diff(stored.seconds) <= 1);
+    }
+
+    #[test]
+    fn future_stored_task_is_an_error() {
+        let in_an_hour = SystemTime::now() + std::time::Duration::from_secs(3600);
+        let stored = storage::StoredTask::running("Test", in_an_hour);
+        assert_eq!(
+            Task::try_from(stored).err(),
+            Some(ConversionError::FutureTimestamp)
+        );
+    }
+
+    #[test]
+    fn duration_format_flags_include_zero_units() {
+        let mut task = Task::new(&"Test".to_string());
+        task.start = task.end - std::time::Duration::from_secs(1);
+        // the seconds are shown either way, since there is one
+        for always_include_seconds in [false, true] {
+            let flags = |days, hours, minutes| DurationFormatFlags {
+                always_include_days: days,
+                always_include_hours: hours,
+                always_include_minutes: minutes,
+                always_include_seconds,
+            };
+            let cases = [
+                (flags(false, false, false), "1 Second"),
+                (flags(true, false, false), "0 Days and 1 Second"),
+                (flags(false, true, false), "0 Hours and 1 Second"),
+                (flags(false, false, true), "0 Minutes and 1 Second"),
+                (flags(true, true, false), "0 Days, 0 Hours, and 1 Second"),
+                (flags(true, false, true), "0 Days, 0 Minutes, and 1 Second"),
+                (flags(false, true, true), "0 Hours, 0 Minutes, and 1 Second"),
+                (
+                    flags(true, true, true),
+                    "0 Days, 0 Hours, 0 Minutes, and 1 Second",
+                ),
+            ];
+            for (flags, expected) in cases {
+                assert_eq!(task.time_tracked_string_with_flags(flags), expected);
+            }
+        }
+        assert_eq!(
+            task.time_tracked_string_with_flags(DurationFormatFlags::default()),
+            task.time_tracked_string()
+        );
+        assert_eq!(
+            format_duration_human_with_flags(0, DurationFormatFlags::always_all()),
+            "0 Days, 0 Hours, 0 Minutes, and 0 Seconds"
+        );
+    }
+
+    #[test]
+    fn clone_keeps_time_and_duplicate_restarts() {
+        let mut task = Task::new(&"Test".to_string());
+        task.start = Instant::now() - std::time::Duration::from_secs(5);
+        task.stop();
+
+        let clone = task.clone();
+        assert_eq!(clone.name, "Test");
+        assert_eq!(clone.time_tracked_seconds(), 5);
+
+        let duplicate = task.duplicate();
+        assert_eq!(duplicate.name, "Test");
+        assert_eq!(duplicate.time_tracked_seconds(), 0);
+    }
+
+    #[test]
+    fn sorts_by_duration_then_name() {
+        let task_with = |name: &str, seconds: u64| {
+            let mut task = Task::new(&name.to_string());
+            task.start = task.end - std::time::Duration::from_secs(seconds);
+            task
+        };
+        let mut tasks = Vec::from([
+            task_with("c", 30),
+            task_with("b", 10),
+            task_with("a", 30),
+            task_with("d", 5),
+        ]);
+        tasks.sort();
+
+        let names: Vec<&str> = tasks.iter().map(|task| task.name.as_str()).collect();
+        assert_eq!(names, vec!["d", "b", "a", "c"]);
+        assert!(task_with("a", 30) == task_with("a", 30));
+        assert!(task_with("a", 30) != task_with("b", 30));
+    }
+
     #[test]
     fn correct_duration_as_string() {
         let task_name = "Test".to_string();
@@ -464,4 +1283,14 @@ mod util {
         assert_eq!(get_clock_format(86460), "24:01:00");
         assert_eq!(get_clock_format(99999), "27:46:39");
     }
+
+    #[test]
+    fn test_format_duration_compact() {
+        assert_eq!(format_duration_compact(0), "0s");
+        assert_eq!(format_duration_compact(60), "1m");
+        assert_eq!(format_duration_compact(3600), "1h");
+        assert_eq!(format_duration_compact(3661), "1h1m1s");
+        assert_eq!(format_duration_compact(86400), "24h");
+        assert_eq!(format_duration_compact(86461), "24h1m1s");
+    }
 }
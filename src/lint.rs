@@ -0,0 +1,275 @@
+//! Naming conventions for task names, checked by `time-tracker lint`.
+//!
+//! Rules are read from a TOML file with a `[[rules]]` table per rule, e.g.
+//!
+//! ```toml
+//! [[rules]]
+//! type = "regex"
+//! pattern = "^[A-Z]+-\\d+"
+//! message = "Task names must start with a ticket ID"
+//!
+//! [[rules]]
+//! type = "max-length"
+//! max = 80
+//! ```
+//!
+//! Every kind of rule implements [`LintRule`], so new kinds only need a [`RuleConfig`] variant
+//! that builds them.
+
+use crate::storage::StoredTask;
+use regex::Regex;
+use serde::Deserialize;
+use std::{fmt, fs, io, path::Path};
+
+/// A check that a task name has to pass.
+pub trait LintRule {
+    /// Checks a task name, returning why it breaks the rule if it does.
+    fn check(&self, name: &str) -> Option<String>;
+}
+
+/// A rule that task names must match a regular expression.
+#[derive(Clone, Debug)]
+pub struct RegexRule {
+    /// The expression names must match somewhere (anchor it with `^` and `$` to match all of
+    /// the name).
+    pub pattern: Regex,
+    /// What to print for a name that doesn't match.
+    pub message: Option<String>,
+}
+
+impl LintRule for RegexRule {
+    fn check(&self, name: &str) -> Option<String> {
+        (!self.pattern.is_match(name)).then(|| {
+            self.message
+                .clone()
+                .unwrap_or_else(|| format!("doesn't match /{}/", self.pattern))
+        })
+    }
+}
+
+/// A rule that task names can be at most `max` characters long.
+#[derive(Clone, Debug)]
+pub struct MaxLengthRule {
+    /// The most characters a name can have.
+    pub max: usize,
+    /// What to print for a name that is too long.
+    pub message: Option<String>,
+}
+
+impl LintRule for MaxLengthRule {
+    fn check(&self, name: &str) -> Option<String> {
+        let length = name.chars().count();
+        (length > self.max).then(|| {
+            self.message
+                .clone()
+                .unwrap_or_else(|| format!("is {} characters long, more than {}", length, self.max))
+        })
+    }
+}
+
+/// A rule that task names can't contain any of a list of words, ignoring case.
+#[derive(Clone, Debug)]
+pub struct ForbiddenWordsRule {
+    /// The words names can't contain.
+    pub words: Vec<String>,
+    /// What to print for a name with a forbidden word.
+    pub message: Option<String>,
+}
+
+impl LintRule for ForbiddenWordsRule {
+    fn check(&self, name: &str) -> Option<String> {
+        let name = name.to_lowercase();
+        let word = self
+            .words
+            .iter()
+            .find(|word| name.contains(&word.to_lowercase()))?;
+        Some(
+            self.message
+                .clone()
+                .unwrap_or_else(|| format!("contains '{}'", word)),
+        )
+    }
+}
+
+/// A rule as it is written in the rules file, with its `type` picking the kind of rule.
+#[derive(Clone, Debug, Deserialize, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "kebab-case", deny_unknown_fields)]
+pub enum RuleConfig {
+    /// Names must match `pattern`. See [`RegexRule`].
+    Regex {
+        pattern: String,
+        message: Option<String>,
+    },
+    /// Names can be at most `max` characters long. See [`MaxLengthRule`].
+    MaxLength { max: usize, message: Option<String> },
+    /// Names can't contain any of `words`. See [`ForbiddenWordsRule`].
+    ForbiddenWords {
+        words: Vec<String>,
+        message: Option<String>,
+    },
+}
+
+impl RuleConfig {
+    /// Builds the rule, or explains why it can't be built (like an invalid pattern).
+    pub fn build(&self) -> Result<Box<dyn LintRule>, String> {
+        Ok(match self.clone() {
+            RuleConfig::Regex { pattern, message } => Box::new(RegexRule {
+                pattern: Regex::new(&pattern).map_err(|e| e.to_string())?,
+                message,
+            }),
+            RuleConfig::MaxLength { max, message } => Box::new(MaxLengthRule { max, message }),
+            RuleConfig::ForbiddenWords { words, message } => {
+                Box::new(ForbiddenWordsRule { words, message })
+            }
+        })
+    }
+}
+
+/// The contents of a rules file.
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RulesFile {
+    #[serde(default)]
+    rules: Vec<RuleConfig>,
+}
+
+/// Parses the rules in the contents of a rules file.
+///
+/// # Examples
+///
+/// ```
+/// use timetracker::lint;
+///
+/// let rules = lint::parse_rules(r#"
+///     [[rules]]
+///     type = "regex"
+///     pattern = "^[A-Z]+-\\d+"
+///     message = "Task names must start with a ticket ID"
+/// "#).unwrap();
+/// assert_eq!(rules[0].check("PROJ-1 login"), None);
+/// assert_eq!(
+///     rules[0].check("login").as_deref(),
+///     Some("Task names must start with a ticket ID")
+/// );
+/// ```
+pub fn parse_rules(contents: &str) -> Result<Vec<Box<dyn LintRule>>, String> {
+    let file: RulesFile = toml::from_str(contents).map_err(|e| e.message().to_string())?;
+    file.rules
+        .iter()
+        .enumerate()
+        .map(|(index, rule)| {
+            rule.build()
+                .map_err(|e| format!("rule {}: {}", index + 1, e))
+        })
+        .collect()
+}
+
+/// Reads the rules in a rules file.
+pub fn load_rules(path: &Path) -> io::Result<Vec<Box<dyn LintRule>>> {
+    let contents = fs::read_to_string(path)
+        .map_err(|e| io::Error::new(e.kind(), format!("{}: {}", path.display(), e)))?;
+    parse_rules(&contents).map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{}: {}", path.display(), e),
+        )
+    })
+}
+
+/// A task whose name breaks a rule.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Violation<'a> {
+    /// The task.
+    pub task: &'a StoredTask,
+    /// Why its name breaks the rule.
+    pub message: String,
+}
+
+/// Formats the violation like `2024-01-15 'login': doesn't match /^[A-Z]+-\d+/`.
+impl fmt::Display for Violation<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} '{}': {}",
+            crate::reports::local_date(self.task.started_at),
+            self.task.name,
+            self.message
+        )
+    }
+}
+
+/// Checks every task against every rule, in task order and then rule order.
+pub fn lint<'a>(tasks: &'a [StoredTask], rules: &[Box<dyn LintRule>]) -> Vec<Violation<'a>> {
+    tasks
+        .iter()
+        .flat_map(|task| {
+            rules
+                .iter()
+                .filter_map(|rule| rule.check(&task.name))
+                .map(move |message| Violation { task, message })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests_lint {
+    use super::*;
+    use std::time::UNIX_EPOCH;
+
+    fn task(name: &str) -> StoredTask {
+        StoredTask::new(name, UNIX_EPOCH, UNIX_EPOCH)
+    }
+
+    #[test]
+    fn regex_rule_flags_names_that_dont_match() {
+        let rules = parse_rules(
+            r#"
+            [[rules]]
+            type = "regex"
+            pattern = "^[A-Z]+-\\d+"
+            "#,
+        )
+        .unwrap();
+        let tasks = [
+            task("PROJ-12 login"),
+            task("fix login"),
+            task("proj-3 lower"),
+        ];
+        let violations = lint(&tasks, &rules);
+        let names: Vec<&str> = violations.iter().map(|v| v.task.name.as_str()).collect();
+        assert_eq!(names, ["fix login", "proj-3 lower"]);
+        assert_eq!(violations[0].message, r"doesn't match /^[A-Z]+-\d+/");
+    }
+
+    #[test]
+    fn every_rule_is_checked() {
+        let rules = parse_rules(
+            r#"
+            rules = [
+                { type = "max-length", max = 10 },
+                { type = "forbidden-words", words = ["darn"], message = "Keep it clean" },
+            ]
+            "#,
+        )
+        .unwrap();
+        let tasks = [task("Darn this long bug")];
+        let messages: Vec<String> = lint(&tasks, &rules)
+            .into_iter()
+            .map(|violation| violation.message)
+            .collect();
+        assert_eq!(
+            messages,
+            ["is 18 characters long, more than 10", "Keep it clean"]
+        );
+    }
+
+    #[test]
+    fn invalid_rules_are_errors() {
+        let invalid_pattern = "[[rules]]\ntype = \"regex\"\npattern = \"(\"";
+        assert!(parse_rules(invalid_pattern)
+            .err()
+            .unwrap()
+            .starts_with("rule 1: regex parse error"));
+        assert!(parse_rules("[[rules]]\ntype = \"spelling\"").is_err());
+    }
+}
@@ -2,29 +2,112 @@
 //!
 //! It asks the user for a task name and then starts a timer.
 //! When the user enters "stop", the timer is stopped and the time is printed.
+//! More tasks can be tracked at the same time with "start <name>" and stopped one at a time
+//! with "stop <name>".
 //! The user can then enter another task name and the process repeats.
 //! The user can enter "exit" to exit the program.
 //! Upon exiting, the program prints the total time tracked for each task.
+//!
+//! Completed tasks are saved to the history file, which the subcommands
+//! (e.g. `time-tracker export`) read from.
+
+mod cli;
 
-use std::io::{stdout, Write};
-use timetracker::{Task, Timer};
+use clap::Parser;
+use std::{
+    io::{stdout, Write},
+    time::{Duration, SystemTime},
+};
+use timetracker::{
+    aliases::{self, Aliases},
+    config::Config,
+    context::{ContextDetector, GitContextDetector},
+    environment, running, show_timers,
+    storage::StoredTask,
+    templates::TaskTemplate,
+    Task,
+};
 
 /// A simple time tracking application for the command line interface.
 ///
 /// This function is the entry point of the program.
+/// It runs the given subcommand, or the interactive timer if there is none.
+fn main() {
+    let cli = cli::Cli::parse();
+    let config = match Config::load(&cli.overrides()) {
+        Ok(config) => config,
+        Err(e) => {
+            eprintln!("Error reading config file: {}", e);
+            std::process::exit(1);
+        }
+    };
+    timetracker::set_verbose(config.verbose.value);
+    validate_config(&config, cli.command.as_ref());
+
+    let aliases = match Aliases::load(&aliases::aliases_file_path()) {
+        Ok(aliases) => aliases,
+        Err(e) => {
+            eprintln!("Error reading aliases: {}", e);
+            std::process::exit(1);
+        }
+    };
+    let mut command = cli.command;
+    if let Some(Err(e)) = command
+        .as_mut()
+        .map(|command| command.expand_aliases(&aliases))
+    {
+        eprintln!("Error: {}", e);
+        std::process::exit(1);
+    }
+
+    match command {
+        Some(command) => cli::run(command, &config),
+        None => run_interactive(&config, &aliases),
+    }
+}
+
+/// Checks the settings before running `command`.
+///
+/// The interactive timer, and any subcommand that reads a setting, refuse to run with an
+/// invalid value for it, since e.g. a too short `timer_update_interval_ms` would make the timer
+/// spin. Other invalid settings are only warned about, and `config validate` reports the
+/// problems itself.
+fn validate_config(config: &Config, command: Option<&cli::Command>) {
+    if let Some(cli::Command::Config(cli::ConfigCommand::Validate)) = command {
+        return;
+    }
+    let mut invalid = false;
+    for error in config.validate() {
+        if command.is_none_or(|command| command.uses_setting(error.key())) {
+            eprintln!("ERROR: {}", error);
+            invalid = true;
+        } else {
+            eprintln!("Warning: {}", error);
+        }
+    }
+    if invalid {
+        std::process::exit(1);
+    }
+}
+
+/// Runs the interactive timer.
+///
 /// It asks the user for a task name and then starts a timer.
-/// When the user enters "stop", the timer is stopped and the time is printed.
-/// The user can then enter another task name and the process repeats.
+/// While tasks are running, the user can start more with 'start <name>', stop one with
+/// 'stop <name>', or stop all of them with 'stop'.
+/// Once every task has been stopped, the user can enter another task name and the process repeats.
 /// The user can enter "exit" to exit the program.
 /// Upon exiting, the program prints the total time tracked for each task.
-fn main() {
+fn run_interactive(config: &Config, aliases: &Aliases) {
     println!("Welcome to the time tracker!");
+    let env = environment::detect();
+    let context = GitContextDetector.detect();
     let prompt = "Enter a task name to start tracking it. Exit the program by typing 'exit'.\n";
-    let mut ended = true;
-    let mut tasks: Vec<Task> = Vec::new();
+    let mut current_tasks: Vec<Task> = Vec::new();
     let mut tasks_completed: Vec<String> = Vec::new();
+    let interval = Duration::from_millis(config.timer_update_interval_ms.value);
     loop {
-        if ended {
+        if current_tasks.is_empty() {
             print!("{}", prompt);
             print!("> ");
             stdout().flush().unwrap();
@@ -36,22 +119,53 @@ fn main() {
             if task_name == "exit" {
                 break;
             }
-            tasks.push(Task::new(&task_name));
-            let task = tasks.last().unwrap();
-            ended = false;
-            println!("Started task '{}', stop the task with 'stop'", task.name);
-            // show the timer until the user presses enter
-            let mut new_timer = Timer::new();
-            task.show_timer(&mut new_timer);
+            let Some(task_name) = expand_alias(aliases, &task_name) else {
+                continue;
+            };
+            let task = Task::new(&task_name);
+            add_running(&task, config);
+            current_tasks.push(task);
+            println!("Started task '{}', stop the task with 'stop'", task_name);
+            continue;
+        }
+
+        // show the timers until the user types something
+        let input = show_timers(
+            &current_tasks,
+            env.as_ref(),
+            interval,
+            &config.running_file.value,
+        );
+        let input = input.trim();
+        if input == "stop" {
+            for task in current_tasks.drain(..) {
+                stop_task(task, &mut tasks_completed, &context, config);
+            }
+        } else if let Some(name) = input.strip_prefix("stop ") {
+            match current_tasks
+                .iter()
+                .position(|task| task.name == name.trim())
+            {
+                Some(index) => {
+                    let task = current_tasks.remove(index);
+                    stop_task(task, &mut tasks_completed, &context, config);
+                    print_running(&current_tasks);
+                }
+                None => println!("No running task is named '{}'.", name.trim()),
+            }
+        } else if let Some(name) = input.strip_prefix("start ") {
+            let Some(name) = expand_alias(aliases, name.trim()) else {
+                continue;
+            };
+            let task = Task::new(&name);
+            add_running(&task, config);
+            current_tasks.push(task);
+            println!("Started task '{}'", name);
+            print_running(&current_tasks);
         } else {
-            let mut task = tasks.pop().unwrap();
-            task.stop();
-            ended = true;
-            tasks_completed.push(format!("{}: {}", task.name, task));
             println!(
-                "Task '{}' completed in {}.",
-                task.name,
-                task.time_tracked_string()
+                "Invalid input. Type 'start <name>' to start another task, 'stop <name>' to stop \
+                 one, or 'stop' to stop every task."
             );
         }
     }
@@ -72,3 +186,100 @@ Tasks completed:"
 Goodbye!"
     );
 }
+
+/// Expands an alias typed as a task name, printing an error if the alias is circular.
+fn expand_alias(aliases: &Aliases, name: &str) -> Option<String> {
+    match aliases.expand(name) {
+        Ok(name) => Some(name),
+        Err(e) => {
+            println!("Error: {}", e);
+            None
+        }
+    }
+}
+
+/// Stops a running task, saves it, and adds it to the tasks completed.
+fn stop_task(mut task: Task, tasks_completed: &mut Vec<String>, context: &str, config: &Config) {
+    task.stop();
+    remove_running(&mut task, config);
+    tasks_completed.push(format!("{}: {}", task.name, task));
+    save_task(
+        &task,
+        config.hourly_rate.value.is_some(),
+        None,
+        context,
+        config,
+    );
+    println!(
+        "Task '{}' completed in {}.",
+        task.name,
+        task.time_tracked_string()
+    );
+}
+
+/// Lists the tasks that are still running, if there are any.
+fn print_running(current_tasks: &[Task]) {
+    if current_tasks.is_empty() {
+        return;
+    }
+    let names: Vec<&str> = current_tasks
+        .iter()
+        .map(|task| task.name.as_str())
+        .collect();
+    println!("Running: {}", names.join(", "));
+}
+
+/// Records that a task has started in the running file.
+///
+/// Failing to do so is reported but doesn't interrupt the timer.
+fn add_running(task: &Task, config: &Config) {
+    let running_task = StoredTask::running(&task.name, task.started_at());
+    if let Err(e) = running::add(&config.running_file.value, running_task) {
+        eprintln!(
+            "Could not update {}: {}",
+            config.running_file.value.display(),
+            e
+        );
+    }
+}
+
+/// Records that a task has stopped in the running file, leaving out the time it was paused for
+/// (with `time-tracker pause all`) from the time tracked.
+///
+/// Failing to do so is reported but doesn't interrupt the timer.
+fn remove_running(task: &mut Task, config: &Config) {
+    match running::remove(&config.running_file.value, &task.name, task.started_at()) {
+        Ok(Some(running)) => task.exclude_paused(running.paused_duration(SystemTime::now())),
+        Ok(None) => {}
+        Err(e) => eprintln!(
+            "Could not update {}: {}",
+            config.running_file.value.display(),
+            e
+        ),
+    }
+}
+
+/// Appends a stopped task to the history file, recording the context it was started in and the
+/// settings of the template it was started from, if any.
+///
+/// Failing to save is reported but doesn't interrupt the timer.
+fn save_task(
+    task: &Task,
+    billable: bool,
+    template: Option<&TaskTemplate>,
+    context: &str,
+    config: &Config,
+) {
+    let mut stored = StoredTask::from(task.clone());
+    stored.billable = billable;
+    if let Some(template) = template {
+        template.apply(&mut stored);
+    }
+    stored.context = Some(context.to_string());
+    if let Err(e) = config
+        .open_storage()
+        .and_then(|mut storage| storage.append(&stored))
+    {
+        eprintln!("Could not save task '{}': {}", task.name, e);
+    }
+}
@@ -0,0 +1,147 @@
+//! How much of the stretch from the first task of a day to the last was spent on tasks.
+
+use super::local_time;
+use crate::storage::StoredTask;
+use chrono::{Duration, NaiveDate, NaiveTime};
+use std::{fmt, time::SystemTime};
+
+/// The clock hours of a day's work, from the start of the first task to the end of the last,
+/// against the hours tracked on tasks.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ActiveHoursReport {
+    /// The hours from the start of the first task to the end of the last.
+    pub clock_hours: f64,
+    /// The hours tracked on tasks. Tasks that ran at the same time are each counted.
+    pub tracked_hours: f64,
+    /// The hours between tasks when none was running, leaving out gaps shorter than the
+    /// threshold.
+    pub gap_hours: f64,
+    /// The tracked hours as a share of the clock hours, from 0 to 100 (or more if tasks
+    /// overlapped).
+    pub efficiency_pct: f64,
+}
+
+impl ActiveHoursReport {
+    /// Measures the tasks that ran on `date` (in local time), cut off at the edges of the day.
+    /// Gaps shorter than `threshold_secs` don't count as gaps, e.g. a few minutes between
+    /// meetings. Tasks that are still running are left out.
+    ///
+    /// Returns `None` if no task ran on `date`.
+    pub fn for_date(
+        tasks: &[StoredTask],
+        date: NaiveDate,
+        threshold_secs: u64,
+    ) -> Option<ActiveHoursReport> {
+        let day_start = local_time(date, NaiveTime::MIN);
+        let day_end = local_time(date + Duration::days(1), NaiveTime::MIN);
+        let mut ranges: Vec<(SystemTime, SystemTime)> = tasks
+            .iter()
+            .filter_map(|task| Some((task.started_at, task.tracked_until()?)))
+            .filter(|(start, end)| *start < day_end && *end > day_start)
+            .map(|(start, end)| (start.max(day_start), end.min(day_end)))
+            .collect();
+        ranges.sort();
+        let first = ranges.first()?.0;
+
+        let mut tracked_secs = 0.0;
+        let mut gap_secs = 0.0;
+        let mut covered_until = first;
+        for (start, end) in ranges {
+            tracked_secs += secs_between(start, end);
+            let gap = secs_between(covered_until, start);
+            if gap >= threshold_secs as f64 {
+                gap_secs += gap;
+            }
+            covered_until = covered_until.max(end);
+        }
+        let clock_secs = secs_between(first, covered_until);
+        Some(ActiveHoursReport {
+            clock_hours: clock_secs / 3600.0,
+            tracked_hours: tracked_secs / 3600.0,
+            gap_hours: gap_secs / 3600.0,
+            efficiency_pct: if clock_secs > 0.0 {
+                tracked_secs / clock_secs * 100.0
+            } else {
+                0.0
+            },
+        })
+    }
+}
+
+/// Gets the seconds from `start` to `end`, or 0 if `end` is first.
+fn secs_between(start: SystemTime, end: SystemTime) -> f64 {
+    end.duration_since(start).unwrap_or_default().as_secs_f64()
+}
+
+/// Formats the report a line per number, e.g. `Efficiency: 77.8%`.
+impl fmt::Display for ActiveHoursReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Clock hours: {:.2}", self.clock_hours)?;
+        writeln!(f, "Tracked hours: {:.2}", self.tracked_hours)?;
+        writeln!(f, "Gap hours: {:.2}", self.gap_hours)?;
+        write!(f, "Efficiency: {:.1}%", self.efficiency_pct)
+    }
+}
+
+#[cfg(test)]
+mod tests_active {
+    use super::*;
+
+    fn date() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 1, 15).unwrap()
+    }
+
+    fn task(start: (u32, u32), end: (u32, u32)) -> StoredTask {
+        let at =
+            |(hour, minute)| local_time(date(), NaiveTime::from_hms_opt(hour, minute, 0).unwrap());
+        StoredTask::new("task", at(start), at(end))
+    }
+
+    #[test]
+    fn measures_a_day_with_gaps() {
+        // 09:00 to 18:00 with a two hour gap and a 3 minute one
+        let tasks = [
+            task((9, 0), (12, 0)),
+            task((14, 0), (16, 0)),
+            task((16, 3), (18, 0)),
+        ];
+        let report = ActiveHoursReport::for_date(&tasks, date(), 300).unwrap();
+        assert_eq!(report.clock_hours, 9.0);
+        assert_eq!(report.tracked_hours, 3.0 + 2.0 + 1.95);
+        assert_eq!(report.gap_hours, 2.0);
+        assert!((report.efficiency_pct - 6.95 / 9.0 * 100.0).abs() < 1e-9);
+        assert_eq!(
+            report.to_string(),
+            "Clock hours: 9.00\nTracked hours: 6.95\nGap hours: 2.00\nEfficiency: 77.2%"
+        );
+
+        // without a threshold every gap counts, so the gaps and tasks add up to the clock
+        let report = ActiveHoursReport::for_date(&tasks, date(), 0).unwrap();
+        assert_eq!(report.gap_hours, 2.05);
+        assert_eq!(report.gap_hours + report.tracked_hours, report.clock_hours);
+    }
+
+    #[test]
+    fn overlapping_tasks_are_not_gaps() {
+        let tasks = [
+            task((9, 0), (11, 0)),
+            task((10, 0), (10, 30)),
+            task((11, 30), (12, 0)),
+        ];
+        let report = ActiveHoursReport::for_date(&tasks, date(), 0).unwrap();
+        assert_eq!(report.clock_hours, 3.0);
+        assert_eq!(report.tracked_hours, 3.0);
+        assert_eq!(report.gap_hours, 0.5);
+        assert_eq!(report.efficiency_pct, 100.0);
+    }
+
+    #[test]
+    fn days_without_tasks_have_no_report() {
+        let tasks = [StoredTask::running(
+            "running",
+            local_time(date(), NaiveTime::MIN),
+        )];
+        assert_eq!(ActiveHoursReport::for_date(&tasks, date(), 300), None);
+        assert_eq!(ActiveHoursReport::for_date(&[], date(), 300), None);
+    }
+}
@@ -0,0 +1,118 @@
+//! Billable and non-billable time.
+
+use crate::storage::StoredTask;
+
+/// Which tasks to keep, based on whether they are billable.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum BillableFilter {
+    /// Keep every task.
+    #[default]
+    All,
+    /// Keep only billable tasks.
+    BillableOnly,
+    /// Keep only non-billable tasks.
+    NonBillableOnly,
+}
+
+impl BillableFilter {
+    /// Whether the filter keeps `task`.
+    pub fn matches(&self, task: &StoredTask) -> bool {
+        match self {
+            BillableFilter::All => true,
+            BillableFilter::BillableOnly => task.billable,
+            BillableFilter::NonBillableOnly => !task.billable,
+        }
+    }
+}
+
+/// The time tracked on billable and non-billable tasks.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct BillableTotals {
+    /// Seconds tracked on billable tasks.
+    pub billable_secs: u64,
+    /// Seconds tracked on non-billable tasks.
+    pub non_billable_secs: u64,
+}
+
+impl BillableTotals {
+    /// Adds up the billable and non-billable time of `tasks`.
+    pub fn from_tasks(tasks: &[StoredTask]) -> BillableTotals {
+        let mut totals = BillableTotals::default();
+        for task in tasks {
+            if task.billable {
+                totals.billable_secs += task.seconds;
+            } else {
+                totals.non_billable_secs += task.seconds;
+            }
+        }
+        totals
+    }
+
+    /// Gets what the billable time is worth at `hourly_rate_cents`, rounded to the nearest cent.
+    pub fn billable_cents(&self, hourly_rate_cents: u64) -> u64 {
+        amount_cents(self.billable_secs, hourly_rate_cents)
+    }
+}
+
+/// Gets what `seconds` of work is worth at `hourly_rate_cents`, rounded to the nearest cent.
+pub fn amount_cents(seconds: u64, hourly_rate_cents: u64) -> u64 {
+    (seconds * hourly_rate_cents + 1800) / 3600
+}
+
+/// Formats an amount of money with two decimals and thousands separators, e.g. `$1,234.56`.
+///
+/// # Examples
+///
+/// ```
+/// assert_eq!(timetracker::reports::format_amount(123_456, "$"), "$1,234.56");
+/// ```
+pub fn format_amount(cents: u64, currency_symbol: &str) -> String {
+    let whole = (cents / 100).to_string();
+    let mut grouped = String::new();
+    for (i, digit) in whole.chars().enumerate() {
+        if i > 0 && (whole.len() - i).is_multiple_of(3) {
+            grouped.push(',');
+        }
+        grouped.push(digit);
+    }
+    format!("{}{}.{:02}", currency_symbol, grouped, cents % 100)
+}
+
+#[cfg(test)]
+mod tests_billable {
+    use super::*;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    fn task(seconds: u64, billable: bool) -> StoredTask {
+        let started_at = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let mut task = StoredTask::new(
+            "task",
+            started_at,
+            started_at + Duration::from_secs(seconds),
+        );
+        task.billable = billable;
+        task
+    }
+
+    #[test]
+    fn filters_by_billable() {
+        let tasks = [task(60, true), task(120, false), task(180, true)];
+        let count =
+            |filter: BillableFilter| tasks.iter().filter(|task| filter.matches(task)).count();
+        assert_eq!(count(BillableFilter::All), 3);
+        assert_eq!(count(BillableFilter::BillableOnly), 2);
+        assert_eq!(count(BillableFilter::NonBillableOnly), 1);
+
+        let totals = BillableTotals::from_tasks(&tasks);
+        assert_eq!(totals.billable_secs, 240);
+        assert_eq!(totals.non_billable_secs, 120);
+        assert_eq!(totals.billable_cents(9000), 600);
+    }
+
+    #[test]
+    fn formats_amounts() {
+        assert_eq!(format_amount(0, "$"), "$0.00");
+        assert_eq!(format_amount(99_999, "$"), "$999.99");
+        assert_eq!(format_amount(100_000_000, "£"), "£1,000,000.00");
+    }
+}
@@ -0,0 +1,218 @@
+//! The billing periods of clients, used to invoice each client for their current period.
+
+use chrono::{Datelike, Duration, Months, NaiveDate};
+use serde::Deserialize;
+use std::{fmt, ops::Range, str::FromStr};
+
+/// How often a client is billed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub enum BillingCycle {
+    /// Every month, on the day of the month of the anchor date.
+    Monthly,
+    /// Every other week, on the weekday of the anchor date.
+    BiWeekly,
+    /// Every week, on the weekday of the anchor date.
+    Weekly,
+    /// Every given number of days.
+    Custom(u32),
+}
+
+impl FromStr for BillingCycle {
+    type Err = String;
+
+    /// Parses `monthly`, `biweekly`, `weekly`, or a number of days like `10d`.
+    fn from_str(value: &str) -> Result<BillingCycle, String> {
+        let invalid = || {
+            format!(
+                "expected monthly, biweekly, weekly, or a number of days like 10d, got '{}'",
+                value
+            )
+        };
+        match value {
+            "monthly" => Ok(BillingCycle::Monthly),
+            "biweekly" => Ok(BillingCycle::BiWeekly),
+            "weekly" => Ok(BillingCycle::Weekly),
+            _ => match value.strip_suffix('d').map(str::parse) {
+                Some(Ok(days)) if days > 0 => Ok(BillingCycle::Custom(days)),
+                _ => Err(invalid()),
+            },
+        }
+    }
+}
+
+impl TryFrom<String> for BillingCycle {
+    type Error = String;
+
+    fn try_from(value: String) -> Result<BillingCycle, String> {
+        value.parse()
+    }
+}
+
+/// Formats the cycle the way it is written in `config.toml`.
+impl fmt::Display for BillingCycle {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BillingCycle::Monthly => write!(f, "monthly"),
+            BillingCycle::BiWeekly => write!(f, "biweekly"),
+            BillingCycle::Weekly => write!(f, "weekly"),
+            BillingCycle::Custom(days) => write!(f, "{}d", days),
+        }
+    }
+}
+
+/// When a client is billed: a cycle, and a date one of the periods starts on.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct BillingPeriod {
+    /// How often the client is billed.
+    pub cycle: BillingCycle,
+    /// The first day of any one billing period, e.g. the first one.
+    pub anchor_date: NaiveDate,
+}
+
+impl BillingPeriod {
+    /// Gets the days of the billing period `today` is in, as `[start, end)`.
+    ///
+    /// Periods repeat before the anchor date too, so any day is in a period. A monthly period
+    /// anchored on a day some months don't have (like the 31st) starts on the last day of those
+    /// months.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chrono::NaiveDate;
+    /// use timetracker::reports::{BillingCycle, BillingPeriod};
+    ///
+    /// let date = |month, day| NaiveDate::from_ymd_opt(2024, month, day).unwrap();
+    /// let period = BillingPeriod { cycle: BillingCycle::BiWeekly, anchor_date: date(1, 1) };
+    /// assert_eq!(period.current(date(1, 20)), date(1, 15)..date(1, 29));
+    /// ```
+    pub fn current(&self, today: NaiveDate) -> Range<NaiveDate> {
+        let days = match self.cycle {
+            BillingCycle::Monthly => return self.current_month(today),
+            BillingCycle::BiWeekly => 14,
+            BillingCycle::Weekly => 7,
+            BillingCycle::Custom(days) => i64::from(days.max(1)),
+        };
+        let offset = (today - self.anchor_date).num_days().div_euclid(days) * days;
+        let start = self.anchor_date + Duration::days(offset);
+        start..start + Duration::days(days)
+    }
+
+    /// Gets the monthly period `today` is in.
+    fn current_month(&self, today: NaiveDate) -> Range<NaiveDate> {
+        let anchor = self.anchor_date;
+        let months_between =
+            (today.year() - anchor.year()) * 12 + today.month() as i32 - anchor.month() as i32;
+        // the period starting in today's month may not have started yet
+        let mut months = months_between;
+        if self.month_start(months) > today {
+            months -= 1;
+        }
+        self.month_start(months)..self.month_start(months + 1)
+    }
+
+    /// Gets the start of the monthly period `months` months after the anchor date's.
+    fn month_start(&self, months: i32) -> NaiveDate {
+        let shifted = if months >= 0 {
+            self.anchor_date
+                .checked_add_months(Months::new(months as u32))
+        } else {
+            self.anchor_date
+                .checked_sub_months(Months::new(months.unsigned_abs()))
+        };
+        shifted.expect("billing periods stay within the supported dates")
+    }
+}
+
+/// Formats the period as it is shown by `config show`, e.g. `biweekly from 2024-01-01`.
+impl fmt::Display for BillingPeriod {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{} from {}", self.cycle, self.anchor_date)
+    }
+}
+
+#[cfg(test)]
+mod tests_billing {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    fn period(cycle: BillingCycle, anchor_date: NaiveDate) -> BillingPeriod {
+        BillingPeriod { cycle, anchor_date }
+    }
+
+    #[test]
+    fn monthly_periods_run_to_the_same_day_next_month() {
+        let first = period(BillingCycle::Monthly, date(2024, 1, 1));
+        assert_eq!(
+            first.current(date(2024, 2, 29)),
+            date(2024, 2, 1)..date(2024, 3, 1)
+        );
+        assert_eq!(
+            first.current(date(2023, 12, 1)),
+            date(2023, 12, 1)..date(2024, 1, 1)
+        );
+
+        let fifteenth = period(BillingCycle::Monthly, date(2024, 1, 15));
+        assert_eq!(
+            fifteenth.current(date(2024, 3, 14)),
+            date(2024, 2, 15)..date(2024, 3, 15)
+        );
+        assert_eq!(
+            fifteenth.current(date(2024, 3, 15)),
+            date(2024, 3, 15)..date(2024, 4, 15)
+        );
+
+        let last = period(BillingCycle::Monthly, date(2024, 1, 31));
+        assert_eq!(
+            last.current(date(2024, 3, 1)),
+            date(2024, 2, 29)..date(2024, 3, 31)
+        );
+    }
+
+    #[test]
+    fn biweekly_periods_alternate_from_the_anchor() {
+        let biweekly = period(BillingCycle::BiWeekly, date(2024, 1, 1));
+        assert_eq!(
+            biweekly.current(date(2024, 1, 14)),
+            date(2024, 1, 1)..date(2024, 1, 15)
+        );
+        assert_eq!(
+            biweekly.current(date(2024, 1, 15)),
+            date(2024, 1, 15)..date(2024, 1, 29)
+        );
+        assert_eq!(
+            biweekly.current(date(2023, 12, 31)),
+            date(2023, 12, 18)..date(2024, 1, 1)
+        );
+    }
+
+    #[test]
+    fn weekly_periods_start_on_the_anchor_weekday() {
+        let weekly = period(BillingCycle::Weekly, date(2024, 1, 3));
+        assert_eq!(
+            weekly.current(date(2024, 1, 9)),
+            date(2024, 1, 3)..date(2024, 1, 10)
+        );
+        assert_eq!(
+            weekly.current(date(2024, 1, 10)),
+            date(2024, 1, 10)..date(2024, 1, 17)
+        );
+    }
+
+    #[test]
+    fn custom_periods_have_the_given_length() {
+        let ten_days = period(BillingCycle::Custom(10), date(2024, 1, 1));
+        assert_eq!(
+            ten_days.current(date(2024, 1, 25)),
+            date(2024, 1, 21)..date(2024, 1, 31)
+        );
+        assert_eq!("10d".parse(), Ok(BillingCycle::Custom(10)));
+        assert!("0d".parse::<BillingCycle>().is_err());
+        assert!("fortnightly".parse::<BillingCycle>().is_err());
+    }
+}
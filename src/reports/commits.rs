@@ -0,0 +1,199 @@
+//! The time spent on each git commit, for tasks named after one (e.g. `abc1234 fix login bug`).
+//!
+//! Only built with the experimental `git-integration` feature.
+
+use crate::{get_clock_format, storage::StoredTask};
+use std::{
+    collections::HashMap,
+    fmt,
+    path::PathBuf,
+    process::{Command, Stdio},
+};
+
+/// The shortest run of hex digits in a task name that is taken for a commit hash, the length
+/// git abbreviates hashes to by default.
+pub const MIN_HASH_LEN: usize = 7;
+
+/// Finds the words of a task name that could be commit hashes: runs of at least
+/// [`MIN_HASH_LEN`] hex digits that aren't part of a longer word.
+///
+/// # Examples
+///
+/// ```
+/// use timetracker::reports::commits::commit_hashes;
+///
+/// assert_eq!(commit_hashes("abc1234 fix login bug"), ["abc1234"]);
+/// assert!(commit_hashes("review abc12 and xyzabc1234").is_empty());
+/// ```
+pub fn commit_hashes(name: &str) -> Vec<&str> {
+    name.split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| word.len() >= MIN_HASH_LEN && word.chars().all(|c| c.is_ascii_hexdigit()))
+        .collect()
+}
+
+/// Looks commits up in a git repository by running `git log`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GitCommitResolver {
+    /// The repository to look commits up in.
+    pub repo_path: PathBuf,
+}
+
+impl GitCommitResolver {
+    /// Gets the commit `hash_prefix` names as `git log --oneline` shows it, e.g.
+    /// `abc1234 Fix login bug`.
+    ///
+    /// Returns `None` if git isn't installed, `repo_path` isn't a repository, or the prefix
+    /// doesn't name exactly one commit in it.
+    pub fn resolve(&self, hash_prefix: &str) -> Option<String> {
+        let output = Command::new("git")
+            .arg("-C")
+            .arg(&self.repo_path)
+            .args(["log", "--oneline", "-1", hash_prefix, "--"])
+            .stderr(Stdio::null())
+            .output()
+            .ok()
+            .filter(|output| output.status.success())?;
+        let stdout = String::from_utf8(output.stdout).ok()?;
+        let line = stdout.lines().next()?.trim();
+        (!line.is_empty()).then(|| line.to_string())
+    }
+}
+
+/// The time spent on each commit that tasks are named after.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CommitReport {
+    /// Each commit as `git log --oneline` shows it, with the seconds tracked on it, most time
+    /// first.
+    pub commits: Vec<(String, u64)>,
+    /// The seconds tracked on tasks that don't name a commit.
+    pub unlinked_secs: u64,
+}
+
+impl CommitReport {
+    /// Adds up the time of each task under the first commit its name has a hash of, looking
+    /// hashes up with `resolve` (which is called once per hash).
+    ///
+    /// Tasks naming the same commit by different prefixes are counted together, as long as
+    /// `resolve` describes the commit the same way for each.
+    pub fn from_tasks(
+        tasks: &[StoredTask],
+        mut resolve: impl FnMut(&str) -> Option<String>,
+    ) -> CommitReport {
+        let mut resolved: HashMap<String, Option<String>> = HashMap::new();
+        let mut totals: HashMap<String, u64> = HashMap::new();
+        let mut unlinked_secs = 0;
+        for task in tasks {
+            let commit = commit_hashes(&task.name).into_iter().find_map(|hash| {
+                let hash = hash.to_ascii_lowercase();
+                resolved
+                    .entry(hash)
+                    .or_insert_with_key(|hash| resolve(hash))
+                    .clone()
+            });
+            match commit {
+                Some(commit) => *totals.entry(commit).or_default() += task.seconds,
+                None => unlinked_secs += task.seconds,
+            }
+        }
+        let mut commits: Vec<(String, u64)> = totals.into_iter().collect();
+        commits.sort_by(|(a, a_secs), (b, b_secs)| b_secs.cmp(a_secs).then_with(|| a.cmp(b)));
+        CommitReport {
+            commits,
+            unlinked_secs,
+        }
+    }
+}
+
+/// Formats a row per commit, e.g. `abc1234 Fix login bug  01:30:00`, then an `Unlinked` row
+/// for the tasks that don't name a commit, with the times lined up.
+impl fmt::Display for CommitReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut rows: Vec<(&str, u64)> = self
+            .commits
+            .iter()
+            .map(|(commit, secs)| (commit.as_str(), *secs))
+            .collect();
+        if self.unlinked_secs > 0 || rows.is_empty() {
+            rows.push(("Unlinked", self.unlinked_secs));
+        }
+        let width = rows
+            .iter()
+            .map(|(label, _)| label.chars().count())
+            .max()
+            .unwrap_or_default();
+        let lines: Vec<String> = rows
+            .iter()
+            .map(|(label, secs)| {
+                let padding = width - label.chars().count();
+                format!(
+                    "{}{}  {}",
+                    label,
+                    " ".repeat(padding),
+                    get_clock_format(*secs)
+                )
+            })
+            .collect();
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+#[cfg(test)]
+mod tests_commits {
+    use super::*;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    fn task(name: &str, seconds: u64) -> StoredTask {
+        StoredTask::new(name, UNIX_EPOCH, UNIX_EPOCH + Duration::from_secs(seconds))
+    }
+
+    fn resolve(hash: &str) -> Option<String> {
+        match hash {
+            "abc1234" | "abc1234def" => Some(String::from("abc1234 Fix login bug")),
+            "0123456" => Some(String::from("0123456 Add tests")),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn finds_hex_words_of_seven_or_more_digits() {
+        assert_eq!(
+            commit_hashes("ABC1234, then fix-0123456789abcdef"),
+            ["ABC1234", "0123456789abcdef"]
+        );
+        assert!(commit_hashes("deadbe feature/login").is_empty());
+    }
+
+    #[test]
+    fn groups_time_by_commit() {
+        let tasks = [
+            task("abc1234 fix login bug", 3600),
+            task("review abc1234def", 1800),
+            task("defaced 0123456 tests", 1200),
+            task("standup", 900),
+            task("fffffff not a commit", 600),
+        ];
+        let mut lookups = Vec::new();
+        let report = CommitReport::from_tasks(&tasks, |hash| {
+            lookups.push(hash.to_string());
+            resolve(hash)
+        });
+        assert_eq!(
+            report.commits,
+            [
+                (String::from("abc1234 Fix login bug"), 5400),
+                (String::from("0123456 Add tests"), 1200),
+            ]
+        );
+        assert_eq!(report.unlinked_secs, 1500);
+        assert_eq!(
+            lookups,
+            ["abc1234", "abc1234def", "defaced", "0123456", "fffffff"]
+        );
+        assert_eq!(
+            report.to_string(),
+            "abc1234 Fix login bug  01:30:00\n\
+             0123456 Add tests      00:20:00\n\
+             Unlinked               00:25:00"
+        );
+    }
+}
@@ -0,0 +1,187 @@
+//! How the time spent on each task changed from one period to another.
+
+use crate::storage::StoredTask;
+use std::{collections::BTreeMap, fmt};
+
+/// The time spent on a task in each of the two periods.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ComparisonRow {
+    /// The name of the task.
+    pub name: String,
+    /// The seconds tracked in the first period, or `None` if the task wasn't worked on.
+    pub secs_a: Option<u64>,
+    /// The seconds tracked in the second period, or `None` if the task wasn't worked on.
+    pub secs_b: Option<u64>,
+}
+
+impl ComparisonRow {
+    /// Gets how many more seconds were tracked in the second period than in the first.
+    pub fn delta_secs(&self) -> i64 {
+        self.secs_b.unwrap_or(0) as i64 - self.secs_a.unwrap_or(0) as i64
+    }
+
+    /// Gets the name with `+` in front if the task is new in the second period, or `-` if it
+    /// is missing from it.
+    fn label(&self) -> String {
+        match (self.secs_a, self.secs_b) {
+            (None, Some(_)) => format!("+ {}", self.name),
+            (Some(_), None) => format!("- {}", self.name),
+            _ => self.name.clone(),
+        }
+    }
+}
+
+/// The time spent on each task in two periods, side by side.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ComparisonReport {
+    /// The first period, as it was given on the command line.
+    pub period1: String,
+    /// The second period, as it was given on the command line.
+    pub period2: String,
+    /// A row per task worked on in either period, most time first.
+    pub rows: Vec<ComparisonRow>,
+}
+
+impl ComparisonReport {
+    /// Adds up the time spent on each task in `tasks_a`, from the first period, and `tasks_b`,
+    /// from the second.
+    pub fn from_tasks(
+        period1: &str,
+        tasks_a: &[StoredTask],
+        period2: &str,
+        tasks_b: &[StoredTask],
+    ) -> ComparisonReport {
+        let mut totals: BTreeMap<&str, (Option<u64>, Option<u64>)> = BTreeMap::new();
+        for task in tasks_a {
+            let secs = &mut totals.entry(&task.name).or_default().0;
+            *secs = Some(secs.unwrap_or(0) + task.seconds);
+        }
+        for task in tasks_b {
+            let secs = &mut totals.entry(&task.name).or_default().1;
+            *secs = Some(secs.unwrap_or(0) + task.seconds);
+        }
+        let mut rows: Vec<ComparisonRow> = totals
+            .into_iter()
+            .map(|(name, (secs_a, secs_b))| ComparisonRow {
+                name: name.to_string(),
+                secs_a,
+                secs_b,
+            })
+            .collect();
+        // the sort is stable, so tasks with the same time stay in alphabetical order
+        rows.sort_by_key(|row| std::cmp::Reverse(row.secs_a.max(row.secs_b)));
+        ComparisonReport {
+            period1: period1.to_string(),
+            period2: period2.to_string(),
+            rows,
+        }
+    }
+
+    /// Draws a table of the time in each period and the change, e.g. `write docs  1:00  1:30
+    /// +0:30`. With `color`, increases are green and decreases red.
+    pub fn render(&self, color: bool) -> String {
+        let labels: Vec<String> = self.rows.iter().map(ComparisonRow::label).collect();
+        let name_width = labels
+            .iter()
+            .map(|label| label.chars().count())
+            .chain([4])
+            .max()
+            .unwrap_or(0);
+        let width_a = self.period1.len().max(5);
+        let width_b = self.period2.len().max(5);
+
+        let mut lines = vec![format!(
+            "{:<name_width$}  {:>width_a$}  {:>width_b$}  {:>6}",
+            "Task", self.period1, self.period2, "Change"
+        )];
+        for (row, label) in self.rows.iter().zip(&labels) {
+            let delta = row.delta_secs();
+            let change = format!("{:>6}", format_delta(delta));
+            let change = match (color, delta.signum()) {
+                (true, 1) => format!("\x1b[32m{}\x1b[0m", change),
+                (true, -1) => format!("\x1b[31m{}\x1b[0m", change),
+                _ => change,
+            };
+            let padding = name_width - label.chars().count();
+            lines.push(format!(
+                "{}{}  {:>width_a$}  {:>width_b$}  {}",
+                label,
+                " ".repeat(padding),
+                row.secs_a.map_or_else(|| String::from("-"), format_hours),
+                row.secs_b.map_or_else(|| String::from("-"), format_hours),
+                change
+            ));
+        }
+        lines.join("\n")
+    }
+}
+
+/// Draws the table without color.
+impl fmt::Display for ComparisonReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.render(false))
+    }
+}
+
+/// Formats seconds as `H:MM`, rounded to the minute.
+fn format_hours(secs: u64) -> String {
+    let minutes = (secs + 30) / 60;
+    format!("{}:{:02}", minutes / 60, minutes % 60)
+}
+
+/// Formats a change in seconds as `+H:MM` or `-H:MM`, rounded to the minute, or `0:00` if it
+/// rounds to nothing.
+fn format_delta(secs: i64) -> String {
+    let hours = format_hours(secs.unsigned_abs());
+    match secs.signum() {
+        _ if hours == "0:00" => hours,
+        -1 => format!("-{}", hours),
+        _ => format!("+{}", hours),
+    }
+}
+
+#[cfg(test)]
+mod tests_compare {
+    use super::*;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    fn task(name: &str, seconds: u64) -> StoredTask {
+        StoredTask::new(name, UNIX_EPOCH, UNIX_EPOCH + Duration::from_secs(seconds))
+    }
+
+    #[test]
+    fn computes_deltas_between_periods() {
+        let report = ComparisonReport::from_tasks(
+            "2024-W01",
+            &[task("docs", 1800), task("docs", 1800), task("old", 4500)],
+            "2024-W02",
+            &[task("docs", 5400), task("new", 900)],
+        );
+        let deltas: Vec<(&str, i64)> = report
+            .rows
+            .iter()
+            .map(|row| (row.name.as_str(), row.delta_secs()))
+            .collect();
+        assert_eq!(deltas, [("docs", 1800), ("old", -4500), ("new", 900)]);
+        assert_eq!(format_delta(-4500), "-1:15");
+        assert_eq!(format_delta(29), "0:00");
+    }
+
+    #[test]
+    fn marks_new_and_missing_tasks() {
+        let report = ComparisonReport::from_tasks(
+            "2024-W01",
+            &[task("docs", 3600), task("old", 4500)],
+            "2024-W02",
+            &[task("docs", 5400), task("new", 900)],
+        );
+        assert_eq!(
+            report.to_string(),
+            "Task   2024-W01  2024-W02  Change\n\
+             docs       1:00      1:30   +0:30\n\
+             - old      1:15         -   -1:15\n\
+             + new         -      0:15   +0:15"
+        );
+        assert!(report.render(true).contains("\x1b[31m -1:15\x1b[0m"));
+    }
+}
@@ -0,0 +1,223 @@
+//! How much the duration of each task name varies from one time to the next.
+
+use crate::{format_duration_compact, storage::StoredTask};
+use std::{collections::HashMap, fmt};
+
+/// The fewest times a task name has to be tracked to be in the report.
+pub const MIN_CONSISTENCY_COUNT: u32 = 3;
+
+/// The highest coefficient of variation of a routine task. Tasks above it are variable.
+pub const ROUTINE_CV: f64 = 0.25;
+
+/// How consistent the durations of one task name are.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConsistencyEntry {
+    /// The name of the task.
+    pub name: String,
+    /// The mean of its durations, in seconds.
+    pub mean_secs: f64,
+    /// The (population) standard deviation of its durations, in seconds.
+    pub stddev_secs: f64,
+    /// The coefficient of variation, `stddev_secs / mean_secs`, or 0 if the mean is 0.
+    pub cv: f64,
+    /// How many times it was tracked.
+    pub task_count: u32,
+}
+
+impl ConsistencyEntry {
+    /// Whether the task takes about the same time every time.
+    pub fn is_routine(&self) -> bool {
+        self.cv <= ROUTINE_CV
+    }
+}
+
+/// The task names tracked at least [`MIN_CONSISTENCY_COUNT`] times, the most variable first.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ConsistencyReport {
+    /// The task names, by coefficient of variation from highest to lowest, then by name.
+    pub entries: Vec<ConsistencyEntry>,
+}
+
+impl ConsistencyReport {
+    /// Works out the mean and spread of the durations of each task name. Names tracked fewer
+    /// than [`MIN_CONSISTENCY_COUNT`] times are left out, as too few to say how they vary.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::{Duration, UNIX_EPOCH};
+    /// use timetracker::{reports::ConsistencyReport, storage::StoredTask};
+    ///
+    /// let task = |name: &str, minutes: u64| {
+    ///     StoredTask::new(name, UNIX_EPOCH, UNIX_EPOCH + Duration::from_secs(minutes * 60))
+    /// };
+    /// let report = ConsistencyReport::from_tasks(&[
+    ///     task("standup", 15),
+    ///     task("standup", 15),
+    ///     task("standup", 15),
+    ///     task("taxes", 120),
+    /// ]);
+    /// assert_eq!(report.entries.len(), 1);
+    /// assert_eq!(report.entries[0].mean_secs, 900.0);
+    /// assert_eq!(report.entries[0].cv, 0.0);
+    /// ```
+    pub fn from_tasks(tasks: &[StoredTask]) -> ConsistencyReport {
+        let mut durations: HashMap<&str, Vec<f64>> = HashMap::new();
+        for task in tasks {
+            durations
+                .entry(&task.name)
+                .or_default()
+                .push(task.seconds as f64);
+        }
+        let mut entries: Vec<ConsistencyEntry> = durations
+            .into_iter()
+            .filter(|(_, seconds)| seconds.len() >= MIN_CONSISTENCY_COUNT as usize)
+            .map(|(name, seconds)| {
+                let count = seconds.len() as f64;
+                let mean_secs = seconds.iter().sum::<f64>() / count;
+                let variance = seconds
+                    .iter()
+                    .map(|seconds| (seconds - mean_secs).powi(2))
+                    .sum::<f64>()
+                    / count;
+                let stddev_secs = variance.sqrt();
+                ConsistencyEntry {
+                    name: name.to_string(),
+                    mean_secs,
+                    stddev_secs,
+                    cv: if mean_secs > 0.0 {
+                        stddev_secs / mean_secs
+                    } else {
+                        0.0
+                    },
+                    task_count: seconds.len() as u32,
+                }
+            })
+            .collect();
+        entries.sort_by(|a, b| b.cv.total_cmp(&a.cv).then_with(|| a.name.cmp(&b.name)));
+        ConsistencyReport { entries }
+    }
+}
+
+/// Formats a line per task name with its mean, standard deviation, coefficient of variation,
+/// and whether it is routine or variable, in lined-up columns, e.g.
+///
+/// ```text
+/// code review  mean 45m  stddev 30m  CV 0.67  variable  (12 times)
+/// standup      mean 15m  stddev 1m   CV 0.07  routine   (40 times)
+/// ```
+impl fmt::Display for ConsistencyReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let rows: Vec<[String; 3]> = self
+            .entries
+            .iter()
+            .map(|entry| {
+                [
+                    entry.name.clone(),
+                    format_duration_compact(entry.mean_secs.round() as u64),
+                    format_duration_compact(entry.stddev_secs.round() as u64),
+                ]
+            })
+            .collect();
+        let width = |column: usize| {
+            rows.iter()
+                .map(|row| row[column].chars().count())
+                .max()
+                .unwrap_or_default()
+        };
+        let (name_width, mean_width, stddev_width) = (width(0), width(1), width(2));
+        for (index, (row, entry)) in rows.iter().zip(&self.entries).enumerate() {
+            if index > 0 {
+                writeln!(f)?;
+            }
+            write!(
+                f,
+                "{:<name_width$}  mean {:<mean_width$}  stddev {:<stddev_width$}  CV {:.2}  {:<8}  \
+                 ({} times)",
+                row[0],
+                row[1],
+                row[2],
+                entry.cv,
+                if entry.is_routine() {
+                    "routine"
+                } else {
+                    "variable"
+                },
+                entry.task_count
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests_consistency {
+    use super::*;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    const TOLERANCE: f64 = 1e-6;
+
+    fn task(name: &str, seconds: u64) -> StoredTask {
+        let start = UNIX_EPOCH + Duration::from_secs(1_705_320_000);
+        StoredTask::new(name, start, start + Duration::from_secs(seconds))
+    }
+
+    fn report() -> ConsistencyReport {
+        let mut tasks = Vec::new();
+        // a mean of 5 minutes with a population standard deviation of 2
+        for minutes in [2, 4, 4, 4, 5, 5, 7, 9] {
+            tasks.push(task("textbook", minutes * 60));
+        }
+        for seconds in [900, 900, 900] {
+            tasks.push(task("standup", seconds));
+        }
+        // mean 2000, standard deviation sqrt(2000000 / 3)
+        for seconds in [1000, 2000, 3000] {
+            tasks.push(task("code review", seconds));
+        }
+        // too few to tell
+        tasks.push(task("taxes", 7200));
+        tasks.push(task("taxes", 60));
+        ConsistencyReport::from_tasks(&tasks)
+    }
+
+    #[test]
+    fn works_out_the_standard_deviation_of_each_name() {
+        let report = report();
+        let names: Vec<&str> = report
+            .entries
+            .iter()
+            .map(|entry| entry.name.as_str())
+            .collect();
+        assert_eq!(names, ["code review", "textbook", "standup"]);
+
+        let review = &report.entries[0];
+        assert_eq!(review.task_count, 3);
+        assert!((review.mean_secs - 2000.0).abs() < TOLERANCE);
+        assert!((review.stddev_secs - 816.496_580_927_726).abs() < TOLERANCE);
+        assert!((review.cv - 0.408_248_290_463_863).abs() < TOLERANCE);
+
+        let textbook = &report.entries[1];
+        assert!((textbook.mean_secs - 300.0).abs() < TOLERANCE);
+        assert!((textbook.stddev_secs - 120.0).abs() < TOLERANCE);
+        assert!((textbook.cv - 0.4).abs() < TOLERANCE);
+
+        let standup = &report.entries[2];
+        assert!(standup.stddev_secs.abs() < TOLERANCE);
+        assert!(standup.is_routine());
+        assert!(!textbook.is_routine());
+    }
+
+    #[test]
+    fn lines_up_the_columns() {
+        let lines: Vec<String> = report().to_string().lines().map(String::from).collect();
+        assert_eq!(
+            lines,
+            [
+                "code review  mean 33m20s  stddev 13m36s  CV 0.41  variable  (3 times)",
+                "textbook     mean 5m      stddev 2m      CV 0.40  variable  (8 times)",
+                "standup      mean 15m     stddev 0s      CV 0.00  routine   (3 times)",
+            ]
+        );
+    }
+}
@@ -0,0 +1,188 @@
+//! How often tags show up on the same task.
+
+use crate::storage::StoredTask;
+use std::{
+    collections::{BTreeSet, HashMap},
+    fmt,
+};
+
+/// How many tasks share each pair of tags.
+///
+/// Only pairs that share at least one task are stored, under the key with the tag that comes
+/// first alphabetically on the left. Use [`CoOccurrenceMatrix::count`] to look a pair up in
+/// either order.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct CoOccurrenceMatrix {
+    /// The tags that share a task with at least one other tag, sorted alphabetically.
+    pub tags: Vec<String>,
+    /// The number of tasks with both tags, for each pair that has any.
+    pub counts: HashMap<(String, String), usize>,
+}
+
+impl CoOccurrenceMatrix {
+    /// Counts the tasks that have each pair of tags. Tasks with fewer than two tags are skipped,
+    /// and a tag repeated on a task counts once.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::UNIX_EPOCH;
+    /// use timetracker::{reports::CoOccurrenceMatrix, storage::StoredTask};
+    ///
+    /// let task = |tags: &[&str]| {
+    ///     let mut task = StoredTask::new("task", UNIX_EPOCH, UNIX_EPOCH);
+    ///     task.tags = tags.iter().map(|tag| tag.to_string()).collect();
+    ///     task
+    /// };
+    /// let matrix = CoOccurrenceMatrix::from_tasks(&[
+    ///     task(&["design", "frontend"]),
+    ///     task(&["frontend", "design", "review"]),
+    ///     task(&["review"]),
+    /// ]);
+    /// assert_eq!(matrix.count("frontend", "design"), 2);
+    /// assert_eq!(matrix.count("design", "review"), 1);
+    /// ```
+    pub fn from_tasks(tasks: &[StoredTask]) -> CoOccurrenceMatrix {
+        let mut tags = BTreeSet::new();
+        let mut counts = HashMap::new();
+        for task in tasks {
+            let task_tags: Vec<&String> = task
+                .tags
+                .iter()
+                .collect::<BTreeSet<_>>()
+                .into_iter()
+                .collect();
+            if task_tags.len() < 2 {
+                continue;
+            }
+            for (i, first) in task_tags.iter().enumerate() {
+                for second in &task_tags[i + 1..] {
+                    *counts
+                        .entry(((*first).clone(), (*second).clone()))
+                        .or_default() += 1;
+                }
+                tags.insert((*first).clone());
+            }
+        }
+        CoOccurrenceMatrix {
+            tags: tags.into_iter().collect(),
+            counts,
+        }
+    }
+
+    /// The number of tasks with both `a` and `b`, in either order.
+    pub fn count(&self, a: &str, b: &str) -> usize {
+        let key = if a <= b { (a, b) } else { (b, a) };
+        self.counts
+            .get(&(key.0.to_string(), key.1.to_string()))
+            .copied()
+            .unwrap_or_default()
+    }
+
+    /// Whether no two tags share a task.
+    pub fn is_empty(&self) -> bool {
+        self.counts.is_empty()
+    }
+}
+
+/// Formats the matrix as a table with the tags down the side and across the top, leaving the
+/// cells of pairs that never share a task (and the diagonal) blank, e.g.
+///
+/// ```text
+///           design  frontend  review
+/// design                   2       1
+/// frontend       2                 1
+/// review         1         1
+/// ```
+impl fmt::Display for CoOccurrenceMatrix {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let label_width = self
+            .tags
+            .iter()
+            .map(|tag| tag.chars().count())
+            .max()
+            .unwrap_or_default();
+        write!(f, "{:label_width$}", "")?;
+        for tag in &self.tags {
+            write!(f, "  {}", tag)?;
+        }
+        for row in &self.tags {
+            writeln!(f)?;
+            let mut line = format!("{:label_width$}", row);
+            for column in &self.tags {
+                let count = self.count(row, column);
+                let cell = if row == column || count == 0 {
+                    String::new()
+                } else {
+                    count.to_string()
+                };
+                line.push_str(&format!(
+                    "  {:>width$}",
+                    cell,
+                    width = column.chars().count()
+                ));
+            }
+            write!(f, "{}", line.trim_end())?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests_cooccurrence {
+    use super::*;
+    use std::time::UNIX_EPOCH;
+
+    fn task(tags: &[&str]) -> StoredTask {
+        let mut task = StoredTask::new("task", UNIX_EPOCH, UNIX_EPOCH);
+        task.tags = tags.iter().map(|tag| tag.to_string()).collect();
+        task
+    }
+
+    fn matrix() -> CoOccurrenceMatrix {
+        CoOccurrenceMatrix::from_tasks(&[
+            task(&["design", "frontend"]),
+            task(&["frontend", "design", "review"]),
+            task(&["backend", "review"]),
+            task(&["frontend", "review", "frontend"]),
+            task(&["docs"]),
+            task(&[]),
+        ])
+    }
+
+    #[test]
+    fn counts_each_pair_of_tags() {
+        let matrix = matrix();
+        assert_eq!(matrix.count("design", "frontend"), 2);
+        assert_eq!(matrix.count("frontend", "design"), 2);
+        assert_eq!(matrix.count("frontend", "review"), 2);
+        assert_eq!(matrix.count("design", "review"), 1);
+        assert_eq!(matrix.count("backend", "review"), 1);
+        assert_eq!(matrix.count("backend", "design"), 0);
+        assert_eq!(matrix.counts.len(), 4);
+    }
+
+    #[test]
+    fn skips_tasks_with_fewer_than_two_tags() {
+        let matrix = matrix();
+        assert_eq!(matrix.tags, ["backend", "design", "frontend", "review"]);
+        assert!(
+            CoOccurrenceMatrix::from_tasks(&[task(&["docs"]), task(&["docs", "docs"])]).is_empty()
+        );
+    }
+
+    #[test]
+    fn formats_a_table() {
+        let lines: Vec<String> = matrix().to_string().lines().map(String::from).collect();
+        assert_eq!(
+            lines,
+            [
+                "          backend  design  frontend  review",
+                "backend                                   1",
+                "design                            2       1",
+                "frontend                2                 2",
+                "review          1       1         2",
+            ]
+        );
+    }
+}
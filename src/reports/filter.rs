@@ -0,0 +1,313 @@
+//! The per-task filters every report is run through, and queries built from them for library users.
+
+use super::DateRange;
+use crate::storage::StoredTask;
+use std::time::SystemTime;
+
+/// Which tasks reports include, combining every filter that looks at one task at a time.
+///
+/// Each field left at its default keeps every task, so new filters can be added here without
+/// changing the reports.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TaskFilter {
+    /// When the tasks started.
+    pub range: DateRange,
+    /// The name the tasks have, if only one is included.
+    pub name: Option<String>,
+    /// A tag the tasks have.
+    pub tag: Option<String>,
+    /// The client the tasks are for.
+    pub client: Option<String>,
+    /// The shortest a task can be, in seconds.
+    pub min_duration_secs: Option<u64>,
+    /// The longest a task can be, in seconds.
+    pub max_duration_secs: Option<u64>,
+}
+
+impl TaskFilter {
+    /// Whether the filter keeps `task`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::{Duration, UNIX_EPOCH};
+    /// use timetracker::{reports::TaskFilter, storage::StoredTask};
+    ///
+    /// let task = |secs| StoredTask::new("task", UNIX_EPOCH, UNIX_EPOCH + Duration::from_secs(secs));
+    /// let filter = TaskFilter { min_duration_secs: Some(60), ..TaskFilter::default() };
+    /// assert!(!filter.matches(&task(59)));
+    /// assert!(filter.matches(&task(60)));
+    /// ```
+    pub fn matches(&self, task: &StoredTask) -> bool {
+        self.range.contains(task)
+            && self.name.as_ref().is_none_or(|name| &task.name == name)
+            && self.tag.as_ref().is_none_or(|tag| task.tags.contains(tag))
+            && self
+                .client
+                .as_ref()
+                .is_none_or(|client| task.client.as_ref() == Some(client))
+            && self.min_duration_secs.is_none_or(|min| task.seconds >= min)
+            && self.max_duration_secs.is_none_or(|max| task.seconds <= max)
+    }
+}
+
+/// Builds a [`TaskQueryFilter`] a condition at a time, for programs that use the library to
+/// pick tasks out of a history.
+///
+/// Every condition is optional and they all have to hold. The builder only holds a few strings,
+/// so it is cheap to clone and extend into several queries.
+///
+/// # Examples
+///
+/// Billable tasks for a client in January 2024:
+///
+/// ```
+/// use std::time::{Duration, UNIX_EPOCH};
+/// use timetracker::{reports::TaskQuery, storage::StoredTask};
+///
+/// let january = UNIX_EPOCH + Duration::from_secs(1_704_067_200);
+/// let february = UNIX_EPOCH + Duration::from_secs(1_706_745_600);
+/// let task = |name: &str, client: &str, billable: bool| {
+///     let start = january + Duration::from_secs(86_400);
+///     let mut task = StoredTask::new(name, start, start + Duration::from_secs(3600));
+///     task.client = Some(client.to_string());
+///     task.billable = billable;
+///     task
+/// };
+/// let tasks = [
+///     task("design", "ACME", true),
+///     task("internal call", "ACME", false),
+///     task("design", "Globex", true),
+/// ];
+///
+/// let filter = TaskQuery::new()
+///     .client("ACME")
+///     .billable_only(true)
+///     .since(january)
+///     .until(february)
+///     .build();
+/// let names: Vec<&str> = tasks
+///     .iter()
+///     .filter(|task| filter.matches(task))
+///     .map(|task| task.name.as_str())
+///     .collect();
+/// assert_eq!(names, ["design"]);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TaskQuery {
+    filter: TaskQueryFilter,
+}
+
+impl TaskQuery {
+    /// Starts a query that matches every task.
+    pub fn new() -> TaskQuery {
+        TaskQuery::default()
+    }
+
+    /// Only matches tasks whose name contains `q`, ignoring case.
+    pub fn name_contains(mut self, q: &str) -> TaskQuery {
+        self.filter.name_contains = Some(q.to_lowercase());
+        self
+    }
+
+    /// Only matches tasks with the tag `t`.
+    pub fn tag(mut self, t: &str) -> TaskQuery {
+        self.filter.task_filter.tag = Some(t.to_string());
+        self
+    }
+
+    /// Only matches tasks for the client `c`.
+    pub fn client(mut self, c: &str) -> TaskQuery {
+        self.filter.task_filter.client = Some(c.to_string());
+        self
+    }
+
+    /// Only matches tasks started at or after `dt`.
+    pub fn since(mut self, dt: SystemTime) -> TaskQuery {
+        self.filter.task_filter.range.start = Some(dt);
+        self
+    }
+
+    /// Only matches tasks started before `dt`.
+    pub fn until(mut self, dt: SystemTime) -> TaskQuery {
+        self.filter.task_filter.range.end = Some(dt);
+        self
+    }
+
+    /// Only matches tasks that took at least `n` seconds.
+    pub fn min_duration_secs(mut self, n: u64) -> TaskQuery {
+        self.filter.task_filter.min_duration_secs = Some(n);
+        self
+    }
+
+    /// Only matches tasks that took at most `n` seconds.
+    pub fn max_duration_secs(mut self, n: u64) -> TaskQuery {
+        self.filter.task_filter.max_duration_secs = Some(n);
+        self
+    }
+
+    /// With `true`, only matches billable tasks.
+    pub fn billable_only(mut self, b: bool) -> TaskQuery {
+        self.filter.billable_only = b;
+        self
+    }
+
+    /// With `true`, only matches tasks that are still running.
+    pub fn running_only(mut self, b: bool) -> TaskQuery {
+        self.filter.running_only = b;
+        self
+    }
+
+    /// Finishes the query.
+    pub fn build(self) -> TaskQueryFilter {
+        self.filter
+    }
+}
+
+/// The conditions of a [`TaskQuery`].
+///
+/// Stable Rust doesn't let types implement the `Fn` traits, so pass it to `Iterator::filter`
+/// through [`matches`](TaskQueryFilter::matches), or turn it into a closure with
+/// [`into_fn`](TaskQueryFilter::into_fn).
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TaskQueryFilter {
+    /// The conditions reports share: when tasks started, how long they took, and their tag and
+    /// client.
+    pub task_filter: TaskFilter,
+    /// Text the name contains, in lower case.
+    pub name_contains: Option<String>,
+    /// Whether only billable tasks match.
+    pub billable_only: bool,
+    /// Whether only running tasks match.
+    pub running_only: bool,
+}
+
+impl TaskQueryFilter {
+    /// Whether `task` meets every condition.
+    pub fn matches(&self, task: &StoredTask) -> bool {
+        self.task_filter.matches(task)
+            && self
+                .name_contains
+                .as_ref()
+                .is_none_or(|q| task.name.to_lowercase().contains(q))
+            && (!self.billable_only || task.billable)
+            && (!self.running_only || task.stopped_at.is_none())
+    }
+
+    /// Turns the filter into a closure, e.g. for `Vec::retain`.
+    pub fn into_fn(self) -> impl Fn(&StoredTask) -> bool {
+        move |task| self.matches(task)
+    }
+}
+
+#[cfg(test)]
+mod tests_filter {
+    use super::*;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    fn task(name: &str, start: u64, secs: u64) -> StoredTask {
+        let started_at = UNIX_EPOCH + Duration::from_secs(start);
+        StoredTask::new(name, started_at, started_at + Duration::from_secs(secs))
+    }
+
+    #[test]
+    fn combines_every_predicate() {
+        let filter = TaskFilter {
+            range: DateRange {
+                start: Some(UNIX_EPOCH + Duration::from_secs(1000)),
+                end: None,
+            },
+            name: Some(String::from("code")),
+            tag: None,
+            client: None,
+            min_duration_secs: Some(60),
+            max_duration_secs: Some(3600),
+        };
+        assert!(filter.matches(&task("code", 1000, 60)));
+        assert!(filter.matches(&task("code", 1000, 3600)));
+        assert!(!filter.matches(&task("code", 1000, 59)));
+        assert!(!filter.matches(&task("code", 1000, 3601)));
+        assert!(!filter.matches(&task("code", 999, 600)));
+        assert!(!filter.matches(&task("review", 1000, 600)));
+        assert!(TaskFilter::default().matches(&task("anything", 0, 0)));
+
+        let mut tagged = task("code", 1000, 600);
+        tagged.tags = vec![String::from("deep-work")];
+        tagged.client = Some(String::from("ACME"));
+        let filter = TaskFilter {
+            tag: Some(String::from("deep-work")),
+            client: Some(String::from("ACME")),
+            ..filter
+        };
+        assert!(filter.matches(&tagged));
+        assert!(!filter.matches(&task("code", 1000, 600)));
+    }
+
+    #[test]
+    fn query_builder_sets_each_condition() {
+        let mut tagged = task("Write Docs", 1000, 600);
+        tagged.tags = vec![String::from("writing")];
+        tagged.client = Some(String::from("ACME"));
+        let mut running = StoredTask::running("write tests", UNIX_EPOCH);
+        running.billable = false;
+
+        let query = TaskQuery::new().name_contains("WRITE");
+        let matches = |filter: TaskQueryFilter| {
+            [&tagged, &running]
+                .into_iter()
+                .filter(|task| filter.matches(task))
+                .map(|task| task.name.as_str())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            matches(query.clone().build()),
+            ["Write Docs", "write tests"]
+        );
+        assert_eq!(
+            matches(query.clone().tag("writing").build()),
+            ["Write Docs"]
+        );
+        assert_eq!(
+            matches(query.clone().client("ACME").build()),
+            ["Write Docs"]
+        );
+        assert_eq!(
+            matches(query.clone().billable_only(true).build()),
+            ["Write Docs"]
+        );
+        assert_eq!(
+            matches(query.clone().running_only(true).build()),
+            ["write tests"]
+        );
+        assert_eq!(
+            matches(
+                query
+                    .clone()
+                    .since(UNIX_EPOCH + Duration::from_secs(1))
+                    .build()
+            ),
+            ["Write Docs"]
+        );
+        assert_eq!(
+            matches(
+                query
+                    .clone()
+                    .until(UNIX_EPOCH + Duration::from_secs(1))
+                    .build()
+            ),
+            ["write tests"]
+        );
+        assert_eq!(
+            matches(query.clone().min_duration_secs(1).build()),
+            ["Write Docs"]
+        );
+        assert_eq!(
+            matches(query.max_duration_secs(599).build()),
+            ["write tests"]
+        );
+
+        let mut tasks = vec![tagged.clone(), running.clone()];
+        tasks.retain(TaskQuery::new().tag("writing").build().into_fn());
+        assert_eq!(tasks, [tagged]);
+    }
+}
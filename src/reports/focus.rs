@@ -0,0 +1,152 @@
+//! The longest stretch of a day spent working without a real break.
+
+use super::local_time;
+use crate::storage::StoredTask;
+use chrono::{DateTime, Duration, Local, NaiveDate, NaiveTime};
+use std::{fmt, time::SystemTime};
+
+/// A stretch of time during which tasks were running, with no gap between them longer than the
+/// gap threshold.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FocusBlock {
+    /// When the first task of the block started.
+    pub start: SystemTime,
+    /// When the last task of the block stopped.
+    pub end: SystemTime,
+    /// How long the block was, in seconds, including the short gaps in it.
+    pub duration_secs: u64,
+}
+
+/// Formats the block in local time as `2 hours 14 minutes (10:23 – 12:37)`.
+impl fmt::Display for FocusBlock {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let hours = self.duration_secs / 3600;
+        let minutes = self.duration_secs % 3600 / 60;
+        let plural = |count: u64| if count == 1 { "" } else { "s" };
+        if hours > 0 {
+            write!(f, "{} hour{} ", hours, plural(hours))?;
+        }
+        write!(
+            f,
+            "{} minute{} ({} – {})",
+            minutes,
+            plural(minutes),
+            DateTime::<Local>::from(self.start).format("%H:%M"),
+            DateTime::<Local>::from(self.end).format("%H:%M")
+        )
+    }
+}
+
+/// Finds focus blocks in the task history.
+pub struct FocusAnalyzer;
+
+impl FocusAnalyzer {
+    /// Finds the longest focus block on `date` (in local time), joining tasks separated by at
+    /// most `gap_threshold` seconds. Of blocks that are as long, the earliest is returned.
+    ///
+    /// Tasks are cut off at the edges of the day and may overlap each other. Tasks that are
+    /// still running are ignored. Returns `None` if no task ran on `date`.
+    pub fn longest_block(
+        tasks: &[StoredTask],
+        date: NaiveDate,
+        gap_threshold: u64,
+    ) -> Option<FocusBlock> {
+        let day_start = local_time(date, NaiveTime::MIN);
+        let day_end = local_time(date + Duration::days(1), NaiveTime::MIN);
+        let gap_threshold = std::time::Duration::from_secs(gap_threshold);
+
+        let mut ranges: Vec<(SystemTime, SystemTime)> = tasks
+            .iter()
+            .filter_map(|task| Some((task.started_at, task.tracked_until()?)))
+            .filter(|(start, end)| *start < day_end && *end > day_start)
+            .map(|(start, end)| (start.max(day_start), end.min(day_end)))
+            .collect();
+        ranges.sort();
+
+        let mut longest: Option<FocusBlock> = None;
+        let mut current: Option<(SystemTime, SystemTime)> = None;
+        for (start, end) in ranges {
+            current = match current {
+                Some((block_start, block_end)) if start <= block_end + gap_threshold => {
+                    Some((block_start, block_end.max(end)))
+                }
+                Some(block) => {
+                    longest = longer(longest, block);
+                    Some((start, end))
+                }
+                None => Some((start, end)),
+            };
+        }
+        match current {
+            Some(block) => longer(longest, block),
+            None => longest,
+        }
+    }
+}
+
+/// Keeps whichever of `longest` and the block from `start` to `end` is longer, preferring
+/// `longest` when they are as long.
+fn longer(
+    longest: Option<FocusBlock>,
+    (start, end): (SystemTime, SystemTime),
+) -> Option<FocusBlock> {
+    let duration_secs = end.duration_since(start).unwrap_or_default().as_secs();
+    match longest {
+        Some(longest) if longest.duration_secs >= duration_secs => Some(longest),
+        _ => Some(FocusBlock {
+            start,
+            end,
+            duration_secs,
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests_focus {
+    use super::*;
+
+    fn date() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 1, 15).unwrap()
+    }
+
+    fn at(hour: u32, minute: u32) -> SystemTime {
+        local_time(date(), NaiveTime::from_hms_opt(hour, minute, 0).unwrap())
+    }
+
+    fn task(start: (u32, u32), end: (u32, u32)) -> StoredTask {
+        StoredTask::new("task", at(start.0, start.1), at(end.0, end.1))
+    }
+
+    #[test]
+    fn joins_overlapping_tasks() {
+        let tasks = [
+            task((10, 23), (11, 30)),
+            task((11, 0), (12, 37)),
+            task((14, 0), (15, 0)),
+        ];
+        let block = FocusAnalyzer::longest_block(&tasks, date(), 300).unwrap();
+        assert_eq!((block.start, block.end), (at(10, 23), at(12, 37)));
+        assert_eq!(block.duration_secs, 8040);
+        assert_eq!(block.to_string(), "2 hours 14 minutes (10:23 – 12:37)");
+    }
+
+    #[test]
+    fn gaps_of_exactly_the_threshold_dont_break_a_block() {
+        let tasks = [
+            task((9, 0), (9, 30)),
+            task((9, 35), (10, 0)),
+            task((10, 10), (10, 50)),
+        ];
+        let block = FocusAnalyzer::longest_block(&tasks, date(), 300).unwrap();
+        assert_eq!((block.start, block.end), (at(9, 0), at(10, 0)));
+        let block = FocusAnalyzer::longest_block(&tasks, date(), 299).unwrap();
+        assert_eq!((block.start, block.end), (at(10, 10), at(10, 50)));
+    }
+
+    #[test]
+    fn empty_days_have_no_block() {
+        let tasks = [StoredTask::running("running", at(9, 0))];
+        assert_eq!(FocusAnalyzer::longest_block(&tasks, date(), 300), None);
+        assert_eq!(FocusAnalyzer::longest_block(&[], date(), 300), None);
+    }
+}
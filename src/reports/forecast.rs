@@ -0,0 +1,187 @@
+//! How much time each task is likely to take over the next few days, from its recent history.
+
+use super::{format_days, hours_and_minutes, local_date};
+use crate::storage::StoredTask;
+use chrono::{Duration, NaiveDate};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+};
+
+/// The fewest days a task has to be tracked on in the lookback window to be forecast.
+pub const MIN_FORECAST_DAYS: usize = 3;
+
+/// Projects the time spent on each task forward from its average over a recent window.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Forecaster {
+    /// How many days before today to average over.
+    pub lookback_days: u32,
+    /// How many days to project forward.
+    pub forecast_days: u32,
+}
+
+/// The forecast for one task.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ForecastRow {
+    /// The name of the task.
+    pub name: String,
+    /// How many days of the lookback window the task was tracked on.
+    pub days_tracked: usize,
+    /// The average seconds tracked on a day the task was worked on, or `None` if it was
+    /// tracked on fewer than [`MIN_FORECAST_DAYS`] days.
+    pub daily_secs: Option<u64>,
+    /// How many days the forecast covers.
+    pub forecast_days: u32,
+}
+
+impl ForecastRow {
+    /// The seconds expected over the whole forecast, if there is enough data.
+    pub fn total_secs(&self) -> Option<u64> {
+        self.daily_secs
+            .map(|daily| daily * u64::from(self.forecast_days))
+    }
+}
+
+impl Forecaster {
+    /// Forecasts every task tracked in the `lookback_days` days before `today`.
+    ///
+    /// A task's daily average is the time tracked on it divided by the days it was tracked
+    /// on, so a task done every weekday is expected to take that long on each day it's done.
+    /// Tasks with enough data come first, the most time a day first; the rest follow in name
+    /// order.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chrono::NaiveDate;
+    /// use std::time::{Duration, UNIX_EPOCH};
+    /// use timetracker::{reports::Forecaster, storage::StoredTask};
+    ///
+    /// // noon UTC on January 15th to 17th, 2024
+    /// let tasks: Vec<StoredTask> = (0..3)
+    ///     .map(|day| {
+    ///         let start = UNIX_EPOCH + Duration::from_secs(1_705_320_000 + day * 86_400);
+    ///         StoredTask::new("standup", start, start + Duration::from_secs(23 * 60))
+    ///     })
+    ///     .collect();
+    /// let today = NaiveDate::from_ymd_opt(2024, 1, 18).unwrap();
+    /// let forecaster = Forecaster { lookback_days: 30, forecast_days: 5 };
+    /// let rows = forecaster.forecast(&tasks, today);
+    /// assert_eq!(rows[0].to_string(), "standup: expected 0h 23m/day → ~1h 55m over 5 days");
+    /// ```
+    pub fn forecast(&self, tasks: &[StoredTask], today: NaiveDate) -> Vec<ForecastRow> {
+        let first = today - Duration::days(i64::from(self.lookback_days));
+        let mut history: BTreeMap<&str, (u64, BTreeSet<NaiveDate>)> = BTreeMap::new();
+        for task in tasks {
+            let date = local_date(task.started_at);
+            if date < first || date >= today {
+                continue;
+            }
+            let (seconds, days) = history.entry(&task.name).or_default();
+            *seconds += task.seconds;
+            days.insert(date);
+        }
+        let mut rows: Vec<ForecastRow> = history
+            .into_iter()
+            .map(|(name, (seconds, days))| ForecastRow {
+                name: name.to_string(),
+                days_tracked: days.len(),
+                daily_secs: (days.len() >= MIN_FORECAST_DAYS).then(|| seconds / days.len() as u64),
+                forecast_days: self.forecast_days,
+            })
+            .collect();
+        // `None` sorts before `Some`, so reversing puts tasks without enough data last
+        rows.sort_by(|a, b| {
+            b.daily_secs
+                .cmp(&a.daily_secs)
+                .then_with(|| a.name.cmp(&b.name))
+        });
+        rows
+    }
+}
+
+/// Formats the forecast like `standup: expected 0h 23m/day → ~1h 55m over 5 days`, or
+/// `standup: insufficient data (2 days tracked)`.
+impl fmt::Display for ForecastRow {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match (self.daily_secs, self.total_secs()) {
+            (Some(daily), Some(total)) => write!(
+                f,
+                "{}: expected {}/day → ~{} over {}",
+                self.name,
+                hours_and_minutes(daily),
+                hours_and_minutes(total),
+                format_days(self.forecast_days)
+            ),
+            _ => write!(
+                f,
+                "{}: insufficient data ({} tracked)",
+                self.name,
+                format_days(self.days_tracked as u32)
+            ),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests_forecast {
+    use super::*;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    /// A task started at noon UTC, `day` days after Monday, January 1st 2024.
+    fn task(name: &str, day: u64, minutes: u64) -> StoredTask {
+        let start = UNIX_EPOCH + Duration::from_secs(1_704_110_400 + day * 86_400);
+        StoredTask::new(name, start, start + Duration::from_secs(minutes * 60))
+    }
+
+    #[test]
+    fn averages_over_the_days_tracked_in_a_ten_day_history() {
+        let mut tasks = Vec::new();
+        // weekdays of the first ten days of January, 20 to 26 minutes
+        for (i, day) in [0, 1, 2, 3, 4, 7, 8, 9].into_iter().enumerate() {
+            tasks.push(task("morning standup", day, 20 + i as u64 % 4 * 2));
+        }
+        // two sessions on one day count as one day
+        tasks.push(task("reading", 0, 30));
+        tasks.push(task("reading", 0, 30));
+        tasks.push(task("reading", 5, 45));
+        tasks.push(task("reading", 9, 75));
+        tasks.push(task("taxes", 6, 120));
+        // before the lookback window and on the day of the forecast
+        tasks.push(task("reading", 0, 600));
+        tasks.last_mut().unwrap().started_at -= Duration::from_secs(40 * 86_400);
+        tasks.push(task("reading", 10, 600));
+
+        let today = NaiveDate::from_ymd_opt(2024, 1, 11).unwrap();
+        let forecaster = Forecaster {
+            lookback_days: 30,
+            forecast_days: 5,
+        };
+        let rows = forecaster.forecast(&tasks, today);
+        let lines: Vec<String> = rows.iter().map(ToString::to_string).collect();
+
+        assert_eq!(rows[0].daily_secs, Some(60 * 60));
+        assert_eq!(rows[1].days_tracked, 8);
+        assert_eq!(rows[1].daily_secs, Some(23 * 60));
+        assert_eq!(rows[1].total_secs(), Some(5 * 23 * 60));
+        assert_eq!(
+            lines,
+            [
+                "reading: expected 1h 0m/day → ~5h 0m over 5 days",
+                "morning standup: expected 0h 23m/day → ~1h 55m over 5 days",
+                "taxes: insufficient data (1 day tracked)",
+            ]
+        );
+    }
+
+    #[test]
+    fn ignores_tasks_outside_the_lookback_window() {
+        let tasks = [task("a", 0, 10), task("a", 1, 10), task("a", 2, 10)];
+        let forecaster = Forecaster {
+            lookback_days: 2,
+            forecast_days: 1,
+        };
+        let rows = forecaster.forecast(&tasks, NaiveDate::from_ymd_opt(2024, 1, 4).unwrap());
+        assert_eq!(rows[0].to_string(), "a: insufficient data (2 days tracked)");
+    }
+}
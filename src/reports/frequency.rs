@@ -0,0 +1,208 @@
+//! How often each task name comes up in the history, to tell habits from one-off tasks.
+
+use crate::{format_duration_compact, storage::StoredTask};
+use std::{collections::HashMap, fmt};
+
+/// How often one task name was tracked.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FrequencyEntry {
+    /// The name of the task.
+    pub name: String,
+    /// How many times it was tracked.
+    pub count: u32,
+    /// The average seconds it took, rounded to the second.
+    pub avg_duration_secs: u64,
+    /// The seconds tracked on it altogether.
+    pub total_duration_secs: u64,
+}
+
+/// The task names of the history, the most often tracked first.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct FrequencyReport {
+    /// The task names, the most often tracked first, then by name.
+    pub entries: Vec<FrequencyEntry>,
+    /// The seconds tracked on every task, including names filtered out by `min_count`, which
+    /// the share of each entry is worked out of.
+    pub total_secs: u64,
+}
+
+impl FrequencyReport {
+    /// Counts the tasks with each name, leaving out names tracked fewer than `min_count` times.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::{Duration, UNIX_EPOCH};
+    /// use timetracker::{reports::FrequencyReport, storage::StoredTask};
+    ///
+    /// let task = |name: &str, minutes: u64| {
+    ///     StoredTask::new(name, UNIX_EPOCH, UNIX_EPOCH + Duration::from_secs(minutes * 60))
+    /// };
+    /// let report = FrequencyReport::from_tasks(
+    ///     &[task("standup", 20), task("standup", 26), task("taxes", 120)],
+    ///     2,
+    /// );
+    /// assert_eq!(report.entries.len(), 1);
+    /// assert_eq!(report.entries[0].count, 2);
+    /// assert_eq!(report.entries[0].avg_duration_secs, 23 * 60);
+    /// ```
+    pub fn from_tasks(tasks: &[StoredTask], min_count: u32) -> FrequencyReport {
+        let mut totals: HashMap<&str, (u32, u64)> = HashMap::new();
+        for task in tasks {
+            let (count, seconds) = totals.entry(&task.name).or_default();
+            *count += 1;
+            *seconds += task.seconds;
+        }
+        let mut entries: Vec<FrequencyEntry> = totals
+            .into_iter()
+            .filter(|(_, (count, _))| *count >= min_count)
+            .map(|(name, (count, seconds))| FrequencyEntry {
+                name: name.to_string(),
+                count,
+                avg_duration_secs: (seconds + u64::from(count) / 2) / u64::from(count),
+                total_duration_secs: seconds,
+            })
+            .collect();
+        entries.sort_by(|a, b| b.count.cmp(&a.count).then_with(|| a.name.cmp(&b.name)));
+        FrequencyReport {
+            entries,
+            total_secs: tasks.iter().map(|task| task.seconds).sum(),
+        }
+    }
+
+    /// The percentage of all the time tracked that went to an entry.
+    pub fn share(&self, entry: &FrequencyEntry) -> f64 {
+        if self.total_secs == 0 {
+            return 0.0;
+        }
+        entry.total_duration_secs as f64 * 100.0 / self.total_secs as f64
+    }
+}
+
+/// The average duration of an entry, rounded to the minute unless it's under one.
+fn average(entry: &FrequencyEntry) -> String {
+    let seconds = entry.avg_duration_secs;
+    if seconds < 60 {
+        return format_duration_compact(seconds);
+    }
+    format_duration_compact((seconds + 30) / 60 * 60)
+}
+
+/// Formats a line per task name, with its share of all the time tracked lined up on the right,
+/// e.g.
+///
+/// ```text
+/// morning standup: 47 times (avg 23m)   35.2%
+/// write docs: 3 times (avg 1h30m)       11.8%
+/// taxes: 1 time (avg 2h)                 5.2%
+/// ```
+impl fmt::Display for FrequencyReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let lines: Vec<String> = self
+            .entries
+            .iter()
+            .map(|entry| {
+                format!(
+                    "{}: {} time{} (avg {})",
+                    entry.name,
+                    entry.count,
+                    if entry.count == 1 { "" } else { "s" },
+                    average(entry)
+                )
+            })
+            .collect();
+        let width = lines
+            .iter()
+            .map(|line| line.chars().count())
+            .max()
+            .unwrap_or_default();
+        for (index, (line, entry)) in lines.iter().zip(&self.entries).enumerate() {
+            if index > 0 {
+                writeln!(f)?;
+            }
+            write!(f, "{:<width$}  {:>5.1}%", line, self.share(entry))?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests_frequency {
+    use super::*;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    fn task(name: &str, minutes: u64) -> StoredTask {
+        let start = UNIX_EPOCH + Duration::from_secs(1_705_320_000);
+        StoredTask::new(name, start, start + Duration::from_secs(minutes * 60))
+    }
+
+    fn history() -> Vec<StoredTask> {
+        let mut tasks = Vec::new();
+        for minutes in [20, 22, 24, 26] {
+            tasks.push(task("morning standup", minutes));
+        }
+        tasks.push(task("write docs", 60));
+        tasks.push(task("write docs", 120));
+        tasks.push(task("taxes", 228));
+        tasks
+    }
+
+    #[test]
+    fn counts_and_averages_each_task_name() {
+        let report = FrequencyReport::from_tasks(&history(), 1);
+        assert_eq!(
+            report.entries,
+            [
+                FrequencyEntry {
+                    name: String::from("morning standup"),
+                    count: 4,
+                    avg_duration_secs: 23 * 60,
+                    total_duration_secs: 92 * 60,
+                },
+                FrequencyEntry {
+                    name: String::from("write docs"),
+                    count: 2,
+                    avg_duration_secs: 90 * 60,
+                    total_duration_secs: 180 * 60,
+                },
+                FrequencyEntry {
+                    name: String::from("taxes"),
+                    count: 1,
+                    avg_duration_secs: 228 * 60,
+                    total_duration_secs: 228 * 60,
+                },
+            ]
+        );
+        assert_eq!(report.total_secs, 500 * 60);
+    }
+
+    #[test]
+    fn leaves_out_names_tracked_fewer_than_min_count_times() {
+        let report = FrequencyReport::from_tasks(&history(), 2);
+        let names: Vec<&str> = report
+            .entries
+            .iter()
+            .map(|entry| entry.name.as_str())
+            .collect();
+        assert_eq!(names, ["morning standup", "write docs"]);
+        // shares are still out of all the time tracked
+        assert_eq!(report.share(&report.entries[1]), 36.0);
+    }
+
+    #[test]
+    fn lines_up_the_share_of_time_on_the_right() {
+        let lines: Vec<String> = FrequencyReport::from_tasks(&history(), 1)
+            .to_string()
+            .lines()
+            .map(String::from)
+            .collect();
+        assert_eq!(
+            lines,
+            [
+                "morning standup: 4 times (avg 23m)   18.4%",
+                "write docs: 2 times (avg 1h30m)      36.0%",
+                "taxes: 1 time (avg 3h48m)            45.6%",
+            ]
+        );
+    }
+}
@@ -0,0 +1,196 @@
+//! When each task of a day ran, as a text Gantt chart.
+
+use super::local_time;
+use crate::storage::StoredTask;
+use chrono::{DateTime, Duration, Local, NaiveDate, NaiveTime};
+use std::time::SystemTime;
+
+/// The width of the chart when the terminal width isn't known.
+pub const DEFAULT_WIDTH: usize = 80;
+
+/// The most characters of a task name shown on the left of its row.
+const MAX_LABEL_WIDTH: usize = 20;
+
+/// The fewest columns the timeline gets, however narrow the chart is.
+const MIN_TIMELINE_WIDTH: usize = 10;
+
+/// A timeline from the start of the first task of a day to the end of the last, with a row per
+/// task. Tasks that overlap each get their own row, so they show up side by side.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GanttChart {
+    /// When the first task started.
+    pub start: SystemTime,
+    /// When the last task stopped.
+    pub end: SystemTime,
+    /// The tasks, cut off at the edges of the day, in the order they started. Each one stops
+    /// when its [tracked time](StoredTask::tracked_until) runs out.
+    pub tasks: Vec<StoredTask>,
+}
+
+impl GanttChart {
+    /// Charts the tasks that ran on `date` (in local time). Tasks that are still running are
+    /// left out. Returns `None` if no task ran on `date`.
+    pub fn for_date(tasks: &[StoredTask], date: NaiveDate) -> Option<GanttChart> {
+        let day_start = local_time(date, NaiveTime::MIN);
+        let day_end = local_time(date + Duration::days(1), NaiveTime::MIN);
+        let mut tasks: Vec<StoredTask> = tasks
+            .iter()
+            .filter_map(|task| {
+                let stopped_at = task.tracked_until()?;
+                if task.started_at >= day_end || stopped_at <= day_start {
+                    return None;
+                }
+                let mut task = task.clone();
+                task.started_at = task.started_at.max(day_start);
+                let stopped_at = stopped_at.min(day_end);
+                task.stopped_at = Some(stopped_at);
+                task.seconds = stopped_at
+                    .duration_since(task.started_at)
+                    .unwrap_or_default()
+                    .as_secs();
+                Some(task)
+            })
+            .collect();
+        tasks.sort_by_key(|task| task.started_at);
+        let start = tasks.first()?.started_at;
+        let end = tasks.iter().filter_map(|task| task.stopped_at).max()?;
+        Some(GanttChart { start, end, tasks })
+    }
+
+    /// Gets the columns of a timeline `columns` wide that a task running from `started_at` to
+    /// `stopped_at` covers. Every task covers at least one column, however short it is.
+    pub fn span(
+        &self,
+        started_at: SystemTime,
+        stopped_at: SystemTime,
+        columns: usize,
+    ) -> std::ops::Range<usize> {
+        let total = self.end.duration_since(self.start).unwrap_or_default();
+        if total.is_zero() {
+            return 0..columns;
+        }
+        let column = |time: SystemTime| {
+            let offset = time.duration_since(self.start).unwrap_or_default();
+            offset.as_secs_f64() / total.as_secs_f64() * columns as f64
+        };
+        let first = (column(started_at).floor() as usize).min(columns - 1);
+        let last = (column(stopped_at).ceil() as usize).clamp(first + 1, columns);
+        first..last
+    }
+
+    /// Draws the chart `width` characters wide: the start and end times above the timeline,
+    /// then a row per task of its name and `█` where it ran, with `.` for the rest.
+    pub fn render(&self, width: usize) -> String {
+        let label_width = self
+            .tasks
+            .iter()
+            .map(|task| task.name.chars().count())
+            .max()
+            .unwrap_or_default()
+            .min(MAX_LABEL_WIDTH);
+        let columns = width
+            .saturating_sub(label_width + 1)
+            .max(MIN_TIMELINE_WIDTH);
+
+        let format_time = |time: SystemTime| DateTime::<Local>::from(time).format("%H:%M");
+        let start = format_time(self.start).to_string();
+        let end = format_time(self.end).to_string();
+        let mut lines = vec![format!(
+            "{} {}{:>width$}",
+            " ".repeat(label_width),
+            start,
+            end,
+            width = columns.saturating_sub(start.len())
+        )];
+        for task in &self.tasks {
+            let label: String = task.name.chars().take(label_width).collect();
+            let padding = label_width - label.chars().count();
+            let span = self.span(
+                task.started_at,
+                task.stopped_at.unwrap_or(self.end),
+                columns,
+            );
+            let timeline: String = (0..columns)
+                .map(|column| if span.contains(&column) { '█' } else { '.' })
+                .collect();
+            lines.push(format!("{}{} {}", label, " ".repeat(padding), timeline));
+        }
+        lines.join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests_gantt {
+    use super::*;
+
+    fn date() -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 1, 15).unwrap()
+    }
+
+    fn at(hour: u32, minute: u32) -> SystemTime {
+        local_time(date(), NaiveTime::from_hms_opt(hour, minute, 0).unwrap())
+    }
+
+    fn task(name: &str, start: (u32, u32), end: (u32, u32)) -> StoredTask {
+        StoredTask::new(name, at(start.0, start.1), at(end.0, end.1))
+    }
+
+    #[test]
+    fn scales_tasks_to_the_width() {
+        let tasks = [
+            task("review", (10, 0), (11, 0)),
+            task("code", (9, 0), (10, 0)),
+            task("call", (10, 30), (11, 0)),
+            task("lunch", (12, 0), (13, 0)),
+        ];
+        let chart = GanttChart::for_date(&tasks, date()).unwrap();
+        assert_eq!((chart.start, chart.end), (at(9, 0), at(13, 0)));
+        assert_eq!(
+            chart.render(23),
+            "       09:00      13:00\n\
+             code   ████............\n\
+             review ....████........\n\
+             call   ......██........\n\
+             lunch  ............████"
+        );
+    }
+
+    #[test]
+    fn short_tasks_cover_a_column() {
+        let tasks = [
+            task("all day", (9, 0), (17, 0)),
+            task("quick", (12, 0), (12, 1)),
+        ];
+        let chart = GanttChart::for_date(&tasks, date()).unwrap();
+        assert_eq!(chart.span(at(12, 0), at(12, 1), 16), 6..7);
+        assert_eq!(chart.span(at(9, 0), at(17, 0), 16), 0..16);
+    }
+
+    #[test]
+    fn paused_time_is_not_drawn() {
+        // stopped at 11:00 after being paused for an hour, so only two hours were tracked
+        let mut paused = task("write", (9, 0), (11, 0));
+        paused.seconds = 3600;
+        let tasks = [paused, task("review", (10, 0), (11, 0))];
+        let chart = GanttChart::for_date(&tasks, date()).unwrap();
+        assert_eq!(chart.tasks[0].stopped_at, Some(at(10, 0)));
+        assert_eq!(
+            chart.render(27),
+            "       09:00          11:00\n\
+             write  ██████████..........\n\
+             review ..........██████████"
+        );
+    }
+
+    #[test]
+    fn days_without_tasks_have_no_chart() {
+        let tasks = [StoredTask::running("running", at(9, 0))];
+        assert_eq!(GanttChart::for_date(&tasks, date()), None);
+        let yesterday = [StoredTask::new(
+            "yesterday",
+            at(9, 0) - std::time::Duration::from_secs(86_400),
+            at(10, 0) - std::time::Duration::from_secs(86_400),
+        )];
+        assert_eq!(GanttChart::for_date(&yesterday, date()), None);
+    }
+}
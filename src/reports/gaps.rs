@@ -0,0 +1,113 @@
+//! Detection of untracked time within a work day.
+
+use super::local_time;
+use crate::storage::StoredTask;
+use chrono::{DateTime, Local, NaiveDate, NaiveTime};
+use std::{fmt, time::SystemTime};
+
+/// A period of the work day when no task was running.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GapReport {
+    /// When the gap started.
+    pub start: SystemTime,
+    /// When the gap ended.
+    pub end: SystemTime,
+    /// How long the gap was, in seconds.
+    pub duration_secs: u64,
+}
+
+/// Formats the gap in local time as `Gap: 10:30–11:15 (45 minutes)`.
+impl fmt::Display for GapReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Gap: {}–{} ({} minutes)",
+            DateTime::<Local>::from(self.start).format("%H:%M"),
+            DateTime::<Local>::from(self.end).format("%H:%M"),
+            self.duration_secs / 60
+        )
+    }
+}
+
+/// Finds the periods between `day_start` and `day_end` on `date` (in local time) when no task
+/// was running.
+///
+/// Tasks don't need to be sorted, and may overlap each other or the edges of the work day. Tasks
+/// that are still running are ignored.
+pub fn detect_gaps(
+    tasks: &[StoredTask],
+    day_start: NaiveTime,
+    day_end: NaiveTime,
+    date: NaiveDate,
+) -> Vec<GapReport> {
+    let day_start = local_time(date, day_start);
+    let day_end = local_time(date, day_end);
+
+    let mut ranges: Vec<(SystemTime, SystemTime)> = tasks
+        .iter()
+        .filter_map(|task| Some((task.started_at, task.tracked_until()?)))
+        .filter(|(start, end)| *start < day_end && *end > day_start)
+        .collect();
+    ranges.sort();
+
+    let mut gaps = Vec::new();
+    let mut covered_until = day_start;
+    for (start, end) in ranges.into_iter().chain([(day_end, day_end)]) {
+        let start = start.min(day_end);
+        if let Ok(duration) = start.duration_since(covered_until) {
+            if !duration.is_zero() {
+                gaps.push(GapReport {
+                    start: covered_until,
+                    end: start,
+                    duration_secs: duration.as_secs(),
+                });
+            }
+        }
+        covered_until = covered_until.max(end);
+    }
+    gaps
+}
+
+#[cfg(test)]
+mod tests_gaps {
+    use super::*;
+
+    fn at(hour: u32, minute: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(hour, minute, 0).unwrap()
+    }
+
+    fn task(date: NaiveDate, start: NaiveTime, end: NaiveTime) -> StoredTask {
+        StoredTask::new("task", local_time(date, start), local_time(date, end))
+    }
+
+    #[test]
+    fn finds_gaps_between_unsorted_tasks() {
+        let date = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+        let tasks = [
+            task(date, at(11, 15), at(12, 0)),
+            task(date, at(8, 30), at(10, 30)),
+            task(date, at(11, 30), at(11, 45)),
+            task(date, at(17, 0), at(19, 0)),
+        ];
+
+        let gaps: Vec<String> = detect_gaps(&tasks, at(9, 0), at(18, 0), date)
+            .iter()
+            .map(|gap| gap.to_string())
+            .collect();
+        assert_eq!(
+            gaps,
+            vec![
+                "Gap: 10:30–11:15 (45 minutes)",
+                "Gap: 12:00–17:00 (300 minutes)"
+            ]
+        );
+    }
+
+    #[test]
+    fn empty_day_is_one_gap() {
+        let date = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+        let gaps = detect_gaps(&[], at(9, 0), at(18, 0), date);
+        assert_eq!(gaps.len(), 1);
+        assert_eq!(gaps[0].duration_secs, 9 * 3600);
+    }
+}
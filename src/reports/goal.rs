@@ -0,0 +1,164 @@
+//! How the time tracked on each task compares to its daily goal.
+
+use crate::{format_duration_compact, storage::StoredTask};
+use std::{collections::HashMap, fmt};
+
+/// The time tracked on a task against its goal.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TaskGoalDelta {
+    /// The name of the task.
+    pub task_name: String,
+    /// The seconds tracked on the task.
+    pub actual_secs: u64,
+    /// The seconds the task was meant to get.
+    pub goal_secs: u64,
+    /// How many seconds more than the goal were tracked, negative if less.
+    pub delta_secs: i64,
+}
+
+impl TaskGoalDelta {
+    /// Compares the time tracked on each task with a goal to the goal, in name order.
+    ///
+    /// `goals` are the seconds a day each task is meant to get, so over `days` days the goal
+    /// is `days` times that. Tasks with a goal but no time tracked are included, and tasks
+    /// without a goal are left out.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::{collections::HashMap, time::{Duration, UNIX_EPOCH}};
+    /// use timetracker::{reports::TaskGoalDelta, storage::StoredTask};
+    ///
+    /// let goals = HashMap::from([(String::from("Spanish"), 1800)]);
+    /// let tasks = [StoredTask::new("Spanish", UNIX_EPOCH, UNIX_EPOCH + Duration::from_secs(1500))];
+    /// let deltas = TaskGoalDelta::compute(&tasks, &goals, 1);
+    /// assert_eq!(deltas[0].to_string(), "Spanish: actual 25m / goal 30m (−5m)");
+    /// ```
+    pub fn compute(
+        tasks: &[StoredTask],
+        goals: &HashMap<String, u64>,
+        days: u32,
+    ) -> Vec<TaskGoalDelta> {
+        let mut deltas: Vec<TaskGoalDelta> = goals
+            .iter()
+            .map(|(name, goal)| {
+                let actual_secs: u64 = tasks
+                    .iter()
+                    .filter(|task| &task.name == name)
+                    .map(|task| task.seconds)
+                    .sum();
+                let goal_secs = goal * u64::from(days);
+                TaskGoalDelta {
+                    task_name: name.clone(),
+                    actual_secs,
+                    goal_secs,
+                    delta_secs: actual_secs as i64 - goal_secs as i64,
+                }
+            })
+            .collect();
+        deltas.sort_by(|a, b| a.task_name.cmp(&b.task_name));
+        deltas
+    }
+
+    /// Checks whether at least the goal was tracked.
+    pub fn met(&self) -> bool {
+        self.delta_secs >= 0
+    }
+
+    /// Formats the comparison like [`Display`](fmt::Display). With `color`, the change is green
+    /// if the goal was met and red if not.
+    pub fn render(&self, color: bool) -> String {
+        let change = format_delta(self.delta_secs);
+        let change = match (color, self.met()) {
+            (true, true) => format!("\x1b[32m{}\x1b[0m", change),
+            (true, false) => format!("\x1b[31m{}\x1b[0m", change),
+            (false, _) => change,
+        };
+        format!(
+            "{}: actual {} / goal {} ({})",
+            self.task_name,
+            format_duration_compact(self.actual_secs),
+            format_duration_compact(self.goal_secs),
+            change
+        )
+    }
+}
+
+/// Formats the comparison as `Spanish: actual 25m / goal 30m (−5m)`, without color.
+impl fmt::Display for TaskGoalDelta {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.render(false))
+    }
+}
+
+/// Formats a change in seconds with its sign, e.g. `+5m`, `−1h30m`, or `±0s`.
+fn format_delta(delta: i64) -> String {
+    let sign = match delta.signum() {
+        1 => "+",
+        -1 => "−",
+        _ => "±",
+    };
+    format!("{}{}", sign, format_duration_compact(delta.unsigned_abs()))
+}
+
+#[cfg(test)]
+mod tests_goal {
+    use super::*;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    fn task(name: &str, seconds: u64) -> StoredTask {
+        StoredTask::new(name, UNIX_EPOCH, UNIX_EPOCH + Duration::from_secs(seconds))
+    }
+
+    fn goals() -> HashMap<String, u64> {
+        HashMap::from([
+            (String::from("Spanish"), 1800),
+            (String::from("exercise"), 3600),
+            (String::from("reading"), 1200),
+        ])
+    }
+
+    #[test]
+    fn deltas_are_positive_negative_or_zero() {
+        let tasks = [
+            task("Spanish", 900),
+            task("Spanish", 600),
+            task("exercise", 4500),
+            task("reading", 1200),
+            task("email", 7200),
+        ];
+        let deltas = TaskGoalDelta::compute(&tasks, &goals(), 1);
+        let lines: Vec<String> = deltas.iter().map(ToString::to_string).collect();
+        assert_eq!(
+            lines,
+            [
+                "Spanish: actual 25m / goal 30m (−5m)",
+                "exercise: actual 1h15m / goal 1h (+15m)",
+                "reading: actual 20m / goal 20m (±0s)",
+            ]
+        );
+        assert_eq!(
+            deltas
+                .iter()
+                .map(|delta| delta.delta_secs)
+                .collect::<Vec<_>>(),
+            [-300, 900, 0]
+        );
+        assert!(!deltas[0].met() && deltas[1].met() && deltas[2].met());
+    }
+
+    #[test]
+    fn goals_add_up_over_days() {
+        let deltas = TaskGoalDelta::compute(&[], &goals(), 7);
+        assert_eq!(deltas[0].goal_secs, 7 * 1800);
+        assert_eq!(deltas[0].actual_secs, 0);
+        assert_eq!(deltas[0].delta_secs, -7 * 1800);
+    }
+
+    #[test]
+    fn colors_the_change() {
+        let deltas = TaskGoalDelta::compute(&[task("reading", 1300)], &goals(), 1);
+        assert!(deltas[0].render(true).ends_with("(\x1b[31m−30m\x1b[0m)"));
+        assert!(deltas[2].render(true).ends_with("(\x1b[32m+1m40s\x1b[0m)"));
+    }
+}
@@ -0,0 +1,206 @@
+//! The past year of daily effort as a grid, like the contribution graph on a GitHub profile.
+
+use super::local_date;
+use crate::storage::StoredTask;
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+use std::fmt;
+
+/// The number of weeks (columns) in a heatmap.
+pub const WEEKS: usize = 53;
+
+/// The width of the day labels on the left of the grid, including the space after them.
+const LABEL_WIDTH: usize = 4;
+
+/// The past year of daily totals, one column per week and one row per day of the week.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Heatmap {
+    /// The last day shown, usually today. It is in the last column.
+    pub end: NaiveDate,
+    /// The first day of the first week, 52 weeks before the week of `end`.
+    pub start: NaiveDate,
+    /// The seconds tracked on each day, by week and then by day of the week.
+    pub cells: [[u64; 7]; WEEKS],
+}
+
+impl Heatmap {
+    /// Adds up the seconds tracked on each day of the 53 weeks (starting on `first_day`) up to
+    /// and including the week of `end`.
+    ///
+    /// Days after `end` are left empty.
+    pub fn ending(tasks: &[StoredTask], end: NaiveDate, first_day: Weekday) -> Heatmap {
+        let last_week = end - Duration::days(i64::from(end.weekday().days_since(first_day)));
+        let start = last_week - Duration::weeks(WEEKS as i64 - 1);
+        let mut heatmap = Heatmap {
+            end,
+            start,
+            cells: [[0; 7]; WEEKS],
+        };
+        for task in tasks {
+            let date = local_date(task.started_at);
+            if date < start || date > end {
+                continue;
+            }
+            let days = (date - start).num_days() as usize;
+            heatmap.cells[days / 7][days % 7] += task.seconds;
+        }
+        heatmap
+    }
+
+    /// Gets the date of a cell.
+    pub fn date(&self, week: usize, day: usize) -> NaiveDate {
+        self.start + Duration::days((week * 7 + day) as i64)
+    }
+
+    /// Gets the character for a cell: `░` for nothing tracked, `▒` for under 2 hours, `▓` for
+    /// under 4 hours, and `█` for 4 hours or more. Days after the end are blank.
+    pub fn cell(&self, week: usize, day: usize) -> char {
+        if self.date(week, day) > self.end {
+            return ' ';
+        }
+        match self.cells[week][day] {
+            0 => '░',
+            seconds if seconds < 2 * 3600 => '▒',
+            seconds if seconds < 4 * 3600 => '▓',
+            _ => '█',
+        }
+    }
+
+    /// Draws the heatmap at most `max_width` characters wide, leaving out the oldest weeks if
+    /// they don't fit.
+    pub fn render(&self, max_width: usize) -> String {
+        let columns = max_width.saturating_sub(LABEL_WIDTH).clamp(1, WEEKS);
+        let weeks = WEEKS - columns..WEEKS;
+
+        // the months are labelled above the week their first day is in, if there is room
+        let mut months = String::new();
+        for week in weeks.clone() {
+            let first_of_month = (0..7)
+                .map(|day| self.date(week, day))
+                .find(|date| date.day() == 1 && *date <= self.end)
+                .map(|date| date.format("%b").to_string());
+            let used = months.chars().count();
+            let column = week - weeks.start;
+            if let Some(month) = first_of_month
+                .filter(|month| used <= column && column + month.chars().count() <= columns)
+            {
+                months.push_str(&" ".repeat(column - used));
+                months.push_str(&month);
+            }
+        }
+        let mut lines = vec![format!("{}{}", " ".repeat(LABEL_WIDTH), months)];
+
+        for day in 0..7 {
+            let row: String = weeks.clone().map(|week| self.cell(week, day)).collect();
+            lines.push(format!(
+                "{:<width$}{}",
+                self.date(0, day).weekday().to_string(),
+                row,
+                width = LABEL_WIDTH
+            ));
+        }
+        // even the day labels are cut off when there isn't room for a single week
+        lines
+            .iter()
+            .map(|line| {
+                line.chars()
+                    .take(max_width)
+                    .collect::<String>()
+                    .trim_end()
+                    .to_string()
+            })
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+}
+
+/// Draws every week of the heatmap, with the day labels on the left and the months on top.
+impl fmt::Display for Heatmap {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.render(LABEL_WIDTH + WEEKS))
+    }
+}
+
+#[cfg(test)]
+mod tests_heatmap {
+    use super::*;
+    use crate::reports::local_time;
+    use chrono::NaiveTime;
+
+    fn task(date: NaiveDate, hours: u64) -> StoredTask {
+        let started_at = local_time(date, NaiveTime::from_hms_opt(9, 0, 0).unwrap());
+        StoredTask::new(
+            "task",
+            started_at,
+            started_at + std::time::Duration::from_secs(hours * 3600),
+        )
+    }
+
+    #[test]
+    fn cells_show_the_hours_tracked() {
+        let date = |year, month, day| NaiveDate::from_ymd_opt(year, month, day).unwrap();
+        let tasks = [
+            task(date(2022, 12, 25), 1),
+            task(date(2023, 1, 1), 8),
+            task(date(2023, 1, 2), 1),
+            task(date(2023, 1, 3), 3),
+            task(date(2023, 6, 16), 5),
+            task(date(2023, 6, 16), 1),
+            task(date(2023, 12, 31), 2),
+        ];
+        // 2023 ends on a Sunday, so with weeks starting on Monday the last column is a full week
+        // and the first starts 52 weeks before it
+        let heatmap = Heatmap::ending(&tasks, date(2023, 12, 31), Weekday::Mon);
+        assert_eq!(heatmap.start, date(2022, 12, 26));
+        assert_eq!(heatmap.cell(0, 6), '█');
+        assert_eq!(heatmap.cell(1, 0), '▒');
+        assert_eq!(heatmap.cell(1, 1), '▓');
+        assert_eq!(heatmap.cell(1, 2), '░');
+        // June 16th is the Friday of the 25th week
+        assert_eq!(heatmap.date(24, 4), date(2023, 6, 16));
+        assert_eq!(heatmap.cell(24, 4), '█');
+        assert_eq!(heatmap.cell(52, 6), '▓');
+        // December 25th is before the first week
+        assert_eq!(heatmap.cells.iter().flatten().sum::<u64>(), 20 * 3600);
+    }
+
+    #[test]
+    fn days_after_the_end_are_blank() {
+        let end = NaiveDate::from_ymd_opt(2024, 3, 13).unwrap();
+        let heatmap = Heatmap::ending(&[task(end, 1)], end, Weekday::Mon);
+        assert_eq!(
+            heatmap.date(52, 0),
+            NaiveDate::from_ymd_opt(2024, 3, 11).unwrap()
+        );
+        assert_eq!(heatmap.cell(52, 2), '▒');
+        assert_eq!(heatmap.cell(52, 3), ' ');
+        assert_eq!(heatmap.cell(0, 0), '░');
+    }
+
+    #[test]
+    fn keeps_december_31st_of_a_54_week_year() {
+        // 2000 is a leap year that starts on a Saturday, so its days span 54 Sunday-to-Saturday
+        // weeks, but the 53 weeks up to the end of it still include December 31st
+        let end = NaiveDate::from_ymd_opt(2000, 12, 31).unwrap();
+        let heatmap = Heatmap::ending(&[task(end, 5)], end, Weekday::Sun);
+        assert_eq!(heatmap.date(52, 0), end);
+        assert_eq!(heatmap.cell(52, 0), '█');
+    }
+
+    #[test]
+    fn renders_labels_and_fits_the_width() {
+        let end = NaiveDate::from_ymd_opt(2023, 12, 31).unwrap();
+        let heatmap = Heatmap::ending(&[], end, Weekday::Mon);
+        let full = heatmap.to_string();
+        let lines: Vec<&str> = full.lines().collect();
+        assert_eq!(lines.len(), 8);
+        assert!(lines[0].starts_with("    Jan  Feb Mar Apr"));
+        assert!(lines[1].starts_with("Mon ░░░░"));
+        assert!(lines[7].starts_with("Sun ░░░░"));
+
+        for width in [3, 14, 20, 40] {
+            let narrow = heatmap.render(width);
+            assert!(narrow.lines().all(|line| line.chars().count() <= width));
+        }
+        assert!(heatmap.render(14).lines().next().unwrap().contains("Dec"));
+    }
+}
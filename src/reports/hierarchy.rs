@@ -0,0 +1,286 @@
+//! The time tracked for each client, broken down by project and then by task.
+
+use crate::{format_duration_compact, storage::StoredTask};
+use std::{collections::HashMap, fmt};
+
+/// What a task without a client is listed under.
+pub const NO_CLIENT: &str = "(No Client)";
+
+/// What a task without a project is listed under.
+pub const NO_PROJECT: &str = "(No Project)";
+
+/// The time tracked on one task name within a project.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TaskTotal {
+    /// The name of the task.
+    pub name: String,
+    /// The seconds tracked on it.
+    pub seconds: u64,
+}
+
+/// The tasks of one project.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ProjectGroup {
+    /// The project, or `None` for the client's tasks without one.
+    pub project: Option<String>,
+    /// The seconds tracked on the project's tasks.
+    pub seconds: u64,
+    /// The project's tasks, the most time first.
+    pub tasks: Vec<TaskTotal>,
+}
+
+/// The projects of one client.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ClientGroup {
+    /// The client, or `None` for tasks without one.
+    pub client: Option<String>,
+    /// The seconds tracked for the client.
+    pub seconds: u64,
+    /// The client's projects, the most time first.
+    pub projects: Vec<ProjectGroup>,
+}
+
+/// The history grouped by client, then project, then task name.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct HierarchyReport {
+    /// The clients, the most time first, with tasks without a client last.
+    pub root: Vec<ClientGroup>,
+}
+
+/// The seconds tracked on each task name, by project, by client.
+type Totals<'a> = HashMap<&'a Option<String>, HashMap<&'a Option<String>, HashMap<&'a str, u64>>>;
+
+/// Sorts groups by their seconds, most first, then by name, with the unnamed group last.
+fn sort_groups<T>(groups: &mut [T], key: impl Fn(&T) -> (Option<&String>, u64)) {
+    groups.sort_by(|a, b| {
+        let (a_name, a_seconds) = key(a);
+        let (b_name, b_seconds) = key(b);
+        a_name
+            .is_none()
+            .cmp(&b_name.is_none())
+            .then_with(|| b_seconds.cmp(&a_seconds))
+            .then_with(|| a_name.cmp(&b_name))
+    });
+}
+
+impl HierarchyReport {
+    /// Groups the tasks by their `client`, then their `project`, adding up the time of tasks
+    /// with the same name.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::{Duration, UNIX_EPOCH};
+    /// use timetracker::{reports::HierarchyReport, storage::StoredTask};
+    ///
+    /// let task = |name: &str, client: Option<&str>, hours: u64| {
+    ///     let mut task =
+    ///         StoredTask::new(name, UNIX_EPOCH, UNIX_EPOCH + Duration::from_secs(hours * 3600));
+    ///     task.client = client.map(String::from);
+    ///     task.project = Some(String::from("Website"));
+    ///     task
+    /// };
+    /// let report = HierarchyReport::from_tasks(&[
+    ///     task("design", Some("ACME"), 3),
+    ///     task("build", Some("ACME"), 5),
+    ///     task("blog", None, 1),
+    /// ]);
+    /// assert_eq!(report.root[0].client.as_deref(), Some("ACME"));
+    /// assert_eq!(report.root[0].seconds, 8 * 3600);
+    /// assert_eq!(report.root[1].client, None);
+    /// ```
+    pub fn from_tasks(tasks: &[StoredTask]) -> HierarchyReport {
+        let mut totals = Totals::new();
+        for task in tasks {
+            *totals
+                .entry(&task.client)
+                .or_default()
+                .entry(&task.project)
+                .or_default()
+                .entry(&task.name)
+                .or_default() += task.seconds;
+        }
+        let mut root: Vec<ClientGroup> = totals
+            .into_iter()
+            .map(|(client, projects)| {
+                let mut projects: Vec<ProjectGroup> = projects
+                    .into_iter()
+                    .map(|(project, names)| {
+                        let mut tasks: Vec<TaskTotal> = names
+                            .into_iter()
+                            .map(|(name, seconds)| TaskTotal {
+                                name: name.to_string(),
+                                seconds,
+                            })
+                            .collect();
+                        tasks.sort_by(|a, b| b.seconds.cmp(&a.seconds).then(a.name.cmp(&b.name)));
+                        ProjectGroup {
+                            project: project.clone(),
+                            seconds: tasks.iter().map(|task| task.seconds).sum(),
+                            tasks,
+                        }
+                    })
+                    .collect();
+                sort_groups(&mut projects, |group| {
+                    (group.project.as_ref(), group.seconds)
+                });
+                ClientGroup {
+                    client: client.clone(),
+                    seconds: projects.iter().map(|project| project.seconds).sum(),
+                    projects,
+                }
+            })
+            .collect();
+        sort_groups(&mut root, |group| (group.client.as_ref(), group.seconds));
+        HierarchyReport { root }
+    }
+
+    /// Whether there are no tasks in the report.
+    pub fn is_empty(&self) -> bool {
+        self.root.is_empty()
+    }
+}
+
+/// Formats the report as an indented tree, with the durations of the tasks in each project
+/// lined up, e.g.
+///
+/// ```text
+/// Client: ACME (12h total)
+///   Project: Website (8h)
+///     build:  5h
+///     design: 3h
+///   Project: Mobile (4h)
+///     release: 4h
+///
+/// Client: (No Client) (1h total)
+///   Project: (No Project) (1h)
+///     inbox: 1h
+/// ```
+impl fmt::Display for HierarchyReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, client) in self.root.iter().enumerate() {
+            if i > 0 {
+                write!(f, "\n\n")?;
+            }
+            write!(
+                f,
+                "Client: {} ({} total)",
+                client.client.as_deref().unwrap_or(NO_CLIENT),
+                format_duration_compact(client.seconds)
+            )?;
+            for project in &client.projects {
+                write!(
+                    f,
+                    "\n  Project: {} ({})",
+                    project.project.as_deref().unwrap_or(NO_PROJECT),
+                    format_duration_compact(project.seconds)
+                )?;
+                let name_width = project
+                    .tasks
+                    .iter()
+                    .map(|task| task.name.chars().count() + 1)
+                    .max()
+                    .unwrap_or_default();
+                for task in &project.tasks {
+                    write!(
+                        f,
+                        "\n    {:name_width$} {}",
+                        format!("{}:", task.name),
+                        format_duration_compact(task.seconds)
+                    )?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests_hierarchy {
+    use super::*;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    fn task(name: &str, client: Option<&str>, project: Option<&str>, hours: u64) -> StoredTask {
+        let mut task = StoredTask::new(
+            name,
+            UNIX_EPOCH,
+            UNIX_EPOCH + Duration::from_secs(hours * 3600),
+        );
+        task.client = client.map(String::from);
+        task.project = project.map(String::from);
+        task
+    }
+
+    fn report() -> HierarchyReport {
+        HierarchyReport::from_tasks(&[
+            task("task A", Some("ACME"), Some("Website"), 2),
+            task("task B", Some("ACME"), Some("Website"), 5),
+            task("task C", Some("ACME"), Some("Mobile"), 4),
+            task("inbox", None, None, 1),
+            task("task A", Some("ACME"), Some("Website"), 1),
+            task("audit", Some("Globex"), None, 6),
+            task("a longer task", Some("Globex"), Some("Audit"), 1),
+        ])
+    }
+
+    #[test]
+    fn groups_by_client_then_project_then_task() {
+        let report = report();
+        let clients: Vec<Option<&str>> = report
+            .root
+            .iter()
+            .map(|group| group.client.as_deref())
+            .collect();
+        assert_eq!(clients, [Some("ACME"), Some("Globex"), None]);
+        let acme = &report.root[0];
+        assert_eq!(acme.seconds, 12 * 3600);
+        assert_eq!(acme.projects[0].project.as_deref(), Some("Website"));
+        assert_eq!(acme.projects[0].seconds, 8 * 3600);
+        assert_eq!(
+            acme.projects[0].tasks[1],
+            TaskTotal {
+                name: String::from("task A"),
+                seconds: 3 * 3600
+            }
+        );
+        // the project-less tasks of a client come after its projects
+        assert_eq!(report.root[1].projects[1].project, None);
+    }
+
+    #[test]
+    fn formats_an_indented_tree() {
+        let lines: Vec<String> = report().to_string().lines().map(String::from).collect();
+        assert_eq!(
+            lines,
+            [
+                "Client: ACME (12h total)",
+                "  Project: Website (8h)",
+                "    task B: 5h",
+                "    task A: 3h",
+                "  Project: Mobile (4h)",
+                "    task C: 4h",
+                "",
+                "Client: Globex (7h total)",
+                "  Project: Audit (1h)",
+                "    a longer task: 1h",
+                "  Project: (No Project) (6h)",
+                "    audit: 6h",
+                "",
+                "Client: (No Client) (1h total)",
+                "  Project: (No Project) (1h)",
+                "    inbox: 1h",
+            ]
+        );
+    }
+
+    #[test]
+    fn lines_up_durations_within_a_project() {
+        let report = HierarchyReport::from_tasks(&[
+            task("review", Some("ACME"), Some("Website"), 2),
+            task("deploy to staging", Some("ACME"), Some("Website"), 1),
+        ]);
+        let lines: Vec<String> = report.to_string().lines().map(String::from).collect();
+        assert_eq!(lines[2], "    review:            2h");
+        assert_eq!(lines[3], "    deploy to staging: 1h");
+    }
+}
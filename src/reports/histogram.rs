@@ -0,0 +1,255 @@
+//! How task durations are distributed.
+
+use crate::{format_duration_compact, parse_natural_duration, storage::StoredTask};
+use std::{fmt, str::FromStr};
+
+/// The width of the longest bar when a [`Histogram`] is displayed.
+pub const DEFAULT_BAR_WIDTH: usize = 40;
+
+/// The edges of a histogram's buckets, in seconds, in increasing order. The last edge can be
+/// `None` for a bucket that has no upper end.
+///
+/// Parsed from a comma-separated list of durations such as `0,15m,1h,inf`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct BucketEdges(pub Vec<Option<u64>>);
+
+impl Default for BucketEdges {
+    fn default() -> BucketEdges {
+        "0,15m,1h,4h,8h,inf"
+            .parse()
+            .expect("the default bucket edges are valid")
+    }
+}
+
+impl FromStr for BucketEdges {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let edges = s
+            .split(',')
+            .map(|edge| match edge.trim() {
+                "inf" => Ok(None),
+                edge => parse_natural_duration(edge).map(Some),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        if edges.len() < 2 {
+            return Err(format!("expected at least two bucket edges, got '{}'", s));
+        }
+        let increasing = edges.windows(2).all(|pair| match pair {
+            [Some(low), Some(high)] => low < high,
+            [Some(_), None] => true,
+            _ => false,
+        });
+        if !increasing {
+            return Err(format!(
+                "bucket edges must increase, with inf only last, got '{}'",
+                s
+            ));
+        }
+        Ok(BucketEdges(edges))
+    }
+}
+
+/// Formats the edges the way they are written on the command line.
+impl fmt::Display for BucketEdges {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let edges: Vec<String> = self
+            .0
+            .iter()
+            .map(|edge| edge.map_or_else(|| String::from("inf"), format_edge))
+            .collect();
+        write!(f, "{}", edges.join(","))
+    }
+}
+
+/// Formats an edge for a label, writing no time at all as `0` rather than `0s`.
+fn format_edge(secs: u64) -> String {
+    if secs == 0 {
+        String::from("0")
+    } else {
+        format_duration_compact(secs)
+    }
+}
+
+/// The tasks whose duration is at least `min_secs` and less than `max_secs`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HistogramBucket {
+    /// The shortest duration in the bucket.
+    pub min_secs: u64,
+    /// The duration the bucket ends before, or `None` if it has no upper end.
+    pub max_secs: Option<u64>,
+    /// How many tasks fall in the bucket.
+    pub task_count: usize,
+    /// The time tracked on those tasks.
+    pub total_seconds: u64,
+}
+
+impl HistogramBucket {
+    /// Checks whether a task that took `seconds` belongs in the bucket.
+    pub fn contains(&self, seconds: u64) -> bool {
+        seconds >= self.min_secs && self.max_secs.is_none_or(|max| seconds < max)
+    }
+
+    /// Gets the label of the bucket, e.g. `15m–1h`, or `8h+` if it has no upper end.
+    pub fn label(&self) -> String {
+        match self.max_secs {
+            Some(max) => format!("{}–{}", format_edge(self.min_secs), format_edge(max)),
+            None => format!("{}+", format_edge(self.min_secs)),
+        }
+    }
+}
+
+/// How many completed tasks took each range of durations.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Histogram {
+    /// The buckets, shortest durations first.
+    pub buckets: Vec<HistogramBucket>,
+}
+
+impl Histogram {
+    /// Sorts the completed tasks into the buckets between `edges`. Tasks that fall outside
+    /// every bucket, and tasks that are still running, are left out.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::{Duration, UNIX_EPOCH};
+    /// use timetracker::{reports::Histogram, storage::StoredTask};
+    ///
+    /// let task = |seconds: u64| {
+    ///     StoredTask::new("task", UNIX_EPOCH, UNIX_EPOCH + Duration::from_secs(seconds))
+    /// };
+    /// let edges = "0,15m,1h,inf".parse().unwrap();
+    /// let histogram = Histogram::from_tasks(&[task(60), task(900), task(7200)], &edges);
+    /// let counts: Vec<usize> = histogram.buckets.iter().map(|b| b.task_count).collect();
+    /// assert_eq!(counts, [1, 1, 1]);
+    /// ```
+    pub fn from_tasks(tasks: &[StoredTask], edges: &BucketEdges) -> Histogram {
+        let mut buckets: Vec<HistogramBucket> = edges
+            .0
+            .windows(2)
+            .filter_map(|pair| match *pair {
+                [Some(min_secs), max_secs] => Some(HistogramBucket {
+                    min_secs,
+                    max_secs,
+                    task_count: 0,
+                    total_seconds: 0,
+                }),
+                _ => None,
+            })
+            .collect();
+        for task in tasks.iter().filter(|task| task.stopped_at.is_some()) {
+            if let Some(bucket) = buckets
+                .iter_mut()
+                .find(|bucket| bucket.contains(task.seconds))
+            {
+                bucket.task_count += 1;
+                bucket.total_seconds += task.seconds;
+            }
+        }
+        Histogram { buckets }
+    }
+
+    /// Draws a line per bucket, e.g. `15m–1h: ████ (4 tasks)`, with the bucket holding the
+    /// most tasks drawn `bar_width` blocks wide and the others in proportion.
+    pub fn render(&self, bar_width: usize) -> String {
+        let max_count = self
+            .buckets
+            .iter()
+            .map(|bucket| bucket.task_count)
+            .max()
+            .unwrap_or(0);
+        let labels: Vec<String> = self.buckets.iter().map(HistogramBucket::label).collect();
+        let label_width = labels
+            .iter()
+            .map(|label| label.chars().count())
+            .max()
+            .unwrap_or(0);
+        self.buckets
+            .iter()
+            .zip(&labels)
+            .map(|(bucket, label)| {
+                let blocks = (bucket.task_count * bar_width + max_count / 2)
+                    .checked_div(max_count)
+                    .unwrap_or(0);
+                let padding = label_width - label.chars().count();
+                format!(
+                    "{}:{} {} ({} task{})",
+                    label,
+                    " ".repeat(padding),
+                    "█".repeat(blocks),
+                    bucket.task_count,
+                    if bucket.task_count == 1 { "" } else { "s" }
+                )
+            })
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+}
+
+/// Draws the histogram with bars up to [`DEFAULT_BAR_WIDTH`] wide.
+impl fmt::Display for Histogram {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.render(DEFAULT_BAR_WIDTH))
+    }
+}
+
+#[cfg(test)]
+mod tests_histogram {
+    use super::*;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    fn task(seconds: u64) -> StoredTask {
+        StoredTask::new(
+            "task",
+            UNIX_EPOCH,
+            UNIX_EPOCH + Duration::from_secs(seconds),
+        )
+    }
+
+    #[test]
+    fn boundaries_go_in_the_bucket_they_start() {
+        let edges: BucketEdges = "0,15m,1h,inf".parse().unwrap();
+        let tasks = [task(0), task(899), task(900), task(3599), task(3600)];
+        let histogram = Histogram::from_tasks(&tasks, &edges);
+        let counts: Vec<(usize, u64)> = histogram
+            .buckets
+            .iter()
+            .map(|bucket| (bucket.task_count, bucket.total_seconds))
+            .collect();
+        assert_eq!(counts, [(2, 899), (2, 4499), (1, 3600)]);
+    }
+
+    #[test]
+    fn leaves_out_tasks_outside_the_buckets_and_running_tasks() {
+        let edges: BucketEdges = "15m,1h".parse().unwrap();
+        let tasks = [
+            task(60),
+            task(1800),
+            task(3600),
+            StoredTask::running("running", UNIX_EPOCH),
+        ];
+        let histogram = Histogram::from_tasks(&tasks, &edges);
+        assert_eq!(histogram.buckets.len(), 1);
+        assert_eq!(histogram.buckets[0].task_count, 1);
+    }
+
+    #[test]
+    fn rejects_edges_that_dont_increase() {
+        assert!("0,1h,15m".parse::<BucketEdges>().is_err());
+        assert!("0,inf,1h".parse::<BucketEdges>().is_err());
+        assert!("1h".parse::<BucketEdges>().is_err());
+        assert_eq!(BucketEdges::default().to_string(), "0,15m,1h,4h,8h,inf");
+    }
+
+    #[test]
+    fn scales_bars_to_the_fullest_bucket() {
+        let edges: BucketEdges = "0,15m,1h,inf".parse().unwrap();
+        let tasks: Vec<StoredTask> = [60; 8].into_iter().chain([1800; 4]).map(task).collect();
+        let histogram = Histogram::from_tasks(&tasks, &edges);
+        assert_eq!(
+            histogram.render(8),
+            "0–15m:  ████████ (8 tasks)\n15m–1h: ████ (4 tasks)\n1h+:     (0 tasks)"
+        );
+    }
+}
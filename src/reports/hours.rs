@@ -0,0 +1,144 @@
+//! When in the week work happens, hour by hour.
+
+use crate::storage::StoredTask;
+use chrono::{DateTime, Datelike, Local, Timelike, Weekday};
+use std::{fmt, time::Duration};
+
+/// The time tracked in each hour of each day of the week, across the whole history.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WeekHourHeatmap {
+    /// The seconds tracked in each hour (0–23, local time) of each day, starting with Monday.
+    pub cells: [[u64; 24]; 7],
+}
+
+/// Adds the time of a task to the hours it ran in.
+///
+/// A task that runs past the end of an hour is split, so that each hour (and, past midnight,
+/// each day) only gets the part of the task that fell inside it.
+pub fn fill_buckets(task: &StoredTask, cells: &mut [[u64; 24]; 7]) {
+    let mut current = task.started_at;
+    let mut remaining = task.seconds;
+    while remaining > 0 {
+        let local = DateTime::<Local>::from(current);
+        let into_hour = u64::from(local.minute() * 60 + local.second());
+        let chunk = (3600 - into_hour).min(remaining);
+        cells[local.weekday().num_days_from_monday() as usize][local.hour() as usize] += chunk;
+        current += Duration::from_secs(chunk);
+        remaining -= chunk;
+    }
+}
+
+impl WeekHourHeatmap {
+    /// Adds up the time of every task by day of the week and hour of the day.
+    pub fn from_tasks(tasks: &[StoredTask]) -> WeekHourHeatmap {
+        let mut cells = [[0; 24]; 7];
+        for task in tasks {
+            fill_buckets(task, &mut cells);
+        }
+        WeekHourHeatmap { cells }
+    }
+
+    /// Gets the character for an hour, on the same scale as the daily
+    /// [`Heatmap`](super::Heatmap): `░` when nothing was tracked, `▒` for under 2 hours, `▓` for
+    /// under 4 hours, and `█` for 4 hours or more.
+    pub fn cell(&self, weekday: Weekday, hour: usize) -> char {
+        match self.cells[weekday.num_days_from_monday() as usize][hour] {
+            0 => '░',
+            seconds if seconds < 2 * 3600 => '▒',
+            seconds if seconds < 4 * 3600 => '▓',
+            _ => '█',
+        }
+    }
+
+    /// Draws the heatmap with a row per day, starting on `first_day`, and the hours along the
+    /// top.
+    pub fn render(&self, first_day: Weekday) -> String {
+        let hours: String = (0..24)
+            .step_by(3)
+            .map(|hour| format!("{:<3}", format!("{:02}", hour)))
+            .collect();
+        let mut output = format!("    {}", hours.trim_end());
+        let mut weekday = first_day;
+        for _ in 0..7 {
+            let row: String = (0..24).map(|hour| self.cell(weekday, hour)).collect();
+            output.push_str(&format!("\n{} {}", weekday, row));
+            weekday = weekday.succ();
+        }
+        output
+    }
+}
+
+/// Draws the heatmap with weeks starting on Monday.
+impl fmt::Display for WeekHourHeatmap {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.render(Weekday::Mon))
+    }
+}
+
+#[cfg(test)]
+mod tests_hours {
+    use super::*;
+    use crate::reports::local_time;
+    use chrono::{NaiveDate, NaiveTime};
+
+    fn task(date: NaiveDate, hour: u32, minute: u32, seconds: u64) -> StoredTask {
+        let started_at = local_time(date, NaiveTime::from_hms_opt(hour, minute, 0).unwrap());
+        StoredTask::new(
+            "task",
+            started_at,
+            started_at + Duration::from_secs(seconds),
+        )
+    }
+
+    #[test]
+    fn splits_a_task_across_the_hours_it_spans() {
+        // a Monday, 09:30 to 12:00
+        let monday = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+        let mut cells = [[0; 24]; 7];
+        fill_buckets(&task(monday, 9, 30, 9000), &mut cells);
+        assert_eq!(cells[0][9], 1800);
+        assert_eq!(cells[0][10], 3600);
+        assert_eq!(cells[0][11], 3600);
+        assert_eq!(cells.iter().flatten().sum::<u64>(), 9000);
+    }
+
+    #[test]
+    fn splits_a_task_across_midnight() {
+        // a Sunday, 23:00 to 01:00
+        let sunday = NaiveDate::from_ymd_opt(2024, 1, 21).unwrap();
+        let heatmap = WeekHourHeatmap::from_tasks(&[task(sunday, 23, 0, 7200)]);
+        assert_eq!(heatmap.cells[6][23], 3600);
+        assert_eq!(heatmap.cells[0][0], 3600);
+    }
+
+    #[test]
+    fn cells_use_absolute_thresholds() {
+        let mut heatmap = WeekHourHeatmap {
+            cells: [[0; 24]; 7],
+        };
+        let hours = [0, 1, 2 * 3600 - 1, 2 * 3600, 4 * 3600 - 1, 4 * 3600];
+        for (hour, seconds) in hours.into_iter().enumerate() {
+            heatmap.cells[0][hour] = seconds;
+        }
+        let cells: String = (0..hours.len())
+            .map(|hour| heatmap.cell(Weekday::Mon, hour))
+            .collect();
+        assert_eq!(cells, "░▒▒▓▓█");
+    }
+
+    #[test]
+    fn renders_a_row_per_day() {
+        let monday = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+        let heatmap =
+            WeekHourHeatmap::from_tasks(&[task(monday, 9, 0, 3600), task(monday, 10, 0, 1200)]);
+        let rendered = heatmap.render(Weekday::Sun);
+        let lines: Vec<&str> = rendered.lines().collect();
+        assert_eq!(lines[0], "    00 03 06 09 12 15 18 21");
+        assert!(lines[1].starts_with("Sun ░"));
+        assert_eq!(
+            lines[2],
+            format!("Mon {}▒▒{}", "░".repeat(9), "░".repeat(13))
+        );
+        assert_eq!(lines.len(), 8);
+    }
+}
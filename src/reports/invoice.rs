@@ -0,0 +1,226 @@
+//! Plain-text invoices for billable time.
+
+use super::{amount_cents, format_amount, local_date};
+use crate::storage::StoredTask;
+use chrono::{Datelike, Months, NaiveDate};
+use std::{collections::BTreeMap, fmt, ops::Range};
+
+/// One line of an invoice: the billable time of a single task.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InvoiceRow {
+    /// The name of the task.
+    pub task: String,
+    /// The billable time tracked for the task, in seconds.
+    pub seconds: u64,
+    /// The hourly rate, in cents.
+    pub rate_cents: u64,
+    /// What the time is worth, in cents.
+    pub amount_cents: u64,
+}
+
+/// An invoice for a client's billable time in one period, usually a month.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Invoice {
+    /// Who the invoice is for.
+    pub client: String,
+    /// The period being billed, e.g. `YYYY-MM` for a month.
+    pub period: String,
+    /// The date of the invoice: the last day of the period.
+    pub date: NaiveDate,
+    /// One row per task, in alphabetical order.
+    pub rows: Vec<InvoiceRow>,
+    /// The sum of every row's amount, in cents.
+    pub total_cents: u64,
+    /// The symbol amounts are shown with, e.g. `$`.
+    pub currency_symbol: String,
+}
+
+impl Invoice {
+    /// Bills the billable tasks for `client` started in the month of `month` at
+    /// `hourly_rate_cents`.
+    ///
+    /// Tasks with the same name are combined into one row. Tasks for other clients, or for no
+    /// client, are left out.
+    pub fn new(
+        client: &str,
+        month: NaiveDate,
+        tasks: &[StoredTask],
+        hourly_rate_cents: u64,
+        currency_symbol: &str,
+    ) -> Invoice {
+        let first_day = month.with_day(1).expect("every month has a first day");
+        Invoice::for_dates(
+            client,
+            &first_day.format("%Y-%m").to_string(),
+            first_day..first_day + Months::new(1),
+            tasks,
+            hourly_rate_cents,
+            currency_symbol,
+        )
+    }
+
+    /// Bills the billable tasks for `client` started on one of `dates` at `hourly_rate_cents`,
+    /// labelling the period `period`.
+    ///
+    /// Tasks with the same name are combined into one row. Tasks for other clients, or for no
+    /// client, are left out.
+    pub fn for_dates(
+        client: &str,
+        period: &str,
+        dates: Range<NaiveDate>,
+        tasks: &[StoredTask],
+        hourly_rate_cents: u64,
+        currency_symbol: &str,
+    ) -> Invoice {
+        let mut seconds_by_task: BTreeMap<&str, u64> = BTreeMap::new();
+        for task in tasks
+            .iter()
+            .filter(|task| task.billable && task.client.as_deref() == Some(client))
+        {
+            if dates.contains(&local_date(task.started_at)) {
+                *seconds_by_task.entry(&task.name).or_default() += task.seconds;
+            }
+        }
+
+        let rows: Vec<InvoiceRow> = seconds_by_task
+            .into_iter()
+            .map(|(task, seconds)| InvoiceRow {
+                task: task.to_string(),
+                seconds,
+                rate_cents: hourly_rate_cents,
+                amount_cents: amount_cents(seconds, hourly_rate_cents),
+            })
+            .collect();
+        Invoice {
+            client: client.to_string(),
+            period: period.to_string(),
+            date: dates.end.pred_opt().expect("the period has a last day"),
+            total_cents: rows.iter().map(|row| row.amount_cents).sum(),
+            rows,
+            currency_symbol: currency_symbol.to_string(),
+        }
+    }
+}
+
+/// Formats the invoice as plain text, ready to paste into an email.
+impl fmt::Display for Invoice {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "INVOICE")?;
+        writeln!(f, "Date: {}", self.date)?;
+        writeln!(f, "Client: {}", self.client)?;
+        writeln!(f, "Period: {}", self.period)?;
+        writeln!(f)?;
+        writeln!(f, "task | hours | rate | amount")?;
+        for row in &self.rows {
+            writeln!(
+                f,
+                "{} | {:.2} | {} | {}",
+                row.task,
+                row.seconds as f64 / 3600.0,
+                format_amount(row.rate_cents, &self.currency_symbol),
+                format_amount(row.amount_cents, &self.currency_symbol)
+            )?;
+        }
+        writeln!(f)?;
+        write!(
+            f,
+            "Total: {}",
+            format_amount(self.total_cents, &self.currency_symbol)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests_invoice {
+    use super::*;
+    use crate::reports::local_time;
+    use chrono::NaiveTime;
+
+    fn task(name: &str, date: NaiveDate, seconds: u64, billable: bool) -> StoredTask {
+        let started_at = local_time(date, NaiveTime::from_hms_opt(9, 0, 0).unwrap());
+        let mut task = StoredTask::new(
+            name,
+            started_at,
+            started_at + std::time::Duration::from_secs(seconds),
+        );
+        task.billable = billable;
+        task.client = Some(String::from("ACME Corp"));
+        task
+    }
+
+    #[test]
+    fn bills_billable_tasks_in_the_period() {
+        let day = |d| NaiveDate::from_ymd_opt(2024, 1, d).unwrap();
+        let tasks = [
+            task("design", day(3), 3600, true),
+            task("design", day(20), 5400, true),
+            task("admin", day(5), 3600, false),
+            task("build", day(31), 1800, true),
+            task(
+                "build",
+                NaiveDate::from_ymd_opt(2024, 2, 1).unwrap(),
+                3600,
+                true,
+            ),
+        ];
+
+        let invoice = Invoice::new("ACME Corp", day(15), &tasks, 8500, "€");
+        assert_eq!(invoice.date, day(31));
+        assert_eq!(invoice.rows.len(), 2);
+        assert_eq!(invoice.total_cents, 25_500);
+        assert_eq!(
+            invoice.to_string(),
+            "INVOICE\nDate: 2024-01-31\nClient: ACME Corp\nPeriod: 2024-01\n\n\
+             task | hours | rate | amount\n\
+             build | 0.50 | €85.00 | €42.50\n\
+             design | 2.50 | €85.00 | €212.50\n\n\
+             Total: €255.00"
+        );
+    }
+
+    #[test]
+    fn bills_only_the_clients_tasks_in_the_month() {
+        let day = |d| NaiveDate::from_ymd_opt(2024, 1, d).unwrap();
+        let mut other = task("support", day(10), 7200, true);
+        other.client = Some(String::from("Globex"));
+        let mut unassigned = task("admin", day(11), 900, true);
+        unassigned.client = None;
+        let tasks = [task("design", day(10), 3600, true), other, unassigned];
+
+        let invoice = Invoice::new("ACME Corp", day(1), &tasks, 10_000, "$");
+        assert_eq!(invoice.rows.len(), 1);
+        assert_eq!(invoice.rows[0].task, "design");
+        assert_eq!(invoice.total_cents, 10_000);
+    }
+
+    #[test]
+    fn bills_only_the_clients_tasks_for_dates() {
+        let day = |d| NaiveDate::from_ymd_opt(2024, 1, d).unwrap();
+        let for_client = |name, seconds, client: Option<&str>| {
+            let mut task = task(name, day(10), seconds, true);
+            task.client = client.map(String::from);
+            task
+        };
+        let january = day(1)..NaiveDate::from_ymd_opt(2024, 2, 1).unwrap();
+        let tasks = [
+            for_client("design", 3600, Some("ACME")),
+            for_client("design", 1800, Some("Globex")),
+            for_client("support", 7200, Some("Globex")),
+            for_client("admin", 900, None),
+        ];
+
+        let acme = Invoice::for_dates("ACME", "January", january.clone(), &tasks, 10_000, "$");
+        assert_eq!(
+            acme.rows,
+            [InvoiceRow {
+                task: String::from("design"),
+                seconds: 3600,
+                rate_cents: 10_000,
+                amount_cents: 10_000,
+            }]
+        );
+        let globex = Invoice::for_dates("Globex", "January", january, &tasks, 10_000, "$");
+        assert_eq!(globex.rows.len(), 2);
+        assert_eq!(globex.total_cents, 25_000);
+    }
+}
@@ -0,0 +1,269 @@
+//! Reports built from the task history, used by `time-tracker report`.
+//!
+//! Reports work in the local time zone: a task counts towards the day (and week) it started on
+//! according to the user's clock, not UTC.
+//!
+//! Reports that place tasks on a timeline, like the Gantt chart, the daily log, and the start and
+//! end times of days, end each task when its [tracked time](StoredTask::tracked_until) runs out,
+//! so time left out of `seconds` (e.g. while the task was paused) isn't shown as worked.
+
+pub mod active;
+pub mod billable;
+pub mod billing;
+#[cfg(feature = "git-integration")]
+pub mod commits;
+pub mod compare;
+pub mod consistency;
+pub mod cooccurrence;
+pub mod filter;
+pub mod focus;
+pub mod forecast;
+pub mod frequency;
+pub mod gantt;
+pub mod gaps;
+pub mod goal;
+pub mod heatmap;
+pub mod hierarchy;
+pub mod histogram;
+pub mod hours;
+pub mod invoice;
+pub mod normalize;
+pub mod overlap;
+pub mod percentile;
+pub mod period;
+pub mod range;
+pub mod rate;
+pub mod rolling;
+pub mod sankey;
+pub mod sparkline;
+pub mod startend;
+pub mod streak;
+pub mod tagtrend;
+pub mod top;
+pub mod trend;
+pub mod utilization;
+pub mod velocity;
+pub mod weekday;
+pub mod weekly;
+
+use crate::storage::StoredTask;
+use chrono::{DateTime, Duration, Local, NaiveDate, NaiveTime, TimeZone};
+use std::{collections::HashMap, time::SystemTime};
+
+pub use active::ActiveHoursReport;
+pub use billable::{amount_cents, format_amount, BillableFilter, BillableTotals};
+pub use billing::{BillingCycle, BillingPeriod};
+#[cfg(feature = "git-integration")]
+pub use commits::{CommitReport, GitCommitResolver};
+pub use compare::{ComparisonReport, ComparisonRow};
+pub use consistency::{ConsistencyEntry, ConsistencyReport};
+pub use cooccurrence::CoOccurrenceMatrix;
+pub use filter::{TaskFilter, TaskQuery, TaskQueryFilter};
+pub use focus::{FocusAnalyzer, FocusBlock};
+pub use forecast::{ForecastRow, Forecaster};
+pub use frequency::{FrequencyEntry, FrequencyReport};
+pub use gantt::GanttChart;
+pub use gaps::{detect_gaps, GapReport};
+pub use goal::TaskGoalDelta;
+pub use heatmap::Heatmap;
+pub use hierarchy::{ClientGroup, HierarchyReport, ProjectGroup, TaskTotal};
+pub use histogram::{BucketEdges, Histogram, HistogramBucket};
+pub use hours::{fill_buckets, WeekHourHeatmap};
+pub use invoice::{Invoice, InvoiceRow};
+pub use normalize::{DailyLog, TimezoneAdapter};
+pub use overlap::{detect_overlaps, OverlapReport};
+pub use percentile::{PercentileAnalysis, PercentileResult};
+pub use period::Period;
+pub use range::DateRange;
+pub use rate::WorkRateReport;
+pub use rolling::RollingAverage;
+pub use sankey::{SankeyNode, SankeyRenderer};
+pub use sparkline::{MultiWeekSparkline, Sparkline};
+pub use startend::{circular_mean, StartEndAnalyzer, TimeOfDaySummary};
+pub use streak::{format_days, StreakComputer, TaskStreak, TaskStreakAnalyzer};
+pub use tagtrend::TagTimeSeries;
+pub use top::TopNReport;
+pub use trend::{MonthlyTotal, MonthlyTrend, TrendDirection};
+pub use utilization::WorkdayUtilization;
+pub use velocity::SprintVelocity;
+pub use weekday::WeekdayAverages;
+pub use weekly::WeeklyReport;
+
+/// Gets the local date of a point in time.
+pub fn local_date(time: SystemTime) -> NaiveDate {
+    DateTime::<Local>::from(time).date_naive()
+}
+
+/// Gets the point in time of a local date and time.
+///
+/// Times skipped by a daylight saving change are moved forward an hour; ambiguous times use the
+/// earlier of the two.
+pub fn local_time(date: NaiveDate, time: NaiveTime) -> SystemTime {
+    let datetime = date.and_time(time);
+    let local = Local
+        .from_local_datetime(&datetime)
+        .earliest()
+        .or_else(|| {
+            Local
+                .from_local_datetime(&(datetime + Duration::hours(1)))
+                .earliest()
+        })
+        .expect("a local time exists within an hour of any other");
+    SystemTime::from(local)
+}
+
+/// Formats seconds as hours and minutes, e.g. `1h 55m`, rounded to the minute.
+pub(crate) fn hours_and_minutes(seconds: u64) -> String {
+    let minutes = (seconds + 30) / 60;
+    format!("{}h {}m", minutes / 60, minutes % 60)
+}
+
+/// Adds up the seconds tracked for each task name, most time first.
+///
+/// Names with the same total are ordered alphabetically.
+pub fn totals_by_name(tasks: &[StoredTask]) -> Vec<(String, u64)> {
+    let mut totals: HashMap<&str, u64> = HashMap::new();
+    for task in tasks {
+        *totals.entry(&task.name).or_default() += task.seconds;
+    }
+    let mut totals: Vec<(String, u64)> = totals
+        .into_iter()
+        .map(|(name, seconds)| (name.to_string(), seconds))
+        .collect();
+    totals.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    totals
+}
+
+/// The context shown for tasks saved before contexts were recorded.
+pub const UNKNOWN_CONTEXT: &str = "(unknown)";
+
+/// Groups the tasks by the context they were started in and adds up the seconds tracked for
+/// each task name within each group.
+///
+/// Groups are ordered like [`totals_by_name`], by their total time and then by context.
+pub fn totals_by_context(tasks: &[StoredTask]) -> Vec<(String, Vec<(String, u64)>)> {
+    let mut groups: HashMap<&str, Vec<StoredTask>> = HashMap::new();
+    for task in tasks {
+        let context = task.context.as_deref().unwrap_or(UNKNOWN_CONTEXT);
+        groups.entry(context).or_default().push(task.clone());
+    }
+    let mut groups: Vec<(String, Vec<(String, u64)>)> = groups
+        .into_iter()
+        .map(|(context, tasks)| (context.to_string(), totals_by_name(&tasks)))
+        .collect();
+    let total = |totals: &[(String, u64)]| totals.iter().map(|(_, seconds)| seconds).sum::<u64>();
+    groups.sort_by(|a, b| total(&b.1).cmp(&total(&a.1)).then_with(|| a.0.cmp(&b.0)));
+    groups
+}
+
+/// Finds the tasks that are still running and were started more than `limit_secs` before `now`,
+/// along with how long each has been running.
+pub fn running_longer_than(
+    tasks: &[StoredTask],
+    now: SystemTime,
+    limit_secs: u64,
+) -> Vec<(&StoredTask, u64)> {
+    tasks
+        .iter()
+        .filter(|task| task.stopped_at.is_none())
+        .filter_map(|task| {
+            let elapsed = now.duration_since(task.started_at).ok()?.as_secs();
+            (elapsed > limit_secs).then_some((task, elapsed))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests_reports {
+    use super::*;
+
+    #[test]
+    fn totals_by_name_orders_by_time() {
+        let started_at = local_time(
+            NaiveDate::from_ymd_opt(2024, 1, 15).unwrap(),
+            NaiveTime::from_hms_opt(12, 0, 0).unwrap(),
+        );
+        let task = |name: &str, seconds: u64| {
+            StoredTask::new(
+                name,
+                started_at,
+                started_at + std::time::Duration::from_secs(seconds),
+            )
+        };
+        let tasks = [task("a", 60), task("b", 30), task("c", 30), task("a", 10)];
+        assert_eq!(
+            totals_by_name(&tasks),
+            vec![
+                (String::from("a"), 70),
+                (String::from("b"), 30),
+                (String::from("c"), 30),
+            ]
+        );
+    }
+
+    #[test]
+    fn totals_by_context_groups_by_detected_context() {
+        use crate::context::ContextDetector;
+
+        struct FakeDetector(&'static str);
+        impl ContextDetector for FakeDetector {
+            fn detect(&self) -> String {
+                self.0.to_string()
+            }
+        }
+
+        let started_at = local_time(
+            NaiveDate::from_ymd_opt(2024, 1, 15).unwrap(),
+            NaiveTime::from_hms_opt(12, 0, 0).unwrap(),
+        );
+        let task = |name: &str, seconds: u64, detector: Option<&dyn ContextDetector>| {
+            let mut task = StoredTask::new(
+                name,
+                started_at,
+                started_at + std::time::Duration::from_secs(seconds),
+            );
+            task.context = detector.map(|detector| detector.detect());
+            task
+        };
+        let app = FakeDetector("/src/app");
+        let docs = FakeDetector("/src/docs");
+        let tasks = [
+            task("code", 60, Some(&app)),
+            task("write", 90, Some(&docs)),
+            task("review", 30, Some(&app)),
+            task("old", 10, None),
+        ];
+        assert_eq!(
+            totals_by_context(&tasks),
+            vec![
+                (
+                    String::from("/src/app"),
+                    vec![(String::from("code"), 60), (String::from("review"), 30)]
+                ),
+                (String::from("/src/docs"), vec![(String::from("write"), 90)]),
+                (String::from("(unknown)"), vec![(String::from("old"), 10)]),
+            ]
+        );
+    }
+
+    #[test]
+    fn finds_tasks_running_too_long() {
+        let now = local_time(
+            NaiveDate::from_ymd_opt(2024, 1, 15).unwrap(),
+            NaiveTime::from_hms_opt(12, 0, 0).unwrap(),
+        );
+        let hours_ago = |hours: u64| now - std::time::Duration::from_secs(hours * 3600);
+        let stopped = StoredTask::new("stopped", hours_ago(5), hours_ago(4));
+        let mut old = stopped.clone();
+        old.name = String::from("old");
+        old.stopped_at = None;
+        let mut recent = StoredTask::new("recent", hours_ago(1), now);
+        recent.stopped_at = None;
+
+        let tasks = [stopped, old, recent];
+        let running = running_longer_than(&tasks, now, 2 * 3600);
+        assert_eq!(running.len(), 1);
+        assert_eq!(running[0].0.name, "old");
+        assert_eq!(running[0].1, 5 * 3600);
+    }
+}
@@ -0,0 +1,194 @@
+//! When each task ran, shown in one time zone whatever zone the tasks were tracked in.
+//!
+//! The history stores UTC timestamps, so a user who travels, or who reports for a client in
+//! another zone, can use `time-tracker report --normalize --timezone America/Chicago` to see the
+//! start and stop of every task in that zone, grouped by its calendar days. The reports of times
+//! of day, like `--gantt` or `--first-task-time`, show their times in the zone too.
+
+use super::local_time;
+use crate::storage::StoredTask;
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use chrono_tz::Tz;
+use std::{fmt, time::SystemTime};
+
+/// Converts the UTC timestamps of tasks to local times in a time zone.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TimezoneAdapter {
+    /// The zone to show times in.
+    pub zone: Tz,
+}
+
+impl TimezoneAdapter {
+    /// Creates an adapter that keeps times in UTC.
+    pub fn utc() -> TimezoneAdapter {
+        TimezoneAdapter { zone: Tz::UTC }
+    }
+
+    /// Gets the local date and time of `utc` in the zone.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::{Duration, UNIX_EPOCH};
+    /// use timetracker::reports::TimezoneAdapter;
+    ///
+    /// let tokyo = TimezoneAdapter { zone: "Asia/Tokyo".parse().unwrap() };
+    /// let local = tokyo.to_local(UNIX_EPOCH + Duration::from_secs(1_704_150_000));
+    /// assert_eq!(local.to_string(), "2024-01-02 08:00:00");
+    /// ```
+    pub fn to_local(&self, utc: SystemTime) -> NaiveDateTime {
+        DateTime::<Utc>::from(utc)
+            .with_timezone(&self.zone)
+            .naive_local()
+    }
+
+    /// Gets the calendar day `utc` falls on in the zone.
+    pub fn date(&self, utc: SystemTime) -> NaiveDate {
+        self.to_local(utc).date()
+    }
+
+    /// Moves `utc` to the time that reads, in the local time zone of the computer, what `utc`
+    /// reads in the zone. Reports that show local times then show the times in the zone.
+    pub fn shift(&self, utc: SystemTime) -> SystemTime {
+        let local = self.to_local(utc);
+        local_time(local.date(), local.time())
+    }
+
+    /// Copies `tasks` with their starts and stops moved by [`TimezoneAdapter::shift`], so that
+    /// reports working in local time show them in the zone.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chrono::{DateTime, Local};
+    /// use std::time::{Duration, UNIX_EPOCH};
+    /// use timetracker::{reports::TimezoneAdapter, storage::StoredTask};
+    ///
+    /// let start = UNIX_EPOCH + Duration::from_secs(1_704_150_000);
+    /// let task = StoredTask::new("late", start, start + Duration::from_secs(1800));
+    /// let shifted = TimezoneAdapter::utc().shift_tasks(&[task]);
+    /// let local = DateTime::<Local>::from(shifted[0].started_at).naive_local();
+    /// assert_eq!(local.to_string(), "2024-01-01 23:00:00");
+    /// ```
+    pub fn shift_tasks(&self, tasks: &[StoredTask]) -> Vec<StoredTask> {
+        tasks
+            .iter()
+            .map(|task| {
+                let mut task = task.clone();
+                task.started_at = self.shift(task.started_at);
+                task.stopped_at = task.stopped_at.map(|stop| self.shift(stop));
+                task
+            })
+            .collect()
+    }
+}
+
+/// Formats the name of the zone, e.g. `America/Chicago`.
+impl fmt::Display for TimezoneAdapter {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.zone.name())
+    }
+}
+
+/// The tasks of each calendar day in a time zone, with the times they started and stopped.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DailyLog {
+    /// The zone the days and times are in.
+    pub adapter: TimezoneAdapter,
+    /// Each day with tasks, earliest first, with its tasks in the order they started.
+    pub days: Vec<(NaiveDate, Vec<StoredTask>)>,
+}
+
+impl DailyLog {
+    /// Groups `tasks` by the day they started on in the zone of `adapter`.
+    pub fn from_tasks(tasks: &[StoredTask], adapter: TimezoneAdapter) -> DailyLog {
+        let mut tasks = tasks.to_vec();
+        tasks.sort_by_key(|task| task.started_at);
+        let mut days: Vec<(NaiveDate, Vec<StoredTask>)> = Vec::new();
+        for task in tasks {
+            let date = adapter.date(task.started_at);
+            match days.last_mut() {
+                Some((last, day_tasks)) if *last == date => day_tasks.push(task),
+                _ => days.push((date, vec![task])),
+            }
+        }
+        DailyLog { adapter, days }
+    }
+}
+
+/// Formats a heading per day, like `2024-01-02 (America/Chicago)`, then a line per task like
+/// `  09:00–10:30  write report`. A stop on a later day has its date, and a running task has
+/// none.
+impl fmt::Display for DailyLog {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut lines = Vec::new();
+        for (date, tasks) in &self.days {
+            lines.push(format!("{} ({})", date, self.adapter));
+            for task in tasks {
+                let start = self.adapter.to_local(task.started_at);
+                let stop = match task.tracked_until().map(|stop| self.adapter.to_local(stop)) {
+                    Some(stop) if stop.date() == *date => stop.format("%H:%M").to_string(),
+                    Some(stop) => stop.format("%Y-%m-%d %H:%M").to_string(),
+                    None => String::new(),
+                };
+                lines.push(format!(
+                    "  {}–{}  {}",
+                    start.format("%H:%M"),
+                    stop,
+                    task.name
+                ));
+            }
+        }
+        write!(f, "{}", lines.join("\n"))
+    }
+}
+
+#[cfg(test)]
+mod tests_normalize {
+    use super::*;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    /// 2024-01-01 23:00 UTC.
+    fn new_years_night() -> SystemTime {
+        UNIX_EPOCH + Duration::from_secs(1_704_150_000)
+    }
+
+    #[test]
+    fn days_follow_the_zone() {
+        // the Etc zones have their signs flipped: Etc/GMT-2 is UTC+2
+        let plus_two = TimezoneAdapter {
+            zone: "Etc/GMT-2".parse().unwrap(),
+        };
+        let january = |day| NaiveDate::from_ymd_opt(2024, 1, day).unwrap();
+        assert_eq!(TimezoneAdapter::utc().date(new_years_night()), january(1));
+        assert_eq!(plus_two.date(new_years_night()), january(2));
+    }
+
+    #[test]
+    fn groups_tasks_by_local_day() {
+        let start = new_years_night();
+        let tasks = [
+            StoredTask::new("late", start, start + Duration::from_secs(5400)),
+            StoredTask::new(
+                "earlier",
+                start - Duration::from_secs(7200),
+                start - Duration::from_secs(3600),
+            ),
+        ];
+        assert_eq!(
+            DailyLog::from_tasks(&tasks, TimezoneAdapter::utc()).to_string(),
+            "2024-01-01 (UTC)\n  \
+               21:00–22:00  earlier\n  \
+               23:00–2024-01-02 00:30  late"
+        );
+        let chicago = TimezoneAdapter {
+            zone: "America/Chicago".parse().unwrap(),
+        };
+        assert_eq!(
+            DailyLog::from_tasks(&tasks, chicago).to_string(),
+            "2024-01-01 (America/Chicago)\n  \
+               15:00–16:00  earlier\n  \
+               17:00–18:30  late"
+        );
+    }
+}
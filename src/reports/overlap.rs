@@ -0,0 +1,120 @@
+//! Detection of tasks whose time ranges overlap.
+
+use crate::storage::StoredTask;
+use std::{fmt, time::SystemTime};
+
+/// Two tasks that were running at the same time.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OverlapReport {
+    /// The name of the task that comes first in the history.
+    pub first: String,
+    /// The name of the task that comes second in the history.
+    pub second: String,
+    /// How long both tasks were running, in seconds.
+    pub seconds: u64,
+}
+
+/// Formats the overlap as `OVERLAP: 'task A' and 'task B' overlap by 0:14:30`.
+impl fmt::Display for OverlapReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "OVERLAP: '{}' and '{}' overlap by {}:{:02}:{:02}",
+            self.first,
+            self.second,
+            self.seconds / 3600,
+            self.seconds / 60 % 60,
+            self.seconds % 60
+        )
+    }
+}
+
+/// Finds every pair of completed tasks whose `[started_at, stopped_at]` ranges overlap.
+///
+/// Tasks that are still running are skipped, as are tasks that only touch (one stops at the
+/// second the other starts). Every pair is compared, which is fine for the size of a typical
+/// history.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::{Duration, UNIX_EPOCH};
+/// use timetracker::{reports::detect_overlaps, storage::StoredTask};
+///
+/// let nine = UNIX_EPOCH + Duration::from_secs(1_705_309_200);
+/// let tasks = [
+///     StoredTask::new("a", nine, nine + Duration::from_secs(1800)),
+///     StoredTask::new("b", nine + Duration::from_secs(930), nine + Duration::from_secs(3600)),
+/// ];
+/// let overlaps = detect_overlaps(&tasks);
+/// assert_eq!(overlaps[0].to_string(), "OVERLAP: 'a' and 'b' overlap by 0:14:30");
+/// ```
+pub fn detect_overlaps(tasks: &[StoredTask]) -> Vec<OverlapReport> {
+    let ranges: Vec<(&StoredTask, SystemTime, SystemTime)> = tasks
+        .iter()
+        .filter_map(|task| Some((task, task.started_at, task.stopped_at?)))
+        .collect();
+
+    let mut overlaps = Vec::new();
+    for (i, (first, first_start, first_end)) in ranges.iter().enumerate() {
+        for (second, second_start, second_end) in &ranges[i + 1..] {
+            let start = first_start.max(second_start);
+            let end = first_end.min(second_end);
+            if let Ok(duration) = end.duration_since(*start) {
+                if !duration.is_zero() {
+                    overlaps.push(OverlapReport {
+                        first: first.name.clone(),
+                        second: second.name.clone(),
+                        seconds: duration.as_secs(),
+                    });
+                }
+            }
+        }
+    }
+    overlaps
+}
+
+#[cfg(test)]
+mod tests_overlap {
+    use super::*;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    fn task(name: &str, start_minute: u64, end_minute: u64) -> StoredTask {
+        let nine = UNIX_EPOCH + Duration::from_secs(1_705_309_200);
+        StoredTask::new(
+            name,
+            nine + Duration::from_secs(start_minute * 60),
+            nine + Duration::from_secs(end_minute * 60),
+        )
+    }
+
+    #[test]
+    fn finds_overlapping_pairs_only() {
+        let mut running = task("running", 0, 100);
+        running.stopped_at = None;
+        let tasks = [
+            task("a", 0, 30),
+            task("b", 30, 60),
+            task("c", 20, 40),
+            task("d", 90, 100),
+            running,
+        ];
+
+        let overlaps = detect_overlaps(&tasks);
+        assert_eq!(
+            overlaps,
+            vec![
+                OverlapReport {
+                    first: String::from("a"),
+                    second: String::from("c"),
+                    seconds: 600,
+                },
+                OverlapReport {
+                    first: String::from("b"),
+                    second: String::from("c"),
+                    seconds: 600,
+                },
+            ]
+        );
+    }
+}
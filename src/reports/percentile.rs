@@ -0,0 +1,116 @@
+//! Which few tasks took most of the time, a Pareto analysis of the history.
+
+use crate::storage::StoredTask;
+use std::cmp::Reverse;
+
+/// The fewest (longest) tasks that together took at least a given share of the tracked time.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PercentileResult {
+    /// The name of the shortest of the tasks.
+    pub threshold_task_name: String,
+    /// How long the shortest of the tasks took, in seconds. Every task at least this long is
+    /// one of them, except for ties that weren't needed to reach the share.
+    pub threshold_secs: u64,
+    /// How many tasks it took.
+    pub task_count: usize,
+    /// The share of the tracked time the tasks took, from 0 to 100.
+    pub actual_percentage: f64,
+}
+
+/// Finds the tasks that took most of the time.
+pub struct PercentileAnalysis;
+
+impl PercentileAnalysis {
+    /// Takes tasks from the longest down, ties in name order like [`TopNReport`], until they add
+    /// up to at least `percentile` percent of the time of `tasks`.
+    ///
+    /// If nothing was tracked, no tasks are needed and the result is empty.
+    ///
+    /// [`TopNReport`]: super::TopNReport
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::{Duration, UNIX_EPOCH};
+    /// use timetracker::{reports::PercentileAnalysis, storage::StoredTask};
+    ///
+    /// let task = |name: &str, seconds: u64| {
+    ///     StoredTask::new(name, UNIX_EPOCH, UNIX_EPOCH + Duration::from_secs(seconds))
+    /// };
+    /// let tasks = [task("a", 50), task("b", 30), task("c", 15), task("d", 5)];
+    /// let result = PercentileAnalysis::compute(&tasks, 75);
+    /// assert_eq!(result.task_count, 2);
+    /// assert_eq!(result.threshold_task_name, "b");
+    /// assert_eq!(result.actual_percentage, 80.0);
+    /// ```
+    pub fn compute(tasks: &[StoredTask], percentile: u8) -> PercentileResult {
+        let mut sorted: Vec<&StoredTask> = tasks.iter().collect();
+        sorted.sort_by_key(|task| (Reverse(task.seconds), &task.name));
+        let total: u64 = sorted.iter().map(|task| task.seconds).sum();
+
+        let mut result = PercentileResult {
+            threshold_task_name: String::new(),
+            threshold_secs: 0,
+            task_count: 0,
+            actual_percentage: 0.0,
+        };
+        if total == 0 {
+            return result;
+        }
+        let target = u128::from(total) * u128::from(percentile.min(100));
+        let mut cumulative: u64 = 0;
+        for task in sorted {
+            if u128::from(cumulative) * 100 >= target {
+                break;
+            }
+            cumulative += task.seconds;
+            result.threshold_task_name = task.name.clone();
+            result.threshold_secs = task.seconds;
+            result.task_count += 1;
+        }
+        result.actual_percentage = cumulative as f64 / total as f64 * 100.0;
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests_percentile {
+    use super::*;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    fn task(name: &str, seconds: u64) -> StoredTask {
+        StoredTask::new(name, UNIX_EPOCH, UNIX_EPOCH + Duration::from_secs(seconds))
+    }
+
+    #[test]
+    fn one_task_can_exceed_the_percentile_alone() {
+        let tasks = [task("small", 100), task("huge", 900)];
+        let result = PercentileAnalysis::compute(&tasks, 75);
+        assert_eq!(
+            result,
+            PercentileResult {
+                threshold_task_name: String::from("huge"),
+                threshold_secs: 900,
+                task_count: 1,
+                actual_percentage: 90.0,
+            }
+        );
+    }
+
+    #[test]
+    fn stops_as_soon_as_the_percentile_is_reached() {
+        let tasks = [task("a", 25), task("b", 25), task("c", 25), task("d", 25)];
+        let result = PercentileAnalysis::compute(&tasks, 75);
+        assert_eq!(result.task_count, 3);
+        assert_eq!(result.threshold_task_name, "c");
+        assert_eq!(result.actual_percentage, 75.0);
+        assert_eq!(PercentileAnalysis::compute(&tasks, 100).task_count, 4);
+    }
+
+    #[test]
+    fn nothing_tracked_needs_no_tasks() {
+        let result = PercentileAnalysis::compute(&[task("empty", 0)], 75);
+        assert_eq!(result.task_count, 0);
+        assert_eq!(result.actual_percentage, 0.0);
+    }
+}
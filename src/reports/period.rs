@@ -0,0 +1,108 @@
+//! The stretches of time reports can cover.
+
+use super::WeeklyReport;
+use chrono::{Datelike, Duration, Months, NaiveDate, Weekday};
+use std::{fmt, ops::Range, str::FromStr};
+
+/// A stretch of time given with `--period`: `today`, `week`, an ISO week as `YYYY-Www`, or a
+/// month as `YYYY-MM`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Period {
+    /// The current day.
+    Today,
+    /// The current week.
+    Week,
+    /// The ISO week starting on the given Monday.
+    IsoWeek(NaiveDate),
+    /// The month starting on the given day.
+    Month(NaiveDate),
+}
+
+impl Period {
+    /// Gets the days in the period, given the current day and the day weeks start on.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chrono::{NaiveDate, Weekday};
+    /// use timetracker::reports::Period;
+    ///
+    /// let today = NaiveDate::from_ymd_opt(2024, 1, 17).unwrap();
+    /// let days = "2024-02".parse::<Period>().unwrap().dates(today, Weekday::Mon);
+    /// assert_eq!(days.start, NaiveDate::from_ymd_opt(2024, 2, 1).unwrap());
+    /// assert_eq!(days.end, NaiveDate::from_ymd_opt(2024, 3, 1).unwrap());
+    /// ```
+    pub fn dates(&self, today: NaiveDate, first_day: Weekday) -> Range<NaiveDate> {
+        match self {
+            Period::Today => today..today + Duration::days(1),
+            Period::Week => {
+                let week = WeeklyReport::for_week(today, first_day);
+                week.start..week.start + Duration::days(7)
+            }
+            Period::IsoWeek(monday) => *monday..*monday + Duration::days(7),
+            Period::Month(first) => *first..*first + Months::new(1),
+        }
+    }
+}
+
+impl FromStr for Period {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "today" => Ok(Period::Today),
+            "week" => Ok(Period::Week),
+            _ if s.contains('W') => NaiveDate::parse_from_str(&format!("{}-1", s), "%G-W%V-%u")
+                .map(Period::IsoWeek)
+                .map_err(|_| format!("expected an ISO week like 2024-W01, got '{}'", s)),
+            _ => NaiveDate::parse_from_str(&format!("{}-01", s), "%Y-%m-%d")
+                .map(Period::Month)
+                .map_err(|_| format!("expected today, week, YYYY-Www, or YYYY-MM, got '{}'", s)),
+        }
+    }
+}
+
+/// Formats the period the way it is written on the command line.
+impl fmt::Display for Period {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Period::Today => write!(f, "today"),
+            Period::Week => write!(f, "week"),
+            Period::IsoWeek(monday) => write!(f, "{}", monday.format("%G-W%V")),
+            Period::Month(first) => write!(f, "{}-{:02}", first.year(), first.month()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests_period {
+    use super::*;
+
+    #[test]
+    fn parses_and_formats_periods() {
+        for period in ["today", "week", "2024-01", "2024-W01", "2020-W53"] {
+            assert_eq!(period.parse::<Period>().unwrap().to_string(), period);
+        }
+        assert!("yesterday".parse::<Period>().is_err());
+    }
+
+    #[test]
+    fn week_follows_first_day_of_week() {
+        let wednesday = NaiveDate::from_ymd_opt(2024, 1, 17).unwrap();
+        let days = Period::Week.dates(wednesday, Weekday::Sun);
+        assert_eq!(days.start, NaiveDate::from_ymd_opt(2024, 1, 14).unwrap());
+        assert_eq!(days.end, NaiveDate::from_ymd_opt(2024, 1, 21).unwrap());
+    }
+
+    #[test]
+    fn iso_weeks_start_on_monday_across_years() {
+        let sunday = NaiveDate::from_ymd_opt(2024, 1, 14).unwrap();
+        let days = "2025-W01"
+            .parse::<Period>()
+            .unwrap()
+            .dates(sunday, Weekday::Sun);
+        assert_eq!(days.start, NaiveDate::from_ymd_opt(2024, 12, 30).unwrap());
+        assert_eq!(days.end, NaiveDate::from_ymd_opt(2025, 1, 6).unwrap());
+        assert!("2024-W54".parse::<Period>().is_err());
+    }
+}
@@ -0,0 +1,212 @@
+//! Arbitrary stretches of time given with `--since` and `--until`.
+
+use super::local_time;
+use crate::storage::StoredTask;
+use chrono::{Duration, Local, NaiveDate, NaiveDateTime, NaiveTime};
+use std::time::SystemTime;
+
+/// The tasks started between two points in time, either of which may be left open.
+///
+/// Every report is filtered by the range before anything else, so it combines with the other
+/// filters (and with `--top` and `--client`) like any of them.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct DateRange {
+    /// The earliest a task may have started, if there is a limit.
+    pub start: Option<SystemTime>,
+    /// The time tasks must have started before, if there is a limit.
+    pub end: Option<SystemTime>,
+}
+
+impl DateRange {
+    /// Checks whether `task` started in the range.
+    ///
+    /// Tasks that started before the end but stopped after it (or are still running) are in
+    /// the range, like the other reports count a task towards the day it started on.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::{Duration, UNIX_EPOCH};
+    /// use timetracker::{reports::DateRange, storage::StoredTask};
+    ///
+    /// let at = |secs| UNIX_EPOCH + Duration::from_secs(secs);
+    /// let range = DateRange { start: Some(at(100)), end: Some(at(200)) };
+    /// assert!(range.contains(&StoredTask::new("spans the end", at(150), at(250))));
+    /// assert!(!range.contains(&StoredTask::new("spans the start", at(50), at(150))));
+    /// ```
+    pub fn contains(&self, task: &StoredTask) -> bool {
+        self.start.is_none_or(|start| task.started_at >= start)
+            && self.end.is_none_or(|end| task.started_at < end)
+    }
+
+    /// Checks whether no task can be in the range, because it ends before it starts.
+    pub fn is_empty(&self) -> bool {
+        matches!((self.start, self.end), (Some(start), Some(end)) if end <= start)
+    }
+}
+
+/// Parses the start of a range, a local `YYYY-MM-DD` date (from midnight),
+/// `YYYY-MM-DDTHH:MM:SS` date and time, `YYYY-Www` ISO week (from Monday), or a number of days
+/// before today like `30d` (from midnight that day).
+pub fn parse_since(value: &str) -> Result<SystemTime, String> {
+    match parse_bound(value)? {
+        Bound::Date(date) | Bound::Week(date) => Ok(local_time(date, NaiveTime::MIN)),
+        Bound::DateTime(datetime) => Ok(local_time(datetime.date(), datetime.time())),
+    }
+}
+
+/// Parses the end of a range like [`parse_since`]. The end is included: a date covers the
+/// whole day, a date and time the whole second, and a week all seven days.
+pub fn parse_until(value: &str) -> Result<SystemTime, String> {
+    match parse_bound(value)? {
+        Bound::Date(date) => Ok(local_time(date + Duration::days(1), NaiveTime::MIN)),
+        Bound::Week(monday) => Ok(local_time(monday + Duration::days(7), NaiveTime::MIN)),
+        Bound::DateTime(datetime) => {
+            let next_second = datetime + Duration::seconds(1);
+            Ok(local_time(next_second.date(), next_second.time()))
+        }
+    }
+}
+
+/// Parses an ISO week, e.g. `2024-W03`, into its Monday.
+///
+/// # Examples
+///
+/// ```
+/// use chrono::NaiveDate;
+/// use timetracker::reports::range::parse_week;
+///
+/// assert_eq!(parse_week("2024-W03"), Ok(NaiveDate::from_ymd_opt(2024, 1, 15).unwrap()));
+/// assert!(parse_week("2024-W54").is_err());
+/// ```
+pub fn parse_week(value: &str) -> Result<NaiveDate, String> {
+    NaiveDate::parse_from_str(&format!("{}-1", value), "%G-W%V-%u")
+        .map_err(|_| format!("expected an ISO week like 2024-W03, got '{}'", value))
+}
+
+/// A limit of a range as it was written.
+enum Bound {
+    Date(NaiveDate),
+    DateTime(NaiveDateTime),
+    /// The Monday of an ISO week.
+    Week(NaiveDate),
+}
+
+/// Parses a date, a date and time, a week, or a number of days ago.
+fn parse_bound(value: &str) -> Result<Bound, String> {
+    if let Ok(date) = NaiveDate::parse_from_str(value, "%Y-%m-%d") {
+        return Ok(Bound::Date(date));
+    }
+    if let Some(days) = value
+        .strip_suffix('d')
+        .and_then(|days| days.parse::<u32>().ok())
+    {
+        let today = Local::now().date_naive();
+        return today
+            .checked_sub_signed(Duration::days(i64::from(days)))
+            .map(Bound::Date)
+            .ok_or_else(|| format!("{} days ago is too long ago", days));
+    }
+    if let Ok(monday) = parse_week(value) {
+        return Ok(Bound::Week(monday));
+    }
+    NaiveDateTime::parse_from_str(value, "%Y-%m-%dT%H:%M:%S")
+        .map(Bound::DateTime)
+        .map_err(|_| {
+            format!(
+                "expected YYYY-MM-DD, YYYY-MM-DDTHH:MM:SS, YYYY-Www, or a number of days like \
+                 30d, got '{}'",
+                value
+            )
+        })
+}
+
+#[cfg(test)]
+mod tests_range {
+    use super::*;
+
+    fn at(day: u32, hour: u32, minute: u32, second: u32) -> SystemTime {
+        local_time(
+            NaiveDate::from_ymd_opt(2024, 1, day).unwrap(),
+            NaiveTime::from_hms_opt(hour, minute, second).unwrap(),
+        )
+    }
+
+    fn task(start: SystemTime, secs: u64) -> StoredTask {
+        StoredTask::new("task", start, start + std::time::Duration::from_secs(secs))
+    }
+
+    #[test]
+    fn dates_cover_whole_days() {
+        let range = DateRange {
+            start: Some(parse_since("2024-01-01").unwrap()),
+            end: Some(parse_until("2024-01-31").unwrap()),
+        };
+        assert!(range.contains(&task(at(1, 0, 0, 0), 60)));
+        assert!(range.contains(&task(at(31, 23, 59, 59), 3600)));
+        assert!(!range.contains(&task(
+            at(1, 0, 0, 0) - std::time::Duration::from_secs(1),
+            60
+        )));
+        assert!(!range.contains(&task(
+            at(31, 23, 59, 59) + std::time::Duration::from_secs(1),
+            60
+        )));
+    }
+
+    #[test]
+    fn datetimes_include_the_given_second() {
+        let range = DateRange {
+            start: Some(parse_since("2024-01-15T09:00:00").unwrap()),
+            end: Some(parse_until("2024-01-15T17:30:00").unwrap()),
+        };
+        assert!(!range.contains(&task(at(15, 8, 59, 59), 60)));
+        assert!(range.contains(&task(at(15, 9, 0, 0), 60)));
+        assert!(range.contains(&task(at(15, 17, 30, 0), 60)));
+        assert!(!range.contains(&task(at(15, 17, 30, 1), 60)));
+    }
+
+    #[test]
+    fn weeks_run_from_monday_to_sunday() {
+        // 2024-W03 is January 15 to 21
+        let range = DateRange {
+            start: Some(parse_since("2024-W03").unwrap()),
+            end: Some(parse_until("2024-W03").unwrap()),
+        };
+        assert!(!range.contains(&task(at(14, 23, 59, 59), 60)));
+        assert!(range.contains(&task(at(15, 0, 0, 0), 60)));
+        assert!(range.contains(&task(at(21, 23, 59, 59), 60)));
+        assert!(!range.contains(&task(at(22, 0, 0, 0), 60)));
+    }
+
+    #[test]
+    fn days_ago_count_back_from_today() {
+        let today = Local::now().date_naive();
+        assert_eq!(
+            parse_since("30d"),
+            Ok(local_time(today - Duration::days(30), NaiveTime::MIN))
+        );
+        assert_eq!(
+            parse_until("0d"),
+            Ok(local_time(today + Duration::days(1), NaiveTime::MIN))
+        );
+    }
+
+    #[test]
+    fn open_ranges_and_invalid_bounds() {
+        assert!(DateRange::default().contains(&task(at(15, 12, 0, 0), 60)));
+        let since = DateRange {
+            start: Some(parse_since("2024-01-16").unwrap()),
+            end: None,
+        };
+        assert!(!since.contains(&task(at(15, 12, 0, 0), 60)));
+        assert!(DateRange {
+            start: since.start,
+            end: Some(parse_until("2024-01-15").unwrap()),
+        }
+        .is_empty());
+        assert!(parse_since("01/15/2024").is_err());
+        assert!(parse_since("d").is_err());
+        assert!(parse_until("2024-01-15 17:30").is_err());
+    }
+}
@@ -0,0 +1,147 @@
+//! How quickly tasks get done.
+
+use crate::{format_duration_compact, storage::StoredTask};
+use std::{collections::HashSet, fmt};
+
+/// Throughput over a period: how many tasks were completed and how long they took.
+#[derive(Clone, Debug, PartialEq)]
+pub struct WorkRateReport {
+    /// The period covered, as given on the command line.
+    pub period: String,
+    /// The number of distinct tasks completed: several sessions of a task with the same name
+    /// count once.
+    pub task_count: usize,
+    /// The total time tracked on completed tasks, in hours.
+    pub total_hours: f64,
+    /// The average time a distinct completed task took, in seconds.
+    pub avg_task_duration_secs: u64,
+    /// Distinct completed tasks per hour between the first task starting and the last one
+    /// stopping.
+    pub tasks_per_hour: f64,
+}
+
+impl WorkRateReport {
+    /// The fewest completed tasks the rates are shown for.
+    pub const MIN_TASKS: usize = 2;
+
+    /// Measures the tasks completed in `tasks`, which should already be limited to the period.
+    ///
+    /// Tasks that are still running are left out, and tasks with the same name are counted as
+    /// one.
+    pub fn from_tasks(period: &str, tasks: &[StoredTask]) -> WorkRateReport {
+        let completed: Vec<&StoredTask> = tasks
+            .iter()
+            .filter(|task| task.stopped_at.is_some())
+            .collect();
+        let task_count = completed
+            .iter()
+            .map(|task| task.name.as_str())
+            .collect::<HashSet<&str>>()
+            .len();
+        let total_secs: u64 = completed.iter().map(|task| task.seconds).sum();
+        let first_start = completed.iter().map(|task| task.started_at).min();
+        let last_stop = completed.iter().filter_map(|task| task.stopped_at).max();
+        let elapsed_hours = match (first_start, last_stop) {
+            (Some(start), Some(stop)) => {
+                stop.duration_since(start).unwrap_or_default().as_secs_f64() / 3600.0
+            }
+            _ => 0.0,
+        };
+        WorkRateReport {
+            period: period.to_string(),
+            task_count,
+            total_hours: total_secs as f64 / 3600.0,
+            avg_task_duration_secs: total_secs
+                .checked_div(task_count as u64)
+                .unwrap_or_default(),
+            tasks_per_hour: if elapsed_hours > 0.0 {
+                task_count as f64 / elapsed_hours
+            } else {
+                0.0
+            },
+        }
+    }
+
+    /// Whether enough tasks were completed for the rates to mean anything.
+    pub fn has_enough_data(&self) -> bool {
+        self.task_count >= Self::MIN_TASKS
+    }
+}
+
+/// Formats the report as one metric per line, with `Insufficient data` in place of the rates
+/// when fewer than [`MIN_TASKS`](WorkRateReport::MIN_TASKS) tasks were completed.
+impl fmt::Display for WorkRateReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "Period: {}", self.period)?;
+        writeln!(f, "Tasks completed: {}", self.task_count)?;
+        writeln!(f, "Hours tracked: {:.2}", self.total_hours)?;
+        if !self.has_enough_data() {
+            return write!(f, "Insufficient data");
+        }
+        writeln!(
+            f,
+            "Average task duration: {}",
+            format_duration_compact(self.avg_task_duration_secs)
+        )?;
+        write!(f, "Tasks per hour: {:.2}", self.tasks_per_hour)
+    }
+}
+
+#[cfg(test)]
+mod tests_rate {
+    use super::*;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    fn task(name: &str, start_minute: u64, minutes: u64) -> StoredTask {
+        let started_at = UNIX_EPOCH + Duration::from_secs(1_700_000_000 + start_minute * 60);
+        StoredTask::new(
+            name,
+            started_at,
+            started_at + Duration::from_secs(minutes * 60),
+        )
+    }
+
+    #[test]
+    fn measures_completed_tasks() {
+        let mut running = task("d", 200, 0);
+        running.stopped_at = None;
+        let tasks = [
+            task("a", 0, 30),
+            task("b", 30, 60),
+            task("c", 90, 30),
+            running,
+        ];
+        let report = WorkRateReport::from_tasks("today", &tasks);
+        assert_eq!(report.task_count, 3);
+        assert_eq!(report.total_hours, 2.0);
+        assert_eq!(report.avg_task_duration_secs, 40 * 60);
+        assert_eq!(report.tasks_per_hour, 1.5);
+    }
+
+    #[test]
+    fn one_task_is_not_enough() {
+        let report = WorkRateReport::from_tasks("today", &[task("a", 0, 30)]);
+        assert_eq!(
+            report.to_string(),
+            "Period: today\nTasks completed: 1\nHours tracked: 0.50\nInsufficient data"
+        );
+    }
+
+    #[test]
+    fn counts_each_task_name_once() {
+        let tasks = [
+            task("a", 0, 30),
+            task("b", 30, 30),
+            task("a", 60, 30),
+            task("a", 90, 30),
+        ];
+        let report = WorkRateReport::from_tasks("today", &tasks);
+        assert_eq!(report.task_count, 2);
+        assert_eq!(report.total_hours, 2.0);
+        assert_eq!(report.avg_task_duration_secs, 60 * 60);
+        assert_eq!(report.tasks_per_hour, 1.0);
+
+        let report = WorkRateReport::from_tasks("today", &[task("a", 0, 30), task("a", 30, 30)]);
+        assert!(!report.has_enough_data());
+    }
+}
@@ -0,0 +1,167 @@
+//! Rolling averages of the time tracked each day.
+
+use super::{local_date, sparkline};
+use crate::{format_duration_compact, storage::StoredTask};
+use chrono::{Duration, NaiveDate};
+use std::collections::HashMap;
+
+/// The daily totals of every day from the first day with a task to the last, with the average
+/// of each day and the days before it.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RollingAverage {
+    /// How many days each average covers, counting the day itself.
+    pub window_size: usize,
+    /// The seconds tracked on each day, in order. Days with nothing tracked are 0.
+    pub daily_totals: Vec<(NaiveDate, u64)>,
+}
+
+impl RollingAverage {
+    /// Adds up the seconds tracked on each day (in local time) between the first and last task.
+    pub fn from_tasks(tasks: &[StoredTask], window_size: usize) -> RollingAverage {
+        let mut totals: HashMap<NaiveDate, u64> = HashMap::new();
+        for task in tasks {
+            *totals.entry(local_date(task.started_at)).or_default() += task.seconds;
+        }
+        let mut daily_totals = Vec::new();
+        if let (Some(&first), Some(&last)) = (totals.keys().min(), totals.keys().max()) {
+            let mut date = first;
+            while date <= last {
+                daily_totals.push((date, totals.get(&date).copied().unwrap_or_default()));
+                date += Duration::days(1);
+            }
+        }
+        RollingAverage {
+            window_size: window_size.max(1),
+            daily_totals,
+        }
+    }
+
+    /// Gets the average seconds a day over the window ending on each day, rounded down.
+    ///
+    /// Until there are enough days to fill the window, the average is over the days so far.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chrono::NaiveDate;
+    /// use timetracker::reports::RollingAverage;
+    ///
+    /// let day = |day| NaiveDate::from_ymd_opt(2024, 1, day).unwrap();
+    /// let rolling = RollingAverage {
+    ///     window_size: 2,
+    ///     daily_totals: vec![(day(1), 3600), (day(2), 0), (day(3), 1800)],
+    /// };
+    /// assert_eq!(rolling.averages(), [3600, 1800, 900]);
+    /// ```
+    pub fn averages(&self) -> Vec<u64> {
+        let mut sum = 0;
+        self.daily_totals
+            .iter()
+            .enumerate()
+            .map(|(day, (_, seconds))| {
+                sum += seconds;
+                if day >= self.window_size {
+                    sum -= self.daily_totals[day - self.window_size].1;
+                }
+                sum / (day + 1).min(self.window_size) as u64
+            })
+            .collect()
+    }
+
+    /// Draws a row per day, e.g. `2024-01-15 | actual: 6h | 7d avg: 5h23m`, with the columns
+    /// lined up. With `sparkline`, each row starts with a block character scaled to the
+    /// highest average.
+    pub fn render(&self, sparkline: bool) -> String {
+        let averages = self.averages();
+        let actuals: Vec<String> = self
+            .daily_totals
+            .iter()
+            .map(|(_, seconds)| format_duration_compact(*seconds))
+            .collect();
+        let width = actuals
+            .iter()
+            .map(|actual| actual.len())
+            .max()
+            .unwrap_or_default();
+        let max = averages.iter().copied().max().unwrap_or_default();
+        let rows: Vec<String> = self
+            .daily_totals
+            .iter()
+            .zip(actuals)
+            .zip(&averages)
+            .map(|(((date, _), actual), average)| {
+                let row = format!(
+                    "{} | actual: {:<width$} | {}d avg: {}",
+                    date,
+                    actual,
+                    self.window_size,
+                    format_duration_compact(*average)
+                );
+                if sparkline {
+                    format!("{} {}", sparkline::block(*average, max), row)
+                } else {
+                    row
+                }
+            })
+            .collect();
+        rows.join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests_rolling {
+    use super::*;
+    use crate::reports::local_time;
+    use chrono::NaiveTime;
+
+    fn day(day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(2024, 1, day).unwrap()
+    }
+
+    fn task(date: NaiveDate, hours: u64) -> StoredTask {
+        let start = local_time(date, NaiveTime::from_hms_opt(9, 0, 0).unwrap());
+        StoredTask::new(
+            "task",
+            start,
+            start + std::time::Duration::from_secs(hours * 3600),
+        )
+    }
+
+    #[test]
+    fn averages_two_weeks_over_seven_days() {
+        // 1 hour more each day of the first week, then nothing on the 8th and 6 hours after
+        let mut tasks: Vec<StoredTask> = (1..=7).map(|d| task(day(d), u64::from(d))).collect();
+        tasks.extend((9..=14).map(|d| task(day(d), 6)));
+        let rolling = RollingAverage::from_tasks(&tasks, 7);
+        assert_eq!(rolling.daily_totals.len(), 14);
+        assert_eq!(rolling.daily_totals[7], (day(8), 0));
+
+        let averages = rolling.averages();
+        // day 7: (1 + 2 + ... + 7) / 7 hours
+        assert_eq!(averages[6], 4 * 3600);
+        // day 8: (2 + ... + 7 + 0) / 7 hours, rounded down to the second
+        assert_eq!(averages[7], 27 * 3600 / 7);
+        // day 9: (3 + ... + 7 + 0 + 6) / 7 hours
+        assert_eq!(averages[8], 31 * 3600 / 7);
+        // day 14: the 0 is the oldest day left in the window
+        assert_eq!(averages[13], 36 * 3600 / 7);
+        // the first days average the days so far
+        assert_eq!(averages[1], 5400);
+    }
+
+    #[test]
+    fn renders_aligned_rows() {
+        let rolling = RollingAverage {
+            window_size: 2,
+            daily_totals: vec![(day(1), 36000), (day(2), 0), (day(3), 1800)],
+        };
+        assert_eq!(
+            rolling.render(false),
+            "2024-01-01 | actual: 10h | 2d avg: 10h\n\
+             2024-01-02 | actual: 0s  | 2d avg: 5h\n\
+             2024-01-03 | actual: 30m | 2d avg: 15m"
+        );
+        assert!(rolling.render(true).starts_with("█ 2024-01-01"));
+        assert_eq!(RollingAverage::from_tasks(&[], 7).render(false), "");
+    }
+}
@@ -0,0 +1,245 @@
+//! How time flows from tasks to projects to clients, as a text Sankey diagram.
+
+use crate::{format_duration_compact, storage::StoredTask};
+use std::collections::HashMap;
+
+/// The label of the project node for tasks without a project.
+pub const NO_PROJECT: &str = "(No Project)";
+
+/// The label of the client node for tasks without a client.
+pub const NO_CLIENT: &str = "(No Client)";
+
+/// The arrow drawn between the columns.
+const ARROW: &str = " ──→ ";
+
+/// A box in one column of the diagram.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SankeyNode {
+    /// What the node is, e.g. the name of a task.
+    pub label: String,
+    /// The seconds flowing through the node.
+    pub seconds: u64,
+    /// The node of the next column this one flows into, as an index into it, or `None` in the
+    /// last column.
+    pub target: Option<usize>,
+}
+
+/// Lays out columns of nodes, each flowing into a node of the next column, with bars as wide
+/// as their share of the total time.
+///
+/// Every row of the diagram is the path from a node of the first column to the last column.
+/// A node reached by several rows is labelled on each, but its bar is only drawn on the first,
+/// so the bars of a column add up to the total.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SankeyRenderer {
+    /// How wide the diagram is, in characters, which sets how long the bars can be.
+    pub width: usize,
+    /// The columns, from the first (the tasks) to the last (the clients).
+    pub levels: Vec<Vec<SankeyNode>>,
+}
+
+impl SankeyRenderer {
+    /// Lays out the flow of time from each task name, to the project of the tasks, to their
+    /// client. The nodes of each column are grouped under the node they flow into, and ordered
+    /// by their time.
+    pub fn from_tasks(tasks: &[StoredTask], width: usize) -> SankeyRenderer {
+        type Path<'a> = (&'a str, &'a str, &'a str);
+        let mut totals: HashMap<Path, u64> = HashMap::new();
+        for task in tasks {
+            let client = task.client.as_deref().unwrap_or(NO_CLIENT);
+            let project = task.project.as_deref().unwrap_or(NO_PROJECT);
+            *totals.entry((client, project, &task.name)).or_default() += task.seconds;
+        }
+
+        // adds up the seconds under each key, most time first
+        fn sorted<'a>(entries: impl Iterator<Item = (&'a str, u64)>) -> Vec<(&'a str, u64)> {
+            let mut sums: HashMap<&str, u64> = HashMap::new();
+            for (key, seconds) in entries {
+                *sums.entry(key).or_default() += seconds;
+            }
+            let mut sums: Vec<(&str, u64)> = sums.into_iter().collect();
+            sums.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(b.0)));
+            sums
+        }
+
+        let mut levels = vec![Vec::new(), Vec::new(), Vec::new()];
+        let clients = sorted(totals.iter().map(|(path, secs)| (path.0, *secs)));
+        for (client, client_secs) in clients {
+            let client_index = levels[2].len();
+            levels[2].push(node(client, client_secs, None));
+            let projects = sorted(
+                totals
+                    .iter()
+                    .filter(|(path, _)| path.0 == client)
+                    .map(|(path, secs)| (path.1, *secs)),
+            );
+            for (project, project_secs) in projects {
+                let project_index = levels[1].len();
+                levels[1].push(node(project, project_secs, Some(client_index)));
+                let names = sorted(
+                    totals
+                        .iter()
+                        .filter(|(path, _)| path.0 == client && path.1 == project)
+                        .map(|(path, secs)| (path.2, *secs)),
+                );
+                for (name, secs) in names {
+                    levels[0].push(node(name, secs, Some(project_index)));
+                }
+            }
+        }
+        SankeyRenderer { width, levels }
+    }
+
+    /// Gets the length of the bar of a node of `seconds` when the whole flow is `max_bar`
+    /// characters long. Nodes with any time get at least one character.
+    pub fn bar_length(&self, seconds: u64, max_bar: usize) -> usize {
+        let total: u64 = self
+            .levels
+            .first()
+            .map_or(0, |level| level.iter().map(|node| node.seconds).sum());
+        if total == 0 || seconds == 0 {
+            return 0;
+        }
+        let length = (seconds as f64 / total as f64 * max_bar as f64).round() as usize;
+        length.max(1)
+    }
+
+    /// Draws the diagram, e.g. `task A (2h) ██ ──→ project X (5h) █████ ──→ ACME (8h) ████████`.
+    pub fn render(&self) -> String {
+        let label = |node: &SankeyNode| {
+            format!("{} ({})", node.label, format_duration_compact(node.seconds))
+        };
+        let label_widths: Vec<usize> = self
+            .levels
+            .iter()
+            .map(|level| {
+                level
+                    .iter()
+                    .map(|node| label(node).chars().count())
+                    .max()
+                    .unwrap_or_default()
+            })
+            .collect();
+        let columns = self.levels.len().max(1);
+        let fixed =
+            label_widths.iter().sum::<usize>() + (columns - 1) * ARROW.chars().count() + columns;
+        let max_bar = (self.width.saturating_sub(fixed) / columns).max(1);
+
+        let mut lines = Vec::new();
+        let mut drawn = vec![Vec::new(); self.levels.len()];
+        for (leaf, _) in self.levels.first().into_iter().flatten().enumerate() {
+            let mut cells = Vec::new();
+            let mut index = Some(leaf);
+            for (level, nodes) in self.levels.iter().enumerate() {
+                let Some(node) = index.and_then(|index| nodes.get(index)) else {
+                    break;
+                };
+                let bar = if drawn[level].contains(&index) {
+                    0
+                } else {
+                    drawn[level].push(index);
+                    self.bar_length(node.seconds, max_bar)
+                };
+                let text = label(node);
+                let padding = label_widths[level] - text.chars().count();
+                cells.push(format!(
+                    "{}{} {}{}",
+                    text,
+                    " ".repeat(padding),
+                    "█".repeat(bar),
+                    " ".repeat(max_bar - bar.min(max_bar))
+                ));
+                index = node.target;
+            }
+            lines.push(cells.join(ARROW).trim_end().to_string());
+        }
+        lines.join("\n")
+    }
+}
+
+/// Creates a node.
+fn node(label: &str, seconds: u64, target: Option<usize>) -> SankeyNode {
+    SankeyNode {
+        label: label.to_string(),
+        seconds,
+        target,
+    }
+}
+
+#[cfg(test)]
+mod tests_sankey {
+    use super::*;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    #[test]
+    fn bars_are_proportional_to_time() {
+        // two tasks flowing into one project
+        let renderer = SankeyRenderer {
+            width: 0,
+            levels: vec![
+                vec![node("a", 3600, Some(0)), node("b", 10800, Some(0))],
+                vec![node("X", 14400, None)],
+            ],
+        };
+        assert_eq!(renderer.bar_length(3600, 8), 2);
+        assert_eq!(renderer.bar_length(10800, 8), 6);
+        assert_eq!(renderer.bar_length(14400, 8), 8);
+        assert_eq!(renderer.bar_length(60, 8), 1);
+
+        // 2 labels of 6 characters, an arrow of 5, and a space before each bar leave 8 per bar
+        let renderer = SankeyRenderer {
+            width: 35,
+            ..renderer
+        };
+        assert_eq!(
+            renderer.render(),
+            "a (1h) ██       ──→ X (4h) ████████\n\
+             b (3h) ██████   ──→ X (4h)"
+        );
+    }
+
+    #[test]
+    fn flows_from_tasks_to_projects_to_clients() {
+        let task = |name: &str, hours: u64, project: Option<&str>, client: Option<&str>| {
+            let mut task = StoredTask::new(
+                name,
+                UNIX_EPOCH,
+                UNIX_EPOCH + Duration::from_secs(hours * 3600),
+            );
+            task.project = project.map(String::from);
+            task.client = client.map(String::from);
+            task
+        };
+        let tasks = [
+            task("design", 2, Some("website"), Some("ACME")),
+            task("code", 3, Some("website"), Some("ACME")),
+            task("code", 3, Some("app"), Some("ACME")),
+            task("email", 1, None, None),
+        ];
+        let renderer = SankeyRenderer::from_tasks(&tasks, 80);
+        let labels = |level: usize| -> Vec<(&str, u64, Option<usize>)> {
+            renderer.levels[level]
+                .iter()
+                .map(|node| (node.label.as_str(), node.seconds / 3600, node.target))
+                .collect()
+        };
+        assert_eq!(
+            labels(0),
+            [
+                ("code", 3, Some(0)),
+                ("design", 2, Some(0)),
+                ("code", 3, Some(1)),
+                ("email", 1, Some(2)),
+            ]
+        );
+        assert_eq!(
+            labels(1),
+            [
+                ("website", 5, Some(0)),
+                ("app", 3, Some(0)),
+                (NO_PROJECT, 1, Some(1)),
+            ]
+        );
+        assert_eq!(labels(2), [("ACME", 8, None), (NO_CLIENT, 1, None)]);
+    }
+}
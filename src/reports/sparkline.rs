@@ -0,0 +1,152 @@
+//! One-line sparklines of daily effort.
+
+use super::WeeklyReport;
+use crate::storage::StoredTask;
+use chrono::{Duration, NaiveDate};
+use std::fmt;
+
+/// The block characters used for days with tracked time, from least to most.
+const BLOCKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+/// The character used for days with no tracked time.
+const EMPTY: char = '░';
+
+/// A sparkline showing the relative effort of each day of a week.
+pub struct Sparkline;
+
+impl Sparkline {
+    /// Turns a week of daily totals (in seconds) into 7 block characters.
+    ///
+    /// Each day is scaled relative to the busiest day, which is always `█`. Days with nothing
+    /// tracked are shown as `░`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use timetracker::reports::Sparkline;
+    ///
+    /// let sparkline = Sparkline::from_daily_totals([1800, 3600, 0, 0, 0, 0, 0]);
+    /// assert_eq!(sparkline, "▄█░░░░░");
+    /// ```
+    pub fn from_daily_totals(totals: [u64; 7]) -> String {
+        let max = totals.iter().copied().max().unwrap_or_default();
+        totals.iter().map(|&seconds| block(seconds, max)).collect()
+    }
+}
+
+/// Gets the block character for `seconds` on a scale where `max` is `█`, or `░` for nothing.
+pub(crate) fn block(seconds: u64, max: u64) -> char {
+    if seconds == 0 || max == 0 {
+        return EMPTY;
+    }
+    let level = (seconds.min(max) * BLOCKS.len() as u64).div_ceil(max);
+    BLOCKS[level as usize - 1]
+}
+
+/// Sparklines for several consecutive weeks, one row per week.
+pub struct MultiWeekSparkline {
+    /// The first day of the oldest week.
+    pub start: NaiveDate,
+    /// The daily totals (in seconds) of each week, oldest first.
+    pub weeks: Vec<[u64; 7]>,
+}
+
+impl MultiWeekSparkline {
+    /// Gets the daily totals of the `count` weeks up to and including `last`.
+    pub fn for_weeks(tasks: &[StoredTask], last: WeeklyReport, count: usize) -> MultiWeekSparkline {
+        let start = last.start - Duration::weeks(count.saturating_sub(1) as i64);
+        let weeks = (0..count)
+            .map(|week| {
+                WeeklyReport {
+                    start: start + Duration::weeks(week as i64),
+                }
+                .daily_totals(tasks)
+            })
+            .collect();
+        MultiWeekSparkline { start, weeks }
+    }
+
+    /// Adds up the totals of each day of the week across all the weeks.
+    pub fn grand_totals(&self) -> [u64; 7] {
+        let mut totals = [0; 7];
+        for week in &self.weeks {
+            for (total, seconds) in totals.iter_mut().zip(week) {
+                *total += seconds;
+            }
+        }
+        totals
+    }
+}
+
+/// Formats one row per week, labelled with its ISO week, e.g. `2024-W03 ▂▆▇▄█░░`, followed by a
+/// `Total` row of the weeks added together.
+///
+/// Each week is scaled on its own, so its busiest day is always `█`.
+impl fmt::Display for MultiWeekSparkline {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (week, totals) in self.weeks.iter().enumerate() {
+            // the middle of the week decides which ISO week it is, whatever day it starts on
+            let middle = self.start + Duration::weeks(week as i64) + Duration::days(3);
+            writeln!(
+                f,
+                "{} {}",
+                middle.format("%G-W%V"),
+                Sparkline::from_daily_totals(*totals)
+            )?;
+        }
+        write!(
+            f,
+            "{:<8} {}",
+            "Total",
+            Sparkline::from_daily_totals(self.grand_totals())
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests_sparkline {
+    use super::*;
+
+    #[test]
+    fn multi_week_has_a_row_per_week_and_a_total() {
+        let sparkline = MultiWeekSparkline {
+            start: NaiveDate::from_ymd_opt(2024, 1, 1).unwrap(),
+            weeks: vec![[3600, 0, 0, 0, 0, 0, 0], [0, 1800, 0, 0, 0, 0, 0], [0; 7]],
+        };
+        assert_eq!(
+            sparkline.to_string(),
+            "2024-W01 █░░░░░░\n2024-W02 ░█░░░░░\n2024-W03 ░░░░░░░\nTotal    █▄░░░░░"
+        );
+    }
+
+    #[test]
+    fn multi_week_ends_with_the_given_week() {
+        let last = WeeklyReport {
+            start: NaiveDate::from_ymd_opt(2024, 1, 15).unwrap(),
+        };
+        let sparkline = MultiWeekSparkline::for_weeks(&[], last, 4);
+        assert_eq!(
+            sparkline.start,
+            NaiveDate::from_ymd_opt(2023, 12, 25).unwrap()
+        );
+        assert_eq!(sparkline.to_string().lines().count(), 5);
+    }
+
+    #[test]
+    fn all_zeros_are_empty() {
+        assert_eq!(Sparkline::from_daily_totals([0; 7]), "░░░░░░░");
+    }
+
+    #[test]
+    fn all_equal_are_full() {
+        assert_eq!(Sparkline::from_daily_totals([3600; 7]), "███████");
+    }
+
+    #[test]
+    fn scales_relative_to_the_busiest_day() {
+        assert_eq!(
+            Sparkline::from_daily_totals([1, 100, 200, 400, 700, 800, 0]),
+            "▁▁▂▄▇█░"
+        );
+    }
+}
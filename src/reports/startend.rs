@@ -0,0 +1,252 @@
+//! When the work day usually starts and ends: the first task of each day and the last one.
+
+use super::local_date;
+use crate::storage::StoredTask;
+use chrono::{DateTime, Local, NaiveDate, NaiveTime, Timelike};
+use std::{collections::BTreeMap, f64::consts::TAU, fmt, time::SystemTime};
+
+/// The seconds in a day.
+const DAY_SECS: i64 = 86_400;
+
+/// The start of the first task and the end of the last task of each day with tasks.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StartEndAnalyzer {
+    /// When the first task of each day started, in local time, oldest day first.
+    pub first_starts: Vec<NaiveTime>,
+    /// When the last task of each day stopped, in local time, oldest day first. Days whose
+    /// tasks are all still running are left out.
+    pub last_ends: Vec<NaiveTime>,
+}
+
+/// The average, earliest, and latest of a set of times of day, rounded to the minute.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TimeOfDaySummary {
+    /// The circular mean of the times.
+    pub average: NaiveTime,
+    /// The time furthest before the average.
+    pub earliest: NaiveTime,
+    /// The time furthest after the average.
+    pub latest: NaiveTime,
+    /// How many times there were, one per day.
+    pub days: usize,
+}
+
+/// The local time of day of a point in time.
+fn time_of_day(time: SystemTime) -> NaiveTime {
+    DateTime::<Local>::from(time).time()
+}
+
+/// Rounds a time of day to the nearest minute, wrapping 23:59:30 and later around to 00:00.
+fn round_to_minute(time: NaiveTime) -> NaiveTime {
+    let minutes = (time.num_seconds_from_midnight() + 30) / 60 % 1440;
+    NaiveTime::from_num_seconds_from_midnight_opt(minutes * 60, 0).expect("within a day")
+}
+
+/// The seconds from `from` to `to`, going whichever way around the clock is shorter, so from
+/// 23:30 to 00:30 is an hour and from 00:30 to 23:30 is minus an hour.
+fn offset_secs(from: NaiveTime, to: NaiveTime) -> i64 {
+    let difference =
+        i64::from(to.num_seconds_from_midnight()) - i64::from(from.num_seconds_from_midnight());
+    (difference + DAY_SECS / 2).rem_euclid(DAY_SECS) - DAY_SECS / 2
+}
+
+/// Averages times of day on a 24-hour clock, so 23:00 and 01:00 average to midnight rather than
+/// noon.
+///
+/// Each time is a point on a circle; the average is the direction of the sum of the points,
+/// found with `atan2`. Returns `None` if there are no times or they cancel out (e.g. 06:00 and
+/// 18:00), so that no time is more average than another.
+///
+/// # Examples
+///
+/// ```
+/// use chrono::NaiveTime;
+/// use timetracker::reports::circular_mean;
+///
+/// let time = |hour, minute| NaiveTime::from_hms_opt(hour, minute, 0).unwrap();
+/// assert_eq!(circular_mean(&[time(23, 0), time(1, 0)]), Some(time(0, 0)));
+/// assert_eq!(circular_mean(&[time(8, 0), time(9, 0)]), Some(time(8, 30)));
+/// assert_eq!(circular_mean(&[time(6, 0), time(18, 0)]), None);
+/// ```
+pub fn circular_mean(times: &[NaiveTime]) -> Option<NaiveTime> {
+    let (sin, cos) = times.iter().fold((0.0, 0.0), |(sin, cos), time| {
+        let angle = f64::from(time.num_seconds_from_midnight()) / DAY_SECS as f64 * TAU;
+        (sin + angle.sin(), cos + angle.cos())
+    });
+    if times.is_empty() || sin.hypot(cos) < 1e-9 * times.len() as f64 {
+        return None;
+    }
+    let secs = (sin.atan2(cos).rem_euclid(TAU) / TAU * DAY_SECS as f64).round() as i64;
+    NaiveTime::from_num_seconds_from_midnight_opt(secs.rem_euclid(DAY_SECS) as u32, 0)
+}
+
+/// Sums up times of day: their circular mean, and the times furthest before and after it,
+/// all rounded to the minute.
+fn summarize(times: &[NaiveTime]) -> Option<TimeOfDaySummary> {
+    let average = circular_mean(times)?;
+    let earliest = *times
+        .iter()
+        .min_by_key(|time| offset_secs(average, **time))?;
+    let latest = *times
+        .iter()
+        .max_by_key(|time| offset_secs(average, **time))?;
+    Some(TimeOfDaySummary {
+        average: round_to_minute(average),
+        earliest: round_to_minute(earliest),
+        latest: round_to_minute(latest),
+        days: times.len(),
+    })
+}
+
+impl StartEndAnalyzer {
+    /// Finds the first start and last stop of each local day, counting each task on the day it
+    /// started.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chrono::{NaiveDate, NaiveTime};
+    /// use std::time::Duration;
+    /// use timetracker::{
+    ///     reports::{local_time, StartEndAnalyzer},
+    ///     storage::StoredTask,
+    /// };
+    ///
+    /// let day = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+    /// let task = |hour| {
+    ///     let start = local_time(day, NaiveTime::from_hms_opt(hour, 0, 0).unwrap());
+    ///     StoredTask::new("task", start, start + Duration::from_secs(3600))
+    /// };
+    /// let analyzer = StartEndAnalyzer::from_tasks(&[task(13), task(9)]);
+    /// assert_eq!(analyzer.first_starts, [NaiveTime::from_hms_opt(9, 0, 0).unwrap()]);
+    /// assert_eq!(analyzer.last_ends, [NaiveTime::from_hms_opt(14, 0, 0).unwrap()]);
+    /// ```
+    pub fn from_tasks(tasks: &[StoredTask]) -> StartEndAnalyzer {
+        let mut days: BTreeMap<NaiveDate, (SystemTime, Option<SystemTime>)> = BTreeMap::new();
+        for task in tasks {
+            let (first_start, last_end) = days
+                .entry(local_date(task.started_at))
+                .or_insert((task.started_at, None));
+            *first_start = (*first_start).min(task.started_at);
+            *last_end = (*last_end).max(task.tracked_until());
+        }
+        StartEndAnalyzer {
+            first_starts: days
+                .values()
+                .map(|(first_start, _)| time_of_day(*first_start))
+                .collect(),
+            last_ends: days
+                .values()
+                .filter_map(|(_, last_end)| last_end.map(time_of_day))
+                .collect(),
+        }
+    }
+
+    /// Sums up when the first task of the day started, or `None` if there are no days to sum up.
+    pub fn first_start_summary(&self) -> Option<TimeOfDaySummary> {
+        summarize(&self.first_starts)
+    }
+
+    /// Sums up when the last task of the day stopped, or `None` if there are no days to sum up.
+    pub fn last_end_summary(&self) -> Option<TimeOfDaySummary> {
+        summarize(&self.last_ends)
+    }
+}
+
+/// Formats the summary like `avg 08:47, earliest 07:30, latest 10:15 (over 22 days)`.
+impl fmt::Display for TimeOfDaySummary {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "avg {}, earliest {}, latest {} (over {} day{})",
+            self.average.format("%H:%M"),
+            self.earliest.format("%H:%M"),
+            self.latest.format("%H:%M"),
+            self.days,
+            if self.days == 1 { "" } else { "s" }
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests_startend {
+    use super::*;
+    use crate::reports::local_time;
+    use std::time::Duration;
+
+    fn time(hour: u32, minute: u32, second: u32) -> NaiveTime {
+        NaiveTime::from_hms_opt(hour, minute, second).unwrap()
+    }
+
+    #[test]
+    fn the_mean_of_times_around_midnight_is_near_midnight() {
+        let mean = circular_mean(&[time(23, 30, 0), time(0, 30, 0), time(23, 45, 0)]).unwrap();
+        // about 5 minutes before midnight, a little earlier than the arithmetic mean of the
+        // offsets since the times are points on a circle
+        assert_eq!(mean, time(23, 54, 58));
+        assert_eq!(
+            circular_mean(&[time(22, 0, 0), time(2, 0, 0)]),
+            Some(time(0, 0, 0))
+        );
+        assert_eq!(
+            circular_mean(&[time(23, 15, 0), time(0, 45, 0), time(0, 0, 0)]),
+            Some(time(0, 0, 0))
+        );
+        assert_eq!(circular_mean(&[]), None);
+    }
+
+    #[test]
+    fn earliest_and_latest_are_around_the_mean() {
+        let summary = summarize(&[
+            time(23, 10, 0),
+            time(0, 40, 29),
+            time(23, 50, 0),
+            time(0, 0, 0),
+        ])
+        .unwrap();
+        assert_eq!(summary.average, time(23, 55, 0));
+        assert_eq!(summary.earliest, time(23, 10, 0));
+        assert_eq!(summary.latest, time(0, 40, 0));
+        assert_eq!(summary.days, 4);
+        assert_eq!(
+            summary.to_string(),
+            "avg 23:55, earliest 23:10, latest 00:40 (over 4 days)"
+        );
+        assert_eq!(round_to_minute(time(23, 59, 30)), time(0, 0, 0));
+    }
+
+    #[test]
+    fn finds_the_first_start_and_last_end_of_each_day() {
+        let at = |day: u32, hour: u32, minute: u32| {
+            local_time(
+                NaiveDate::from_ymd_opt(2024, 1, day).unwrap(),
+                time(hour, minute, 0),
+            )
+        };
+        let task = |start: SystemTime, minutes: u64| {
+            StoredTask::new("task", start, start + Duration::from_secs(minutes * 60))
+        };
+        let tasks = [
+            task(at(15, 13, 0), 60),
+            task(at(15, 8, 45), 90),
+            // a late session that stops after midnight counts on the day it started
+            task(at(15, 23, 0), 90),
+            task(at(16, 9, 15), 30),
+            StoredTask::running("still going", at(17, 10, 0)),
+        ];
+        let mut paused = task(at(16, 9, 0), 60);
+        paused.seconds = 15 * 60;
+        let tasks = [&tasks[..], &[paused]].concat();
+        let analyzer = StartEndAnalyzer::from_tasks(&tasks);
+        assert_eq!(
+            analyzer.first_starts,
+            [time(8, 45, 0), time(9, 0, 0), time(10, 0, 0)]
+        );
+        // the task paused for 45 minutes of its hour ends 15 minutes after it started
+        assert_eq!(analyzer.last_ends, [time(0, 30, 0), time(9, 45, 0)]);
+        assert_eq!(
+            analyzer.first_start_summary().unwrap().earliest,
+            time(8, 45, 0)
+        );
+    }
+}
@@ -0,0 +1,356 @@
+//! Runs of consecutive days with tracked time.
+
+use super::local_date;
+use crate::storage::StoredTask;
+use chrono::{Datelike, NaiveDate, Weekday};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fmt,
+};
+
+/// Finds streaks of consecutive days on which at least one task was started.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct StreakComputer {
+    /// The local dates tasks were started on.
+    pub dates: BTreeSet<NaiveDate>,
+    /// Whether Saturdays and Sundays are skipped: they neither count towards a streak nor
+    /// break one.
+    pub exclude_weekends: bool,
+}
+
+impl StreakComputer {
+    /// Collects the dates the tasks were started on.
+    pub fn from_tasks(tasks: &[StoredTask], exclude_weekends: bool) -> StreakComputer {
+        StreakComputer {
+            dates: tasks
+                .iter()
+                .map(|task| local_date(task.started_at))
+                .collect(),
+            exclude_weekends,
+        }
+    }
+
+    /// Gets the length of the streak that is still going on `today`.
+    ///
+    /// A day that has no tasks yet doesn't break the streak until it is over, so if nothing
+    /// was tracked today the streak ending yesterday still counts.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chrono::NaiveDate;
+    /// use timetracker::reports::StreakComputer;
+    ///
+    /// let date = |day| NaiveDate::from_ymd_opt(2024, 1, day).unwrap();
+    /// let streaks = StreakComputer {
+    ///     dates: [date(1), date(3), date(4)].into_iter().collect(),
+    ///     exclude_weekends: false,
+    /// };
+    /// assert_eq!(streaks.current_streak(date(4)), 2);
+    /// assert_eq!(streaks.current_streak(date(5)), 2);
+    /// assert_eq!(streaks.current_streak(date(6)), 0);
+    /// ```
+    pub fn current_streak(&self, today: NaiveDate) -> u32 {
+        let mut day = self.counted_day_at_or_before(today);
+        if !self.dates.contains(&day) {
+            day = self.previous_day(day);
+        }
+        let mut streak = 0;
+        while self.dates.contains(&day) {
+            streak += 1;
+            day = self.previous_day(day);
+        }
+        streak
+    }
+
+    /// Gets the length of the longest streak ever.
+    pub fn longest_streak(&self) -> u32 {
+        self.longest_run().map_or(0, |(_, _, days)| days)
+    }
+
+    /// Gets the first and last day of the longest streak ever, and its length. The earliest of
+    /// streaks of the same length wins.
+    pub fn longest_run(&self) -> Option<(NaiveDate, NaiveDate, u32)> {
+        let mut longest: Option<(NaiveDate, NaiveDate, u32)> = None;
+        let mut start = NaiveDate::MIN;
+        let mut streak = 0;
+        let mut previous: Option<NaiveDate> = None;
+        for &date in self.dates.iter().filter(|date| self.counts(**date)) {
+            streak = match previous {
+                Some(previous) if self.next_day(previous) == date => streak + 1,
+                _ => {
+                    start = date;
+                    1
+                }
+            };
+            if longest.is_none_or(|(_, _, days)| streak > days) {
+                longest = Some((start, date, streak));
+            }
+            previous = Some(date);
+        }
+        longest
+    }
+
+    /// Checks whether `date` can be part of a streak.
+    fn counts(&self, date: NaiveDate) -> bool {
+        !(self.exclude_weekends && matches!(date.weekday(), Weekday::Sat | Weekday::Sun))
+    }
+
+    /// Gets `date`, or the last day before it that can be part of a streak.
+    fn counted_day_at_or_before(&self, mut date: NaiveDate) -> NaiveDate {
+        while !self.counts(date) {
+            date = date.pred_opt().expect("dates are far from the end of time");
+        }
+        date
+    }
+
+    /// Gets the last day before `date` that can be part of a streak.
+    fn previous_day(&self, date: NaiveDate) -> NaiveDate {
+        self.counted_day_at_or_before(date.pred_opt().expect("dates are far from the end of time"))
+    }
+
+    /// Gets the first day after `date` that can be part of a streak.
+    fn next_day(&self, date: NaiveDate) -> NaiveDate {
+        let mut date = date.succ_opt().expect("dates are far from the end of time");
+        while !self.counts(date) {
+            date = date.succ_opt().expect("dates are far from the end of time");
+        }
+        date
+    }
+}
+
+/// The longest streak of days on which a task of one name was tracked.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TaskStreak {
+    /// The name of the task.
+    pub name: String,
+    /// How many days the streak lasted.
+    pub days: u32,
+    /// The first day of the streak.
+    pub first: NaiveDate,
+    /// The last day of the streak.
+    pub last: NaiveDate,
+}
+
+/// Formats the streak as `'morning routine' — 14 consecutive days (Jan 1 – Jan 14)`.
+impl fmt::Display for TaskStreak {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let format = if self.first.year() == self.last.year() {
+            "%b %-d"
+        } else {
+            "%b %-d, %Y"
+        };
+        write!(
+            f,
+            "'{}' — {} consecutive day{} ({} – {})",
+            self.name,
+            self.days,
+            if self.days == 1 { "" } else { "s" },
+            self.first.format(format),
+            self.last.format(format)
+        )
+    }
+}
+
+/// The days a task of one name was tracked on, for finding habits: tasks done day after day.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TaskStreakAnalyzer {
+    /// The name of the task.
+    pub name: String,
+    /// The local dates tasks of the name were started on.
+    pub dates: BTreeSet<NaiveDate>,
+}
+
+impl TaskStreakAnalyzer {
+    /// Collects the dates of each task name, in name order.
+    pub fn from_tasks(tasks: &[StoredTask]) -> Vec<TaskStreakAnalyzer> {
+        let mut names: BTreeMap<&str, BTreeSet<NaiveDate>> = BTreeMap::new();
+        for task in tasks {
+            names
+                .entry(&task.name)
+                .or_default()
+                .insert(local_date(task.started_at));
+        }
+        names
+            .into_iter()
+            .map(|(name, dates)| TaskStreakAnalyzer {
+                name: name.to_string(),
+                dates,
+            })
+            .collect()
+    }
+
+    /// Gets the longest streak of the task, skipping weekends like [`StreakComputer`] if
+    /// `exclude_weekends` is set.
+    pub fn longest_streak(&self, exclude_weekends: bool) -> Option<TaskStreak> {
+        let streaks = StreakComputer {
+            dates: self.dates.clone(),
+            exclude_weekends,
+        };
+        let (first, last, days) = streaks.longest_run()?;
+        Some(TaskStreak {
+            name: self.name.clone(),
+            days,
+            first,
+            last,
+        })
+    }
+
+    /// Finds the task names with the longest streak. Several names tie if their streaks are
+    /// equally long.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chrono::{Local, TimeZone};
+    /// use timetracker::{reports::TaskStreakAnalyzer, storage::StoredTask};
+    ///
+    /// let task = |name: &str, day| {
+    ///     let start = Local.with_ymd_and_hms(2024, 1, day, 7, 0, 0).unwrap().into();
+    ///     StoredTask::new(name, start, start)
+    /// };
+    /// let tasks = [task("run", 1), task("run", 2), task("read", 1), task("run", 3)];
+    /// let longest = TaskStreakAnalyzer::longest(&tasks, false);
+    /// assert_eq!(longest.len(), 1);
+    /// assert_eq!(longest[0].to_string(), "'run' — 3 consecutive days (Jan 1 – Jan 3)");
+    /// ```
+    pub fn longest(tasks: &[StoredTask], exclude_weekends: bool) -> Vec<TaskStreak> {
+        let streaks: Vec<TaskStreak> = TaskStreakAnalyzer::from_tasks(tasks)
+            .iter()
+            .filter_map(|analyzer| analyzer.longest_streak(exclude_weekends))
+            .collect();
+        let longest = streaks.iter().map(|streak| streak.days).max();
+        streaks
+            .into_iter()
+            .filter(|streak| Some(streak.days) == longest)
+            .collect()
+    }
+}
+
+/// Formats a number of days, e.g. `1 day` or `14 days`.
+pub fn format_days(days: u32) -> String {
+    format!("{} day{}", days, if days == 1 { "" } else { "s" })
+}
+
+#[cfg(test)]
+mod tests_streak {
+    use super::*;
+
+    fn streaks(dates: &[(i32, u32, u32)], exclude_weekends: bool) -> StreakComputer {
+        StreakComputer {
+            dates: dates
+                .iter()
+                .map(|&(year, month, day)| NaiveDate::from_ymd_opt(year, month, day).unwrap())
+                .collect(),
+            exclude_weekends,
+        }
+    }
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn streaks_continue_across_month_and_year_boundaries() {
+        let streaks = streaks(
+            &[
+                (2023, 12, 30),
+                (2023, 12, 31),
+                (2024, 1, 1),
+                (2024, 1, 31),
+                (2024, 2, 1),
+            ],
+            false,
+        );
+        assert_eq!(streaks.longest_streak(), 3);
+        assert_eq!(streaks.current_streak(date(2024, 2, 1)), 2);
+        assert_eq!(streaks.current_streak(date(2024, 1, 1)), 3);
+    }
+
+    #[test]
+    fn leap_days_are_part_of_the_streak() {
+        assert_eq!(
+            streaks(&[(2024, 2, 28), (2024, 3, 1)], false).longest_streak(),
+            1
+        );
+        let with_leap_day = streaks(&[(2024, 2, 28), (2024, 2, 29), (2024, 3, 1)], false);
+        assert_eq!(with_leap_day.longest_streak(), 3);
+    }
+
+    #[test]
+    fn weekends_can_be_skipped() {
+        // Friday 2024-01-05 to Monday 2024-01-08
+        let dates = [(2024, 1, 4), (2024, 1, 5), (2024, 1, 8)];
+        assert_eq!(streaks(&dates, false).longest_streak(), 2);
+        assert_eq!(streaks(&dates, true).longest_streak(), 3);
+        assert_eq!(streaks(&dates, true).current_streak(date(2024, 1, 8)), 3);
+        // on Sunday the streak ending on Friday is still going
+        assert_eq!(
+            streaks(&dates[..2], true).current_streak(date(2024, 1, 7)),
+            2
+        );
+    }
+
+    #[test]
+    fn task_streaks_span_month_boundaries() {
+        let task = |name: &str, month: u32, day: u32| {
+            let start = super::super::local_time(
+                date(2024, month, day),
+                chrono::NaiveTime::from_hms_opt(7, 0, 0).unwrap(),
+            );
+            StoredTask::new(name, start, start)
+        };
+        let mut tasks: Vec<StoredTask> =
+            (25..=31).map(|day| task("review inbox", 1, day)).collect();
+        tasks.extend((1..=3).map(|day| task("review inbox", 2, day)));
+        tasks.push(task("review inbox", 1, 10));
+        tasks.push(task("morning routine", 1, 1));
+        tasks.push(task("morning routine", 1, 2));
+
+        let longest = TaskStreakAnalyzer::longest(&tasks, false);
+        assert_eq!(
+            longest,
+            [TaskStreak {
+                name: String::from("review inbox"),
+                days: 10,
+                first: date(2024, 1, 25),
+                last: date(2024, 2, 3),
+            }]
+        );
+        assert_eq!(
+            longest[0].to_string(),
+            "'review inbox' — 10 consecutive days (Jan 25 – Feb 3)"
+        );
+    }
+
+    #[test]
+    fn task_streaks_report_ties() {
+        let analyzer = |name: &str, dates: &[(i32, u32, u32)]| TaskStreakAnalyzer {
+            name: name.to_string(),
+            dates: streaks(dates, false).dates,
+        };
+        let a = analyzer("a", &[(2023, 12, 31), (2024, 1, 1)]);
+        let b = analyzer("b", &[(2024, 1, 5), (2024, 1, 6)]);
+        assert_eq!(a.longest_streak(false).unwrap().days, 2);
+        assert_eq!(
+            a.longest_streak(false).unwrap().to_string(),
+            "'a' — 2 consecutive days (Dec 31, 2023 – Jan 1, 2024)"
+        );
+        // Friday and Saturday: without weekends only Friday is left
+        assert_eq!(b.longest_streak(true).unwrap().days, 1);
+        assert_eq!(
+            streaks(&[(2024, 1, 1), (2024, 1, 3), (2024, 1, 4)], false).longest_run(),
+            Some((date(2024, 1, 3), date(2024, 1, 4), 2))
+        );
+        assert_eq!(TaskStreakAnalyzer::default().longest_streak(false), None);
+    }
+
+    #[test]
+    fn no_tasks_is_no_streak() {
+        let streaks = streaks(&[], false);
+        assert_eq!(streaks.longest_streak(), 0);
+        assert_eq!(streaks.current_streak(date(2024, 1, 1)), 0);
+        assert_eq!(format_days(1), "1 day");
+        assert_eq!(format_days(0), "0 days");
+    }
+}
@@ -0,0 +1,183 @@
+//! The time tracked on one tag in each week, to see when a tag came into use and how it trends.
+
+use super::{local_date, Sparkline, WeeklyReport};
+use crate::{format_duration_compact, storage::StoredTask};
+use chrono::{Datelike, Duration, IsoWeek, NaiveDate, Weekday};
+
+/// The seconds tracked on a tag in each ISO week.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TagTimeSeries {
+    /// The tag.
+    pub tag: String,
+    /// Each week from the first to the last week of the history, oldest first, with the seconds
+    /// tracked on tasks with the tag. Weeks before the tag was first used are included, with 0.
+    pub weeks: Vec<(IsoWeek, u64)>,
+}
+
+/// The Monday an ISO week starts on.
+fn monday(week: IsoWeek) -> NaiveDate {
+    NaiveDate::from_isoywd_opt(week.year(), week.week(), Weekday::Mon)
+        .expect("an ISO week has a Monday")
+}
+
+/// Labels an ISO week like `2024-W03`.
+fn label(week: IsoWeek) -> String {
+    format!("{}-W{:02}", week.year(), week.week())
+}
+
+impl TagTimeSeries {
+    /// Adds up the time of the tasks with `tag` by the ISO week they started in, in the local
+    /// time zone, over every week from the first task to the last.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chrono::{NaiveDate, NaiveTime};
+    /// use std::time::Duration;
+    /// use timetracker::{
+    ///     reports::{local_time, TagTimeSeries},
+    ///     storage::StoredTask,
+    /// };
+    ///
+    /// let task = |day: u32, tag: &str| {
+    ///     let date = NaiveDate::from_ymd_opt(2024, 1, day).unwrap();
+    ///     let start = local_time(date, NaiveTime::from_hms_opt(9, 0, 0).unwrap());
+    ///     let mut task = StoredTask::new("focus", start, start + Duration::from_secs(3600));
+    ///     task.tags = vec![tag.to_string()];
+    ///     task
+    /// };
+    /// let tasks = [task(1, "admin"), task(16, "deep-work")];
+    /// let series = TagTimeSeries::from_tasks("deep-work", &tasks);
+    /// let totals: Vec<u64> = series.weeks.iter().map(|(_, seconds)| *seconds).collect();
+    /// assert_eq!(totals, [0, 0, 3600]);
+    /// ```
+    pub fn from_tasks(tag: &str, tasks: &[StoredTask]) -> TagTimeSeries {
+        let weeks_of = |task: &StoredTask| local_date(task.started_at).iso_week();
+        let (Some(first), Some(last)) = (
+            tasks.iter().map(weeks_of).min(),
+            tasks.iter().map(weeks_of).max(),
+        ) else {
+            return TagTimeSeries {
+                tag: tag.to_string(),
+                weeks: Vec::new(),
+            };
+        };
+        let mut weeks = Vec::new();
+        let mut start = monday(first);
+        while start <= monday(last) {
+            let week = start.iso_week();
+            let seconds = tasks
+                .iter()
+                .filter(|task| weeks_of(task) == week && task.tags.iter().any(|t| t == tag))
+                .map(|task| task.seconds)
+                .sum();
+            weeks.push((week, seconds));
+            start += Duration::weeks(1);
+        }
+        TagTimeSeries {
+            tag: tag.to_string(),
+            weeks,
+        }
+    }
+
+    /// Whether no time was tracked on the tag in any week.
+    pub fn is_empty(&self) -> bool {
+        self.weeks.iter().all(|(_, seconds)| *seconds == 0)
+    }
+
+    /// Draws a row per week with its total, e.g. `2024-W03  6h30m`. With `sparkline`, each week
+    /// is followed by a row of 7 block characters for the days of the week, Monday first,
+    /// worked out from the tasks with the tag in `tasks`.
+    pub fn render(&self, tasks: &[StoredTask], sparkline: bool) -> String {
+        let tagged: Vec<StoredTask> = tasks
+            .iter()
+            .filter(|task| task.tags.contains(&self.tag))
+            .cloned()
+            .collect();
+        let mut rows = Vec::new();
+        for (week, seconds) in &self.weeks {
+            rows.push(format!(
+                "{}  {}",
+                label(*week),
+                format_duration_compact(*seconds)
+            ));
+            if sparkline {
+                let totals = WeeklyReport {
+                    start: monday(*week),
+                }
+                .daily_totals(&tagged);
+                rows.push(format!(
+                    "{:width$}  {}",
+                    "",
+                    Sparkline::from_daily_totals(totals),
+                    width = label(*week).len()
+                ));
+            }
+        }
+        rows.join("\n")
+    }
+}
+
+#[cfg(test)]
+mod tests_tagtrend {
+    use super::*;
+    use crate::reports::local_time;
+    use chrono::NaiveTime;
+
+    /// A task on a day of January 2024, which began on a Monday.
+    fn task(day: u32, minutes: i64, tags: &[&str]) -> StoredTask {
+        let start = local_time(
+            NaiveDate::from_ymd_opt(2024, 1, day).unwrap(),
+            NaiveTime::from_hms_opt(9, 0, 0).unwrap(),
+        );
+        let mut task = StoredTask::new(
+            "task",
+            start,
+            start + Duration::minutes(minutes).to_std().unwrap(),
+        );
+        task.tags = tags.iter().map(|tag| tag.to_string()).collect();
+        task
+    }
+
+    fn tasks() -> Vec<StoredTask> {
+        vec![
+            task(2, 60, &["admin"]),
+            // the tag comes into use in the third week
+            task(15, 90, &["deep-work"]),
+            task(17, 120, &["deep-work", "writing"]),
+            task(18, 30, &["admin"]),
+            task(23, 180, &["deep-work"]),
+            // nothing in the fifth week, then some more
+            task(31, 45, &["deep-work"]),
+        ]
+    }
+
+    #[test]
+    fn adds_up_the_tag_by_iso_week() {
+        let series = TagTimeSeries::from_tasks("deep-work", &tasks());
+        let weeks: Vec<(u32, u64)> = series
+            .weeks
+            .iter()
+            .map(|(week, seconds)| (week.week(), seconds / 60))
+            .collect();
+        assert_eq!(weeks, [(1, 0), (2, 0), (3, 210), (4, 180), (5, 45)]);
+        assert!(!series.is_empty());
+        assert!(TagTimeSeries::from_tasks("travel", &tasks()).is_empty());
+        assert!(TagTimeSeries::from_tasks("travel", &[]).weeks.is_empty());
+    }
+
+    #[test]
+    fn renders_a_sparkline_under_each_week() {
+        let tasks = tasks();
+        let series = TagTimeSeries::from_tasks("deep-work", &tasks);
+        let rendered = series.render(&tasks, true);
+        let lines: Vec<&str> = rendered.lines().collect();
+        assert_eq!(lines.len(), 10);
+        assert_eq!(lines[4], "2024-W03  3h30m");
+        assert_eq!(lines[5], "          ▆░█░░░░");
+        assert_eq!(
+            series.render(&tasks, false).lines().nth(3),
+            Some("2024-W04  3h")
+        );
+    }
+}
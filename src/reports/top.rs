@@ -0,0 +1,142 @@
+//! The longest tasks in the history.
+
+use super::local_date;
+use crate::{get_clock_format, storage::StoredTask};
+use std::{cmp::Ordering, cmp::Reverse, collections::BinaryHeap, fmt};
+
+/// A task ranked by how long it took: longer tasks rank higher, and of tasks that took as long,
+/// the one whose name comes first alphabetically ranks higher.
+#[derive(PartialEq, Eq)]
+struct Ranked(StoredTask);
+
+impl Ord for Ranked {
+    fn cmp(&self, other: &Ranked) -> Ordering {
+        self.0
+            .seconds
+            .cmp(&other.0.seconds)
+            .then_with(|| other.0.name.cmp(&self.0.name))
+    }
+}
+
+impl PartialOrd for Ranked {
+    fn partial_cmp(&self, other: &Ranked) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// The `n` tasks that took the longest, longest first.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct TopNReport {
+    /// How many tasks were asked for. There are fewer if the history is shorter.
+    pub n: usize,
+    /// The longest tasks, longest first.
+    pub tasks: Vec<StoredTask>,
+}
+
+impl TopNReport {
+    /// Picks the `n` longest tasks. Ties go to the name that comes first alphabetically.
+    ///
+    /// Only `n` tasks are kept at a time (in a min-heap whose smallest task is dropped when a
+    /// longer one arrives), so `tasks` can be streamed from a large history.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::{Duration, UNIX_EPOCH};
+    /// use timetracker::{reports::TopNReport, storage::StoredTask};
+    ///
+    /// let task = |name: &str, seconds: u64| {
+    ///     StoredTask::new(name, UNIX_EPOCH, UNIX_EPOCH + Duration::from_secs(seconds))
+    /// };
+    /// let top = TopNReport::from_tasks(2, [task("a", 10), task("b", 30), task("c", 20)]);
+    /// let names: Vec<&str> = top.tasks.iter().map(|task| task.name.as_str()).collect();
+    /// assert_eq!(names, ["b", "c"]);
+    /// ```
+    pub fn from_tasks(n: usize, tasks: impl IntoIterator<Item = StoredTask>) -> TopNReport {
+        let mut heap = BinaryHeap::with_capacity(n + 1);
+        for task in tasks {
+            heap.push(Reverse(Ranked(task)));
+            if heap.len() > n {
+                heap.pop();
+            }
+        }
+        TopNReport {
+            n,
+            tasks: heap
+                .into_sorted_vec()
+                .into_iter()
+                .map(|Reverse(Ranked(task))| task)
+                .collect(),
+        }
+    }
+}
+
+/// Formats one task per line with its rank, name, start date, and duration, e.g.
+/// ` 1. write docs  2024-01-15  01:30:00`. Names are padded so the columns line up.
+impl fmt::Display for TopNReport {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let rank_width = self.tasks.len().to_string().len();
+        let name_width = self
+            .tasks
+            .iter()
+            .map(|task| task.name.chars().count())
+            .max()
+            .unwrap_or_default();
+        for (index, task) in self.tasks.iter().enumerate() {
+            if index > 0 {
+                writeln!(f)?;
+            }
+            write!(
+                f,
+                "{:>rank_width$}. {:<name_width$}  {}  {}",
+                index + 1,
+                task.name,
+                local_date(task.started_at),
+                get_clock_format(task.seconds),
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests_top {
+    use super::*;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    fn task(name: &str, seconds: u64) -> StoredTask {
+        let started_at = UNIX_EPOCH + Duration::from_secs(1_705_309_200);
+        StoredTask::new(name, started_at, started_at + Duration::from_secs(seconds))
+    }
+
+    #[test]
+    fn keeps_the_ten_longest_of_twenty() {
+        let tasks: Vec<StoredTask> = (0..20)
+            .map(|i| task(&format!("task {:02}", i), (i * 7 % 20) * 60))
+            .collect();
+        let top = TopNReport::from_tasks(10, tasks);
+        let minutes: Vec<u64> = top.tasks.iter().map(|task| task.seconds / 60).collect();
+        assert_eq!(minutes, [19, 18, 17, 16, 15, 14, 13, 12, 11, 10]);
+    }
+
+    #[test]
+    fn breaks_ties_alphabetically() {
+        let tasks = [task("c", 60), task("a", 60), task("b", 60), task("d", 30)];
+        let top = TopNReport::from_tasks(2, tasks);
+        let names: Vec<&str> = top.tasks.iter().map(|task| task.name.as_str()).collect();
+        assert_eq!(names, ["a", "b"]);
+    }
+
+    #[test]
+    fn formats_rows_with_aligned_names() {
+        let top = TopNReport::from_tasks(3, [task("write docs", 5400), task("email", 60)]);
+        let date = local_date(top.tasks[0].started_at);
+        assert_eq!(
+            top.to_string(),
+            format!(
+                "1. write docs  {}  01:30:00\n2. email       {}  00:01:00",
+                date, date
+            )
+        );
+    }
+}
@@ -0,0 +1,196 @@
+//! Whether more or less time is tracked from month to month.
+
+use super::local_date;
+use crate::storage::StoredTask;
+use chrono::{Datelike, Months, NaiveDate};
+use std::fmt;
+
+/// How much a month's total has to differ from the month before, as a fraction of it, to count
+/// as a change.
+pub const TREND_THRESHOLD: f64 = 0.05;
+
+/// How a month's total compares to the month before.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TrendDirection {
+    /// More than [`TREND_THRESHOLD`] more time was tracked.
+    Up,
+    /// More than [`TREND_THRESHOLD`] less time was tracked.
+    Down,
+    /// About the same time was tracked.
+    Flat,
+}
+
+impl TrendDirection {
+    /// Compares the total of a month to the total of the month before it.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use timetracker::reports::TrendDirection;
+    ///
+    /// assert_eq!(TrendDirection::between(10.0, 10.4), TrendDirection::Flat);
+    /// assert_eq!(TrendDirection::between(10.0, 11.0), TrendDirection::Up);
+    /// assert_eq!(TrendDirection::between(0.0, 1.0), TrendDirection::Up);
+    /// ```
+    pub fn between(previous: f64, current: f64) -> TrendDirection {
+        let threshold = previous * TREND_THRESHOLD;
+        if current - previous > threshold {
+            TrendDirection::Up
+        } else if previous - current > threshold {
+            TrendDirection::Down
+        } else {
+            TrendDirection::Flat
+        }
+    }
+}
+
+/// Formats the direction as an arrow: `↑`, `↓`, or `→`.
+impl fmt::Display for TrendDirection {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            TrendDirection::Up => write!(f, "↑"),
+            TrendDirection::Down => write!(f, "↓"),
+            TrendDirection::Flat => write!(f, "→"),
+        }
+    }
+}
+
+/// The time tracked in a month.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MonthlyTotal {
+    /// The month, e.g. `Jan 2024`.
+    pub month: String,
+    /// The hours tracked on tasks started in the month.
+    pub total_hours: f64,
+    /// How the total compares to the month before.
+    pub trend: TrendDirection,
+}
+
+/// The time tracked in each of the last few months, oldest first.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MonthlyTrend {
+    /// A total per month, oldest first.
+    pub months: Vec<MonthlyTotal>,
+}
+
+impl MonthlyTrend {
+    /// Adds up the time tracked in the `count` months up to and including the one `today` is
+    /// in. The oldest month is compared to the month before it too.
+    pub fn for_months(tasks: &[StoredTask], today: NaiveDate, count: u32) -> MonthlyTrend {
+        let this_month = today.with_day(1).expect("every month has a first day");
+        let first = this_month - Months::new(count);
+        let mut totals = vec![0u64; count as usize + 1];
+        for task in tasks {
+            let date = local_date(task.started_at);
+            if date < first || date >= this_month + Months::new(1) {
+                continue;
+            }
+            let index =
+                (date.year() - first.year()) * 12 + date.month() as i32 - first.month() as i32;
+            totals[index as usize] += task.seconds;
+        }
+
+        let hours: Vec<f64> = totals
+            .iter()
+            .map(|&seconds| seconds as f64 / 3600.0)
+            .collect();
+        let months = hours
+            .windows(2)
+            .enumerate()
+            .map(|(index, pair)| MonthlyTotal {
+                month: (first + Months::new(index as u32 + 1))
+                    .format("%b %Y")
+                    .to_string(),
+                total_hours: pair[1],
+                trend: TrendDirection::between(pair[0], pair[1]),
+            })
+            .collect();
+        MonthlyTrend { months }
+    }
+}
+
+/// Formats a row per month, e.g. `Jan 2024   12.50h ↑`, with the labels and hours aligned.
+impl fmt::Display for MonthlyTrend {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let label_width = self
+            .months
+            .iter()
+            .map(|month| month.month.chars().count())
+            .max()
+            .unwrap_or(0);
+        let hours: Vec<String> = self
+            .months
+            .iter()
+            .map(|month| format!("{:.2}h", month.total_hours))
+            .collect();
+        let hours_width = hours.iter().map(String::len).max().unwrap_or(0);
+        let rows: Vec<String> = self
+            .months
+            .iter()
+            .zip(&hours)
+            .map(|(month, hours)| {
+                format!(
+                    "{:<label_width$}  {:>hours_width$} {}",
+                    month.month, hours, month.trend
+                )
+            })
+            .collect();
+        write!(f, "{}", rows.join("\n"))
+    }
+}
+
+#[cfg(test)]
+mod tests_trend {
+    use super::*;
+    use crate::reports::local_time;
+    use chrono::NaiveTime;
+    use std::time::Duration;
+
+    fn task(year: i32, month: u32, hours: u64) -> StoredTask {
+        let date = NaiveDate::from_ymd_opt(year, month, 15).unwrap();
+        let started_at = local_time(date, NaiveTime::from_hms_opt(9, 0, 0).unwrap());
+        StoredTask::new(
+            "task",
+            started_at,
+            started_at + Duration::from_secs(hours * 3600),
+        )
+    }
+
+    #[test]
+    fn trend_needs_more_than_five_percent() {
+        assert_eq!(TrendDirection::between(20.0, 21.0), TrendDirection::Flat);
+        assert_eq!(TrendDirection::between(20.0, 21.5), TrendDirection::Up);
+        assert_eq!(TrendDirection::between(20.0, 19.0), TrendDirection::Flat);
+        assert_eq!(TrendDirection::between(20.0, 18.5), TrendDirection::Down);
+        assert_eq!(TrendDirection::between(0.0, 0.0), TrendDirection::Flat);
+    }
+
+    #[test]
+    fn compares_each_month_to_the_one_before() {
+        let tasks = [
+            task(2023, 11, 10),
+            task(2023, 12, 5),
+            task(2024, 1, 5),
+            task(2024, 2, 8),
+        ];
+        let today = NaiveDate::from_ymd_opt(2024, 2, 20).unwrap();
+        let trend = MonthlyTrend::for_months(&tasks, today, 3);
+        let months: Vec<(&str, f64, TrendDirection)> = trend
+            .months
+            .iter()
+            .map(|month| (month.month.as_str(), month.total_hours, month.trend))
+            .collect();
+        assert_eq!(
+            months,
+            [
+                ("Dec 2023", 5.0, TrendDirection::Down),
+                ("Jan 2024", 5.0, TrendDirection::Flat),
+                ("Feb 2024", 8.0, TrendDirection::Up),
+            ]
+        );
+        assert_eq!(
+            trend.to_string(),
+            "Dec 2023  5.00h ↓\nJan 2024  5.00h →\nFeb 2024  8.00h ↑"
+        );
+    }
+}
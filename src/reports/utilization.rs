@@ -0,0 +1,93 @@
+//! How much of the work day was tracked.
+
+use crate::format_duration_compact;
+use std::fmt;
+
+/// The time tracked during a work day compared to the length of the work day.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WorkdayUtilization {
+    /// The time tracked, in seconds.
+    pub tracked_secs: u64,
+    /// The length of the work day, in seconds.
+    pub work_day_secs: u64,
+}
+
+impl WorkdayUtilization {
+    /// Gets the tracked time as a percentage of the work day. Overtime gives more than 100.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use timetracker::reports::WorkdayUtilization;
+    ///
+    /// let utilization = WorkdayUtilization { tracked_secs: 6 * 3600, work_day_secs: 10 * 3600 };
+    /// assert_eq!(utilization.utilization_percent(), 60.0);
+    /// ```
+    pub fn utilization_percent(&self) -> f64 {
+        if self.work_day_secs == 0 {
+            return 0.0;
+        }
+        self.tracked_secs as f64 / self.work_day_secs as f64 * 100.0
+    }
+
+    /// Whether more time was tracked than the work day is long.
+    pub fn is_overtime(&self) -> bool {
+        self.tracked_secs > self.work_day_secs
+    }
+
+    /// Draws the utilization as a bar of `width` blocks, e.g. `[██████░░░░]`.
+    ///
+    /// The bar is full when the utilization is 100% or more.
+    pub fn bar(&self, width: usize) -> String {
+        let filled =
+            ((self.utilization_percent() / 100.0 * width as f64).round() as usize).min(width);
+        format!("[{}{}]", "█".repeat(filled), "░".repeat(width - filled))
+    }
+}
+
+/// Formats the utilization as `Time utilization: 60.0% (6h tracked of 10h work day)`, or
+/// starting with `Overtime:` when more than the work day was tracked.
+impl fmt::Display for WorkdayUtilization {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{}: {:.1}% ({} tracked of {} work day)",
+            if self.is_overtime() {
+                "Overtime"
+            } else {
+                "Time utilization"
+            },
+            self.utilization_percent(),
+            format_duration_compact(self.tracked_secs),
+            format_duration_compact(self.work_day_secs)
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests_utilization {
+    use super::*;
+
+    #[test]
+    fn formats_utilization_and_overtime() {
+        let utilization = WorkdayUtilization {
+            tracked_secs: 6 * 3600,
+            work_day_secs: 10 * 3600,
+        };
+        assert_eq!(
+            utilization.to_string(),
+            "Time utilization: 60.0% (6h tracked of 10h work day)"
+        );
+        assert_eq!(utilization.bar(10), "[██████░░░░]");
+
+        let overtime = WorkdayUtilization {
+            tracked_secs: 12 * 3600,
+            work_day_secs: 10 * 3600,
+        };
+        assert_eq!(
+            overtime.to_string(),
+            "Overtime: 120.0% (12h tracked of 10h work day)"
+        );
+        assert_eq!(overtime.bar(10), "[██████████]");
+    }
+}
@@ -0,0 +1,207 @@
+//! Sprint velocity: the story points completed each sprint, read from the task names.
+//!
+//! A task named `PROJ-123 (5pts) implement login` is worth 5 points (`(1pt)` works too). Each
+//! ISO week is a sprint.
+
+use super::local_date;
+use crate::storage::StoredTask;
+use chrono::{Datelike, Duration, NaiveDate};
+use std::{collections::BTreeMap, fmt};
+
+/// Reads the story points from a task name, the number in the first `(<n>pts)` or `(<n>pt)`.
+///
+/// # Examples
+///
+/// ```
+/// use timetracker::reports::velocity::story_points;
+///
+/// assert_eq!(story_points("PROJ-123 (5pts) implement login"), Some(5));
+/// assert_eq!(story_points("(1pt) fix typo"), Some(1));
+/// assert_eq!(story_points("lunch (pts)"), None);
+/// ```
+pub fn story_points(name: &str) -> Option<u32> {
+    name.match_indices('(').find_map(|(start, _)| {
+        let rest = &name[start + 1..];
+        let digits = rest.len() - rest.trim_start_matches(|c: char| c.is_ascii_digit()).len();
+        let suffix = &rest[digits..];
+        if digits > 0 && (suffix.starts_with("pts)") || suffix.starts_with("pt)")) {
+            rest[..digits].parse().ok()
+        } else {
+            None
+        }
+    })
+}
+
+/// Counts the completed tasks without story points in their name, which count as 0 points.
+pub fn tasks_without_points(tasks: &[StoredTask]) -> usize {
+    tasks
+        .iter()
+        .filter(|task| task.stopped_at.is_some() && story_points(&task.name).is_none())
+        .count()
+}
+
+/// The points and time of the tasks completed in a sprint.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SprintVelocity {
+    /// The name of the sprint, e.g. `2024-W03`.
+    pub sprint: String,
+    /// The story points of the tasks.
+    pub total_points: u32,
+    /// How many tasks were completed, with or without points.
+    pub task_count: usize,
+    /// The time tracked on the tasks, in hours.
+    pub total_hours: f64,
+    /// The points completed per hour tracked, or 0 if nothing was tracked.
+    pub points_per_hour: f64,
+}
+
+impl SprintVelocity {
+    /// Adds up the points and time of the completed tasks of `tasks`. Tasks that are still
+    /// running aren't done yet, so they are left out.
+    pub fn from_tasks(sprint: impl Into<String>, tasks: &[StoredTask]) -> SprintVelocity {
+        let done: Vec<&StoredTask> = tasks
+            .iter()
+            .filter(|task| task.stopped_at.is_some())
+            .collect();
+        let total_points = done
+            .iter()
+            .filter_map(|task| story_points(&task.name))
+            .sum();
+        let total_hours = done.iter().map(|task| task.seconds).sum::<u64>() as f64 / 3600.0;
+        SprintVelocity {
+            sprint: sprint.into(),
+            total_points,
+            task_count: done.len(),
+            total_hours,
+            points_per_hour: if total_hours > 0.0 {
+                f64::from(total_points) / total_hours
+            } else {
+                0.0
+            },
+        }
+    }
+
+    /// Gets the velocity of each ISO week (in local time) a task started in, oldest first.
+    pub fn by_sprint(tasks: &[StoredTask]) -> Vec<SprintVelocity> {
+        let mut sprints: BTreeMap<NaiveDate, Vec<StoredTask>> = BTreeMap::new();
+        for task in tasks {
+            let date = local_date(task.started_at);
+            let monday = date - Duration::days(i64::from(date.weekday().num_days_from_monday()));
+            sprints.entry(monday).or_default().push(task.clone());
+        }
+        sprints
+            .into_iter()
+            .map(|(monday, tasks)| SprintVelocity::from_tasks(sprint_name(monday), &tasks))
+            .collect()
+    }
+}
+
+/// Gets the name of the sprint of a week, e.g. `2024-W03`.
+pub fn sprint_name(date: NaiveDate) -> String {
+    date.format("%G-W%V").to_string()
+}
+
+/// Formats the velocity as `Sprint 2024-W03 velocity: 23 points across 12 tasks (30.5h, 0.75
+/// points/hour)`.
+impl fmt::Display for SprintVelocity {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Sprint {} velocity: {} points across {} tasks ({:.1}h, {:.2} points/hour)",
+            self.sprint, self.total_points, self.task_count, self.total_hours, self.points_per_hour
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests_velocity {
+    use super::*;
+    use crate::reports::local_time;
+    use chrono::NaiveTime;
+
+    fn task(name: &str, day: u32, hours: u64) -> StoredTask {
+        let start = local_time(
+            NaiveDate::from_ymd_opt(2024, 1, day).unwrap(),
+            NaiveTime::from_hms_opt(9, 0, 0).unwrap(),
+        );
+        StoredTask::new(
+            name,
+            start,
+            start + std::time::Duration::from_secs(hours * 3600),
+        )
+    }
+
+    #[test]
+    fn reads_points_from_names() {
+        assert_eq!(story_points("PROJ-1 (13pts) refactor"), Some(13));
+        assert_eq!(story_points("(call) PROJ-2 (3pt)"), Some(3));
+        assert_eq!(story_points("PROJ-3 (3 pts)"), None);
+        assert_eq!(story_points("PROJ-4 (3ptsx)"), None);
+        assert_eq!(story_points("unclosed (5pts"), None);
+    }
+
+    #[test]
+    fn adds_up_points_of_completed_tasks() {
+        let mut running = task("PROJ-9 (8pts) unfinished", 15, 1);
+        running.stopped_at = None;
+        let tasks = [
+            task("PROJ-1 (5pts) login", 15, 2),
+            task("PROJ-2 (3pts) logout", 16, 2),
+            task("standup", 17, 4),
+            running,
+        ];
+        let velocity = SprintVelocity::from_tasks("2024-W03", &tasks);
+        assert_eq!(
+            velocity,
+            SprintVelocity {
+                sprint: String::from("2024-W03"),
+                total_points: 8,
+                task_count: 3,
+                total_hours: 8.0,
+                points_per_hour: 1.0,
+            }
+        );
+        assert_eq!(
+            velocity.to_string(),
+            "Sprint 2024-W03 velocity: 8 points across 3 tasks (8.0h, 1.00 points/hour)"
+        );
+        assert_eq!(tasks_without_points(&tasks), 1);
+    }
+
+    #[test]
+    fn groups_sprints_by_iso_week() {
+        // January 14 is the Sunday of W02, January 15 the Monday of W03
+        let tasks = [
+            task("(2pts) c", 22, 1),
+            task("(1pt) a", 14, 1),
+            task("(3pts) b", 15, 1),
+            task("(4pts) b", 21, 1),
+        ];
+        let sprints: Vec<(String, u32)> = SprintVelocity::by_sprint(&tasks)
+            .into_iter()
+            .map(|sprint| (sprint.sprint, sprint.total_points))
+            .collect();
+        assert_eq!(
+            sprints,
+            [
+                (String::from("2024-W02"), 1),
+                (String::from("2024-W03"), 7),
+                (String::from("2024-W04"), 2)
+            ]
+        );
+        assert_eq!(
+            SprintVelocity::from_tasks("2024-W05", &[]).points_per_hour,
+            0.0
+        );
+    }
+
+    #[test]
+    fn sprint_names_use_the_iso_week_year() {
+        let date = |year, month, day| NaiveDate::from_ymd_opt(year, month, day).unwrap();
+        // December 30th 2024 is the Monday of the first ISO week of 2025
+        assert_eq!(sprint_name(date(2024, 12, 30)), "2025-W01");
+        assert_eq!(sprint_name(date(2024, 12, 23)), "2024-W52");
+        // January 1st 2021 is in the last ISO week of 2020
+        assert_eq!(sprint_name(date(2021, 1, 1)), "2020-W53");
+    }
+}
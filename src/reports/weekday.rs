@@ -0,0 +1,190 @@
+//! The average time tracked on each day of the week.
+
+use super::{hours_and_minutes, local_date};
+use crate::storage::StoredTask;
+use chrono::{Datelike, NaiveDate, Weekday};
+use std::{collections::BTreeMap, fmt};
+
+/// The days of the week, in the order of the report.
+const WEEKDAYS: [Weekday; 7] = [
+    Weekday::Mon,
+    Weekday::Tue,
+    Weekday::Wed,
+    Weekday::Thu,
+    Weekday::Fri,
+    Weekday::Sat,
+    Weekday::Sun,
+];
+
+/// The English name of a day of the week.
+fn weekday_name(weekday: Weekday) -> &'static str {
+    match weekday {
+        Weekday::Mon => "Monday",
+        Weekday::Tue => "Tuesday",
+        Weekday::Wed => "Wednesday",
+        Weekday::Thu => "Thursday",
+        Weekday::Fri => "Friday",
+        Weekday::Sat => "Saturday",
+        Weekday::Sun => "Sunday",
+    }
+}
+
+/// The average hours tracked on each day of the week, Monday first.
+///
+/// Only days with time tracked on them count: a Monday without any tasks adds nothing to the
+/// average of Mondays, rather than pulling it down.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct WeekdayAverages {
+    /// The average hours tracked on a day, for each day of the week.
+    pub averages: [f64; 7],
+    /// How many days of each day of the week had time tracked on them.
+    pub counts: [u32; 7],
+}
+
+impl WeekdayAverages {
+    /// Adds up the time of the tasks for each day they were started on, in the local time zone,
+    /// and averages those days by day of the week.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chrono::{NaiveDate, NaiveTime};
+    /// use std::time::Duration;
+    /// use timetracker::{
+    ///     reports::{local_time, WeekdayAverages},
+    ///     storage::StoredTask,
+    /// };
+    ///
+    /// let task = |day: u32, hours: u64| {
+    ///     let date = NaiveDate::from_ymd_opt(2024, 1, day).unwrap();
+    ///     let start = local_time(date, NaiveTime::from_hms_opt(9, 0, 0).unwrap());
+    ///     StoredTask::new("write docs", start, start + Duration::from_secs(hours * 3600))
+    /// };
+    /// // Mondays the 1st and 8th of January, 2024
+    /// let averages = WeekdayAverages::from_tasks(&[task(1, 4), task(8, 6)]);
+    /// assert_eq!(averages.averages[0], 5.0);
+    /// assert_eq!(averages.counts[0], 2);
+    /// assert_eq!(averages.counts[1], 0);
+    /// ```
+    pub fn from_tasks(tasks: &[StoredTask]) -> WeekdayAverages {
+        let mut daily: BTreeMap<NaiveDate, u64> = BTreeMap::new();
+        for task in tasks {
+            *daily.entry(local_date(task.started_at)).or_default() += task.seconds;
+        }
+        let mut totals = [0u64; 7];
+        let mut counts = [0u32; 7];
+        for (date, seconds) in daily {
+            let index = date.weekday().num_days_from_monday() as usize;
+            totals[index] += seconds;
+            counts[index] += 1;
+        }
+        let mut averages = [0.0; 7];
+        for (index, average) in averages.iter_mut().enumerate() {
+            if counts[index] > 0 {
+                *average = totals[index] as f64 / 3600.0 / counts[index] as f64;
+            }
+        }
+        WeekdayAverages { averages, counts }
+    }
+
+    /// Whether no time was tracked on any day.
+    pub fn is_empty(&self) -> bool {
+        self.counts.iter().all(|&count| count == 0)
+    }
+}
+
+/// Formats a line per day of the week, Monday first, e.g.
+///
+/// ```text
+/// Monday: avg 6h 12m (over 8 Mondays)
+/// Tuesday: avg 5h 40m (over 1 Tuesday)
+/// Wednesday: no time tracked
+/// ```
+impl fmt::Display for WeekdayAverages {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (index, weekday) in WEEKDAYS.into_iter().enumerate() {
+            if index > 0 {
+                writeln!(f)?;
+            }
+            let name = weekday_name(weekday);
+            match self.counts[index] {
+                0 => write!(f, "{}: no time tracked", name)?,
+                count => write!(
+                    f,
+                    "{}: avg {} (over {} {}{})",
+                    name,
+                    hours_and_minutes((self.averages[index] * 3600.0).round() as u64),
+                    count,
+                    name,
+                    if count == 1 { "" } else { "s" }
+                )?,
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests_weekday {
+    use super::*;
+    use crate::reports::local_time;
+    use chrono::NaiveTime;
+    use std::time::Duration;
+
+    /// A task started at `hour` o'clock local time on a day of January 2024, which began on a
+    /// Monday.
+    fn task(day: u32, hour: u32, minutes: u64) -> StoredTask {
+        let start = local_time(
+            NaiveDate::from_ymd_opt(2024, 1, day).unwrap(),
+            NaiveTime::from_hms_opt(hour, 0, 0).unwrap(),
+        );
+        StoredTask::new("task", start, start + Duration::from_secs(minutes * 60))
+    }
+
+    fn averages() -> WeekdayAverages {
+        WeekdayAverages::from_tasks(&[
+            // Mondays: 6h, 4h and 2h 24m on the same day, and 7h 6m
+            task(1, 9, 360),
+            task(8, 9, 240),
+            task(8, 14, 144),
+            task(15, 9, 426),
+            // a Tuesday
+            task(2, 9, 90),
+            // Fridays
+            task(5, 9, 30),
+            task(12, 9, 60),
+            // a late Sunday session counts on the Sunday it started on
+            task(7, 23, 120),
+        ])
+    }
+
+    #[test]
+    fn averages_daily_totals_by_weekday() {
+        let averages = averages();
+        assert_eq!(averages.counts, [3, 1, 0, 0, 2, 0, 1]);
+        assert_eq!(averages.averages[0], 6.5);
+        assert_eq!(averages.averages[1], 1.5);
+        assert_eq!(averages.averages[2], 0.0);
+        assert_eq!(averages.averages[4], 0.75);
+        assert_eq!(averages.averages[6], 2.0);
+        assert!(!averages.is_empty());
+        assert!(WeekdayAverages::from_tasks(&[]).is_empty());
+    }
+
+    #[test]
+    fn formats_a_line_per_weekday() {
+        let lines: Vec<String> = averages().to_string().lines().map(String::from).collect();
+        assert_eq!(
+            lines,
+            [
+                "Monday: avg 6h 30m (over 3 Mondays)",
+                "Tuesday: avg 1h 30m (over 1 Tuesday)",
+                "Wednesday: no time tracked",
+                "Thursday: no time tracked",
+                "Friday: avg 0h 45m (over 2 Fridays)",
+                "Saturday: no time tracked",
+                "Sunday: avg 2h 0m (over 1 Sunday)",
+            ]
+        );
+    }
+}
@@ -0,0 +1,106 @@
+//! Reports covering a single week.
+
+use super::local_date;
+use crate::storage::StoredTask;
+use chrono::{Datelike, Duration, NaiveDate, Weekday};
+
+/// A week of the task history, starting on a configurable day.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct WeeklyReport {
+    /// The first day of the week.
+    pub start: NaiveDate,
+}
+
+impl WeeklyReport {
+    /// Gets the week that `reference` falls in, for weeks that start on `first_day`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use chrono::{NaiveDate, Weekday};
+    /// use timetracker::reports::WeeklyReport;
+    ///
+    /// let wednesday = NaiveDate::from_ymd_opt(2024, 1, 17).unwrap();
+    /// let week = WeeklyReport::for_week(wednesday, Weekday::Mon);
+    /// assert_eq!(week.start, NaiveDate::from_ymd_opt(2024, 1, 15).unwrap());
+    /// ```
+    pub fn for_week(reference: NaiveDate, first_day: Weekday) -> WeeklyReport {
+        let days_since_start = reference.weekday().days_since(first_day);
+        WeeklyReport {
+            start: reference - Duration::days(i64::from(days_since_start)),
+        }
+    }
+
+    /// Whether a task started in this week.
+    pub fn contains(&self, task: &StoredTask) -> bool {
+        let date = local_date(task.started_at);
+        date >= self.start && date < self.start + Duration::days(7)
+    }
+
+    /// Adds up the seconds tracked on each day of the week, starting with the first day.
+    ///
+    /// Tasks are counted on the day they started. Tasks outside the week are ignored.
+    pub fn daily_totals(&self, tasks: &[StoredTask]) -> [u64; 7] {
+        let mut totals = [0; 7];
+        for task in tasks.iter().filter(|task| self.contains(task)) {
+            let day = (local_date(task.started_at) - self.start).num_days();
+            totals[day as usize] += task.seconds;
+        }
+        totals
+    }
+
+    /// Gets the abbreviated names of the days of the week, starting with the first day.
+    pub fn day_names(&self) -> [String; 7] {
+        let first_day = self.start.weekday();
+        std::array::from_fn(|day| {
+            let weekday = (0..day).fold(first_day, |weekday, _| weekday.succ());
+            weekday.to_string()
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests_weekly {
+    use super::*;
+    use crate::reports::local_time;
+    use chrono::NaiveTime;
+
+    fn task(name: &str, date: NaiveDate, seconds: u64) -> StoredTask {
+        let started_at = local_time(date, NaiveTime::from_hms_opt(12, 0, 0).unwrap());
+        StoredTask::new(
+            name,
+            started_at,
+            started_at + std::time::Duration::from_secs(seconds),
+        )
+    }
+
+    #[test]
+    fn week_starts_on_the_first_day() {
+        let sunday = NaiveDate::from_ymd_opt(2024, 1, 14).unwrap();
+        assert_eq!(WeeklyReport::for_week(sunday, Weekday::Sun).start, sunday);
+        assert_eq!(
+            WeeklyReport::for_week(sunday, Weekday::Mon).start,
+            NaiveDate::from_ymd_opt(2024, 1, 8).unwrap()
+        );
+        assert_eq!(
+            WeeklyReport::for_week(sunday, Weekday::Sun).day_names()[..2],
+            [String::from("Sun"), String::from("Mon")]
+        );
+    }
+
+    #[test]
+    fn daily_totals_only_count_the_week() {
+        let monday = NaiveDate::from_ymd_opt(2024, 1, 15).unwrap();
+        let week =
+            WeeklyReport::for_week(NaiveDate::from_ymd_opt(2024, 1, 21).unwrap(), Weekday::Mon);
+        assert_eq!(week.start, monday);
+
+        let tasks = [
+            task("a", monday, 60),
+            task("b", monday, 30),
+            task("a", NaiveDate::from_ymd_opt(2024, 1, 21).unwrap(), 10),
+            task("a", NaiveDate::from_ymd_opt(2024, 1, 22).unwrap(), 999),
+        ];
+        assert_eq!(week.daily_totals(&tasks), [90, 0, 0, 0, 0, 0, 10]);
+    }
+}
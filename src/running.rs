@@ -0,0 +1,356 @@
+//! The tasks that are running right now.
+//!
+//! Running tasks are kept in a small file of their own (`running.json`, next to the history
+//! file by default). A task is added when it starts and removed when it stops, and the file is
+//! deleted once nothing is running, so `time-tracker summary` can show what's running without
+//! reading the whole history.
+//!
+//! Running tasks can be paused from another process with `time-tracker pause all`. The pause is
+//! recorded in the running file, and the process tracking the task leaves the paused time out
+//! when the task is stopped.
+
+use crate::{format_duration_compact, storage, storage::StoredTask};
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt, fs, io,
+    path::Path,
+    str::FromStr,
+    time::{Duration, SystemTime},
+};
+
+/// A task in the running file, along with whether it is paused.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RunningTask {
+    /// The task, as it started.
+    #[serde(flatten)]
+    pub task: StoredTask,
+    /// When the task was paused, or `None` if it is running.
+    #[serde(
+        default,
+        skip_serializing_if = "Option::is_none",
+        with = "storage::rfc3339_option"
+    )]
+    pub paused_at: Option<SystemTime>,
+    /// How long the task was paused for before it was last resumed (in seconds).
+    #[serde(default, skip_serializing_if = "is_zero")]
+    pub paused_secs: u64,
+}
+
+/// Whether `paused_secs` can be left out of the running file.
+fn is_zero(secs: &u64) -> bool {
+    *secs == 0
+}
+
+impl From<StoredTask> for RunningTask {
+    fn from(task: StoredTask) -> RunningTask {
+        RunningTask {
+            task,
+            paused_at: None,
+            paused_secs: 0,
+        }
+    }
+}
+
+impl RunningTask {
+    /// Checks whether the task is paused.
+    pub fn is_paused(&self) -> bool {
+        self.paused_at.is_some()
+    }
+
+    /// Pauses the task at `now`. Returns `false` if it was already paused.
+    pub fn pause(&mut self, now: SystemTime) -> bool {
+        if self.is_paused() {
+            return false;
+        }
+        self.paused_at = Some(now);
+        true
+    }
+
+    /// Resumes the task at `now`, adding the time since it was paused to `paused_secs`.
+    /// Returns `false` if it wasn't paused.
+    pub fn resume(&mut self, now: SystemTime) -> bool {
+        let Some(paused_at) = self.paused_at.take() else {
+            return false;
+        };
+        self.paused_secs += now.duration_since(paused_at).unwrap_or_default().as_secs();
+        true
+    }
+
+    /// Gets how long the task has been paused for in total by `now`, including the current
+    /// pause.
+    pub fn paused_duration(&self, now: SystemTime) -> Duration {
+        let current = self.paused_at.map_or(Duration::ZERO, |paused_at| {
+            now.duration_since(paused_at).unwrap_or_default()
+        });
+        Duration::from_secs(self.paused_secs) + current
+    }
+
+    /// Gets the time tracked for the task by `now` (in seconds), leaving out pauses.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use std::time::{Duration, UNIX_EPOCH};
+    /// use timetracker::{running::RunningTask, storage::StoredTask};
+    ///
+    /// let started_at = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+    /// let mut task = RunningTask::from(StoredTask::running("meeting", started_at));
+    /// task.pause(started_at + Duration::from_secs(600));
+    /// assert_eq!(task.elapsed_secs(started_at + Duration::from_secs(900)), 600);
+    /// ```
+    pub fn elapsed_secs(&self, now: SystemTime) -> u64 {
+        now.duration_since(self.task.started_at)
+            .unwrap_or_default()
+            .saturating_sub(self.paused_duration(now))
+            .as_secs()
+    }
+}
+
+/// Reads the running tasks, treating a missing file as nothing running.
+pub fn load(path: &Path) -> io::Result<Vec<RunningTask>> {
+    match fs::read_to_string(path) {
+        Ok(contents) => serde_json::from_str(&contents).map_err(|e| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{}: {}", path.display(), e),
+            )
+        }),
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Ok(Vec::new()),
+        Err(e) => Err(e),
+    }
+}
+
+/// Writes the running tasks, deleting the file if there are none.
+///
+/// The file is replaced as a whole, so a failed write leaves the tasks as they were.
+pub fn save(path: &Path, tasks: &[RunningTask]) -> io::Result<()> {
+    if tasks.is_empty() {
+        return match fs::remove_file(path) {
+            Err(e) if e.kind() != io::ErrorKind::NotFound => Err(e),
+            _ => Ok(()),
+        };
+    }
+    storage::write_atomically(path, serde_json::to_string(tasks)?.as_bytes())
+}
+
+/// Records that a task has started.
+pub fn add(path: &Path, task: StoredTask) -> io::Result<()> {
+    let mut tasks = load(path)?;
+    tasks.push(RunningTask::from(task));
+    save(path, &tasks)
+}
+
+/// Finds the task named `name` that started at `started_at` among the running tasks.
+///
+/// The start time is compared to the whole second, the resolution it is saved with.
+pub fn find<'a>(
+    tasks: &'a [RunningTask],
+    name: &str,
+    started_at: SystemTime,
+) -> Option<&'a RunningTask> {
+    position(tasks, name, started_at).map(|index| &tasks[index])
+}
+
+/// Gets the index of the task [`find`] looks for.
+fn position(tasks: &[RunningTask], name: &str, started_at: SystemTime) -> Option<usize> {
+    let started_at = StoredTask::running(name, started_at).started_at;
+    tasks
+        .iter()
+        .position(|running| running.task.name == name && running.task.started_at == started_at)
+}
+
+/// Records that the task named `name` that started at `started_at` has stopped, and returns it
+/// as it was in the running file (if it was there).
+pub fn remove(path: &Path, name: &str, started_at: SystemTime) -> io::Result<Option<RunningTask>> {
+    let mut tasks = load(path)?;
+    let Some(index) = position(&tasks, name, started_at) else {
+        return Ok(None);
+    };
+    let removed = tasks.remove(index);
+    save(path, &tasks)?;
+    Ok(Some(removed))
+}
+
+/// Pauses every running task at once.
+///
+/// Either every task is paused or, if the running file can't be written, none are.
+pub struct PauseAllOperation;
+
+impl PauseAllOperation {
+    /// Pauses the tasks in the running file at `path` that aren't paused yet, and returns how
+    /// many were.
+    pub fn apply(path: &Path, now: SystemTime) -> io::Result<usize> {
+        update_all(path, |task| task.pause(now))
+    }
+}
+
+/// Resumes every paused task at once.
+///
+/// Either every task is resumed or, if the running file can't be written, none are.
+pub struct ResumeAllOperation;
+
+impl ResumeAllOperation {
+    /// Resumes the paused tasks in the running file at `path`, and returns how many were.
+    pub fn apply(path: &Path, now: SystemTime) -> io::Result<usize> {
+        update_all(path, |task| task.resume(now))
+    }
+}
+
+/// Applies `update` to every running task and saves them, returning how many it changed.
+///
+/// The tasks are changed in memory and the file is replaced in one go, so the file on disk only
+/// ever has all of the changes or none of them. Nothing is written if nothing changed.
+fn update_all(path: &Path, mut update: impl FnMut(&mut RunningTask) -> bool) -> io::Result<usize> {
+    let mut tasks = load(path)?;
+    let mut changed = 0;
+    for task in &mut tasks {
+        if update(task) {
+            changed += 1;
+        }
+    }
+    if changed > 0 {
+        save(path, &tasks)?;
+    }
+    Ok(changed)
+}
+
+/// How `time-tracker summary` styles its output.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum SummaryFormat {
+    /// No formatting at all.
+    #[default]
+    Plain,
+    /// Colored, with the color codes marked as zero-width so they are safe to use in `PS1`.
+    Bash,
+}
+
+impl fmt::Display for SummaryFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SummaryFormat::Plain => write!(f, "plain"),
+            SummaryFormat::Bash => write!(f, "bash"),
+        }
+    }
+}
+
+impl FromStr for SummaryFormat {
+    type Err = String;
+
+    fn from_str(value: &str) -> Result<SummaryFormat, String> {
+        match value {
+            "plain" => Ok(SummaryFormat::Plain),
+            "bash" => Ok(SummaryFormat::Bash),
+            _ => Err(format!("expected plain or bash, got '{}'", value)),
+        }
+    }
+}
+
+/// Summarizes the running tasks on one line, as `[task name: 1h23m]`, or returns an empty
+/// string if nothing is running. Paused tasks are marked as `[task name: 1h23m (paused)]`.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::{Duration, SystemTime};
+/// use timetracker::{running::{summary_line, RunningTask, SummaryFormat}, storage::StoredTask};
+///
+/// let now = SystemTime::now();
+/// let task = RunningTask::from(StoredTask::running("write docs", now - Duration::from_secs(4980)));
+/// assert_eq!(summary_line(&[task], now, SummaryFormat::Plain), "[write docs: 1h23m]");
+/// ```
+pub fn summary_line(tasks: &[RunningTask], now: SystemTime, format: SummaryFormat) -> String {
+    let parts: Vec<String> = tasks
+        .iter()
+        .map(|running| {
+            let paused = if running.is_paused() { " (paused)" } else { "" };
+            format!(
+                "[{}: {}{}]",
+                running.task.name,
+                format_duration_compact(running.elapsed_secs(now)),
+                paused
+            )
+        })
+        .collect();
+    let line = parts.join(" ");
+    match format {
+        SummaryFormat::Plain => line,
+        SummaryFormat::Bash if line.is_empty() => line,
+        // \x01 and \x02 tell readline that the color codes between them take up no space
+        SummaryFormat::Bash => format!("\x01\x1b[32m\x02{}\x01\x1b[0m\x02", line),
+    }
+}
+
+#[cfg(test)]
+mod tests_running {
+    use super::*;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    #[test]
+    fn file_is_removed_when_nothing_is_running() {
+        let path = std::env::temp_dir().join(format!("running_{}.json", std::process::id()));
+        let _ = fs::remove_file(&path);
+        let started_at = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+
+        add(&path, StoredTask::running("a", started_at)).unwrap();
+        add(&path, StoredTask::running("b", started_at)).unwrap();
+        remove(&path, "a", started_at).unwrap();
+        let running = load(&path).unwrap();
+        assert_eq!(running.len(), 1);
+        assert_eq!(running[0].task.name, "b");
+
+        remove(&path, "b", started_at).unwrap();
+        assert!(!path.exists());
+        assert!(load(&path).unwrap().is_empty());
+    }
+
+    #[test]
+    fn bash_summary_is_colored() {
+        let now = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let tasks = [
+            RunningTask::from(StoredTask::running("a", now - Duration::from_secs(60))),
+            RunningTask::from(StoredTask::running("b", now)),
+        ];
+        assert_eq!(
+            summary_line(&tasks, now, SummaryFormat::Plain),
+            "[a: 1m] [b: 0s]"
+        );
+        assert_eq!(
+            summary_line(&tasks, now, SummaryFormat::Bash),
+            "\x01\x1b[32m\x02[a: 1m] [b: 0s]\x01\x1b[0m\x02"
+        );
+        assert_eq!(summary_line(&[], now, SummaryFormat::Bash), "");
+    }
+
+    #[test]
+    fn pauses_and_resumes_every_task() {
+        let path = std::env::temp_dir().join(format!("paused_{}.json", std::process::id()));
+        let started_at = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let minutes = |minutes: u64| started_at + Duration::from_secs(minutes * 60);
+        let mut already_paused = RunningTask::from(StoredTask::running("c", started_at));
+        already_paused.pause(minutes(5));
+        let tasks = [
+            RunningTask::from(StoredTask::running("a", started_at)),
+            RunningTask::from(StoredTask::running("b", started_at)),
+            already_paused,
+        ];
+        save(&path, &tasks).unwrap();
+
+        assert_eq!(PauseAllOperation::apply(&path, minutes(10)).unwrap(), 2);
+        let paused = load(&path).unwrap();
+        assert!(paused.iter().all(RunningTask::is_paused));
+        assert_eq!(
+            summary_line(&paused[..1], minutes(30), SummaryFormat::Plain),
+            "[a: 10m (paused)]"
+        );
+
+        assert_eq!(ResumeAllOperation::apply(&path, minutes(40)).unwrap(), 3);
+        let resumed = load(&path).unwrap();
+        assert_eq!(ResumeAllOperation::apply(&path, minutes(40)).unwrap(), 0);
+        save(&path, &[]).unwrap();
+        let elapsed: Vec<u64> = resumed
+            .iter()
+            .map(|task| task.elapsed_secs(minutes(60)))
+            .collect();
+        assert_eq!(elapsed, [30 * 60, 30 * 60, 25 * 60]);
+    }
+}
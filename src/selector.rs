@@ -0,0 +1,194 @@
+//! Picking a task from the history, for commands that change a single task.
+//!
+//! Tasks are picked by their id on the command line, or with a [`TaskSelector`] when running in
+//! a terminal. [`FuzzySelector`] lets the user search the task names as they type.
+
+use crate::{format_duration_compact, storage::StoredTask};
+use chrono::{DateTime, Local};
+use dialoguer::FuzzySelect;
+
+/// Asks the user to pick one of the tasks.
+pub trait TaskSelector {
+    /// Picks one of `tasks`, or `None` if there are none or the user cancelled.
+    fn select<'a>(&self, tasks: &'a [StoredTask]) -> Option<&'a StoredTask>;
+}
+
+/// Lists the tasks in the terminal and lets the user narrow them down by typing part of the
+/// name, then pick one with the arrow keys and Enter. Esc cancels.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct FuzzySelector;
+
+impl TaskSelector for FuzzySelector {
+    fn select<'a>(&self, tasks: &'a [StoredTask]) -> Option<&'a StoredTask> {
+        if tasks.is_empty() {
+            return None;
+        }
+        let labels: Vec<String> = tasks.iter().map(label).collect();
+        let index = FuzzySelect::new()
+            .with_prompt("Task")
+            .items(&labels)
+            .default(tasks.len() - 1)
+            .interact_opt()
+            .ok()??;
+        tasks.get(index)
+    }
+}
+
+/// Always picks the task at an index, for testing the commands that ask for a task.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::UNIX_EPOCH;
+/// use timetracker::{
+///     selector::{MockSelector, TaskSelector},
+///     storage::StoredTask,
+/// };
+///
+/// let tasks = [StoredTask::new("a", UNIX_EPOCH, UNIX_EPOCH)];
+/// assert_eq!(MockSelector(0).select(&tasks).unwrap().name, "a");
+/// assert_eq!(MockSelector(1).select(&tasks), None);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MockSelector(pub usize);
+
+impl TaskSelector for MockSelector {
+    fn select<'a>(&self, tasks: &'a [StoredTask]) -> Option<&'a StoredTask> {
+        tasks.get(self.0)
+    }
+}
+
+/// Describes a task in the selector the way `time-tracker list` does: its short id, when it
+/// started, how long it took, and its name.
+pub fn label(task: &StoredTask) -> String {
+    let duration = match task.stopped_at {
+        Some(_) => format_duration_compact(task.seconds),
+        None => String::from("running"),
+    };
+    format!(
+        "{}  {}  {}  {}",
+        task.short_id(),
+        DateTime::<Local>::from(task.started_at).format("%Y-%m-%d %H:%M"),
+        duration,
+        task.name
+    )
+}
+
+/// Finds the task whose id starts with `id`, as shown by `time-tracker list`. Dashes in `id`
+/// are ignored, so whole ids work too.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::UNIX_EPOCH;
+/// use timetracker::{selector::find_by_id, storage::StoredTask};
+///
+/// let tasks = [StoredTask::new("a", UNIX_EPOCH, UNIX_EPOCH)];
+/// let id = tasks[0].short_id();
+/// assert_eq!(find_by_id(&tasks, &id).unwrap().name, "a");
+/// assert!(find_by_id(&tasks, "not an id").is_err());
+/// ```
+pub fn find_by_id<'a>(tasks: &'a [StoredTask], id: &str) -> Result<&'a StoredTask, String> {
+    let prefix = id.replace('-', "").to_lowercase();
+    if prefix.is_empty() {
+        return Err(String::from("the id is empty"));
+    }
+    let mut matches = tasks
+        .iter()
+        .filter(|task| task.id.simple().to_string().starts_with(&prefix));
+    match (matches.next(), matches.next()) {
+        (Some(task), None) => Ok(task),
+        (None, _) => Err(format!("no task has the id {}", id)),
+        (Some(_), Some(_)) => Err(format!("more than one task has an id starting with {}", id)),
+    }
+}
+
+/// Finds the task with the id `id` or, without one, asks `selector` for a task.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::UNIX_EPOCH;
+/// use timetracker::{
+///     selector::{pick_task, MockSelector},
+///     storage::StoredTask,
+/// };
+///
+/// let tasks = [StoredTask::new("a", UNIX_EPOCH, UNIX_EPOCH)];
+/// assert_eq!(pick_task(&tasks, None, &MockSelector(0)).unwrap().name, "a");
+/// ```
+pub fn pick_task<'a>(
+    tasks: &'a [StoredTask],
+    id: Option<&str>,
+    selector: &dyn TaskSelector,
+) -> Result<&'a StoredTask, String> {
+    match id {
+        Some(id) => find_by_id(tasks, id),
+        None => selector
+            .select(tasks)
+            .ok_or_else(|| String::from("no task was picked")),
+    }
+}
+
+#[cfg(test)]
+mod tests_selector {
+    use super::*;
+    use std::time::{Duration, UNIX_EPOCH};
+    use uuid::Uuid;
+
+    fn task(name: &str, id: &str) -> StoredTask {
+        let mut task = StoredTask::new(name, UNIX_EPOCH, UNIX_EPOCH + Duration::from_secs(90));
+        task.id = Uuid::parse_str(id).unwrap();
+        task
+    }
+
+    #[test]
+    fn finds_tasks_by_id_prefix() {
+        let tasks = [
+            task("a", "12345678-0000-0000-0000-000000000000"),
+            task("b", "12349999-0000-0000-0000-000000000000"),
+        ];
+        assert_eq!(find_by_id(&tasks, "12345678").unwrap().name, "a");
+        assert_eq!(
+            find_by_id(&tasks, "12349999-0000-0000-0000-000000000000")
+                .unwrap()
+                .name,
+            "b"
+        );
+        assert!(find_by_id(&tasks, "1234")
+            .unwrap_err()
+            .contains("more than one"));
+        assert!(find_by_id(&tasks, "ffff").unwrap_err().contains("no task"));
+        assert!(find_by_id(&tasks, "").is_err());
+    }
+
+    #[test]
+    fn labels_tasks_like_list() {
+        let label = label(&task("email", "abcdef01-0000-0000-0000-000000000000"));
+        assert!(label.starts_with("abcdef01  "));
+        assert!(label.ends_with("  1m30s  email"));
+    }
+
+    #[test]
+    fn picks_with_the_selector_unless_given_an_id() {
+        let tasks = [
+            task("a", "12345678-0000-0000-0000-000000000000"),
+            task("b", "abcdef01-0000-0000-0000-000000000000"),
+        ];
+        assert_eq!(pick_task(&tasks, None, &MockSelector(1)).unwrap().name, "b");
+        assert_eq!(
+            pick_task(&tasks, Some("1234"), &MockSelector(1))
+                .unwrap()
+                .name,
+            "a"
+        );
+        assert_eq!(
+            pick_task(&tasks, None, &MockSelector(2)).unwrap_err(),
+            "no task was picked"
+        );
+        assert_eq!(
+            pick_task(&[], None, &MockSelector(0)).unwrap_err(),
+            "no task was picked"
+        );
+    }
+}
@@ -0,0 +1,668 @@
+//! Persistence for tracked tasks.
+//!
+//! Tasks are saved through a [`Storage`] backend. The default, [`JsonStorage`], appends them to a
+//! history file as JSON lines, one `StoredTask` per line. [`SqliteStorage`] keeps them in an
+//! SQLite database instead, and [`BincodeStorage`] in a binary file that is faster to load.
+//! Which backend is used, and where its file lives, is part of the
+//! [`Config`](crate::config::Config).
+
+use chrono::{DateTime, SecondsFormat, Utc};
+use schemars::{gen::SchemaSettings, JsonSchema};
+use serde::{Deserialize, Serialize};
+use std::{
+    fmt, fs,
+    io::{self, BufRead, Read, Seek, Write},
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use uuid::Uuid;
+
+pub mod binary;
+pub mod sqlite;
+
+pub use binary::BincodeStorage;
+pub use sqlite::SqliteStorage;
+
+/// A task as it is written to the history file.
+///
+/// Unlike [`Task`](crate::Task), which measures time with `Instant`, a `StoredTask` records
+/// wall-clock timestamps so it can be read back after the program has exited.
+///
+/// # Examples
+///
+/// ```
+/// use std::time::{Duration, UNIX_EPOCH};
+/// use timetracker::storage::StoredTask;
+///
+/// let started_at = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+/// let stopped_at = started_at + Duration::from_secs(90);
+/// let task = StoredTask::new("Task 1", started_at, stopped_at);
+/// assert_eq!(task.seconds, 90);
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize, JsonSchema)]
+#[serde(from = "StoredTaskRecord")]
+#[schemars(description = "A task as it is written to the history file, one per line.")]
+pub struct StoredTask {
+    /// The name of the task.
+    pub name: String,
+    /// When the task was started.
+    #[serde(with = "rfc3339")]
+    #[schemars(with = "DateTime<Utc>")]
+    pub started_at: SystemTime,
+    /// When the task was stopped, or `None` if it is still running.
+    #[serde(default, with = "rfc3339_option")]
+    #[schemars(with = "Option<DateTime<Utc>>")]
+    pub stopped_at: Option<SystemTime>,
+    /// The total time tracked for the task (in seconds).
+    pub seconds: u64,
+    /// Whether the time can be billed to a client.
+    ///
+    /// Only written to the history file when `false`; tasks saved before this field existed
+    /// count as billable.
+    #[serde(
+        default = "billable_default",
+        skip_serializing_if = "is_billable_default"
+    )]
+    pub billable: bool,
+    /// A stable identity for the task, used to refer to it from the command line.
+    ///
+    /// Tasks saved before this field existed get an id derived from their name and start
+    /// time, so they keep the same id every time they are read.
+    #[schemars(with = "Option<Uuid>")]
+    pub id: Uuid,
+    /// The git repository (or directory, outside of one) the task was started in, if known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context: Option<String>,
+    /// The client the task is done for, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client: Option<String>,
+    /// The project the task belongs to, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project: Option<String>,
+    /// Free-form labels, e.g. the kind of work the task was.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// How long the task was expected to take (in seconds), if known, e.g. from its template.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub estimated_secs: Option<u64>,
+}
+
+/// A line of the history file as it is read, which may be missing fields that were added later.
+#[derive(Deserialize)]
+struct StoredTaskRecord {
+    name: String,
+    #[serde(with = "rfc3339")]
+    started_at: SystemTime,
+    #[serde(default, with = "rfc3339_option")]
+    stopped_at: Option<SystemTime>,
+    seconds: u64,
+    #[serde(default = "billable_default")]
+    billable: bool,
+    id: Option<Uuid>,
+    #[serde(default)]
+    context: Option<String>,
+    #[serde(default)]
+    client: Option<String>,
+    #[serde(default)]
+    project: Option<String>,
+    #[serde(default)]
+    tags: Vec<String>,
+    #[serde(default)]
+    estimated_secs: Option<u64>,
+}
+
+impl From<StoredTaskRecord> for StoredTask {
+    fn from(record: StoredTaskRecord) -> StoredTask {
+        StoredTask {
+            id: record
+                .id
+                .unwrap_or_else(|| legacy_id(&record.name, record.started_at)),
+            name: record.name,
+            started_at: record.started_at,
+            stopped_at: record.stopped_at,
+            seconds: record.seconds,
+            billable: record.billable,
+            context: record.context,
+            client: record.client,
+            project: record.project,
+            tags: record.tags,
+            estimated_secs: record.estimated_secs,
+        }
+    }
+}
+
+/// Derives an id for a task saved without one from its name and start time.
+fn legacy_id(name: &str, started_at: SystemTime) -> Uuid {
+    let key = format!("{}@{}", name, format_timestamp(started_at));
+    Uuid::new_v5(&Uuid::NAMESPACE_OID, key.as_bytes())
+}
+
+impl StoredTask {
+    /// Creates a completed task from its start and stop times.
+    ///
+    /// Both timestamps are truncated to whole seconds, matching the resolution of `seconds`.
+    /// The task is billable; set [`billable`](StoredTask::billable) to change that.
+    pub fn new(name: &str, started_at: SystemTime, stopped_at: SystemTime) -> StoredTask {
+        let started_at = truncate_to_secs(started_at);
+        let stopped_at = truncate_to_secs(stopped_at);
+        let seconds = stopped_at
+            .duration_since(started_at)
+            .unwrap_or_default()
+            .as_secs();
+        StoredTask {
+            name: name.to_string(),
+            started_at,
+            stopped_at: Some(stopped_at),
+            seconds,
+            billable: true,
+            id: Uuid::new_v4(),
+            context: None,
+            client: None,
+            project: None,
+            tags: Vec::new(),
+            estimated_secs: None,
+        }
+    }
+
+    /// Creates a task that is still running.
+    ///
+    /// The start time is truncated to whole seconds, like in [`StoredTask::new`].
+    pub fn running(name: &str, started_at: SystemTime) -> StoredTask {
+        StoredTask {
+            name: name.to_string(),
+            started_at: truncate_to_secs(started_at),
+            stopped_at: None,
+            seconds: 0,
+            billable: true,
+            id: Uuid::new_v4(),
+            context: None,
+            client: None,
+            project: None,
+            tags: Vec::new(),
+            estimated_secs: None,
+        }
+    }
+
+    /// Gets when a stopped task ends on a timeline: `seconds` after it started.
+    ///
+    /// This is earlier than `stopped_at` when time was left out of `seconds`, e.g. because the
+    /// task was paused, so reports that draw tasks as intervals don't show that time as tracked.
+    pub fn tracked_until(&self) -> Option<SystemTime> {
+        self.stopped_at?;
+        Some(self.started_at + Duration::from_secs(self.seconds))
+    }
+
+    /// Gets the first 8 hex digits of the id, which is how ids are shown to the user.
+    pub fn short_id(&self) -> String {
+        self.id.simple().to_string()[..8].to_string()
+    }
+}
+
+/// The value of `billable` for tasks that don't have it.
+fn billable_default() -> bool {
+    true
+}
+
+/// Whether `billable` can be left out of the history file.
+fn is_billable_default(billable: &bool) -> bool {
+    *billable == billable_default()
+}
+
+/// Drops the sub-second part of a timestamp.
+fn truncate_to_secs(time: SystemTime) -> SystemTime {
+    let secs = time
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    UNIX_EPOCH + Duration::from_secs(secs)
+}
+
+/// Gets the directory where the time tracker keeps its files (`~/.timetracker`).
+pub fn data_dir() -> PathBuf {
+    dirs::home_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join(".timetracker")
+}
+
+/// The storage backends a history can be kept in.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StorageFormat {
+    /// A JSON lines file, handled by [`JsonStorage`].
+    Json,
+    /// An SQLite database, handled by [`SqliteStorage`].
+    Sqlite,
+    /// A binary file of `bincode` records, handled by [`BincodeStorage`].
+    Binary,
+}
+
+impl fmt::Display for StorageFormat {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            StorageFormat::Json => write!(f, "json"),
+            StorageFormat::Sqlite => write!(f, "sqlite"),
+            StorageFormat::Binary => write!(f, "binary"),
+        }
+    }
+}
+
+impl FromStr for StorageFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "json" => Ok(StorageFormat::Json),
+            "sqlite" => Ok(StorageFormat::Sqlite),
+            "binary" => Ok(StorageFormat::Binary),
+            _ => Err(format!(
+                "unknown storage format '{}' (expected json, sqlite, or binary)",
+                s
+            )),
+        }
+    }
+}
+
+/// A place where tasks are kept between runs of the program.
+pub trait Storage {
+    /// Reads every stored task, oldest first.
+    fn load(&self) -> io::Result<Vec<StoredTask>>;
+
+    /// Saves a single task.
+    fn append(&mut self, task: &StoredTask) -> io::Result<()>;
+
+    /// Saves several tasks at once. Either every task is saved or, on error, none are.
+    fn append_all(&mut self, tasks: &[StoredTask]) -> io::Result<()>;
+
+    /// Replaces every stored task with `tasks`. On error, the stored tasks are left as they were.
+    fn replace_all(&mut self, tasks: &[StoredTask]) -> io::Result<()>;
+}
+
+/// Opens the storage backend `format` with its data kept at `path`.
+pub fn open(format: StorageFormat, path: &Path) -> io::Result<Box<dyn Storage>> {
+    match format {
+        StorageFormat::Json => Ok(Box::new(JsonStorage::new(path))),
+        StorageFormat::Sqlite => Ok(Box::new(SqliteStorage::open(path)?)),
+        StorageFormat::Binary => Ok(Box::new(BincodeStorage::open(path)?)),
+    }
+}
+
+/// Copies every task from `from` into `to`, returning how many tasks were copied.
+///
+/// The copy is all-or-nothing: if saving fails, `to` is left as it was.
+pub fn migrate(from: &dyn Storage, to: &mut dyn Storage) -> io::Result<usize> {
+    let tasks = from.load()?;
+    to.append_all(&tasks)?;
+    Ok(tasks.len())
+}
+
+/// Gets the path a storage file is renamed to once it has been migrated (`<path>.migrated`).
+pub fn migrated_path(path: &Path) -> PathBuf {
+    let mut migrated = path.as_os_str().to_owned();
+    migrated.push(".migrated");
+    PathBuf::from(migrated)
+}
+
+/// Gets the path a storage file is copied to before it is edited in place (`<path>.bak`).
+pub fn backup_path(path: &Path) -> PathBuf {
+    let mut backup = path.as_os_str().to_owned();
+    backup.push(".bak");
+    PathBuf::from(backup)
+}
+
+/// Stores tasks in a JSON lines history file.
+///
+/// # Examples
+///
+/// ```no_run
+/// use timetracker::storage::{JsonStorage, Storage};
+///
+/// let storage = JsonStorage::new("history.json");
+/// let tasks = storage.load().unwrap();
+/// ```
+pub struct JsonStorage {
+    path: PathBuf,
+}
+
+impl JsonStorage {
+    /// Creates a backend for the history file at `path`. The file is created when the first task
+    /// is saved.
+    pub fn new(path: impl Into<PathBuf>) -> JsonStorage {
+        JsonStorage { path: path.into() }
+    }
+
+    /// Gets the path of the history file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Replaces every task in the history file with `tasks`, writing a new copy of the file
+    /// and moving it into place.
+    pub fn replace_all(&self, tasks: &[StoredTask]) -> io::Result<()> {
+        let mut contents = String::new();
+        for task in tasks {
+            contents.push_str(&serde_json::to_string(task)?);
+            contents.push('\n');
+        }
+        write_atomically(&self.path, contents.as_bytes())
+    }
+
+    /// Reads the tasks on the complete lines after byte `offset`, returning them along with the
+    /// offset to read from next time.
+    ///
+    /// This lets the history file be followed as it grows. A line that is still being written
+    /// is left for the next call, and a file that has become shorter than `offset` is read
+    /// from the start.
+    pub fn read_from(&self, offset: u64) -> io::Result<(Vec<StoredTask>, u64)> {
+        let mut file = match fs::File::open(&self.path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok((Vec::new(), 0)),
+            Err(e) => return Err(e),
+        };
+        let offset = if file.metadata()?.len() < offset {
+            0
+        } else {
+            offset
+        };
+        file.seek(io::SeekFrom::Start(offset))?;
+        let mut contents = String::new();
+        file.read_to_string(&mut contents)?;
+
+        let complete = contents.rfind('\n').map_or(0, |end| end + 1);
+        let mut tasks = Vec::new();
+        for line in contents[..complete].lines() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let task = serde_json::from_str(line).map_err(|e| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("{}: {}", self.path.display(), e),
+                )
+            })?;
+            tasks.push(task);
+        }
+        Ok((tasks, offset + complete as u64))
+    }
+}
+
+impl Storage for JsonStorage {
+    /// Reads every task from the history file.
+    ///
+    /// A missing history file is treated as an empty history.
+    /// Blank lines are skipped; any other line that is not a valid task is an error.
+    fn load(&self) -> io::Result<Vec<StoredTask>> {
+        let file = match fs::File::open(&self.path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+            Err(e) => return Err(e),
+        };
+
+        let mut tasks = Vec::new();
+        for (index, line) in io::BufReader::new(file).lines().enumerate() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            let task = serde_json::from_str(&line).map_err(|e| {
+                io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    format!("{}:{}: {}", self.path.display(), index + 1, e),
+                )
+            })?;
+            tasks.push(task);
+        }
+
+        Ok(tasks)
+    }
+
+    /// Appends a task to the history file, creating the file (and its directory) if needed.
+    fn append(&mut self, task: &StoredTask) -> io::Result<()> {
+        create_parent_dir(&self.path)?;
+        let mut file = fs::OpenOptions::new()
+            .append(true)
+            .create(true)
+            .open(&self.path)?;
+        let line = serde_json::to_string(task)?;
+        writeln!(file, "{}", line)
+    }
+
+    /// Appends the tasks by writing a new copy of the history file and moving it into place.
+    fn append_all(&mut self, tasks: &[StoredTask]) -> io::Result<()> {
+        let mut contents = match fs::read_to_string(&self.path) {
+            Ok(contents) => contents,
+            Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(e),
+        };
+        if !contents.is_empty() && !contents.ends_with('\n') {
+            contents.push('\n');
+        }
+        for task in tasks {
+            contents.push_str(&serde_json::to_string(task)?);
+            contents.push('\n');
+        }
+        write_atomically(&self.path, contents.as_bytes())
+    }
+
+    fn replace_all(&mut self, tasks: &[StoredTask]) -> io::Result<()> {
+        JsonStorage::replace_all(self, tasks)
+    }
+}
+
+/// Creates the directory that `path` lives in, if there is one.
+fn create_parent_dir(path: &Path) -> io::Result<()> {
+    match path.parent() {
+        Some(parent) if !parent.as_os_str().is_empty() => fs::create_dir_all(parent),
+        _ => Ok(()),
+    }
+}
+
+/// Replaces the file at `path` with `contents` by writing a temporary file next to it and
+/// renaming it, so readers never see a half-written file.
+pub fn write_atomically(path: &Path, contents: &[u8]) -> io::Result<()> {
+    create_parent_dir(path)?;
+    let mut temp_path = path.as_os_str().to_owned();
+    temp_path.push(".tmp");
+    let temp_path = PathBuf::from(temp_path);
+
+    let mut file = fs::File::create(&temp_path)?;
+    file.write_all(contents)?;
+    file.sync_all()?;
+    fs::rename(&temp_path, path)
+}
+
+/// Gets the JSON Schema (draft-07) of a line of the history file.
+///
+/// The schema's `$id` includes the crate version, so tools can tell which version of the
+/// format it describes.
+pub fn history_schema() -> serde_json::Value {
+    let schema = SchemaSettings::draft07()
+        .into_generator()
+        .into_root_schema_for::<StoredTask>();
+    let mut schema = serde_json::to_value(schema).expect("schemas always serialize to JSON");
+    schema["$id"] = serde_json::Value::from(format!(
+        "https://github.com/trvswgnr/rust-time-tracker-cli/schemas/{}/stored-task.json",
+        env!("CARGO_PKG_VERSION")
+    ));
+    schema
+}
+
+/// Formats a `SystemTime` the way the history file does: as an RFC 3339 timestamp in UTC
+/// (e.g. `2024-01-15T09:00:00Z`).
+pub fn format_timestamp(time: SystemTime) -> String {
+    DateTime::<Utc>::from(time).to_rfc3339_opts(SecondsFormat::AutoSi, true)
+}
+
+/// Serializes a `SystemTime` as an RFC 3339 timestamp in UTC (e.g. `2024-01-15T09:00:00Z`).
+mod rfc3339 {
+    use super::*;
+    use serde::{de::Error, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(time: &SystemTime, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&format_timestamp(*time))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<SystemTime, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        DateTime::parse_from_rfc3339(&value)
+            .map(SystemTime::from)
+            .map_err(D::Error::custom)
+    }
+}
+
+/// Serializes an optional `SystemTime` as an RFC 3339 timestamp or `null`.
+pub(crate) mod rfc3339_option {
+    use super::*;
+    use serde::{Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        time: &Option<SystemTime>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match time {
+            Some(time) => rfc3339::serialize(time, serializer),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<SystemTime>, D::Error> {
+        #[derive(Deserialize)]
+        struct Wrapper(#[serde(with = "rfc3339")] SystemTime);
+
+        let value = Option::<Wrapper>::deserialize(deserializer)?;
+        Ok(value.map(|Wrapper(time)| time))
+    }
+}
+
+#[cfg(test)]
+mod tests_storage {
+    use super::*;
+
+    #[test]
+    fn tasks_without_ids_get_the_same_id_every_time() {
+        let line = r#"{"name":"a","started_at":"2024-01-15T09:00:00Z","stopped_at":"2024-01-15T09:30:00Z","seconds":1800}"#;
+        let first: StoredTask = serde_json::from_str(line).unwrap();
+        let second: StoredTask = serde_json::from_str(line).unwrap();
+        assert_eq!(first.id, second.id);
+        assert_eq!(first.id.get_version_num(), 5);
+
+        let mut other = first.clone();
+        other.name = String::from("b");
+        let other: StoredTask =
+            serde_json::from_str(&serde_json::to_string(&other).unwrap()).unwrap();
+        assert_eq!(other.id, first.id, "ids that were saved are kept");
+
+        let renamed: StoredTask = serde_json::from_str(&line.replace("\"a\"", "\"b\"")).unwrap();
+        assert_ne!(renamed.id, first.id);
+    }
+
+    #[test]
+    fn history_schema_accepts_stored_tasks() {
+        let schema = history_schema();
+        assert_eq!(schema["$schema"], "http://json-schema.org/draft-07/schema#");
+        assert_eq!(schema["properties"]["started_at"]["format"], "date-time");
+        let schema = jsonschema::JSONSchema::compile(&schema).unwrap();
+
+        let started_at = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let task = StoredTask::new("task", started_at, started_at + Duration::from_secs(60));
+        assert!(schema.is_valid(&serde_json::to_value(&task).unwrap()));
+        assert!(!schema.is_valid(&serde_json::json!({ "name": "task", "seconds": -1 })));
+    }
+
+    #[test]
+    fn round_trips_through_history_file() {
+        let path = std::env::temp_dir().join(format!("history_{}.json", std::process::id()));
+        let _ = fs::remove_file(&path);
+
+        let started_at = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let first = StoredTask::new("first", started_at, started_at + Duration::from_secs(61));
+        let mut second = StoredTask::new(
+            "second",
+            started_at + Duration::from_secs(120),
+            started_at + Duration::from_secs(3720),
+        );
+        second.billable = false;
+        second.project = Some(String::from("website"));
+        second.tags = vec![String::from("design")];
+        let mut storage = JsonStorage::new(&path);
+        storage.append(&first).unwrap();
+        storage.append(&second).unwrap();
+
+        let tasks = storage.load().unwrap();
+        assert_eq!(tasks, vec![first, second]);
+        assert_eq!(tasks[1].seconds, 3600);
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn reads_only_new_complete_lines() {
+        let path = std::env::temp_dir().join(format!("follow_{}.json", std::process::id()));
+        let _ = fs::remove_file(&path);
+
+        let started_at = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let first = StoredTask::new("first", started_at, started_at + Duration::from_secs(61));
+        let mut storage = JsonStorage::new(&path);
+        storage.append(&first).unwrap();
+        let (tasks, offset) = storage.read_from(0).unwrap();
+        assert_eq!(tasks, vec![first.clone()]);
+
+        let second = StoredTask::new("second", started_at, started_at + Duration::from_secs(5));
+        let line = serde_json::to_string(&second).unwrap();
+        let (half, rest) = line.split_at(line.len() / 2);
+        fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap()
+            .write_all(half.as_bytes())
+            .unwrap();
+        assert_eq!(storage.read_from(offset).unwrap(), (Vec::new(), offset));
+
+        fs::OpenOptions::new()
+            .append(true)
+            .open(&path)
+            .unwrap()
+            .write_all(format!("{}\n", rest).as_bytes())
+            .unwrap();
+        let (tasks, _) = storage.read_from(offset).unwrap();
+        assert_eq!(tasks, vec![second]);
+
+        fs::write(&path, "").unwrap();
+        assert_eq!(storage.read_from(offset).unwrap(), (Vec::new(), 0));
+
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn missing_history_file_is_empty() {
+        let path = std::env::temp_dir().join("this_history_file_does_not_exist.json");
+        assert!(JsonStorage::new(path).load().unwrap().is_empty());
+    }
+
+    #[test]
+    fn append_all_keeps_existing_tasks() {
+        let path = std::env::temp_dir().join(format!("append_all_{}.json", std::process::id()));
+        let _ = fs::remove_file(&path);
+
+        let started_at = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let tasks: Vec<StoredTask> = (0..3)
+            .map(|i| {
+                let start = started_at + Duration::from_secs(i * 100);
+                StoredTask::new(
+                    &format!("task {}", i),
+                    start,
+                    start + Duration::from_secs(50),
+                )
+            })
+            .collect();
+
+        let mut storage = JsonStorage::new(&path);
+        storage.append(&tasks[0]).unwrap();
+        storage.append_all(&tasks[1..]).unwrap();
+        assert_eq!(storage.load().unwrap(), tasks);
+
+        fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,491 @@
+//! Binary storage backend.
+//!
+//! The file starts with [`MAGIC`] and a [`VERSION`] byte, followed by one record per task: the
+//! length of the record as a little-endian `u32`, then the task encoded with `bincode`. Parsing
+//! it skips the timestamp and id strings of the history file, which makes loading a long
+//! history several times faster.
+//!
+//! Files written by an older version are still read, and are upgraded to the current version
+//! when they are opened.
+
+use super::{create_parent_dir, write_atomically, Storage, StoredTask};
+use serde::{Deserialize, Serialize};
+use std::{
+    fs,
+    io::{self, Write},
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+use uuid::Uuid;
+
+/// The bytes every binary history file starts with.
+pub const MAGIC: &[u8] = b"TIMETRACKER\x00";
+
+/// The version of the record encoding, written after [`MAGIC`].
+///
+/// Version 2 added the project and tags of a task, version 3 its client, and version 4 its
+/// estimate.
+pub const VERSION: u8 = 4;
+
+/// A task as it is encoded in the binary file.
+///
+/// `StoredTask` leaves default fields out of the history file, which `bincode` can't read back,
+/// so the binary file has its own record with every field always present.
+#[derive(Serialize, Deserialize)]
+struct BinaryRecord {
+    name: String,
+    started_at: SystemTime,
+    stopped_at: Option<SystemTime>,
+    seconds: u64,
+    billable: bool,
+    id: Uuid,
+    context: Option<String>,
+    client: Option<String>,
+    project: Option<String>,
+    tags: Vec<String>,
+    estimated_secs: Option<u64>,
+}
+
+/// A task as it is encoded in a version 1 file, before projects, tags, and clients.
+#[derive(Serialize, Deserialize)]
+struct BinaryRecordV1 {
+    name: String,
+    started_at: SystemTime,
+    stopped_at: Option<SystemTime>,
+    seconds: u64,
+    billable: bool,
+    id: Uuid,
+    context: Option<String>,
+}
+
+impl From<BinaryRecordV1> for BinaryRecord {
+    fn from(record: BinaryRecordV1) -> BinaryRecord {
+        BinaryRecord {
+            name: record.name,
+            started_at: record.started_at,
+            stopped_at: record.stopped_at,
+            seconds: record.seconds,
+            billable: record.billable,
+            id: record.id,
+            context: record.context,
+            client: None,
+            project: None,
+            tags: Vec::new(),
+            estimated_secs: None,
+        }
+    }
+}
+
+/// A task as it is encoded in a version 2 file, before clients.
+#[derive(Serialize, Deserialize)]
+struct BinaryRecordV2 {
+    name: String,
+    started_at: SystemTime,
+    stopped_at: Option<SystemTime>,
+    seconds: u64,
+    billable: bool,
+    id: Uuid,
+    context: Option<String>,
+    project: Option<String>,
+    tags: Vec<String>,
+}
+
+impl From<BinaryRecordV2> for BinaryRecord {
+    fn from(record: BinaryRecordV2) -> BinaryRecord {
+        BinaryRecord {
+            name: record.name,
+            started_at: record.started_at,
+            stopped_at: record.stopped_at,
+            seconds: record.seconds,
+            billable: record.billable,
+            id: record.id,
+            context: record.context,
+            client: None,
+            project: record.project,
+            tags: record.tags,
+            estimated_secs: None,
+        }
+    }
+}
+
+/// A task as it is encoded in a version 3 file, before estimates.
+#[derive(Serialize, Deserialize)]
+struct BinaryRecordV3 {
+    name: String,
+    started_at: SystemTime,
+    stopped_at: Option<SystemTime>,
+    seconds: u64,
+    billable: bool,
+    id: Uuid,
+    context: Option<String>,
+    client: Option<String>,
+    project: Option<String>,
+    tags: Vec<String>,
+}
+
+impl From<BinaryRecordV3> for BinaryRecord {
+    fn from(record: BinaryRecordV3) -> BinaryRecord {
+        BinaryRecord {
+            name: record.name,
+            started_at: record.started_at,
+            stopped_at: record.stopped_at,
+            seconds: record.seconds,
+            billable: record.billable,
+            id: record.id,
+            context: record.context,
+            client: record.client,
+            project: record.project,
+            tags: record.tags,
+            estimated_secs: None,
+        }
+    }
+}
+
+impl From<&StoredTask> for BinaryRecord {
+    fn from(task: &StoredTask) -> BinaryRecord {
+        BinaryRecord {
+            name: task.name.clone(),
+            started_at: task.started_at,
+            stopped_at: task.stopped_at,
+            seconds: task.seconds,
+            billable: task.billable,
+            id: task.id,
+            context: task.context.clone(),
+            client: task.client.clone(),
+            project: task.project.clone(),
+            tags: task.tags.clone(),
+            estimated_secs: task.estimated_secs,
+        }
+    }
+}
+
+impl From<BinaryRecord> for StoredTask {
+    fn from(record: BinaryRecord) -> StoredTask {
+        StoredTask {
+            name: record.name,
+            started_at: record.started_at,
+            stopped_at: record.stopped_at,
+            seconds: record.seconds,
+            billable: record.billable,
+            id: record.id,
+            context: record.context,
+            client: record.client,
+            project: record.project,
+            tags: record.tags,
+            estimated_secs: record.estimated_secs,
+        }
+    }
+}
+
+/// Stores tasks in a binary file of length-prefixed `bincode` records.
+///
+/// # Examples
+///
+/// ```no_run
+/// use timetracker::storage::{BincodeStorage, Storage};
+///
+/// let storage = BincodeStorage::open(std::path::Path::new("history.bin")).unwrap();
+/// let tasks = storage.load().unwrap();
+/// ```
+pub struct BincodeStorage {
+    path: PathBuf,
+}
+
+impl BincodeStorage {
+    /// Opens the binary history at `path`, which is created when the first task is saved.
+    ///
+    /// A file written by an older version is rewritten in the current one, so that new records
+    /// can be appended to it. A file that exists but isn't a binary history of a version this
+    /// program can read is an error, so it isn't appended to.
+    pub fn open(path: &Path) -> io::Result<BincodeStorage> {
+        let mut storage = BincodeStorage {
+            path: path.to_path_buf(),
+        };
+        let (version, bytes) = storage.read()?;
+        if version < VERSION {
+            let tasks = storage.decode(version, &bytes)?;
+            storage.replace_all(&tasks)?;
+        }
+        Ok(storage)
+    }
+
+    /// Reads the whole file, checking its header, and returns the version it was written in
+    /// along with its contents. A missing or empty file is a file with just the header.
+    fn read(&self) -> io::Result<(u8, Vec<u8>)> {
+        let bytes = match fs::read(&self.path) {
+            Ok(bytes) if !bytes.is_empty() => bytes,
+            Ok(_) => return Ok((VERSION, header())),
+            Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok((VERSION, header())),
+            Err(e) => return Err(e),
+        };
+        if !bytes.starts_with(MAGIC) {
+            return Err(self.invalid("not a binary history file"));
+        }
+        match bytes.get(MAGIC.len()) {
+            Some(&version) if (1..=VERSION).contains(&version) => Ok((version, bytes)),
+            Some(version) => Err(self.invalid(&format!(
+                "unsupported version {} (expected {} or earlier)",
+                version, VERSION
+            ))),
+            None => Err(self.invalid("missing version")),
+        }
+    }
+
+    /// Decodes the records of a file written in `version`.
+    fn decode(&self, version: u8, bytes: &[u8]) -> io::Result<Vec<StoredTask>> {
+        let mut rest = &bytes[MAGIC.len() + 1..];
+        let mut tasks = Vec::new();
+        while !rest.is_empty() {
+            let Some((length, after_length)) = rest.split_first_chunk::<4>() else {
+                return Err(self.invalid("truncated record"));
+            };
+            let length = u32::from_le_bytes(*length) as usize;
+            if after_length.len() < length {
+                return Err(self.invalid("truncated record"));
+            }
+            let (record, after_record) = after_length.split_at(length);
+            let record = match version {
+                1 => bincode::deserialize::<BinaryRecordV1>(record).map(BinaryRecord::from),
+                2 => bincode::deserialize::<BinaryRecordV2>(record).map(BinaryRecord::from),
+                3 => bincode::deserialize::<BinaryRecordV3>(record).map(BinaryRecord::from),
+                _ => bincode::deserialize::<BinaryRecord>(record),
+            }
+            .map_err(|e| self.invalid(&format!("record {}: {}", tasks.len() + 1, e)))?;
+            tasks.push(StoredTask::from(record));
+            rest = after_record;
+        }
+        Ok(tasks)
+    }
+
+    /// Creates an error about the contents of the file.
+    fn invalid(&self, message: &str) -> io::Error {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{}: {}", self.path.display(), message),
+        )
+    }
+}
+
+impl Storage for BincodeStorage {
+    fn load(&self) -> io::Result<Vec<StoredTask>> {
+        let (version, bytes) = self.read()?;
+        self.decode(version, &bytes)
+    }
+
+    /// Appends a record to the file, writing the header first if the file is new.
+    fn append(&mut self, task: &StoredTask) -> io::Result<()> {
+        create_parent_dir(&self.path)?;
+        let mut file = fs::OpenOptions::new()
+            .append(true)
+            .create(true)
+            .open(&self.path)?;
+        let mut bytes = if file.metadata()?.len() == 0 {
+            header()
+        } else {
+            Vec::new()
+        };
+        encode(task, &mut bytes)?;
+        file.write_all(&bytes)
+    }
+
+    /// Appends the records by writing a new copy of the file and moving it into place.
+    fn append_all(&mut self, tasks: &[StoredTask]) -> io::Result<()> {
+        let (_, mut bytes) = self.read()?;
+        for task in tasks {
+            encode(task, &mut bytes)?;
+        }
+        write_atomically(&self.path, &bytes)
+    }
+
+    /// Writes a new file with just the records of `tasks` and moves it into place.
+    fn replace_all(&mut self, tasks: &[StoredTask]) -> io::Result<()> {
+        let mut bytes = header();
+        for task in tasks {
+            encode(task, &mut bytes)?;
+        }
+        write_atomically(&self.path, &bytes)
+    }
+}
+
+/// Gets the header a binary history file starts with.
+fn header() -> Vec<u8> {
+    let mut header = MAGIC.to_vec();
+    header.push(VERSION);
+    header
+}
+
+/// Appends the length-prefixed record of `task` to `bytes`.
+fn encode(task: &StoredTask, bytes: &mut Vec<u8>) -> io::Result<()> {
+    let record = bincode::serialize(&BinaryRecord::from(task)).map_err(io::Error::other)?;
+    let length = u32::try_from(record.len())
+        .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "task is too large"))?;
+    bytes.extend_from_slice(&length.to_le_bytes());
+    bytes.extend_from_slice(&record);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests_binary {
+    use super::*;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    fn temp_path(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!("{}_{}.bin", name, std::process::id()));
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn round_trips_a_thousand_tasks() {
+        let path = temp_path("binary_round_trip");
+        let started_at = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let tasks: Vec<StoredTask> = (0..1000)
+            .map(|i| {
+                let started_at = started_at + Duration::from_secs(i * 3600);
+                let mut task = StoredTask::new(
+                    &format!("task {}", i),
+                    started_at,
+                    started_at + Duration::from_secs(i),
+                );
+                task.billable = i % 2 == 0;
+                task.context = (i % 3 == 0).then(|| format!("/projects/{}", i));
+                task.client = (i % 5 == 0).then(|| String::from("ACME"));
+                task.project = (i % 4 == 0).then(|| String::from("website"));
+                task.tags = (0..i % 3).map(|tag| format!("tag {}", tag)).collect();
+                task.estimated_secs = (i % 6 == 0).then_some(1800);
+                task
+            })
+            .collect();
+
+        let mut storage = BincodeStorage::open(&path).unwrap();
+        storage.append(&tasks[0]).unwrap();
+        storage.append_all(&tasks[1..]).unwrap();
+        let loaded = BincodeStorage::open(&path).unwrap().load().unwrap();
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(loaded, tasks);
+    }
+
+    #[test]
+    fn rejects_other_files_and_versions() {
+        let path = temp_path("binary_rejects");
+        fs::write(&path, "{\"name\":\"a\"}\n").unwrap();
+        assert!(BincodeStorage::open(&path).is_err());
+
+        let mut future = MAGIC.to_vec();
+        future.push(VERSION + 1);
+        fs::write(&path, future).unwrap();
+        let error = BincodeStorage::open(&path).err().unwrap();
+        fs::remove_file(&path).unwrap();
+        assert!(error
+            .to_string()
+            .ends_with("unsupported version 5 (expected 4 or earlier)"));
+    }
+
+    #[test]
+    fn upgrades_a_version_1_file() {
+        let path = temp_path("binary_version_1");
+        let started_at = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let task = StoredTask::new("old", started_at, started_at + Duration::from_secs(90));
+        let mut bytes = MAGIC.to_vec();
+        bytes.push(1);
+        let record = bincode::serialize(&BinaryRecordV1 {
+            name: task.name.clone(),
+            started_at: task.started_at,
+            stopped_at: task.stopped_at,
+            seconds: task.seconds,
+            billable: task.billable,
+            id: task.id,
+            context: Some(String::from("/src/app")),
+        })
+        .unwrap();
+        bytes.extend_from_slice(&(record.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&record);
+        fs::write(&path, bytes).unwrap();
+
+        let mut storage = BincodeStorage::open(&path).unwrap();
+        let new = StoredTask::new("new", started_at, started_at + Duration::from_secs(30));
+        storage.append(&new).unwrap();
+        let loaded = storage.load().unwrap();
+        let version = fs::read(&path).unwrap()[MAGIC.len()];
+        fs::remove_file(&path).unwrap();
+
+        let mut old = task;
+        old.context = Some(String::from("/src/app"));
+        assert_eq!(loaded, [old, new]);
+        assert_eq!(version, VERSION);
+    }
+
+    #[test]
+    fn missing_file_is_empty() {
+        let path = temp_path("binary_missing");
+        assert!(BincodeStorage::open(&path)
+            .unwrap()
+            .load()
+            .unwrap()
+            .is_empty());
+    }
+
+    #[test]
+    fn loads_a_version_2_file() {
+        let path = temp_path("binary_version_2");
+        let started_at = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let mut task = StoredTask::new("old", started_at, started_at + Duration::from_secs(90));
+        task.project = Some(String::from("website"));
+        task.tags = vec![String::from("design")];
+        let mut bytes = MAGIC.to_vec();
+        bytes.push(2);
+        let record = bincode::serialize(&BinaryRecordV2 {
+            name: task.name.clone(),
+            started_at: task.started_at,
+            stopped_at: task.stopped_at,
+            seconds: task.seconds,
+            billable: task.billable,
+            id: task.id,
+            context: None,
+            project: task.project.clone(),
+            tags: task.tags.clone(),
+        })
+        .unwrap();
+        bytes.extend_from_slice(&(record.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&record);
+        fs::write(&path, bytes).unwrap();
+
+        let loaded = BincodeStorage::open(&path).unwrap().load().unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(loaded, [task]);
+    }
+    #[test]
+    fn loads_a_version_3_file() {
+        let path = temp_path("binary_version_3");
+        let started_at = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let mut task = StoredTask::new("old", started_at, started_at + Duration::from_secs(90));
+        task.client = Some(String::from("ACME"));
+        task.project = Some(String::from("website"));
+        task.tags = vec![String::from("design")];
+        let mut bytes = MAGIC.to_vec();
+        bytes.push(3);
+        let record = bincode::serialize(&BinaryRecordV3 {
+            name: task.name.clone(),
+            started_at: task.started_at,
+            stopped_at: task.stopped_at,
+            seconds: task.seconds,
+            billable: task.billable,
+            id: task.id,
+            context: None,
+            client: task.client.clone(),
+            project: task.project.clone(),
+            tags: task.tags.clone(),
+        })
+        .unwrap();
+        bytes.extend_from_slice(&(record.len() as u32).to_le_bytes());
+        bytes.extend_from_slice(&record);
+        fs::write(&path, bytes).unwrap();
+
+        let loaded = BincodeStorage::open(&path).unwrap().load().unwrap();
+        let version = fs::read(&path).unwrap()[MAGIC.len()];
+        fs::remove_file(&path).unwrap();
+        assert_eq!(loaded, [task]);
+        assert_eq!(version, VERSION);
+    }
+}
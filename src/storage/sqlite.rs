@@ -0,0 +1,129 @@
+//! SQLite storage backend.
+//!
+//! Each task is kept as one row of the `tasks` table. The task itself is stored as JSON, the same
+//! encoding the history file uses, so the table doesn't need to change when `StoredTask` does.
+
+use super::{create_parent_dir, Storage, StoredTask};
+use rusqlite::{Connection, Transaction};
+use std::{io, path::Path};
+
+/// Stores tasks in an SQLite database.
+///
+/// # Examples
+///
+/// ```no_run
+/// use timetracker::storage::{SqliteStorage, Storage};
+///
+/// let storage = SqliteStorage::open(std::path::Path::new("history.sqlite")).unwrap();
+/// let tasks = storage.load().unwrap();
+/// ```
+pub struct SqliteStorage {
+    connection: Connection,
+}
+
+impl SqliteStorage {
+    /// Opens the database at `path`, creating it (and the `tasks` table) if needed.
+    pub fn open(path: &Path) -> io::Result<SqliteStorage> {
+        create_parent_dir(path)?;
+        let connection = Connection::open(path).map_err(to_io_error)?;
+        connection
+            .execute(
+                "CREATE TABLE IF NOT EXISTS tasks (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    task TEXT NOT NULL
+                )",
+                [],
+            )
+            .map_err(to_io_error)?;
+        Ok(SqliteStorage { connection })
+    }
+}
+
+impl Storage for SqliteStorage {
+    fn load(&self) -> io::Result<Vec<StoredTask>> {
+        let mut statement = self
+            .connection
+            .prepare("SELECT id, task FROM tasks ORDER BY id")
+            .map_err(to_io_error)?;
+        let rows = statement
+            .query_map([], |row| {
+                Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+            })
+            .map_err(to_io_error)?;
+
+        let mut tasks = Vec::new();
+        for row in rows {
+            let (id, json) = row.map_err(to_io_error)?;
+            let task = serde_json::from_str(&json).map_err(|e| {
+                io::Error::new(io::ErrorKind::InvalidData, format!("row {}: {}", id, e))
+            })?;
+            tasks.push(task);
+        }
+        Ok(tasks)
+    }
+
+    fn append(&mut self, task: &StoredTask) -> io::Result<()> {
+        self.append_all(std::slice::from_ref(task))
+    }
+
+    /// Inserts the tasks in a single transaction, which is rolled back if any insert fails.
+    fn append_all(&mut self, tasks: &[StoredTask]) -> io::Result<()> {
+        let transaction = self.connection.transaction().map_err(to_io_error)?;
+        insert(&transaction, tasks)?;
+        transaction.commit().map_err(to_io_error)
+    }
+
+    /// Deletes every row and inserts the tasks in a single transaction, which is rolled back if
+    /// anything fails.
+    fn replace_all(&mut self, tasks: &[StoredTask]) -> io::Result<()> {
+        let transaction = self.connection.transaction().map_err(to_io_error)?;
+        transaction
+            .execute("DELETE FROM tasks", [])
+            .map_err(to_io_error)?;
+        insert(&transaction, tasks)?;
+        transaction.commit().map_err(to_io_error)
+    }
+}
+
+/// Inserts a row for each task as part of `transaction`.
+fn insert(transaction: &Transaction, tasks: &[StoredTask]) -> io::Result<()> {
+    let mut statement = transaction
+        .prepare("INSERT INTO tasks (task) VALUES (?1)")
+        .map_err(to_io_error)?;
+    for task in tasks {
+        let json = serde_json::to_string(task)?;
+        statement.execute([json]).map_err(to_io_error)?;
+    }
+    Ok(())
+}
+
+/// Wraps an SQLite error so it can be returned alongside file errors.
+fn to_io_error(e: rusqlite::Error) -> io::Error {
+    io::Error::other(e)
+}
+
+#[cfg(test)]
+mod tests_sqlite {
+    use super::*;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    #[test]
+    fn round_trips_through_database() {
+        let path = std::env::temp_dir().join(format!("history_{}.sqlite", std::process::id()));
+        let _ = std::fs::remove_file(&path);
+
+        let started_at = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        let first = StoredTask::new("first", started_at, started_at + Duration::from_secs(30));
+        let second = StoredTask::new("second", started_at, started_at + Duration::from_secs(90));
+
+        let mut storage = SqliteStorage::open(&path).unwrap();
+        storage.append(&first).unwrap();
+        storage.append_all(std::slice::from_ref(&second)).unwrap();
+        drop(storage);
+
+        let storage = SqliteStorage::open(&path).unwrap();
+        assert_eq!(storage.load().unwrap(), vec![first, second]);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+}
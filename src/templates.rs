@@ -0,0 +1,207 @@
+//! Predefined tasks to start by a short name, kept in `config.toml`.
+//!
+//! Unlike an alias, which only stands for a task name, a template carries everything a new task
+//! is started with, e.g.
+//!
+//! ```toml
+//! [templates.standup]
+//! name = "Morning Standup"
+//! tags = ["team", "meetings"]
+//! client = "Internal"
+//! estimated_secs = 1800
+//! ```
+
+use crate::{format_duration_compact, storage, storage::StoredTask};
+use serde::{Deserialize, Serialize};
+use std::{fmt, fs, io, path::Path};
+use toml_edit::{DocumentMut, Item, Table};
+
+/// The settings a task started from a template gets.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(deny_unknown_fields)]
+pub struct TaskTemplate {
+    /// The name of the task.
+    pub name: String,
+    /// The task's tags.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+    /// The client the task is done for, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client: Option<String>,
+    /// The project the task belongs to, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub project: Option<String>,
+    /// How long the task is expected to take, in seconds, if known.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub estimated_secs: Option<u64>,
+    /// Whether the time is billable, or `None` to decide as for any other task (billable when
+    /// `hourly_rate` is set).
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub billable: Option<bool>,
+}
+
+impl TaskTemplate {
+    /// Sets the template's tags, client, project, estimate, and (if it has one) billable flag on
+    /// a task.
+    pub fn apply(&self, task: &mut StoredTask) {
+        task.tags = self.tags.clone();
+        task.client = self.client.clone();
+        task.project = self.project.clone();
+        task.estimated_secs = self.estimated_secs;
+        if let Some(billable) = self.billable {
+            task.billable = billable;
+        }
+    }
+}
+
+/// Formats the template like `Morning Standup (tags: team, meetings; client: Internal;
+/// estimated: 30m)`, leaving out the settings it doesn't have.
+impl fmt::Display for TaskTemplate {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let mut details = Vec::new();
+        if !self.tags.is_empty() {
+            details.push(format!("tags: {}", self.tags.join(", ")));
+        }
+        if let Some(client) = &self.client {
+            details.push(format!("client: {}", client));
+        }
+        if let Some(project) = &self.project {
+            details.push(format!("project: {}", project));
+        }
+        if let Some(estimated) = self.estimated_secs {
+            details.push(format!("estimated: {}", format_duration_compact(estimated)));
+        }
+        if self.billable == Some(false) {
+            details.push(String::from("non-billable"));
+        }
+        write!(f, "{}", self.name)?;
+        if !details.is_empty() {
+            write!(f, " ({})", details.join("; "))?;
+        }
+        Ok(())
+    }
+}
+
+/// Reads the config file as an editable document, treating a missing file as an empty one.
+fn read_document(path: &Path) -> io::Result<DocumentMut> {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e),
+    };
+    contents.parse().map_err(|e| {
+        io::Error::new(
+            io::ErrorKind::InvalidData,
+            format!("{}: {}", path.display(), e),
+        )
+    })
+}
+
+/// Adds or replaces a template in the config file at `path`, keeping the rest of the file
+/// (comments included) as it is.
+///
+/// # Examples
+///
+/// ```
+/// use timetracker::templates::{self, TaskTemplate};
+///
+/// let path = std::env::temp_dir().join("timetracker_doctest_templates.toml");
+/// std::fs::write(&path, "# my settings\nmin_gap_secs = 120\n").unwrap();
+/// let template = TaskTemplate { name: String::from("Standup"), ..Default::default() };
+/// templates::save(&path, "standup", &template).unwrap();
+/// assert_eq!(
+///     std::fs::read_to_string(&path).unwrap(),
+///     "# my settings\nmin_gap_secs = 120\n\n[templates.standup]\nname = \"Standup\"\n"
+/// );
+/// # std::fs::remove_file(&path).unwrap();
+/// ```
+pub fn save(path: &Path, key: &str, template: &TaskTemplate) -> io::Result<()> {
+    let mut document = read_document(path)?;
+    let table = toml_edit::ser::to_document(template)
+        .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e.to_string()))?
+        .as_table()
+        .clone();
+    let templates = document
+        .entry("templates")
+        .or_insert_with(|| {
+            let mut templates = Table::new();
+            templates.set_implicit(true);
+            Item::Table(templates)
+        })
+        .as_table_mut()
+        .ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("{}: templates is not a table", path.display()),
+            )
+        })?;
+    templates.insert(key, Item::Table(table));
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    storage::write_atomically(path, document.to_string().as_bytes())
+}
+
+/// Removes a template from the config file at `path`, returning whether there was one.
+pub fn remove(path: &Path, key: &str) -> io::Result<bool> {
+    let mut document = read_document(path)?;
+    let removed = document
+        .get_mut("templates")
+        .and_then(Item::as_table_like_mut)
+        .and_then(|templates| templates.remove(key))
+        .is_some();
+    if removed {
+        storage::write_atomically(path, document.to_string().as_bytes())?;
+    }
+    Ok(removed)
+}
+
+#[cfg(test)]
+mod tests_templates {
+    use super::*;
+    use std::time::UNIX_EPOCH;
+
+    fn standup() -> TaskTemplate {
+        TaskTemplate {
+            name: String::from("Morning Standup"),
+            tags: vec![String::from("team"), String::from("meetings")],
+            client: Some(String::from("Internal")),
+            project: None,
+            estimated_secs: Some(1800),
+            billable: Some(false),
+        }
+    }
+
+    #[test]
+    fn saves_and_removes_templates_keeping_the_rest_of_the_file() {
+        let path = std::env::temp_dir().join("timetracker_test_templates.toml");
+        fs::write(&path, "# rates\nhourly_rate = 100.0\n").unwrap();
+
+        save(&path, "standup", &standup()).unwrap();
+        let contents = fs::read_to_string(&path).unwrap();
+        assert!(contents.starts_with("# rates\nhourly_rate = 100.0\n"));
+        let parsed: toml::Table = toml::from_str(&contents).unwrap();
+        let template: TaskTemplate = parsed["templates"]["standup"].clone().try_into().unwrap();
+        assert_eq!(template, standup());
+
+        assert!(remove(&path, "standup").unwrap());
+        assert!(!remove(&path, "standup").unwrap());
+        let contents = fs::read_to_string(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert!(!contents.contains("standup"));
+    }
+
+    #[test]
+    fn applies_its_settings_to_a_task() {
+        let mut task = StoredTask::new("Morning Standup", UNIX_EPOCH, UNIX_EPOCH);
+        standup().apply(&mut task);
+        assert_eq!(task.tags, ["team", "meetings"]);
+        assert_eq!(task.client.as_deref(), Some("Internal"));
+        assert_eq!(task.estimated_secs, Some(1800));
+        assert!(!task.billable);
+        assert_eq!(
+            standup().to_string(),
+            "Morning Standup (tags: team, meetings; client: Internal; estimated: 30m; non-billable)"
+        );
+    }
+}
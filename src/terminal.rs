@@ -0,0 +1,106 @@
+//! Redrawing a line of the terminal in place.
+//!
+//! The timers are redrawn every second. Reprinting the whole line makes long task names
+//! flicker, so a [`LineUpdater`] only rewrites the characters that changed, which is usually
+//! just the seconds.
+
+/// Moves the cursor to a column of the current line (1-based).
+fn move_to_column(column: usize) -> String {
+    format!("\x1B[{}G", column)
+}
+
+/// Clears the current line from the cursor to its end.
+const CLEAR_TO_END: &str = "\x1B[K";
+
+/// Keeps track of what a line of the terminal shows, to redraw it with as little output as
+/// possible.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct LineUpdater {
+    /// The line as it was last drawn, or empty if nothing has been drawn yet.
+    pub last_line: String,
+}
+
+impl LineUpdater {
+    /// Creates an updater for a line that hasn't been drawn yet.
+    pub fn new() -> LineUpdater {
+        LineUpdater::default()
+    }
+
+    /// Gets the output that turns the line into `new_line`, wherever the cursor is on it.
+    ///
+    /// The first time, the whole line is drawn from its start. After that only the part from
+    /// the first changed character onwards is rewritten, and nothing at all if the line is the
+    /// same. Columns are counted in characters, so wide characters before a change can put it
+    /// in the wrong place.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use timetracker::terminal::LineUpdater;
+    ///
+    /// let mut line = LineUpdater::new();
+    /// assert_eq!(line.update("task: 00:00:09"), "\rtask: 00:00:09");
+    /// assert_eq!(line.update("task: 00:00:10"), "\x1B[13G10");
+    /// ```
+    pub fn update(&mut self, new_line: &str) -> String {
+        if self.last_line.is_empty() {
+            self.last_line = new_line.to_string();
+            return format!("\r{}", new_line);
+        }
+
+        let unchanged = self
+            .last_line
+            .chars()
+            .zip(new_line.chars())
+            .take_while(|(old, new)| old == new)
+            .count();
+        let old_len = self.last_line.chars().count();
+        let new_len = new_line.chars().count();
+        let mut output = String::new();
+        if unchanged < new_len || new_len < old_len {
+            output.push_str(&move_to_column(unchanged + 1));
+            output.extend(new_line.chars().skip(unchanged));
+        }
+        if new_len < old_len {
+            output.push_str(CLEAR_TO_END);
+        }
+        self.last_line = new_line.to_string();
+        output
+    }
+}
+
+#[cfg(test)]
+mod tests_terminal {
+    use super::*;
+
+    #[test]
+    fn first_update_draws_the_whole_line() {
+        let mut line = LineUpdater::new();
+        assert_eq!(line.update("a: 00:00:00"), "\ra: 00:00:00");
+        assert_eq!(line.last_line, "a: 00:00:00");
+    }
+
+    #[test]
+    fn rewrites_only_the_changed_suffix() {
+        let mut line = LineUpdater::new();
+        line.update("a long task name: 00:00:59");
+        assert_eq!(line.update("a long task name: 00:01:00"), "\x1B[23G1:00");
+        assert_eq!(line.update("a long task name: 00:01:00"), "");
+    }
+
+    #[test]
+    fn clears_what_is_left_of_a_longer_line() {
+        let mut line = LineUpdater::new();
+        line.update("a: 00:00:01 | b: 00:00:00");
+        assert_eq!(line.update("a: 00:00:02"), "\x1B[11G2\x1B[K");
+        line.update("abc");
+        assert_eq!(line.update("ab"), "\x1B[3G\x1B[K");
+    }
+
+    #[test]
+    fn counts_columns_in_characters() {
+        let mut line = LineUpdater::new();
+        line.update("café: 00:00:01");
+        assert_eq!(line.update("café: 00:00:02"), "\x1B[14G2");
+    }
+}
@@ -0,0 +1,95 @@
+//! Fixing tasks that were tracked with the wrong time zone.
+//!
+//! If the system clock was set to the wrong zone, say while traveling, the tasks show the right
+//! local times but point at the wrong moments. `time-tracker edit --time-zone <wrong>
+//! --shift-to <right>` keeps the local times and moves the tasks to the moments they have in
+//! the right zone.
+
+use crate::storage::StoredTask;
+use chrono::{DateTime, Offset, TimeZone, Utc};
+use std::time::{Duration, SystemTime};
+
+/// Gets the offset from UTC of `zone` at `time`, in seconds.
+fn offset_secs<Tz: TimeZone>(zone: &Tz, time: SystemTime) -> i64 {
+    let utc = DateTime::<Utc>::from(time).naive_utc();
+    i64::from(zone.offset_from_utc_datetime(&utc).fix().local_minus_utc())
+}
+
+/// Gets how far a task that started at `started_at` moves when its local times are read in
+/// `to` rather than `from`, in seconds. The offsets are those at `started_at`.
+///
+/// # Examples
+///
+/// ```
+/// use chrono::FixedOffset;
+/// use std::time::UNIX_EPOCH;
+/// use timetracker::timezone::shift_secs;
+///
+/// let india = FixedOffset::east_opt(5 * 3600 + 1800).unwrap();
+/// let utc = FixedOffset::east_opt(0).unwrap();
+/// assert_eq!(shift_secs(UNIX_EPOCH, &india, &utc), 5 * 3600 + 1800);
+/// ```
+pub fn shift_secs<From: TimeZone, To: TimeZone>(
+    started_at: SystemTime,
+    from: &From,
+    to: &To,
+) -> i64 {
+    offset_secs(from, started_at) - offset_secs(to, started_at)
+}
+
+/// Moves `time` by `secs` seconds, forwards or backwards.
+fn shift(time: SystemTime, secs: i64) -> SystemTime {
+    let by = Duration::from_secs(secs.unsigned_abs());
+    if secs >= 0 {
+        time + by
+    } else {
+        time - by
+    }
+}
+
+/// Moves the task so that its local times in `to` are what they were in `from`. Its start and
+/// stop move by the same amount, so its duration doesn't change.
+pub fn reinterpret_task<From: TimeZone, To: TimeZone>(task: &mut StoredTask, from: &From, to: &To) {
+    let secs = shift_secs(task.started_at, from, to);
+    task.started_at = shift(task.started_at, secs);
+    task.stopped_at = task.stopped_at.map(|stopped_at| shift(stopped_at, secs));
+}
+
+#[cfg(test)]
+mod tests_timezone {
+    use super::*;
+    use chrono::FixedOffset;
+    use std::time::UNIX_EPOCH;
+
+    #[test]
+    fn reinterprets_india_time_as_utc() {
+        let india = FixedOffset::east_opt(5 * 3600 + 1800).unwrap();
+        let utc = FixedOffset::east_opt(0).unwrap();
+        // 2024-01-15 09:00 in India is 03:30 UTC
+        let started_at = UNIX_EPOCH + Duration::from_secs(1_705_289_400);
+        let mut task = StoredTask::new("task", started_at, started_at + Duration::from_secs(600));
+
+        reinterpret_task(&mut task, &india, &utc);
+
+        // 2024-01-15 09:00 UTC
+        let expected = UNIX_EPOCH + Duration::from_secs(1_705_309_200);
+        assert_eq!(task.started_at, expected);
+        assert_eq!(task.stopped_at, Some(expected + Duration::from_secs(600)));
+        assert_eq!(task.seconds, 600);
+
+        reinterpret_task(&mut task, &utc, &india);
+        assert_eq!(task.started_at, started_at);
+    }
+
+    #[test]
+    fn uses_the_offset_at_the_time_of_the_task() {
+        let new_york: chrono_tz::Tz = "America/New_York".parse().unwrap();
+        let london: chrono_tz::Tz = "Europe/London".parse().unwrap();
+        // 2024-01-15, when New York is 5 hours behind London
+        let winter = UNIX_EPOCH + Duration::from_secs(1_705_309_200);
+        assert_eq!(shift_secs(winter, &new_york, &london), -5 * 3600);
+        // 2024-03-15, between the two daylight saving changes, when it is 4 hours behind
+        let spring = UNIX_EPOCH + Duration::from_secs(1_710_493_200);
+        assert_eq!(shift_secs(spring, &new_york, &london), -4 * 3600);
+    }
+}
@@ -4,8 +4,8 @@ use std::io::{Read, Write};
 use std::process::Command;
 
 lazy_static! {
-    /// The name of the temporary file used to store the output of the program.
-    static ref TEMP_FILENAME: &'static str = "output_{}.log";
+    /// The name of the temporary history file used by the program.
+    static ref TEMP_HISTORY_FILENAME: &'static str = "history_{}.json";
     /// The path to the temporary directory.
     static ref TEMP_DIR: std::path::PathBuf = std::env::temp_dir();
 }
@@ -41,18 +41,14 @@ fn test_shows_tasks_completed() -> Result<(), Box<dyn std::error::Error>> {
     // make sure the program exited
     child.kill()?;
 
-    // read the contents of the file
-    let output = child.read();
-
     // check the output
-    assert!(output.is_ok());
-    assert!(output?.contains("Tasks completed:\ntest task: 00:00:01"));
+    child.assert_output_contains("Tasks completed:\ntest task: 00:00:01")?;
 
     // cleanup the tmp directory
     assert!(child.cleanup().is_ok());
 
     assert!(child.sleep(100).is_ok());
-    return Ok(());
+    Ok(())
 }
 
 #[test]
@@ -66,18 +62,16 @@ fn test_shows_welcome_message() -> Result<(), Box<dyn std::error::Error>> {
     assert!(child.kill().is_ok());
 
     // check the output
-    let output = child.read();
-    assert!(output.is_ok());
-    assert!(output?.contains("Welcome to the time tracker!"));
+    child.assert_output_contains("Welcome to the time tracker!")?;
 
     // cleanup the tmp directory
     assert!(child.cleanup().is_ok());
 
-    return Ok(());
+    Ok(())
 }
 
 #[test]
-fn test_shows_goodbye_message() {
+fn test_shows_goodbye_message() -> Result<(), Box<dyn std::error::Error>> {
     let mut child = TestChild!();
 
     // send the commands to the program
@@ -87,16 +81,16 @@ fn test_shows_goodbye_message() {
     assert!(child.kill().is_ok());
 
     // check the output
-    let output = child.read();
-    assert!(output.is_ok());
-    assert!(output.unwrap().contains("Goodbye!"));
+    child.assert_output_contains("Goodbye!")?;
+    child.assert_output_not_contains("completed in")?;
 
     // cleanup the tmp directory
     assert!(child.cleanup().is_ok());
+    Ok(())
 }
 
 #[test]
-fn test_shows_prompt() {
+fn test_shows_prompt() -> Result<(), Box<dyn std::error::Error>> {
     let mut child = TestChild!();
 
     // send the exit command to the program
@@ -106,14 +100,13 @@ fn test_shows_prompt() {
     assert!(child.kill().is_ok());
 
     // check the output
-    let output = child.read();
-    assert!(output.is_ok());
-    assert!(output
-        .unwrap()
-        .contains("Enter a task name to start tracking it. Exit the program by typing 'exit'."));
+    child.assert_output_contains(
+        "Enter a task name to start tracking it. Exit the program by typing 'exit'.",
+    )?;
 
     // cleanup the tmp directory
     assert!(child.cleanup().is_ok());
+    Ok(())
 }
 
 #[test]
@@ -136,7 +129,7 @@ fn test_shows_task_completed() {
     // check the output
     match child.read() {
         Ok(output) => assert!(output.contains("Task 'test task' completed in")),
-        Err(_) => assert!(false, "Failed to read output file."),
+        Err(_) => panic!("Failed to read output file."),
     }
 
     // cleanup the tmp directory
@@ -174,6 +167,1671 @@ fn test_shows_prompt_after_task() {
     assert!(child.cleanup().is_ok());
 }
 
+#[test]
+fn test_tracks_multiple_tasks() -> Result<(), Box<dyn std::error::Error>> {
+    let mut child = TestChild!();
+
+    // send the commands to the program
+    child.write("task A", 800)?;
+    child.write("start task B", 1500)?;
+    child.write("stop task A", 800)?;
+    child.write("stop", 500)?;
+    child.write("exit", 500)?;
+    child.kill()?;
+
+    // check the output
+    let output = child.read()?;
+    assert!(output.contains("Running: task A, task B"));
+    assert!(output.contains("task A: 00:00:0"));
+    assert!(output.contains(" | task B: 00:00:0"));
+    assert!(output.contains("Task 'task A' completed in"));
+    assert!(output.contains("Task 'task B' completed in"));
+    assert!(output.contains("Tasks completed:\ntask A: "));
+
+    child.cleanup()?;
+    Ok(())
+}
+
+#[test]
+fn test_exports_history_as_pretty_json() -> Result<(), Box<dyn std::error::Error>> {
+    let history_path = TEMP_DIR.join(TEMP_HISTORY_FILENAME.replace("{}", &fn_name!()));
+    std::fs::write(
+        &history_path,
+        concat!(
+            r#"{"name":"first","started_at":"2024-01-15T09:00:00Z","stopped_at":"2024-01-15T09:30:00Z","seconds":1800}"#,
+            "\n",
+            r#"{"name":"second","started_at":"2024-01-15T10:00:00Z","stopped_at":"2024-01-15T11:00:00Z","seconds":3600}"#,
+            "\n",
+        ),
+    )?;
+
+    let output = Command::cargo_bin("time-tracker")?
+        .args(["export", "--format", "json", "--pretty"])
+        .env("TT_HISTORY_FILE", &history_path)
+        .output()?;
+    std::fs::remove_file(&history_path)?;
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout)?;
+    assert!(stdout.starts_with("[\n  {\n    \"name\": \"first\","));
+    assert!(stdout.contains("\"seconds\": 3600"));
+    Ok(())
+}
+
+#[test]
+fn test_pretty_prints_report_json() -> Result<(), Box<dyn std::error::Error>> {
+    let schema = |pretty: bool| -> Result<String, Box<dyn std::error::Error>> {
+        let mut command = Command::cargo_bin("time-tracker")?;
+        command.args(["report", "--json-schema"]);
+        if pretty {
+            command.arg("--pretty");
+        }
+        let output = command.output()?;
+        assert!(output.status.success());
+        Ok(String::from_utf8(output.stdout)?)
+    };
+
+    let compact = schema(false)?;
+    let pretty = schema(true)?;
+    assert_eq!(compact.lines().count(), 1);
+    assert!(pretty.starts_with("{\n  \""));
+    assert_eq!(
+        serde_json::from_str::<serde_json::Value>(&pretty)?,
+        serde_json::from_str::<serde_json::Value>(&compact)?
+    );
+    Ok(())
+}
+
+#[test]
+fn test_reports_totals_by_task() -> Result<(), Box<dyn std::error::Error>> {
+    let history_path = TEMP_DIR.join(TEMP_HISTORY_FILENAME.replace("{}", &fn_name!()));
+    std::fs::write(
+        &history_path,
+        concat!(
+            r#"{"name":"first","started_at":"2024-01-15T09:00:00Z","stopped_at":"2024-01-15T09:30:00Z","seconds":1800}"#,
+            "\n",
+            r#"{"name":"second","started_at":"2024-01-15T10:00:00Z","stopped_at":"2024-01-15T11:00:00Z","seconds":3600}"#,
+            "\n",
+            r#"{"name":"first","started_at":"2024-01-16T09:00:00Z","stopped_at":"2024-01-16T10:00:00Z","seconds":3600}"#,
+            "\n",
+        ),
+    )?;
+
+    let output = Command::cargo_bin("time-tracker")?
+        .arg("report")
+        .env("TT_HISTORY_FILE", &history_path)
+        .output()?;
+    std::fs::remove_file(&history_path)?;
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout)?;
+    assert_eq!(
+        stdout,
+        "first: 1h30m\nsecond: 1h\nTotal: 2h30m\nBillable: 2h30m\nNon-billable: 0s\n"
+    );
+    Ok(())
+}
+
+#[test]
+fn test_reports_invoice_total() -> Result<(), Box<dyn std::error::Error>> {
+    let home = TEMP_DIR.join(format!("home_{}", fn_name!()));
+    std::fs::create_dir_all(home.join(".timetracker"))?;
+    std::fs::write(
+        home.join(".timetracker").join("config.toml"),
+        "hourly_rate = 1234.56\n",
+    )?;
+    let history_path = home.join("history.json");
+    std::fs::write(
+        &history_path,
+        concat!(
+            r#"{"name":"design","started_at":"2024-01-15T12:00:00Z","stopped_at":"2024-01-15T13:00:00Z","seconds":3600,"client":"ACME Corp"}"#,
+            "\n",
+            r#"{"name":"admin","started_at":"2024-01-16T12:00:00Z","stopped_at":"2024-01-16T13:00:00Z","seconds":3600,"billable":false,"client":"ACME Corp"}"#,
+            "\n",
+            r#"{"name":"support","started_at":"2024-01-17T12:00:00Z","stopped_at":"2024-01-17T14:00:00Z","seconds":7200,"client":"Globex"}"#,
+            "\n",
+        ),
+    )?;
+
+    let output = Command::cargo_bin("time-tracker")?
+        .args([
+            "report",
+            "--invoice",
+            "--client",
+            "ACME Corp",
+            "--period",
+            "2024-01",
+        ])
+        .env("HOME", &home)
+        .env("TT_HISTORY_FILE", &history_path)
+        .output()?;
+    std::fs::remove_dir_all(&home)?;
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout)?;
+    assert!(stdout.starts_with("INVOICE\nDate: 2024-01-31\nClient: ACME Corp\n"));
+    assert!(!stdout.contains("admin"));
+    assert!(!stdout.contains("support"));
+    assert_eq!(stdout.lines().last(), Some("Total: $1,234.56"));
+    Ok(())
+}
+
+#[test]
+fn test_alias_expands_started_task_name() -> Result<(), Box<dyn std::error::Error>> {
+    let home = TEMP_DIR.join(format!("home_{}", fn_name!()));
+    let history_path = home.join("history.json");
+    let _ = std::fs::remove_dir_all(&home);
+
+    let output = Command::cargo_bin("time-tracker")?
+        .args(["alias", "standup", "daily standup"])
+        .env("HOME", &home)
+        .output()?;
+    assert!(output.status.success());
+
+    let mut child = Command::cargo_bin("time-tracker")?
+        .args(["start", "standup"])
+        .env("HOME", &home)
+        .env("TT_HISTORY_FILE", &history_path)
+        .env("TT_ENV", "test")
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::null())
+        .spawn()?;
+    child.stdin.take().unwrap().write_all(b"stop\n")?;
+    assert!(child.wait()?.success());
+    let history = std::fs::read_to_string(&history_path)?;
+
+    let output = Command::cargo_bin("time-tracker")?
+        .args(["alias", "list"])
+        .env("HOME", &home)
+        .output()?;
+    std::fs::remove_dir_all(&home)?;
+
+    assert!(history.starts_with(r#"{"name":"daily standup","#));
+    assert_eq!(
+        String::from_utf8(output.stdout)?,
+        "standup = daily standup\n"
+    );
+    Ok(())
+}
+
+#[test]
+fn test_starts_task_from_template() -> Result<(), Box<dyn std::error::Error>> {
+    let home = TEMP_DIR.join(format!("home_{}", fn_name!()));
+    let history_path = home.join("history.json");
+    let _ = std::fs::remove_dir_all(&home);
+
+    let output = Command::cargo_bin("time-tracker")?
+        .args(["template", "add", "standup", "--name", "Morning Standup"])
+        .args([
+            "--tags",
+            "team,meetings",
+            "--estimated",
+            "30m",
+            "--client",
+            "Internal",
+        ])
+        .env("HOME", &home)
+        .output()?;
+    assert!(output.status.success());
+
+    let mut child = Command::cargo_bin("time-tracker")?
+        .args(["template", "start", "standup"])
+        .env("HOME", &home)
+        .env("TT_HISTORY_FILE", &history_path)
+        .env("TT_ENV", "test")
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::piped())
+        .spawn()?;
+    child.stdin.take().unwrap().write_all(b"stop\n")?;
+    let started = child.wait_with_output()?;
+    assert!(started.status.success());
+    let history = std::fs::read_to_string(&history_path)?;
+
+    let output = Command::cargo_bin("time-tracker")?
+        .args(["template", "list"])
+        .env("HOME", &home)
+        .output()?;
+    std::fs::remove_dir_all(&home)?;
+
+    assert!(String::from_utf8(started.stdout)?
+        .starts_with("Started task 'Morning Standup' (estimated 30m)"));
+    assert!(history.starts_with(r#"{"name":"Morning Standup","#));
+    assert!(history.contains(r#""client":"Internal""#));
+    assert!(history.contains(r#""tags":["team","meetings"]"#));
+    assert!(history.contains(r#""estimated_secs":1800"#));
+    assert_eq!(
+        String::from_utf8(output.stdout)?,
+        "standup = Morning Standup (tags: team, meetings; client: Internal; estimated: 30m)\n"
+    );
+    Ok(())
+}
+
+#[test]
+fn test_check_fails_on_long_running_tasks() -> Result<(), Box<dyn std::error::Error>> {
+    let history_path = TEMP_DIR.join(TEMP_HISTORY_FILENAME.replace("{}", &fn_name!()));
+    std::fs::write(
+        &history_path,
+        concat!(
+            r#"{"name":"done","started_at":"2024-01-15T09:00:00Z","stopped_at":"2024-01-15T09:30:00Z","seconds":1800}"#,
+            "\n",
+            r#"{"name":"forgotten","started_at":"2024-01-15T10:00:00Z","stopped_at":null,"seconds":0}"#,
+            "\n",
+        ),
+    )?;
+
+    let failing = Command::cargo_bin("time-tracker")?
+        .args(["check", "--max-running-hours", "2"])
+        .env("TT_HISTORY_FILE", &history_path)
+        .output()?;
+    let passing = Command::cargo_bin("time-tracker")?
+        .args(["check", "--max-running-hours", "1000000"])
+        .env("TT_HISTORY_FILE", &history_path)
+        .output()?;
+    std::fs::remove_file(&history_path)?;
+
+    assert_eq!(failing.status.code(), Some(1));
+    assert!(String::from_utf8(failing.stderr)?.starts_with("forgotten: running for "));
+    assert!(failing.stdout.is_empty());
+    assert!(passing.status.success());
+    assert!(passing.stdout.is_empty() && passing.stderr.is_empty());
+    Ok(())
+}
+
+#[test]
+fn test_summary_shows_running_task() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = TEMP_DIR.join(format!("summary_{}", fn_name!()));
+    std::fs::create_dir_all(&dir)?;
+    let history_path = dir.join("history.json");
+    let summary = || -> Result<String, Box<dyn std::error::Error>> {
+        let output = Command::cargo_bin("time-tracker")?
+            .args(["summary", "--oneline"])
+            .env("TT_HISTORY_FILE", &history_path)
+            .output()?;
+        Ok(String::from_utf8(output.stdout)?)
+    };
+
+    let mut child = Command::cargo_bin("time-tracker")?
+        .args(["start", "write docs"])
+        .env("TT_HISTORY_FILE", &history_path)
+        .env("TT_ENV", "test")
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::null())
+        .spawn()?;
+    std::thread::sleep(std::time::Duration::from_millis(500));
+    let while_running = summary()?;
+    child.stdin.take().unwrap().write_all(b"stop\n")?;
+    child.wait()?;
+    let after_stopping = summary()?;
+    std::fs::remove_dir_all(&dir)?;
+
+    assert!(while_running.starts_with("[write docs: "));
+    assert!(while_running.ends_with("]\n"));
+    assert_eq!(after_stopping, "\n");
+    Ok(())
+}
+
+#[test]
+fn test_lists_tasks_with_short_ids() -> Result<(), Box<dyn std::error::Error>> {
+    let history_path = TEMP_DIR.join(TEMP_HISTORY_FILENAME.replace("{}", &fn_name!()));
+    std::fs::write(
+        &history_path,
+        concat!(
+            r#"{"name":"first","started_at":"2024-01-15T09:00:00Z","stopped_at":"2024-01-15T10:30:00Z","seconds":5400,"id":"a1b2c3d4-0000-4000-8000-000000000000"}"#,
+            "\n",
+            r#"{"name":"second","started_at":"2024-01-15T11:00:00Z","stopped_at":null,"seconds":0}"#,
+            "\n",
+        ),
+    )?;
+
+    let output = Command::cargo_bin("time-tracker")?
+        .arg("list")
+        .env("TT_HISTORY_FILE", &history_path)
+        .output()?;
+    std::fs::remove_file(&history_path)?;
+
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout)?;
+    let lines: Vec<&str> = stdout.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].starts_with("a1b2c3d4  2024-01-15 "));
+    assert!(lines[0].ends_with("  1h30m  first"));
+    assert!(lines[1].ends_with("  running  second"));
+    assert_eq!(lines[1].split("  ").next().map(str::len), Some(8));
+    Ok(())
+}
+
+#[test]
+fn test_deletes_task_by_id() -> Result<(), Box<dyn std::error::Error>> {
+    let history_path = TEMP_DIR.join(TEMP_HISTORY_FILENAME.replace("{}", &fn_name!()));
+    std::fs::write(
+        &history_path,
+        concat!(
+            r#"{"name":"keep","started_at":"2024-01-15T09:00:00Z","stopped_at":"2024-01-15T10:00:00Z","seconds":3600,"id":"11111111-0000-4000-8000-000000000000"}"#,
+            "\n",
+            r#"{"name":"mistake","started_at":"2024-01-15T11:00:00Z","stopped_at":"2024-01-15T11:01:00Z","seconds":60,"id":"a1b2c3d4-0000-4000-8000-000000000000"}"#,
+            "\n",
+        ),
+    )?;
+
+    let delete = |args: &[&str]| -> Result<std::process::Output, Box<dyn std::error::Error>> {
+        Ok(Command::cargo_bin("time-tracker")?
+            .arg("delete")
+            .args(args)
+            .env("TT_HISTORY_FILE", &history_path)
+            .output()?)
+    };
+    // stdin isn't a terminal here, so there is nothing to pick from
+    let without_id = delete(&[])?;
+    let unknown = delete(&["ffffffff"])?;
+    let deleted = delete(&["a1b2c3d4"])?;
+    let history = std::fs::read_to_string(&history_path)?;
+    std::fs::remove_file(&history_path)?;
+
+    assert!(!without_id.status.success());
+    assert!(String::from_utf8(without_id.stderr)?.contains("give the id"));
+    assert!(!unknown.status.success());
+    assert!(deleted.status.success());
+    assert_eq!(
+        String::from_utf8(deleted.stdout)?,
+        "Deleted mistake (a1b2c3d4).\n"
+    );
+    assert_eq!(history.lines().count(), 1);
+    assert!(history.contains("\"keep\""));
+    Ok(())
+}
+
+#[test]
+fn test_reports_totals_by_context() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = TEMP_DIR.join(format!("context_{}", fn_name!()));
+    std::fs::create_dir_all(&dir)?;
+    let dir = dir.canonicalize()?;
+    let history_path = dir.join("history.json");
+
+    let mut child = Command::cargo_bin("time-tracker")?
+        .args(["start", "write docs"])
+        .current_dir(&dir)
+        .env("TT_HISTORY_FILE", &history_path)
+        .env("TT_ENV", "test")
+        .stdin(std::process::Stdio::piped())
+        .stdout(std::process::Stdio::null())
+        .spawn()?;
+    child.stdin.take().unwrap().write_all(b"stop\n")?;
+    child.wait()?;
+
+    let output = Command::cargo_bin("time-tracker")?
+        .args(["report", "--group-by", "context"])
+        .env("TT_HISTORY_FILE", &history_path)
+        .output()?;
+    std::fs::remove_dir_all(&dir)?;
+
+    let stdout = String::from_utf8(output.stdout)?;
+    let mut lines = stdout.lines();
+    assert_eq!(lines.next(), Some(dir.display().to_string().as_str()));
+    assert!(lines
+        .next()
+        .is_some_and(|line| line.starts_with("  write docs: ")));
+    Ok(())
+}
+
+#[test]
+fn test_follows_history_as_ndjson() -> Result<(), Box<dyn std::error::Error>> {
+    use std::io::BufRead;
+
+    let dir = TEMP_DIR.join(format!("follow_{}", fn_name!()));
+    std::fs::create_dir_all(&dir)?;
+    let history_path = dir.join("history.json");
+    let line = |name: &str| {
+        format!(
+            r#"{{"name":"{}","started_at":"2024-01-15T09:00:00Z","stopped_at":"2024-01-15T09:30:00Z","seconds":1800}}"#,
+            name
+        ) + "\n"
+    };
+    std::fs::write(&history_path, line("first"))?;
+
+    let mut child = Command::cargo_bin("time-tracker")?
+        .args(["export", "--format", "ndjson", "--follow"])
+        .env("TT_HISTORY_FILE", &history_path)
+        .stdout(std::process::Stdio::piped())
+        .spawn()?;
+    let mut stdout = std::io::BufReader::new(child.stdout.take().unwrap());
+    let mut first = String::new();
+    stdout.read_line(&mut first)?;
+    std::fs::OpenOptions::new()
+        .append(true)
+        .open(&history_path)?
+        .write_all(line("second").as_bytes())?;
+    let mut second = String::new();
+    stdout.read_line(&mut second)?;
+    child.kill()?;
+    child.wait()?;
+    std::fs::remove_dir_all(&dir)?;
+
+    assert!(first.starts_with(r#"{"timestamp":"2024-01-15T09:00:00Z","name":"first","#));
+    assert!(second.starts_with(r#"{"timestamp":"2024-01-15T09:00:00Z","name":"second","#));
+    Ok(())
+}
+
+#[test]
+fn test_reports_rate_of_work() -> Result<(), Box<dyn std::error::Error>> {
+    let history_path = TEMP_DIR.join(TEMP_HISTORY_FILENAME.replace("{}", &fn_name!()));
+    std::fs::write(
+        &history_path,
+        concat!(
+            r#"{"name":"a","started_at":"2024-01-15T09:00:00Z","stopped_at":"2024-01-15T09:20:00Z","seconds":1200}"#,
+            "\n",
+            r#"{"name":"b","started_at":"2024-01-15T09:20:00Z","stopped_at":"2024-01-15T10:00:00Z","seconds":2400}"#,
+            "\n",
+            r#"{"name":"c","started_at":"2024-01-15T10:00:00Z","stopped_at":"2024-01-15T11:00:00Z","seconds":3600}"#,
+            "\n",
+        ),
+    )?;
+
+    let output = Command::cargo_bin("time-tracker")?
+        .args(["report", "--rate-of-work", "--period", "2024-01"])
+        .env("TT_HISTORY_FILE", &history_path)
+        .output()?;
+    std::fs::remove_file(&history_path)?;
+
+    assert_eq!(
+        String::from_utf8(output.stdout)?,
+        "Period: 2024-01\nTasks completed: 3\nHours tracked: 2.00\nAverage task duration: 40m\n\
+         Tasks per hour: 1.50\n"
+    );
+    Ok(())
+}
+
+#[test]
+fn test_serves_prometheus_metrics() -> Result<(), Box<dyn std::error::Error>> {
+    // the running tasks are counted too, so keep other tests' running file out of the way
+    let dir = TEMP_DIR.join(format!("metrics_{}", fn_name!()));
+    std::fs::create_dir_all(&dir)?;
+    let history_path = dir.join("history.json");
+    std::fs::write(
+        &history_path,
+        concat!(
+            r#"{"name":"a","started_at":"2024-01-15T09:00:00Z","stopped_at":"2024-01-15T10:00:00Z","seconds":3600}"#,
+            "\n",
+        ),
+    )?;
+    let port = std::net::TcpListener::bind("127.0.0.1:0")?
+        .local_addr()?
+        .port();
+
+    let mut child = Command::cargo_bin("time-tracker")?
+        .args(["serve", "--metrics-port", &port.to_string()])
+        .env("TT_HISTORY_FILE", &history_path)
+        .stdout(std::process::Stdio::null())
+        .spawn()?;
+    let mut stream = (0..50)
+        .find_map(|_| {
+            std::thread::sleep(std::time::Duration::from_millis(100));
+            std::net::TcpStream::connect(("127.0.0.1", port)).ok()
+        })
+        .ok_or("the server didn't start")?;
+    stream.write_all(b"GET /metrics HTTP/1.1\r\nHost: localhost\r\n\r\n")?;
+    let mut response = String::new();
+    stream.read_to_string(&mut response)?;
+    child.kill()?;
+    child.wait()?;
+    std::fs::remove_dir_all(&dir)?;
+
+    assert!(response.starts_with("HTTP/1.1 200 OK\r\n"));
+    assert!(response.contains("\ntimetracker_total_seconds 3600\n"));
+    assert!(response.ends_with("\ntimetracker_running_tasks 0\n"));
+    Ok(())
+}
+
+#[test]
+fn test_config_validate_reports_out_of_range_settings() -> Result<(), Box<dyn std::error::Error>> {
+    let home = TEMP_DIR.join(format!("home_{}", fn_name!()));
+    std::fs::create_dir_all(home.join(".timetracker"))?;
+    std::fs::write(
+        home.join(".timetracker").join("config.toml"),
+        "timer_update_interval_ms = 0\n",
+    )?;
+
+    let validate = Command::cargo_bin("time-tracker")?
+        .args(["config", "validate"])
+        .env("HOME", &home)
+        .output()?;
+    let show = Command::cargo_bin("time-tracker")?
+        .args(["config", "show"])
+        .env("HOME", &home)
+        .output()?;
+    // the timer would spin, so commands that run one refuse to start
+    let start = Command::cargo_bin("time-tracker")?
+        .args(["start", "write docs"])
+        .env("HOME", &home)
+        .output()?;
+    std::fs::remove_dir_all(&home)?;
+
+    assert_eq!(validate.status.code(), Some(1));
+    assert_eq!(
+        String::from_utf8(validate.stderr)?,
+        "ERROR: timer_update_interval_ms must be ≥ 100, got 0\n"
+    );
+    assert!(show.status.success());
+    assert_eq!(
+        String::from_utf8(show.stderr)?,
+        "Warning: timer_update_interval_ms must be ≥ 100, got 0\n"
+    );
+    assert_eq!(start.status.code(), Some(1));
+    assert_eq!(
+        String::from_utf8(start.stderr)?,
+        "ERROR: timer_update_interval_ms must be ≥ 100, got 0\n"
+    );
+    Ok(())
+}
+
+#[test]
+fn test_writes_to_output_file_or_stdout() -> Result<(), Box<dyn std::error::Error>> {
+    let history_path = TEMP_DIR.join(TEMP_HISTORY_FILENAME.replace("{}", &fn_name!()));
+    let output_path = TEMP_DIR.join(format!("{}.csv", fn_name!()));
+    std::fs::write(
+        &history_path,
+        concat!(
+            r#"{"name":"first","started_at":"2024-01-15T09:00:00Z","stopped_at":"2024-01-15T09:30:00Z","seconds":1800}"#,
+            "\n",
+        ),
+    )?;
+
+    for command in [&["export", "--format", "csv"][..], &["report"][..]] {
+        let to_stdout = Command::cargo_bin("time-tracker")?
+            .args(command)
+            .env("TT_HISTORY_FILE", &history_path)
+            .output()?;
+        let to_file = Command::cargo_bin("time-tracker")?
+            .args(command)
+            .arg("--output")
+            .arg(&output_path)
+            .env("TT_HISTORY_FILE", &history_path)
+            .output()?;
+        let written = std::fs::read_to_string(&output_path)?;
+        std::fs::remove_file(&output_path)?;
+
+        assert!(to_stdout.status.success());
+        assert!(to_file.status.success());
+        assert!(written.contains("first"));
+        assert_eq!(String::from_utf8(to_stdout.stdout)?, written);
+        assert!(to_file.stdout.is_empty());
+    }
+    std::fs::remove_file(&history_path)?;
+    Ok(())
+}
+
+#[test]
+fn test_edit_shifts_tasks_to_another_time_zone() -> Result<(), Box<dyn std::error::Error>> {
+    let history_path = TEMP_DIR.join(TEMP_HISTORY_FILENAME.replace("{}", &fn_name!()));
+    let original = concat!(
+        r#"{"name":"before","started_at":"2024-01-09T09:00:00Z","stopped_at":"2024-01-09T10:00:00Z","seconds":3600,"id":"00000000-0000-0000-0000-000000000001"}"#,
+        "\n",
+        r#"{"name":"during","started_at":"2024-01-15T09:00:00Z","stopped_at":"2024-01-15T10:00:00Z","seconds":3600,"id":"00000000-0000-0000-0000-000000000002"}"#,
+        "\n",
+    );
+    std::fs::write(&history_path, original)?;
+
+    let output = Command::cargo_bin("time-tracker")?
+        .args([
+            "edit",
+            "--time-zone",
+            "Asia/Kolkata",
+            "--shift-to",
+            "UTC",
+            "--after",
+            "2024-01-10",
+        ])
+        .env("TT_HISTORY_FILE", &history_path)
+        .output()?;
+    let history = std::fs::read_to_string(&history_path)?;
+    let backup_path = format!("{}.bak", history_path.display());
+    let backup = std::fs::read_to_string(&backup_path)?;
+    std::fs::remove_file(&history_path)?;
+    std::fs::remove_file(&backup_path)?;
+
+    assert!(output.status.success());
+    assert!(String::from_utf8(output.stdout)?.starts_with("Shifted 1 tasks"));
+    assert_eq!(backup, original);
+    let lines: Vec<&str> = history.lines().collect();
+    assert_eq!(lines[0], original.lines().next().unwrap());
+    assert!(lines[1].contains(
+        r#""started_at":"2024-01-15T14:30:00Z","stopped_at":"2024-01-15T15:30:00Z","seconds":3600"#
+    ));
+    Ok(())
+}
+
+#[test]
+fn test_imports_redmine_time_entries() -> Result<(), Box<dyn std::error::Error>> {
+    let history_path = TEMP_DIR.join(TEMP_HISTORY_FILENAME.replace("{}", &fn_name!()));
+    let csv_path = history_path.with_extension("csv");
+    let _ = std::fs::remove_file(&history_path);
+    std::fs::write(
+        &csv_path,
+        "Date,User,Activity,Project,Issue,Comment,Hours\n\
+         2024-01-15,Jane Doe,Development,Website,1234,Fix login form,1.5\n\
+         2024-01-16,Jane Doe,Design,Website,1235,,0.25\n",
+    )?;
+
+    let output = Command::cargo_bin("time-tracker")?
+        .args(["import", "--redmine"])
+        .arg(&csv_path)
+        .env("TT_HISTORY_FILE", &history_path)
+        .output()?;
+    let history = std::fs::read_to_string(&history_path)?;
+    std::fs::remove_file(&history_path)?;
+    std::fs::remove_file(&csv_path)?;
+
+    assert!(output.status.success());
+    assert!(String::from_utf8(output.stdout)?.starts_with("Imported 2 tasks"));
+    let lines: Vec<&str> = history.lines().collect();
+    assert_eq!(lines.len(), 2);
+    assert!(lines[0].contains(
+        r#""name":"Fix login form","started_at":"2024-01-15T00:00:00Z","stopped_at":"2024-01-15T01:30:00Z","seconds":5400,"billable":false"#
+    ));
+    assert!(lines[0].ends_with(r#""project":"Website","tags":["Development"]}"#));
+    assert!(lines[1].contains(r#""name":"Issue #1235""#));
+    Ok(())
+}
+
+#[test]
+fn test_pauses_and_resumes_all_running_tasks() -> Result<(), Box<dyn std::error::Error>> {
+    let dir = TEMP_DIR.join(format!("pause_{}", fn_name!()));
+    std::fs::create_dir_all(&dir)?;
+    let history_path = dir.join("history.json");
+    let running: Vec<String> = ["a", "b", "c"]
+        .iter()
+        .map(|name| {
+            format!(
+                r#"{{"name":"{}","started_at":"2024-01-15T09:00:00Z","seconds":0}}"#,
+                name
+            )
+        })
+        .collect();
+    std::fs::write(dir.join("running.json"), format!("[{}]", running.join(",")))?;
+    let run = |args: &[&str]| -> Result<String, Box<dyn std::error::Error>> {
+        let output = Command::cargo_bin("time-tracker")?
+            .args(args)
+            .env("TT_HISTORY_FILE", &history_path)
+            .output()?;
+        assert!(output.status.success());
+        Ok(String::from_utf8(output.stdout)?)
+    };
+
+    let paused = run(&["pause", "all"])?;
+    let summary = run(&["summary", "--oneline"])?;
+    let paused_again = run(&["pause", "all"])?;
+    let resumed = run(&["resume", "all"])?;
+    let summary_after = run(&["summary", "--oneline"])?;
+    std::fs::remove_dir_all(&dir)?;
+
+    assert_eq!(paused, "Paused 3 tasks.\n");
+    assert_eq!(summary.matches(" (paused)]").count(), 3);
+    assert_eq!(paused_again, "Paused 0 tasks.\n");
+    assert_eq!(resumed, "Resumed 3 tasks.\n");
+    assert!(!summary_after.contains("paused"));
+    Ok(())
+}
+
+#[test]
+#[cfg(all(unix, feature = "git-integration"))]
+fn test_reports_time_per_commit() -> Result<(), Box<dyn std::error::Error>> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dir = TEMP_DIR.join(format!("per_commit_{}", fn_name!()));
+    std::fs::create_dir_all(&dir)?;
+    let history_path = dir.join("history.json");
+    std::fs::write(
+        &history_path,
+        concat!(
+            r#"{"name":"abc1234 fix login bug","started_at":"2024-01-15T09:00:00Z","stopped_at":"2024-01-15T10:30:00Z","seconds":5400}"#,
+            "\n",
+            r#"{"name":"standup","started_at":"2024-01-15T11:00:00Z","stopped_at":"2024-01-15T11:15:00Z","seconds":900}"#,
+            "\n",
+        ),
+    )?;
+    // a fake git that only knows one commit, found before the real one on the PATH
+    let git_path = dir.join("git");
+    std::fs::write(
+        &git_path,
+        "#!/bin/sh\n\
+         for arg in \"$@\"; do\n\
+           [ \"$arg\" = abc1234 ] && echo 'abc1234 Fix login bug' && exit 0\n\
+         done\n\
+         exit 128\n",
+    )?;
+    std::fs::set_permissions(&git_path, std::fs::Permissions::from_mode(0o755))?;
+    let path = format!("{}:{}", dir.display(), std::env::var("PATH")?);
+
+    let output = Command::cargo_bin("time-tracker")?
+        .args(["report", "--per-commit"])
+        .env("TT_HISTORY_FILE", &history_path)
+        .env("PATH", path)
+        .output()?;
+    std::fs::remove_dir_all(&dir)?;
+
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout)?,
+        "abc1234 Fix login bug  01:30:00\nUnlinked               00:15:00\n"
+    );
+    Ok(())
+}
+
+#[test]
+fn test_invoices_client_for_current_billing_period() -> Result<(), Box<dyn std::error::Error>> {
+    let home = TEMP_DIR.join(format!("home_{}", fn_name!()));
+    std::fs::create_dir_all(home.join(".timetracker"))?;
+    // a billing period of a day, so the current period is always today
+    std::fs::write(
+        home.join(".timetracker").join("config.toml"),
+        "hourly_rate = 100.0\n\
+         [client_billing_periods.ACME]\n\
+         cycle = \"1d\"\n\
+         anchor_date = \"2024-01-01\"\n",
+    )?;
+    let started_at = chrono::Utc::now();
+    let format = |time: chrono::DateTime<chrono::Utc>| {
+        time.to_rfc3339_opts(chrono::SecondsFormat::Secs, true)
+    };
+    std::fs::write(
+        home.join(".timetracker").join("history.json"),
+        format!(
+            concat!(
+                r#"{{"name":"design","started_at":"{}","stopped_at":"{}","seconds":5400,"client":"ACME"}}"#,
+                "\n",
+                r#"{{"name":"support","started_at":"{}","stopped_at":"{}","seconds":1800,"client":"Globex"}}"#,
+                "\n",
+                r#"{{"name":"old","started_at":"2024-01-15T09:00:00Z","stopped_at":"2024-01-15T10:00:00Z","seconds":3600}}"#,
+                "\n",
+            ),
+            format(started_at),
+            format(started_at + chrono::Duration::seconds(5400)),
+            format(started_at),
+            format(started_at + chrono::Duration::seconds(1800)),
+        ),
+    )?;
+
+    let invoice = Command::cargo_bin("time-tracker")?
+        .args(["report", "--client", "ACME", "--current-period"])
+        .env("HOME", &home)
+        .env_remove("TT_HISTORY_FILE")
+        .output()?;
+    let unknown = Command::cargo_bin("time-tracker")?
+        .args(["report", "--client", "Globex", "--current-period"])
+        .env("HOME", &home)
+        .env_remove("TT_HISTORY_FILE")
+        .output()?;
+    std::fs::remove_dir_all(&home)?;
+
+    assert!(invoice.status.success());
+    let invoice = String::from_utf8(invoice.stdout)?;
+    assert!(invoice.contains("Client: ACME\n"));
+    assert!(invoice.contains("design | 1.50 | $100.00 | $150.00\n"));
+    assert!(!invoice.contains("old"));
+    // another client's task in the same period isn't billed to ACME
+    assert!(!invoice.contains("support"));
+    assert!(invoice.ends_with("Total: $150.00\n"));
+    assert_eq!(unknown.status.code(), Some(1));
+    Ok(())
+}
+
+#[test]
+fn test_reports_tasks_between_since_and_until() -> Result<(), Box<dyn std::error::Error>> {
+    let history_path = TEMP_DIR.join(TEMP_HISTORY_FILENAME.replace("{}", &fn_name!()));
+    std::fs::write(
+        &history_path,
+        concat!(
+            r#"{"name":"december","started_at":"2023-12-15T12:00:00Z","stopped_at":"2023-12-15T13:00:00Z","seconds":3600}"#,
+            "\n",
+            r#"{"name":"january","started_at":"2024-01-15T12:00:00Z","stopped_at":"2024-01-15T12:30:00Z","seconds":1800}"#,
+            "\n",
+            r#"{"name":"later","started_at":"2024-01-20T12:00:00Z","stopped_at":"2024-01-20T14:00:00Z","seconds":7200,"client":"ACME"}"#,
+            "\n",
+            r#"{"name":"february","started_at":"2024-02-15T12:00:00Z","stopped_at":"2024-02-15T13:00:00Z","seconds":3600}"#,
+            "\n",
+        ),
+    )?;
+
+    let report = |args: &[&str]| -> Result<String, Box<dyn std::error::Error>> {
+        let output = Command::cargo_bin("time-tracker")?
+            .arg("report")
+            .args(args)
+            .env("TT_HISTORY_FILE", &history_path)
+            .output()?;
+        assert!(output.status.success());
+        Ok(String::from_utf8(output.stdout)?)
+    };
+    let january = report(&["--since", "2024-01-01", "--until", "2024-01-31"])?;
+    let top = report(&["--since", "2024-01-01T00:00:00", "--top", "1"])?;
+    let acme_january = report(&[
+        "--since",
+        "2024-01-01",
+        "--until",
+        "2024-01-31",
+        "--client",
+        "ACME",
+    ])?;
+    let until_invalid = Command::cargo_bin("time-tracker")?
+        .args(["report", "--since", "2024-02-01", "--until", "2024-01-01"])
+        .env("TT_HISTORY_FILE", &history_path)
+        .output()?;
+    std::fs::remove_file(&history_path)?;
+
+    assert_eq!(
+        january,
+        "later: 2h\njanuary: 30m\nTotal: 2h30m\nBillable: 2h30m\nNon-billable: 0s\n"
+    );
+    assert!(top.contains("later"));
+    assert!(acme_january.starts_with("later: 2h\nTotal: 2h\n"));
+    assert!(!top.contains("december") && !top.contains("february"));
+    assert!(!until_invalid.status.success());
+    Ok(())
+}
+
+#[test]
+fn test_top_combines_with_client_and_tag() -> Result<(), Box<dyn std::error::Error>> {
+    let history_path = TEMP_DIR.join(TEMP_HISTORY_FILENAME.replace("{}", &fn_name!()));
+    std::fs::write(
+        &history_path,
+        concat!(
+            r#"{"name":"acme design","started_at":"2024-01-15T12:00:00Z","stopped_at":"2024-01-15T14:00:00Z","seconds":7200,"client":"ACME","tags":["design"]}"#,
+            "\n",
+            r#"{"name":"acme admin","started_at":"2024-01-16T12:00:00Z","stopped_at":"2024-01-16T13:00:00Z","seconds":3600,"client":"ACME"}"#,
+            "\n",
+            r#"{"name":"globex design","started_at":"2024-01-17T12:00:00Z","stopped_at":"2024-01-17T15:00:00Z","seconds":10800,"client":"Globex","tags":["design"]}"#,
+            "\n",
+        ),
+    )?;
+
+    let report = |args: &[&str]| -> Result<String, Box<dyn std::error::Error>> {
+        let output = Command::cargo_bin("time-tracker")?
+            .args(["report", "--top", "10"])
+            .args(args)
+            .env("TT_HISTORY_FILE", &history_path)
+            .output()?;
+        assert!(output.status.success());
+        Ok(String::from_utf8(output.stdout)?)
+    };
+    let acme = report(&["--client", "ACME"])?;
+    let design = report(&["--tag", "design"])?;
+    let acme_design = report(&["--client", "ACME", "--tag", "design"])?;
+    std::fs::remove_file(&history_path)?;
+
+    assert!(acme.contains("acme design") && acme.contains("acme admin"));
+    assert!(!acme.contains("globex"));
+    assert!(design.contains("acme design") && design.contains("globex design"));
+    assert!(!design.contains("acme admin"));
+    assert_eq!(acme_design.lines().count(), 1);
+    assert!(acme_design.starts_with("1. acme design"));
+    Ok(())
+}
+
+#[test]
+fn test_billable_only_combines_with_client() -> Result<(), Box<dyn std::error::Error>> {
+    let history_path = TEMP_DIR.join(TEMP_HISTORY_FILENAME.replace("{}", &fn_name!()));
+    std::fs::write(
+        &history_path,
+        concat!(
+            r#"{"name":"acme design","started_at":"2024-01-15T12:00:00Z","stopped_at":"2024-01-15T14:00:00Z","seconds":7200,"client":"ACME"}"#,
+            "\n",
+            r#"{"name":"acme admin","started_at":"2024-01-16T12:00:00Z","stopped_at":"2024-01-16T13:00:00Z","seconds":3600,"billable":false,"client":"ACME"}"#,
+            "\n",
+            r#"{"name":"globex design","started_at":"2024-01-17T12:00:00Z","stopped_at":"2024-01-17T15:00:00Z","seconds":10800,"client":"Globex"}"#,
+            "\n",
+        ),
+    )?;
+
+    let report = |args: &[&str]| -> Result<String, Box<dyn std::error::Error>> {
+        let output = Command::cargo_bin("time-tracker")?
+            .arg("report")
+            .args(args)
+            .env("TT_HISTORY_FILE", &history_path)
+            .output()?;
+        assert!(output.status.success());
+        Ok(String::from_utf8(output.stdout)?)
+    };
+    let billable = report(&["--billable-only", "--client", "ACME"])?;
+    let non_billable = report(&["--non-billable-only", "--client", "ACME"])?;
+    std::fs::remove_file(&history_path)?;
+
+    assert_eq!(
+        billable,
+        "acme design: 2h\nTotal: 2h\nBillable: 2h\nNon-billable: 1h\n"
+    );
+    assert!(non_billable.starts_with("acme admin: 1h\nTotal: 1h\n"));
+    Ok(())
+}
+
+#[test]
+fn test_sparkline_week_still_takes_weeks_as_since() -> Result<(), Box<dyn std::error::Error>> {
+    let history_path = TEMP_DIR.join(TEMP_HISTORY_FILENAME.replace("{}", &fn_name!()));
+    std::fs::write(&history_path, "")?;
+
+    let since = Command::cargo_bin("time-tracker")?
+        .args(["report", "--sparkline-week", "--since", "3"])
+        .env("TT_HISTORY_FILE", &history_path)
+        .output()?;
+    let weeks = Command::cargo_bin("time-tracker")?
+        .args(["report", "--sparkline-week", "--weeks", "3"])
+        .env("TT_HISTORY_FILE", &history_path)
+        .output()?;
+    let without_sparkline = Command::cargo_bin("time-tracker")?
+        .args(["report", "--since", "3"])
+        .env("TT_HISTORY_FILE", &history_path)
+        .output()?;
+    std::fs::remove_file(&history_path)?;
+
+    assert!(since.status.success());
+    assert_eq!(String::from_utf8(since.stdout.clone())?.lines().count(), 4);
+    assert_eq!(since.stdout, weeks.stdout);
+    assert_eq!(without_sparkline.status.code(), Some(1));
+    Ok(())
+}
+
+#[test]
+fn test_reports_the_tasks_in_a_percentile() -> Result<(), Box<dyn std::error::Error>> {
+    let history_path = TEMP_DIR.join(TEMP_HISTORY_FILENAME.replace("{}", &fn_name!()));
+    std::fs::write(
+        &history_path,
+        concat!(
+            r#"{"name":"a","started_at":"2024-01-15T09:00:00Z","stopped_at":"2024-01-15T09:50:00Z","seconds":3000}"#,
+            "\n",
+            r#"{"name":"b","started_at":"2024-01-15T10:00:00Z","stopped_at":"2024-01-15T10:30:00Z","seconds":1800}"#,
+            "\n",
+            r#"{"name":"c","started_at":"2024-01-15T11:00:00Z","stopped_at":"2024-01-15T11:15:00Z","seconds":900}"#,
+            "\n",
+            r#"{"name":"d","started_at":"2024-01-15T12:00:00Z","stopped_at":"2024-01-15T12:05:00Z","seconds":300}"#,
+            "\n",
+        ),
+    )?;
+
+    let output = Command::cargo_bin("time-tracker")?
+        .args(["report", "--percentile", "75"])
+        .env("TT_HISTORY_FILE", &history_path)
+        .output()?;
+    std::fs::remove_file(&history_path)?;
+
+    assert!(String::from_utf8(output.stdout)?.starts_with(
+        "Top 25% of time (by duration): 2 tasks out of 4 consumed 80.0% of your tracked time\n"
+    ));
+    Ok(())
+}
+
+#[test]
+fn test_report_excludes_short_tasks() -> Result<(), Box<dyn std::error::Error>> {
+    let history_path = TEMP_DIR.join(TEMP_HISTORY_FILENAME.replace("{}", &fn_name!()));
+    std::fs::write(
+        &history_path,
+        concat!(
+            r#"{"name":"accident","started_at":"2024-01-15T09:00:00Z","stopped_at":"2024-01-15T09:00:59Z","seconds":59}"#,
+            "\n",
+            r#"{"name":"quick","started_at":"2024-01-15T10:00:00Z","stopped_at":"2024-01-15T10:01:00Z","seconds":60}"#,
+            "\n",
+            r#"{"name":"work","started_at":"2024-01-15T11:00:00Z","stopped_at":"2024-01-15T12:00:00Z","seconds":3600}"#,
+            "\n",
+        ),
+    )?;
+
+    let output = Command::cargo_bin("time-tracker")?
+        .args(["report", "--exclude-short", "1m"])
+        .env("TT_HISTORY_FILE", &history_path)
+        .output()?;
+    std::fs::remove_file(&history_path)?;
+
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout)?,
+        "work: 1h\nquick: 1m\nTotal: 1h1m\nBillable: 1h1m\nNon-billable: 0s\n"
+    );
+    Ok(())
+}
+
+#[test]
+fn test_overtime_is_not_colored_when_piped() -> Result<(), Box<dyn std::error::Error>> {
+    let history_path = TEMP_DIR.join(TEMP_HISTORY_FILENAME.replace("{}", &fn_name!()));
+    std::fs::write(
+        &history_path,
+        concat!(
+            r#"{"name":"work","started_at":"2024-01-15T08:00:00Z","stopped_at":"2024-01-15T20:00:00Z","seconds":43200}"#,
+            "\n",
+        ),
+    )?;
+
+    let output = Command::cargo_bin("time-tracker")?
+        .args(["report", "--time-utilization", "--date", "2024-01-15"])
+        .env("TT_HISTORY_FILE", &history_path)
+        .env("TZ", "UTC")
+        .output()?;
+    std::fs::remove_file(&history_path)?;
+
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout)?,
+        "Overtime: 133.3% (12h tracked of 9h work day)\n[██████████]\n"
+    );
+    Ok(())
+}
+
+#[test]
+fn test_report_normalizes_times_to_a_time_zone() -> Result<(), Box<dyn std::error::Error>> {
+    let history_path = TEMP_DIR.join(TEMP_HISTORY_FILENAME.replace("{}", &fn_name!()));
+    std::fs::write(
+        &history_path,
+        concat!(
+            r#"{"name":"late","started_at":"2024-01-01T23:00:00Z","stopped_at":"2024-01-01T23:30:00Z","seconds":1800}"#,
+            "\n",
+        ),
+    )?;
+
+    let report = |args: &[&str]| -> Result<String, Box<dyn std::error::Error>> {
+        let output = Command::cargo_bin("time-tracker")?
+            .args(["report", "--normalize"])
+            .args(args)
+            .env("TT_HISTORY_FILE", &history_path)
+            .env("TZ", "America/New_York")
+            .output()?;
+        assert!(output.status.success());
+        Ok(String::from_utf8(output.stdout)?)
+    };
+    let utc = report(&[])?;
+    let athens = report(&["--timezone", "Europe/Athens"])?;
+    let first_task = report(&["--timezone", "Europe/Athens", "--first-task-time"])?;
+    // --overlap has to check for overlaps, not print a daily log and succeed
+    let overlap = Command::cargo_bin("time-tracker")?
+        .args(["report", "--normalize", "--overlap"])
+        .env("TT_HISTORY_FILE", &history_path)
+        .output()?;
+    std::fs::remove_file(&history_path)?;
+
+    assert_eq!(utc, "2024-01-01 (UTC)\n  23:00–23:30  late\n");
+    assert_eq!(athens, "2024-01-02 (Europe/Athens)\n  01:00–01:30  late\n");
+    // reports of times of day use the zone too, not the local one
+    assert_eq!(
+        first_task,
+        "First task started: avg 01:00, earliest 01:00, latest 01:00 (over 1 day) (Europe/Athens)\n"
+    );
+    assert!(!overlap.status.success());
+    assert!(String::from_utf8(overlap.stderr)?.contains("cannot be used with"));
+    Ok(())
+}
+
+#[test]
+#[cfg(unix)]
+fn test_daemon_status_reports_the_daemon_pid() -> Result<(), Box<dyn std::error::Error>> {
+    use std::io::{BufRead, BufReader};
+    use std::os::unix::net::UnixListener;
+
+    let dir = TEMP_DIR.join(format!("daemon_{}_{}", std::process::id(), fn_name!()));
+    std::fs::create_dir_all(&dir)?;
+    let history_path = dir.join("history.json");
+
+    let not_running = Command::cargo_bin("time-tracker")?
+        .args(["daemon", "status"])
+        .env("TT_HISTORY_FILE", &history_path)
+        .output()?;
+    assert_eq!(not_running.status.code(), Some(1));
+    assert_eq!(
+        String::from_utf8(not_running.stdout)?,
+        "Daemon not running.\n"
+    );
+
+    // stands in for the daemon, answering one status request with this process's id
+    let listener = UnixListener::bind(dir.join("daemon.sock"))?;
+    let daemon = std::thread::spawn(move || -> std::io::Result<String> {
+        let (stream, _) = listener.accept()?;
+        let mut request = String::new();
+        BufReader::new(&stream).read_line(&mut request)?;
+        writeln!(
+            &stream,
+            r#"{{"pid":{},"uptime_secs":90,"running_task_count":1,"completed_task_count":3,"connected_clients":1}}"#,
+            std::process::id()
+        )?;
+        Ok(request)
+    });
+    let output = Command::cargo_bin("time-tracker")?
+        .args(["daemon", "status"])
+        .env("TT_HISTORY_FILE", &history_path)
+        .output()?;
+    let request = daemon.join().unwrap()?;
+    std::fs::remove_dir_all(&dir)?;
+
+    assert!(output.status.success());
+    assert_eq!(request, "{\"cmd\":\"status\"}\n");
+    let stdout = String::from_utf8(output.stdout)?;
+    assert!(stdout.starts_with(&format!("PID: {}\n", std::process::id())));
+    assert!(stdout.contains("Uptime: 1m30s\n"));
+    Ok(())
+}
+
+#[test]
+fn test_exports_an_ods_spreadsheet() -> Result<(), Box<dyn std::error::Error>> {
+    let history_path = TEMP_DIR.join(TEMP_HISTORY_FILENAME.replace("{}", &fn_name!()));
+    let output_path = TEMP_DIR.join(format!("{}.ods", fn_name!()));
+    std::fs::write(
+        &history_path,
+        r#"{"name":"write docs","started_at":"2024-01-15T09:00:00Z","stopped_at":"2024-01-15T10:30:00Z","seconds":5400}"#,
+    )?;
+
+    let output = Command::cargo_bin("time-tracker")?
+        .args(["export", "--format", "ods", "--output"])
+        .arg(&output_path)
+        .env("TT_HISTORY_FILE", &history_path)
+        .output()?;
+    let bytes = std::fs::read(&output_path)?;
+    std::fs::remove_file(&history_path)?;
+    std::fs::remove_file(&output_path)?;
+
+    assert!(output.status.success());
+    assert!(bytes.starts_with(b"PK\x03\x04"));
+    assert!(bytes.windows(11).any(|name| name == b"content.xml"));
+    Ok(())
+}
+
+#[test]
+fn test_exports_a_writing_log_package() -> Result<(), Box<dyn std::error::Error>> {
+    let history_path = TEMP_DIR.join(TEMP_HISTORY_FILENAME.replace("{}", &fn_name!()));
+    let output_dir = TEMP_DIR.join(fn_name!());
+    let _ = std::fs::remove_dir_all(&output_dir);
+    std::fs::write(
+        &history_path,
+        r#"{"name":"Chapter 3 draft","started_at":"2024-01-15T12:00:00Z","stopped_at":"2024-01-15T13:30:00Z","seconds":5400}"#,
+    )?;
+
+    let output = Command::cargo_bin("time-tracker")?
+        .args(["export", "--format", "opf", "--output-dir"])
+        .arg(&output_dir)
+        .env("TT_HISTORY_FILE", &history_path)
+        .output()?;
+    let package = std::fs::read_to_string(output_dir.join("content.opf"))?;
+    let chapter = std::fs::read_to_string(output_dir.join("session-1.xhtml"))?;
+    std::fs::remove_file(&history_path)?;
+    std::fs::remove_dir_all(&output_dir)?;
+
+    assert!(output.status.success());
+    assert!(package.contains(r#"<package xmlns="http://www.idpf.org/2007/opf""#));
+    assert!(package.contains("<dc:title>Chapter 3 draft</dc:title>"));
+    assert!(chapter.contains("<p>2024-01-15: 1 Hour and 30 Minutes</p>"));
+    Ok(())
+}
+
+#[test]
+fn test_fixes_tasks_left_running() -> Result<(), Box<dyn std::error::Error>> {
+    let history_path = TEMP_DIR.join(TEMP_HISTORY_FILENAME.replace("{}", &fn_name!()));
+    std::fs::write(
+        &history_path,
+        concat!(
+            r#"{"name":"done","started_at":"2024-01-15T09:00:00Z","stopped_at":"2024-01-15T09:30:00Z","seconds":1800}"#,
+            "\n",
+            r#"{"name":"crashed","started_at":"2024-01-15T10:00:00Z","seconds":0}"#,
+            "\n",
+        ),
+    )?;
+
+    let report = Command::cargo_bin("time-tracker")?
+        .args(["report", "--incomplete"])
+        .env("TT_HISTORY_FILE", &history_path)
+        .output()?;
+    let fix = Command::cargo_bin("time-tracker")?
+        .args(["fix", "--incomplete", "--stop-at-started-at"])
+        .env("TT_HISTORY_FILE", &history_path)
+        .output()?;
+    let history = std::fs::read_to_string(&history_path)?;
+    let report_after = Command::cargo_bin("time-tracker")?
+        .args(["report", "--incomplete"])
+        .env("TT_HISTORY_FILE", &history_path)
+        .output()?;
+    std::fs::remove_file(&history_path)?;
+
+    let report = String::from_utf8(report.stdout)?;
+    assert!(report.contains("  crashed  (started "));
+    assert!(!report.contains("done"));
+    assert!(fix.status.success());
+    assert_eq!(String::from_utf8(fix.stdout)?, "Fixed 1 incomplete task.\n");
+    assert!(history.contains(
+        r#""name":"crashed","started_at":"2024-01-15T10:00:00Z","stopped_at":"2024-01-15T10:00:00Z""#
+    ));
+    assert_eq!(
+        String::from_utf8(report_after.stdout)?,
+        "No incomplete tasks.\n"
+    );
+    Ok(())
+}
+
+#[test]
+fn test_exports_habits_as_recurring_events() -> Result<(), Box<dyn std::error::Error>> {
+    let history_path = TEMP_DIR.join(TEMP_HISTORY_FILENAME.replace("{}", &fn_name!()));
+    std::fs::write(
+        &history_path,
+        concat!(
+            r#"{"name":"standup","started_at":"2024-01-15T09:00:00Z","stopped_at":"2024-01-15T09:15:00Z","seconds":900}"#,
+            "\n",
+            r#"{"name":"standup","started_at":"2024-01-16T09:05:00Z","stopped_at":"2024-01-16T09:20:00Z","seconds":900}"#,
+            "\n",
+            r#"{"name":"standup","started_at":"2024-01-17T08:55:00Z","stopped_at":"2024-01-17T09:10:00Z","seconds":900}"#,
+            "\n",
+            r#"{"name":"write docs","started_at":"2024-01-16T14:00:00Z","stopped_at":"2024-01-16T15:00:00Z","seconds":3600}"#,
+            "\n",
+        ),
+    )?;
+
+    let output = Command::cargo_bin("time-tracker")?
+        .args([
+            "export",
+            "--format",
+            "ics-recurring",
+            "--min-occurrences",
+            "3",
+        ])
+        .env("TT_HISTORY_FILE", &history_path)
+        .env("TZ", "UTC")
+        .output()?;
+    std::fs::remove_file(&history_path)?;
+
+    let ics = String::from_utf8(output.stdout)?;
+    assert!(output.status.success());
+    assert_eq!(ics.matches("BEGIN:VEVENT").count(), 2);
+    assert!(ics.contains("RRULE:FREQ=WEEKLY;BYDAY=MO,TU,WE;UNTIL=20240117T090000\r\n"));
+    assert!(ics.contains("SUMMARY:write docs\r\nDTSTART:20240116T140000Z\r\n"));
+    assert!(ics.ends_with("\r\nEND:VCALENDAR\r\n"));
+    Ok(())
+}
+
+#[test]
+fn test_exports_a_file_per_month() -> Result<(), Box<dyn std::error::Error>> {
+    let history_path = TEMP_DIR.join(TEMP_HISTORY_FILENAME.replace("{}", &fn_name!()));
+    let output_dir = TEMP_DIR.join(format!("{}_archive", fn_name!()));
+    let _ = std::fs::remove_dir_all(&output_dir);
+    std::fs::write(
+        &history_path,
+        concat!(
+            r#"{"name":"december","started_at":"2023-12-15T12:00:00Z","stopped_at":"2023-12-15T13:00:00Z","seconds":3600}"#,
+            "\n",
+            r#"{"name":"january","started_at":"2024-01-15T12:00:00Z","stopped_at":"2024-01-15T12:30:00Z","seconds":1800}"#,
+            "\n",
+            r#"{"name":"february","started_at":"2024-02-15T12:00:00Z","stopped_at":"2024-02-15T13:00:00Z","seconds":3600}"#,
+            "\n",
+        ),
+    )?;
+
+    let export = |overwrite: bool| -> Result<std::process::Output, Box<dyn std::error::Error>> {
+        let mut command = Command::cargo_bin("time-tracker")?;
+        command
+            .args(["export", "--format", "csv", "--since", "2024-01-01"])
+            .args(["--split-by", "month", "--output-dir"])
+            .arg(&output_dir)
+            .env("TT_HISTORY_FILE", &history_path);
+        if overwrite {
+            command.arg("--overwrite");
+        }
+        Ok(command.output()?)
+    };
+    let first = export(false)?;
+    let mut files: Vec<String> = std::fs::read_dir(&output_dir)?
+        .map(|entry| entry.map(|entry| entry.file_name().to_string_lossy().into_owned()))
+        .collect::<Result<_, _>>()?;
+    files.sort();
+    let january = std::fs::read_to_string(output_dir.join("2024-01.csv"))?;
+    let again = export(false)?;
+    let overwritten = export(true)?;
+    std::fs::remove_file(&history_path)?;
+    std::fs::remove_dir_all(&output_dir)?;
+
+    assert!(first.status.success());
+    assert_eq!(files, ["2024-01.csv", "2024-02.csv"]);
+    assert_eq!(january.lines().count(), 2);
+    assert!(january.contains("january"));
+    assert!(!again.status.success());
+    let errors = String::from_utf8(again.stderr)?;
+    assert_eq!(errors.matches("use --overwrite").count(), 2);
+    assert!(overwritten.status.success());
+    Ok(())
+}
+
+#[test]
+fn test_reports_sprint_velocity() -> Result<(), Box<dyn std::error::Error>> {
+    let history_path = TEMP_DIR.join(TEMP_HISTORY_FILENAME.replace("{}", &fn_name!()));
+    std::fs::write(
+        &history_path,
+        concat!(
+            r#"{"name":"PROJ-1 (5pts) login","started_at":"2024-01-16T12:00:00Z","stopped_at":"2024-01-16T14:00:00Z","seconds":7200}"#,
+            "\n",
+            r#"{"name":"PROJ-2 (3pts) logout","started_at":"2024-01-17T12:00:00Z","stopped_at":"2024-01-17T14:00:00Z","seconds":7200}"#,
+            "\n",
+            r#"{"name":"standup","started_at":"2024-01-18T12:00:00Z","stopped_at":"2024-01-18T12:15:00Z","seconds":900}"#,
+            "\n",
+            r#"{"name":"PROJ-3 (8pts) signup","started_at":"2024-01-24T12:00:00Z","stopped_at":"2024-01-24T16:00:00Z","seconds":14400}"#,
+            "\n",
+        ),
+    )?;
+
+    let report = |args: &[&str]| -> Result<std::process::Output, Box<dyn std::error::Error>> {
+        Ok(Command::cargo_bin("time-tracker")?
+            .args(["report", "--velocity"])
+            .args(args)
+            .env("TT_HISTORY_FILE", &history_path)
+            .output()?)
+    };
+    let sprint = report(&["--sprint", "2024-W03"])?;
+    let trend = report(&["--since", "2024-W03", "--until", "2024-W04"])?;
+    std::fs::remove_file(&history_path)?;
+
+    assert!(sprint.status.success());
+    assert_eq!(
+        String::from_utf8(sprint.stdout)?,
+        "Sprint 2024-W03 velocity: 8 points across 3 tasks (4.2h, 1.88 points/hour)\n"
+    );
+    assert!(String::from_utf8(sprint.stderr)?.contains("Warning: 1 tasks have no (<n>pts)"));
+    let trend = String::from_utf8(trend.stdout)?;
+    assert_eq!(trend.lines().count(), 2);
+    assert!(trend
+        .lines()
+        .nth(1)
+        .unwrap()
+        .starts_with("Sprint 2024-W04 velocity: 8 points across 1 tasks"));
+    Ok(())
+}
+
+#[test]
+fn test_reports_time_against_task_goals() -> Result<(), Box<dyn std::error::Error>> {
+    let home = TEMP_DIR.join(format!("home_{}", fn_name!()));
+    std::fs::create_dir_all(home.join(".timetracker"))?;
+    std::fs::write(
+        home.join(".timetracker").join("config.toml"),
+        "[task_goals]\nSpanish = 1800\nexercise = 3600\n",
+    )?;
+    std::fs::write(
+        home.join(".timetracker").join("history.json"),
+        concat!(
+            r#"{"name":"Spanish","started_at":"2024-01-15T12:00:00Z","stopped_at":"2024-01-15T12:25:00Z","seconds":1500}"#,
+            "\n",
+            r#"{"name":"Spanish","started_at":"2024-01-16T12:00:00Z","stopped_at":"2024-01-16T12:40:00Z","seconds":2400}"#,
+            "\n",
+            r#"{"name":"exercise","started_at":"2024-01-16T13:00:00Z","stopped_at":"2024-01-16T14:00:00Z","seconds":3600}"#,
+            "\n",
+        ),
+    )?;
+
+    let report = |args: &[&str]| -> Result<String, Box<dyn std::error::Error>> {
+        let output = Command::cargo_bin("time-tracker")?
+            .args(["report", "--delta"])
+            .args(args)
+            .env("HOME", &home)
+            .env_remove("TT_HISTORY_FILE")
+            .output()?;
+        assert!(output.status.success());
+        Ok(String::from_utf8(output.stdout)?)
+    };
+    let day = report(&["--since", "2024-01-15", "--until", "2024-01-15"])?;
+    let two_days = report(&[
+        "--compare-to-goal",
+        "--since",
+        "2024-01-15",
+        "--until",
+        "2024-01-16",
+    ])?;
+    std::fs::remove_dir_all(&home)?;
+
+    assert_eq!(
+        day,
+        "Spanish: actual 25m / goal 30m (−5m)\nexercise: actual 0s / goal 1h (−1h)\n"
+    );
+    assert_eq!(
+        two_days,
+        "Spanish: actual 1h5m / goal 1h (+5m)\nexercise: actual 1h / goal 2h (−1h)\n"
+    );
+    Ok(())
+}
+
+#[test]
+fn test_reports_tags_co_occurrence() -> Result<(), Box<dyn std::error::Error>> {
+    let history_path = TEMP_DIR.join(TEMP_HISTORY_FILENAME.replace("{}", &fn_name!()));
+    std::fs::write(
+        &history_path,
+        concat!(
+            r#"{"name":"mockups","started_at":"2024-01-16T12:00:00Z","stopped_at":"2024-01-16T13:00:00Z","seconds":3600,"tags":["design","ui"]}"#,
+            "\n",
+            r#"{"name":"navbar","started_at":"2024-01-17T12:00:00Z","stopped_at":"2024-01-17T13:00:00Z","seconds":3600,"tags":["ui","design"]}"#,
+            "\n",
+            r#"{"name":"standup","started_at":"2024-01-18T12:00:00Z","stopped_at":"2024-01-18T12:15:00Z","seconds":900,"tags":["meeting"]}"#,
+            "\n",
+        ),
+    )?;
+
+    let output = Command::cargo_bin("time-tracker")?
+        .args(["report", "--tags-co-occurrence"])
+        .env("TT_HISTORY_FILE", &history_path)
+        .output()?;
+    std::fs::remove_file(&history_path)?;
+
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout)?,
+        "        design  ui\ndesign           2\nui           2\n"
+    );
+    Ok(())
+}
+
+#[test]
+fn test_reports_by_client_and_project() -> Result<(), Box<dyn std::error::Error>> {
+    let history_path = TEMP_DIR.join(TEMP_HISTORY_FILENAME.replace("{}", &fn_name!()));
+    std::fs::write(
+        &history_path,
+        concat!(
+            r#"{"name":"navbar","started_at":"2024-01-16T12:00:00Z","stopped_at":"2024-01-16T14:00:00Z","seconds":7200,"client":"ACME","project":"Website"}"#,
+            "\n",
+            r#"{"name":"release","started_at":"2024-01-17T12:00:00Z","stopped_at":"2024-01-17T13:00:00Z","seconds":3600,"client":"ACME","project":"Mobile"}"#,
+            "\n",
+            r#"{"name":"inbox","started_at":"2024-01-18T12:00:00Z","stopped_at":"2024-01-18T12:30:00Z","seconds":1800}"#,
+            "\n",
+        ),
+    )?;
+
+    let output = Command::cargo_bin("time-tracker")?
+        .args(["report", "--by-client-project"])
+        .env("TT_HISTORY_FILE", &history_path)
+        .output()?;
+    std::fs::remove_file(&history_path)?;
+
+    assert!(output.status.success());
+    assert_eq!(
+        String::from_utf8(output.stdout)?,
+        concat!(
+            "Client: ACME (3h total)\n",
+            "  Project: Website (2h)\n",
+            "    navbar: 2h\n",
+            "  Project: Mobile (1h)\n",
+            "    release: 1h\n",
+            "\n",
+            "Client: (No Client) (30m total)\n",
+            "  Project: (No Project) (30m)\n",
+            "    inbox: 30m\n",
+        )
+    );
+    Ok(())
+}
+
+#[test]
+fn test_reports_a_kanban_board_for_a_week() -> Result<(), Box<dyn std::error::Error>> {
+    let history_path = TEMP_DIR.join(TEMP_HISTORY_FILENAME.replace("{}", &fn_name!()));
+    std::fs::write(
+        &history_path,
+        concat!(
+            r#"{"name":"standup","started_at":"2024-01-16T12:00:00Z","stopped_at":"2024-01-16T12:15:00Z","seconds":900}"#,
+            "\n",
+            r#"{"name":"planning","started_at":"2024-01-23T12:00:00Z","stopped_at":"2024-01-23T13:00:00Z","seconds":3600}"#,
+            "\n",
+        ),
+    )?;
+
+    let output = Command::cargo_bin("time-tracker")?
+        .args([
+            "report",
+            "--kanban",
+            "--since",
+            "2024-W03",
+            "--column-width",
+            "14",
+        ])
+        .env("TT_HISTORY_FILE", &history_path)
+        .output()?;
+    std::fs::remove_file(&history_path)?;
+
+    assert!(output.status.success());
+    let board = String::from_utf8(output.stdout)?;
+    let lines: Vec<&str> = board.lines().collect();
+    assert_eq!(lines.len(), 3);
+    assert!(lines[0].starts_with("| Mon 2024-01-15 | Tue 2024-01-16 |"));
+    assert!(lines[0].ends_with("| Sun 2024-01-21 |"));
+    assert!(lines[2].starts_with("|                | standup (15m)  |"));
+    Ok(())
+}
+
+#[test]
+fn test_lints_task_names() -> Result<(), Box<dyn std::error::Error>> {
+    let history_path = TEMP_DIR.join(TEMP_HISTORY_FILENAME.replace("{}", &fn_name!()));
+    let rules_path = TEMP_DIR.join(format!("rules_{}.toml", fn_name!()));
+    std::fs::write(
+        &history_path,
+        concat!(
+            r#"{"name":"PROJ-1 login","started_at":"2024-01-16T12:00:00Z","stopped_at":"2024-01-16T13:00:00Z","seconds":3600}"#,
+            "\n",
+            r#"{"name":"fix login","started_at":"2024-01-17T12:00:00Z","stopped_at":"2024-01-17T13:00:00Z","seconds":3600}"#,
+            "\n",
+        ),
+    )?;
+    std::fs::write(
+        &rules_path,
+        concat!(
+            "[[rules]]\ntype = \"regex\"\n",
+            "pattern = \"^[A-Z]+-\\\\d+\"\nmessage = \"needs a ticket ID\"\n",
+        ),
+    )?;
+
+    let lint = |args: &[&str]| -> Result<std::process::Output, Box<dyn std::error::Error>> {
+        Ok(Command::cargo_bin("time-tracker")?
+            .arg("lint")
+            .arg("--rules")
+            .arg(&rules_path)
+            .args(args)
+            .env("TT_HISTORY_FILE", &history_path)
+            .output()?)
+    };
+    let all = lint(&[])?;
+    let before = lint(&["--until", "2024-01-16"])?;
+    std::fs::remove_file(&history_path)?;
+    std::fs::remove_file(&rules_path)?;
+
+    assert_eq!(all.status.code(), Some(1));
+    assert_eq!(
+        String::from_utf8(all.stdout)?,
+        "2024-01-17 'fix login': needs a ticket ID\n1 violations in 1 tasks.\n"
+    );
+    assert!(before.status.success());
+    assert_eq!(
+        String::from_utf8(before.stdout)?,
+        "All 1 task names follow the rules.\n"
+    );
+    Ok(())
+}
+
+#[test]
+fn test_migrates_between_json_and_sqlite() -> Result<(), Box<dyn std::error::Error>> {
+    let history_path = TEMP_DIR.join(TEMP_HISTORY_FILENAME.replace("{}", &fn_name!()));
+    let database_path = history_path.with_extension("sqlite");
+    let backup_path = TEMP_DIR.join(format!("{}.migrated", history_path.display()));
+    for path in [&database_path, &backup_path] {
+        let _ = std::fs::remove_file(path);
+    }
+
+    let history: String = (0..100)
+        .map(|i| {
+            format!(
+                "{{\"name\":\"task {}\",\"started_at\":\"2024-01-15T09:{:02}:00Z\",\"stopped_at\":\"2024-01-15T10:{:02}:00Z\",\"seconds\":3600}}\n",
+                i,
+                i % 60,
+                i % 60
+            )
+        })
+        .collect();
+    std::fs::write(&history_path, &history)?;
+
+    let migrate = |from: &str, to: &str, dry_run: bool| -> std::io::Result<String> {
+        let mut command = Command::cargo_bin("time-tracker").unwrap();
+        command
+            .args(["migrate", "--from", from, "--to", to])
+            .env("TT_HISTORY_FILE", &history_path);
+        if dry_run {
+            command.arg("--dry-run");
+        }
+        let output = command.output()?;
+        assert!(output.status.success());
+        Ok(String::from_utf8_lossy(&output.stdout).to_string())
+    };
+
+    assert!(migrate("json", "sqlite", true)?.contains("Would migrate 100 tasks"));
+    assert!(history_path.exists());
+
+    assert!(migrate("json", "sqlite", false)?.contains("Migrated 100 tasks"));
+    assert!(!history_path.exists());
+    assert_eq!(std::fs::read_to_string(&backup_path)?, history);
+
+    assert!(migrate("sqlite", "json", false)?.contains("Migrated 100 tasks"));
+    // the tasks come back the same, except that they now have ids
+    let migrated = std::fs::read_to_string(&history_path)?;
+    assert_eq!(migrated.lines().count(), 100);
+    for (line, original) in migrated.lines().zip(history.lines()) {
+        let mut task: serde_json::Value = serde_json::from_str(line)?;
+        let id = task.as_object_mut().unwrap().remove("id");
+        assert!(id.is_some_and(|id| id.is_string()));
+        assert_eq!(task, serde_json::from_str::<serde_json::Value>(original)?);
+    }
+
+    std::fs::remove_file(&history_path)?;
+    std::fs::remove_file(&backup_path)?;
+    std::fs::remove_file(TEMP_DIR.join(format!("{}.migrated", database_path.display())))?;
+    Ok(())
+}
+
+#[test]
+fn test_doctor_checks_the_configured_storage() -> Result<(), Box<dyn std::error::Error>> {
+    let history_path = TEMP_DIR.join(TEMP_HISTORY_FILENAME.replace("{}", &fn_name!()));
+    let binary_path = history_path.with_extension("bin");
+    let _ = std::fs::remove_file(&binary_path);
+    std::fs::write(
+        &history_path,
+        "{\"name\":\"a\",\"started_at\":\"2024-01-15T09:00:00Z\",\"stopped_at\":\"2024-01-15T10:00:00Z\",\"seconds\":60}\n",
+    )?;
+    let status = Command::cargo_bin("time-tracker")?
+        .args(["migrate", "--from", "json", "--to", "binary"])
+        .env("TT_HISTORY_FILE", &history_path)
+        .env("TT_BINARY_FILE", &binary_path)
+        .output()?
+        .status;
+    assert!(status.success());
+
+    let doctor = |fix: bool| -> std::io::Result<std::process::Output> {
+        let mut command = Command::cargo_bin("time-tracker").unwrap();
+        command
+            .arg("doctor")
+            .env("TT_HISTORY_FILE", &history_path)
+            .env("TT_BINARY_FILE", &binary_path)
+            .env("TT_STORAGE", "binary");
+        if fix {
+            command.arg("--fix");
+        }
+        command.output()
+    };
+
+    let output = doctor(false)?;
+    assert!(!output.status.success());
+    assert!(String::from_utf8(output.stdout)?.contains("[FAIL] seconds matches the timestamps"));
+    assert!(doctor(true)?.status.success());
+    assert!(doctor(false)?.status.success());
+
+    std::fs::remove_file(&binary_path)?;
+    std::fs::remove_file(TEMP_DIR.join(format!("{}.migrated", history_path.display())))?;
+    Ok(())
+}
+
 #[macro_export]
 macro_rules! fn_name {
     () => {{
@@ -193,12 +1851,18 @@ macro_rules! fn_name {
     }};
 }
 
-#[cfg(test)]
-mod test_macros {
-    #[test]
-    fn test_fn_name() {
-        assert_eq!(fn_name!(), "test_fn_name");
-    }
+/// Gets the path of the file the output of test `name` is written to.
+///
+/// Characters other than ASCII letters and digits in `name` are replaced with `_` and it is cut
+/// to 64 bytes, so the file name is always valid. The process id keeps runs of the tests that
+/// overlap from writing to the same file.
+fn temp_file_path(name: &str) -> std::path::PathBuf {
+    let mut name: String = name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    name.truncate(64);
+    TEMP_DIR.join(format!("output_{}_{}.log", std::process::id(), name))
 }
 
 /// A Child process used for testing.
@@ -207,36 +1871,38 @@ struct TestChild {
     process: std::process::Child,
     /// The path to the file where the output of the child process is written.
     file_path: std::path::PathBuf,
+    /// The path to the history file used by the child process.
+    history_path: std::path::PathBuf,
 }
 
 impl TestChild {
     /// Creates a new `TestChild` from a `std::process::Child`.
     fn new(name: String) -> TestChild {
-        let filename = TEMP_FILENAME.replace("{}", &name);
-        let file_path = TEMP_DIR.join(&filename);
-        let output_file = std::fs::File::create(file_path).unwrap();
+        let file_path = temp_file_path(&name);
+        let output_file = std::fs::File::create(&file_path).unwrap();
         let stdout = std::process::Stdio::from(output_file);
+        let history_path = TEMP_DIR.join(TEMP_HISTORY_FILENAME.replace("{}", &name));
         let process = Command::cargo_bin("time-tracker")
             .unwrap()
+            .env("TT_ENV", "test")
             .stdin(std::process::Stdio::piped())
             .stdout(stdout)
-            .env("TT_ENV", "test")
+            .env("TT_HISTORY_FILE", &history_path)
             .spawn()
             .unwrap();
 
-        return TestChild {
+        TestChild {
             process,
-            file_path: TEMP_DIR.join(&filename),
-        };
+            file_path,
+            history_path,
+        }
     }
 
     /// Write a string to the stdin of the process.
     fn write(&mut self, input: &str, sleep_ms: u64) -> Result<(), Box<dyn std::error::Error>> {
-        // check if input ends with a newline
-        let mut input = input.to_string();
-        if !input.ends_with('\n') {
-            input.push('\n');
-        }
+        // end the input with the line ending the platform expects
+        let mut input = input.trim_end_matches(['\r', '\n']).to_string();
+        input.push_str(PLATFORM.line_ending());
 
         // write the input to the program
         self.process
@@ -246,7 +1912,7 @@ impl TestChild {
             .write_all(input.as_bytes())?;
 
         self.sleep(sleep_ms)?;
-        return Ok(());
+        Ok(())
     }
 
     /// Read the output file and return the contents.
@@ -260,46 +1926,72 @@ impl TestChild {
         // close the file
         output_file.sync_all()?;
 
-        return Ok(output);
+        Ok(output)
+    }
+
+    /// Read the output file and panic, showing the whole output, if it doesn't contain `expected`.
+    fn assert_output_contains(&mut self, expected: &str) -> Result<(), Box<dyn std::error::Error>> {
+        let output = self.read()?;
+        assert!(
+            output.contains(expected),
+            "expected the output to contain {:?}, but it was:\n{}",
+            expected,
+            output
+        );
+        Ok(())
+    }
+
+    /// Read the output file and panic, showing the whole output, if it contains `unexpected`.
+    fn assert_output_not_contains(
+        &mut self,
+        unexpected: &str,
+    ) -> Result<(), Box<dyn std::error::Error>> {
+        let output = self.read()?;
+        assert!(
+            !output.contains(unexpected),
+            "expected the output not to contain {:?}, but it was:\n{}",
+            unexpected,
+            output
+        );
+        Ok(())
     }
 
     /// Sleep for a given amount of milliseconds.
     fn sleep(&mut self, ms: u64) -> Result<(), Box<dyn std::error::Error>> {
         std::thread::sleep(std::time::Duration::from_millis(ms));
-        return Ok(());
+        Ok(())
     }
 
     /// Remove all files in the tmp directory.
     #[allow(unreachable_code)]
     fn cleanup(&mut self) -> Result<(), Box<dyn std::error::Error>> {
         std::fs::remove_file(&self.file_path)?;
+        if self.history_path.exists() {
+            std::fs::remove_file(&self.history_path)?;
+        }
 
-        return Ok(());
+        Ok(())
     }
 
     /// Kill the process.
     fn kill(&mut self) -> Result<(), std::io::Error> {
-        return self.process.kill();
+        self.process.kill()
     }
 
     /// Kill all processes with the name `time-tracker`.
     fn kill_all(&mut self) -> Result<(), std::io::Error> {
-        let mut child = Command::new("pkill")
-            .arg("-f")
-            .arg("time-tracker")
+        let mut child = PLATFORM
+            .kill_all_command()
             .spawn()
             .expect("failed to execute process");
 
         let ecode = child.wait().expect("failed to wait on child");
 
         if !ecode.success() {
-            return Err(std::io::Error::new(
-                std::io::ErrorKind::Other,
-                "Failed to kill all processes.",
-            ));
+            return Err(std::io::Error::other("Failed to kill all processes."));
         }
 
-        return Ok(());
+        Ok(())
     }
 }
 
@@ -321,6 +2013,53 @@ impl Drop for TestChild {
     }
 }
 
+/// Process management that differs between platforms, kept apart from [`TestChild`] so the
+/// commands for every platform can be checked wherever the tests run.
+trait PlatformKiller {
+    /// Builds the command that kills every `time-tracker` process.
+    fn kill_all_command(&self) -> Command;
+
+    /// Gets the line ending the program expects on stdin.
+    fn line_ending(&self) -> &'static str;
+}
+
+/// Linux, macOS, and the other Unix-like platforms.
+struct Unix;
+
+impl PlatformKiller for Unix {
+    fn kill_all_command(&self) -> Command {
+        let mut command = Command::new("pkill");
+        command.arg("-f").arg("time-tracker");
+        command
+    }
+
+    fn line_ending(&self) -> &'static str {
+        "\n"
+    }
+}
+
+/// Windows, which has no `pkill` and ends lines with CR+LF.
+struct Windows;
+
+impl PlatformKiller for Windows {
+    fn kill_all_command(&self) -> Command {
+        let mut command = Command::new("taskkill");
+        command.args(["/F", "/IM", "time-tracker.exe"]);
+        command
+    }
+
+    fn line_ending(&self) -> &'static str {
+        "\r\n"
+    }
+}
+
+/// The platform the tests are running on.
+#[cfg(target_os = "windows")]
+const PLATFORM: Windows = Windows;
+/// The platform the tests are running on.
+#[cfg(not(target_os = "windows"))]
+const PLATFORM: Unix = Unix;
+
 /// Creates a new [`TestChild`](TestChild) with the current function name passed.
 #[macro_export]
 macro_rules! TestChild {
@@ -328,3 +2067,46 @@ macro_rules! TestChild {
         TestChild::new(fn_name!())
     };
 }
+
+#[cfg(test)]
+mod test_macros {
+    #[test]
+    fn test_fn_name() {
+        assert_eq!(fn_name!(), "test_fn_name");
+    }
+
+    #[test]
+    fn test_temp_file_path_is_sanitized() {
+        let path = super::temp_file_path(&format!("a/b c{}", "x".repeat(100)));
+        let file_name = path.file_name().unwrap().to_str().unwrap();
+        let expected_name = format!("a_b_c{}", "x".repeat(59));
+        assert_eq!(
+            file_name,
+            format!("output_{}_{}.log", std::process::id(), expected_name)
+        );
+        assert_eq!(path.parent(), Some(super::TEMP_DIR.as_path()));
+    }
+
+    #[test]
+    fn test_platform_kill_all_commands() {
+        use super::PlatformKiller;
+
+        let args = |platform: &dyn PlatformKiller| {
+            let command = platform.kill_all_command();
+            let mut args = vec![command.get_program().to_str().unwrap().to_string()];
+            args.extend(
+                command
+                    .get_args()
+                    .map(|arg| arg.to_str().unwrap().to_string()),
+            );
+            args
+        };
+        assert_eq!(args(&super::Unix), ["pkill", "-f", "time-tracker"]);
+        assert_eq!(
+            args(&super::Windows),
+            ["taskkill", "/F", "/IM", "time-tracker.exe"]
+        );
+        assert_eq!(super::Unix.line_ending(), "\n");
+        assert_eq!(super::Windows.line_ending(), "\r\n");
+    }
+}